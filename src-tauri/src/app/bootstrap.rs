@@ -9,7 +9,7 @@ use crate::engine_factory;
 use ashpd::{register_host_app, AppID};
 use std::sync::{Arc, Mutex};
 use tauri::{
-    menu::{Menu, MenuItem},
+    menu::{CheckMenuItem, Menu, MenuItem},
     tray::{TrayIconBuilder, TrayIconEvent},
     Emitter, Manager,
 };
@@ -33,7 +33,19 @@ fn read_linux_distribution_name() -> Option<String> {
 fn create_tray_menu(app: &tauri::AppHandle) -> Result<Menu<tauri::Wry>, tauri::Error> {
     let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
     let open_item = MenuItem::with_id(app, "open", "Open Voquill", true, None::<&str>)?;
-    Menu::with_items(app, &[&open_item, &quit_item])
+    let armed = app
+        .state::<AppState>()
+        .voice_activation_armed
+        .load(std::sync::atomic::Ordering::SeqCst);
+    let hands_free_item = CheckMenuItem::with_id(
+        app,
+        "hands_free",
+        "Hands-Free Listening",
+        true,
+        armed,
+        None::<&str>,
+    )?;
+    Menu::with_items(app, &[&open_item, &hands_free_item, &quit_item])
 }
 
 pub fn build_app_state(initial_config: &Config) -> AppState {
@@ -176,6 +188,10 @@ pub fn run_setup(
                     let _ = window.set_focus();
                 }
             }
+            "hands_free" => {
+                let state = app_handle.state::<AppState>();
+                crate::app::voice_activation::toggle_armed(&state);
+            }
             _ => {}
         })
         .on_tray_icon_event(|tray, event| {
@@ -246,10 +262,28 @@ pub fn run_setup(
         app.handle(),
     );
     spawn_python_runner_prewarm(app.handle(), initial_config);
+    crate::app::voice_activation::spawn_listener(app.handle().clone());
+    crate::app::device_watchdog::spawn_watchdog(app.handle().clone());
+    spawn_recording_migration(initial_config);
 
     Ok(())
 }
 
+/// Converts saved WAV recordings to FLAC in the background when FLAC is the
+/// configured format. Fired at startup and when the format is switched.
+pub fn spawn_recording_migration(config: &Config) {
+    if config.recording_audio_format != crate::config::RecordingAudioFormat::Flac {
+        return;
+    }
+    tauri::async_runtime::spawn_blocking(
+        || match audio::history_audio::migrate_recordings_to_flac() {
+            Ok(0) => {}
+            Ok(converted) => crate::log_info!("Converted {} saved recording(s) to FLAC", converted),
+            Err(e) => crate::log_warn!("Saved recording conversion failed: {}", e),
+        },
+    );
+}
+
 /// Pre-loads the transcription engine model into its cache so the first
 /// recording reuses a warm model instead of paying the full load cost.
 /// Fired at startup and re-armed whenever the engine/model changes or a
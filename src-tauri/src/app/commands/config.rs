@@ -24,7 +24,9 @@ pub async fn save_config(
         let config_guard = state.config.lock().unwrap();
         let audio_changed = config_guard.audio_device != normalized_config.audio_device
             || config_guard.input_sensitivity != normalized_config.input_sensitivity;
-        let hotkey_changed = config_guard.hotkey != normalized_config.hotkey;
+        let hotkey_changed = config_guard.hotkey != normalized_config.hotkey
+            || config_guard.code_dictation_hotkey != normalized_config.code_dictation_hotkey
+            || config_guard.command_mode_hotkey != normalized_config.command_mode_hotkey;
         let previous_config = config_guard.clone();
 
         let mut merged_config = normalized_config.clone();
@@ -142,6 +144,10 @@ pub async fn save_config(
 
     reconcile_engine_warmup(&state, &previous_config, &merged_config, &app_handle);
 
+    if previous_config.recording_audio_format != merged_config.recording_audio_format {
+        crate::app::bootstrap::spawn_recording_migration(&merged_config);
+    }
+
     if hotkey_changed {
         if let Err(error) = re_register_hotkey(&app_handle, &merged_config.hotkey).await {
             let mut error_lock = state.hotkey_error.lock().unwrap();
@@ -173,25 +179,25 @@ fn reconcile_engine_warmup(
     app_handle: &tauri::AppHandle,
 ) {
     use crate::config::{PostProcessProvider, TranscriptionMode};
+    use crate::post_process::provider_local::LlamaOptions;
 
-    let was_local_post_process = previous_config.post_process_enabled
-        && previous_config.post_process_provider == PostProcessProvider::Local;
-    let is_local_post_process = merged_config.post_process_enabled
-        && merged_config.post_process_provider == PostProcessProvider::Local;
-
-    if was_local_post_process && !is_local_post_process {
+    // Presets can pick their own backend, so the sidecars are only unloaded
+    // once no preset runs locally; switching presets keeps them warm.
+    if previous_config.uses_local_post_process() && !merged_config.uses_local_post_process() {
         crate::log_info!("Local post-processing disabled; unloading llama-server sidecar");
         state.post_process_factory.invalidate_local();
     }
 
+    let merged_target = merged_config.resolve_post_process_target();
     let post_process_changed = previous_config.post_process_enabled
         != merged_config.post_process_enabled
-        || previous_config.post_process_provider != merged_config.post_process_provider
-        || previous_config.post_process_engine != merged_config.post_process_engine
-        || previous_config.post_process_model != merged_config.post_process_model
-        || previous_config.post_process_threads != merged_config.post_process_threads;
+        || previous_config.resolve_post_process_target() != merged_target
+        || LlamaOptions::from_config(previous_config) != LlamaOptions::from_config(merged_config);
 
-    if post_process_changed && is_local_post_process {
+    if post_process_changed
+        && merged_config.post_process_enabled
+        && merged_target.provider == PostProcessProvider::Local
+    {
         crate::app::bootstrap::spawn_post_process_warmup(
             state.post_process_factory.clone(),
             merged_config,
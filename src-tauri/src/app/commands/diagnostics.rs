@@ -152,7 +152,9 @@ pub async fn clear_recording_logs() -> Result<u32, String> {
             .flatten()
         {
             let path = entry.path();
-            if path.extension().map(|ext| ext == "wav").unwrap_or(false)
+            if path
+                .extension()
+                .is_some_and(|ext| ext == "wav" || ext == "flac")
                 && std::fs::remove_file(&path).is_ok()
             {
                 total += 1;
@@ -1,16 +1,78 @@
 use std::collections::HashSet;
 
 use crate::{
-    audio,
+    audio::{self, WhisperFile},
     diarization::{DiarizationResult, Segment},
     history,
 };
 use tauri::{Emitter, Manager};
 
-fn wav_duration_secs(wav_data: &[u8]) -> Result<f64, String> {
-    let reader =
-        hound::WavReader::new(std::io::Cursor::new(wav_data)).map_err(|e| e.to_string())?;
-    Ok(reader.duration() as f64 / reader.spec().sample_rate as f64)
+/// Where audio run through the import pipeline came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportSource {
+    /// A file picked or dropped by the user.
+    File,
+    /// System playback captured from a monitor source in listen mode.
+    System,
+    /// Listen mode with the microphone on its own channel.
+    Call,
+    /// A dictation whose session was interrupted, recovered from its spool.
+    Recovered,
+}
+
+impl ImportSource {
+    fn history_source(self) -> &'static str {
+        match self {
+            ImportSource::File => "file",
+            ImportSource::System => "system",
+            ImportSource::Call => "call",
+            ImportSource::Recovered => "mic",
+        }
+    }
+
+    fn debug_prefix(self) -> &'static str {
+        match self {
+            ImportSource::File => "import",
+            ImportSource::System => "system",
+            ImportSource::Call => "call",
+            ImportSource::Recovered => "recovered",
+        }
+    }
+
+    /// Listen mode exists to transcribe calls and videos with several
+    /// voices, so it always tries diarization. Call channels are already
+    /// one speaker each.
+    fn diarize(self, config: &crate::config::Config) -> bool {
+        match self {
+            ImportSource::File => config.diarization_enabled_files,
+            ImportSource::System => true,
+            ImportSource::Call => false,
+            ImportSource::Recovered => config.diarization_enabled_recording,
+        }
+    }
+}
+
+/// Padding around the speech found in each call channel, so words are not
+/// clipped at the region edges.
+const CHANNEL_SPEECH_PADDING_MS: u64 = 200;
+
+/// Audio handed to the import pipeline, on disk.
+pub struct ImportAudio {
+    /// Everything that was heard; this is what history keeps and what gets
+    /// transcribed when there are no channels.
+    pub wav: WhisperFile,
+    /// Separately captured channels, each transcribed on its own and
+    /// labelled with its speaker instead of running diarization.
+    pub channels: Vec<(&'static str, WhisperFile)>,
+}
+
+impl ImportAudio {
+    pub fn single(wav: WhisperFile) -> Self {
+        Self {
+            wav,
+            channels: Vec::new(),
+        }
+    }
 }
 
 #[tauri::command]
@@ -25,22 +87,49 @@ pub async fn transcribe_audio_file(
         path
     );
 
+    let wav_data = read_audio_file(&path).map(ImportAudio::single);
+    transcribe_import(&app_handle, &session_uuid, ImportSource::File, wav_data).await
+}
+
+fn read_audio_file(path: &str) -> Result<WhisperFile, String> {
+    let audio_data = std::fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    crate::log_info!("File size: {} bytes", audio_data.len());
+    audio::convert_audio_file_for_whisper(&audio_data)
+        .map_err(|e| format!("Failed to convert audio: {}", e))
+}
+
+/// Runs whisper-ready audio through the import pipeline (diarization,
+/// cleanup, post-processing) and records it in history. A failure, including
+/// one loading `input`, is recorded as a failed history item.
+pub async fn transcribe_import(
+    app_handle: &tauri::AppHandle,
+    session_uuid: &str,
+    source: ImportSource,
+    input: Result<ImportAudio, String>,
+) -> Result<DiarizationResult, String> {
     let app_state = app_handle.state::<crate::AppState>();
     let current_config = {
         let guard = app_state.config.lock().unwrap();
         guard.clone()
     };
 
-    match transcribe_audio_file_inner(&path, &session_uuid, &app_handle, &current_config).await {
+    let result = match input {
+        Ok(input) => {
+            transcribe_audio(&input, source, session_uuid, app_handle, &current_config).await
+        }
+        Err(error) => Err(error),
+    };
+    match result {
         Ok(result) => Ok(result),
         Err(error) => {
             crate::log_info!(
-                "[session:{}] File transcription failed: {}",
+                "[session:{}] {} transcription failed: {}",
                 &session_uuid[..8],
+                source.history_source(),
                 error
             );
             let _ = history::add_history_item(&history::NewHistoryItem {
-                session_uuid: &session_uuid,
+                session_uuid,
                 status: "failed",
                 text: "",
                 raw_text: None,
@@ -49,9 +138,10 @@ pub async fn transcribe_audio_file(
                 audio_file: None,
                 duration_secs: None,
                 engine: Some(&current_config.local_engine),
-                source: Some("file"),
+                source: Some(source.history_source()),
                 language: Some(&current_config.language),
                 prompt_name: current_config.resolve_post_process_prompt_name().as_deref(),
+                metadata: None,
                 limit: Some(current_config.history_limit),
             });
             if let Some(window) = app_handle.get_webview_window("main") {
@@ -62,18 +152,15 @@ pub async fn transcribe_audio_file(
     }
 }
 
-async fn transcribe_audio_file_inner(
-    path: &str,
+async fn transcribe_audio(
+    input: &ImportAudio,
+    source: ImportSource,
     session_uuid: &str,
     app_handle: &tauri::AppHandle,
     current_config: &crate::config::Config,
 ) -> Result<DiarizationResult, String> {
-    let audio_data = std::fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
-    crate::log_info!("File size: {} bytes", audio_data.len());
-
-    let wav_data = audio::convert_audio_file_for_whisper(&audio_data)
-        .map_err(|e| format!("Failed to convert audio: {}", e))?;
-    let duration_secs = wav_duration_secs(&wav_data).ok();
+    let wav_data = &input.wav;
+    let duration_secs = Some(wav_data.duration_secs());
 
     let app_state = app_handle.state::<crate::AppState>();
     let engine_factory_state = app_state.engine_factory.clone();
@@ -103,21 +190,24 @@ async fn transcribe_audio_file_inner(
     );
 
     let saved_audio_file = if current_config.enable_recording_logs {
-        match crate::paths::debug_recordings_dir() {
-            Ok(dir) => {
-                let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-                let file_name = format!("import_{}_{}.wav", timestamp, &session_uuid[..8]);
-                let file_path = dir.join(&file_name);
-                if let Err(e) = std::fs::write(&file_path, &wav_data) {
-                    crate::log_warn!("Failed to save debug import audio: {}", e);
-                    None
-                } else {
-                    crate::log_info!("Debug import audio saved: {:?}", file_path);
-                    Some(file_name)
-                }
+        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+        let file_stem = format!(
+            "{}_{}_{}",
+            source.debug_prefix(),
+            timestamp,
+            &session_uuid[..8]
+        );
+        match audio::history_audio::save_recording(
+            &file_stem,
+            wav_data,
+            current_config.recording_audio_format,
+        ) {
+            Ok(file_name) => {
+                crate::log_info!("Debug import audio saved: {}", file_name);
+                Some(file_name)
             }
             Err(e) => {
-                crate::log_warn!("Failed to get debug recordings directory: {}", e);
+                crate::log_warn!("Failed to save debug import audio: {}", e);
                 None
             }
         }
@@ -125,39 +215,32 @@ async fn transcribe_audio_file_inner(
         None
     };
 
-    // ── Diarization temp file ──
-    // The Python runner reads audio via soundfile/libsnfile, which cannot decode
-    // compressed containers (m4a/aac). Write the decoded WAV to a temp file so
-    // diarization always receives a format libsndfile supports.
-    let diar_path: Option<std::path::PathBuf> = if current_config.diarization_enabled_files {
-        let temp_dir = crate::paths::temp_dir();
-        let _ = std::fs::create_dir_all(&temp_dir);
-        let temp_path = temp_dir.join(format!(
-            "file_{}.wav",
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_nanos()
-        ));
-        match std::fs::write(&temp_path, &wav_data) {
-            Ok(()) => Some(temp_path),
-            Err(e) => {
-                crate::log_warn!("Failed to write temp audio for diarization: {}", e);
-                None
-            }
-        }
-    } else {
-        None
-    };
+    // The Python runner reads audio via soundfile/libsndfile, which cannot
+    // decode compressed containers (m4a/aac); it gets the decoded WAV.
+    let diar_path = source.diarize(current_config).then(|| wav_data.path());
 
     // ── Transcription: per-segment or full-file ──
     let diarization_cluster_threshold = current_config.diarization_cluster_threshold;
-    let result = match &diar_path {
+    let mut result = match diar_path {
+        None if !input.channels.is_empty() => {
+            transcribe_channels(
+                &input.channels,
+                service.as_ref(),
+                lang_code,
+                prompt_hint.as_deref(),
+                current_config,
+            )
+            .await?
+        }
         None => {
-            let text = service
-                .transcribe(&wav_data, lang_code, prompt_hint.as_deref())
-                .await
-                .map_err(|e| format!("Transcription failed: {}", e))?;
+            let text = crate::transcription::transcribe_file(
+                service.as_ref(),
+                wav_data,
+                lang_code,
+                prompt_hint.as_deref(),
+            )
+            .await
+            .map_err(|e| format!("Transcription failed: {}", e))?;
             crate::log_info!(
                 "Transcription received ({}): \"{}\"",
                 service.service_name(),
@@ -183,14 +266,15 @@ async fn transcribe_audio_file_inner(
                     diar.provider
                 );
 
-                let full_duration = wav_duration_secs(&wav_data)?;
+                let full_duration = wav_data.duration_secs();
                 let mut segment_texts: Vec<(Option<String>, String)> = Vec::new();
 
                 for seg in &diar.segments {
                     let start = seg.start_sec.unwrap_or(0.0);
                     let end = seg.end_sec.unwrap_or(full_duration);
 
-                    let seg_wav = audio::extract_segment_wav(&wav_data, start, end)
+                    let seg_wav = wav_data
+                        .segment_wav(start, end)
                         .map_err(|e| format!("Failed to extract segment: {}", e))?;
 
                     let seg_text = service
@@ -238,10 +322,14 @@ async fn transcribe_audio_file_inner(
             }
             Ok(mut diar) => {
                 crate::log_warn!("Diarization returned 0 segments — transcribing full file");
-                let text = service
-                    .transcribe(&wav_data, lang_code, prompt_hint.as_deref())
-                    .await
-                    .map_err(|e| format!("Transcription failed: {}", e))?;
+                let text = crate::transcription::transcribe_file(
+                    service.as_ref(),
+                    wav_data,
+                    lang_code,
+                    prompt_hint.as_deref(),
+                )
+                .await
+                .map_err(|e| format!("Transcription failed: {}", e))?;
                 crate::log_info!(
                     "Transcription received ({}): \"{}\"",
                     service.service_name(),
@@ -252,10 +340,14 @@ async fn transcribe_audio_file_inner(
             }
             Err(e) => {
                 crate::log_warn!("Diarization failed, transcribing full file: {}", e);
-                let text = service
-                    .transcribe(&wav_data, lang_code, prompt_hint.as_deref())
-                    .await
-                    .map_err(|e| format!("Transcription failed: {}", e))?;
+                let text = crate::transcription::transcribe_file(
+                    service.as_ref(),
+                    wav_data,
+                    lang_code,
+                    prompt_hint.as_deref(),
+                )
+                .await
+                .map_err(|e| format!("Transcription failed: {}", e))?;
                 crate::log_info!(
                     "Transcription received ({}): \"{}\"",
                     service.service_name(),
@@ -270,66 +362,113 @@ async fn transcribe_audio_file_inner(
         },
     };
 
-    // ── Cleanup temp file ──
-    if let Some(path) = &diar_path {
-        let _ = std::fs::remove_file(path);
-    }
-
     // ── Filler word removal (pre-processing, no LLM needed) ──
     let cleaned_text = crate::text_cleanup::clean_transcription(
         &result.text,
         current_config.filler_word_removal_enabled,
         &current_config.custom_filler_words,
     );
-
-    // Save the raw (pre-post-process) text for history display
-    let file_raw_text = if current_config.post_process_enabled {
-        Some(cleaned_text.clone())
-    } else {
-        None
-    };
+    let cleaned_text =
+        crate::dictionary::apply_dictionary_correction(&cleaned_text, current_config);
+
+    // Save the raw (pre-post-process, unmasked) text for history display
+    let file_raw_text =
+        if current_config.post_process_enabled || current_config.profanity_filter_enabled {
+            Some(cleaned_text.clone())
+        } else {
+            None
+        };
+
+    // ── Sensitive-term masking ──
+    let cleaned_text = crate::profanity::apply_profanity_filter(&cleaned_text, current_config);
+    for segment in &mut result.segments {
+        segment.text = crate::profanity::apply_profanity_filter(&segment.text, current_config);
+    }
 
     // ── Post-processing ──
+    let mut history_metadata = history::HistoryMetadata::default();
     let result_text = if !cleaned_text.trim().is_empty() && current_config.post_process_enabled {
         crate::log_info!("Post-processing file transcription...");
         let post_process_factory = app_state.post_process_factory.clone();
-        match post_process_factory.get_service(current_config).await {
-            Ok(processor) => match processor
-                .post_process(
+        let variables =
+            crate::post_process::template::PromptVariables::gather(current_config, None);
+        let prompts =
+            crate::post_process::template::render_selected_prompts(current_config, &variables);
+        let structured = crate::post_process::structured::StructuredPreset::resolve(current_config);
+        let mut attempts =
+            crate::post_process::policy::Attempts::new(&post_process_factory, current_config);
+        let mut succeeded = None;
+        while let Some(processor) = attempts.next().await {
+            let result = match &structured {
+                Some(preset) => crate::post_process::structured::post_process(
+                    &processor,
                     &cleaned_text,
-                    &current_config.resolve_post_process_prompt(),
-                    &current_config.resolve_user_prompt_template(),
-                    current_config.resolve_max_output_tokens(),
+                    &prompts,
+                    preset,
+                    current_config,
                 )
                 .await
-            {
-                Ok(cleaned) => {
-                    crate::log_info!(
-                        "Post-processed ({}): \"{}\"",
-                        processor.service_name(),
-                        cleaned
-                    );
-                    cleaned
-                }
-                Err(e) => {
-                    crate::log_warn!("Post-processing failed, using cleaned text: {}", e);
-                    if matches!(e, crate::post_process::PostProcessError::Network(_)) {
-                        post_process_factory.invalidate_local();
+                .map(|output| {
+                    history_metadata.structured = output.value;
+                    output.text
+                }),
+                None => crate::post_process::chunked::post_process(
+                    &processor,
+                    &cleaned_text,
+                    &prompts,
+                    current_config,
+                    |progress| {
+                        let _ = app_handle.emit("file-import-progress", progress);
+                    },
+                )
+                .await
+                .map(|output| {
+                    if output.chunks > 1 {
+                        history_metadata.chunks = Some(output.chunks);
                     }
-                    cleaned_text.clone()
+                    output.text
+                }),
+            };
+            match result {
+                Ok(cleaned) => {
+                    succeeded = Some((processor, cleaned));
+                    break;
                 }
-            },
-            Err(e) => {
-                crate::log_warn!(
-                    "Could not create post-process service, using cleaned text: {}",
-                    e
+                Err(e) => attempts.failed(&e),
+            }
+        }
+        match succeeded {
+            Some((processor, cleaned)) => {
+                history_metadata.post_process = Some(attempts.succeeded());
+                crate::log_info!(
+                    "Post-processed ({}): \"{}\"",
+                    processor.service_name(),
+                    cleaned
                 );
+                let guarded = crate::post_process::guardrail::enforce(
+                    &processor,
+                    &cleaned_text,
+                    cleaned,
+                    &prompts,
+                    current_config,
+                )
+                .await;
+                history_metadata.guardrail = guarded.note;
+                guarded.text
+            }
+            None => {
+                // An imported file has nothing else to show for itself, so
+                // it keeps the transcript whatever the failure output says.
+                crate::log_warn!("Post-processing failed, using cleaned text");
+                history_metadata.post_process =
+                    Some(attempts.exhausted(crate::config::PostProcessFailureOutput::Raw));
                 cleaned_text.clone()
             }
         }
     } else {
         cleaned_text.clone()
     };
+    let result_text = crate::profanity::apply_profanity_filter(&result_text, current_config);
 
     if result_text.trim().is_empty() {
         let _ = history::add_history_item(&history::NewHistoryItem {
@@ -342,9 +481,10 @@ async fn transcribe_audio_file_inner(
             audio_file: saved_audio_file.as_deref(),
             duration_secs,
             engine: Some(&service_name),
-            source: Some("file"),
+            source: Some(source.history_source()),
             language: Some(&language),
             prompt_name: prompt_name.as_deref(),
+            metadata: None,
             limit: Some(current_config.history_limit),
         });
         if let Some(window) = app_handle.get_webview_window("main") {
@@ -379,9 +519,10 @@ async fn transcribe_audio_file_inner(
         audio_file: saved_audio_file.as_deref(),
         duration_secs,
         engine: Some(&service_name),
-        source: Some("file"),
+        source: Some(source.history_source()),
         language: Some(&language),
         prompt_name: prompt_name.as_deref(),
+        metadata: Some(&history_metadata),
         limit: Some(current_config.history_limit),
     }) {
         crate::log_warn!("Failed to save history: {}", e);
@@ -393,6 +534,66 @@ async fn transcribe_audio_file_inner(
     Ok(result)
 }
 
+/// Transcribes each channel's speech regions separately and interleaves them
+/// into one conversation labelled by channel.
+async fn transcribe_channels(
+    channels: &[(&'static str, WhisperFile)],
+    service: &(dyn crate::transcription::TranscriptionService + Send + Sync),
+    lang_code: Option<&str>,
+    prompt_hint: Option<&str>,
+    config: &crate::config::Config,
+) -> Result<DiarizationResult, String> {
+    let vad_settings = crate::audio::vad::VadSettings {
+        threshold_db: config.vad_threshold_db,
+        min_speech_ms: config.vad_min_speech_ms,
+        silence_ms: config.vad_silence_ms,
+    };
+    let mut per_channel = Vec::new();
+    for (speaker, wav) in channels {
+        let mut finder = crate::audio::vad::SpeechRegions::new(
+            audio::whisper_file::WHISPER_SAMPLE_RATE,
+            vad_settings,
+            CHANNEL_SPEECH_PADDING_MS,
+        );
+        wav.for_each_block(|block| {
+            finder.push(block);
+            Ok(())
+        })
+        .map_err(|e| format!("Failed to read {} channel: {}", speaker, e))?;
+        let regions = finder.finish();
+        crate::log_info!("Channel [{}]: {} speech regions", speaker, regions.len());
+
+        let mut segments = Vec::new();
+        for (start, end) in regions {
+            let seg_wav = wav
+                .segment_wav(start, end)
+                .map_err(|e| format!("Failed to extract segment: {}", e))?;
+            let text = service
+                .transcribe(&seg_wav, lang_code, prompt_hint)
+                .await
+                .map_err(|e| format!("Segment transcription failed: {}", e))?;
+            crate::log_info!("Segment [{}] {:.1}s: \"{}\"", speaker, start, text);
+            if text.trim().is_empty() {
+                continue;
+            }
+            segments.push(Segment {
+                speaker: Some(speaker.to_string()),
+                text: text.trim().to_string(),
+                start_sec: Some(start),
+                end_sec: Some(end),
+            });
+        }
+        per_channel.push(segments);
+    }
+
+    let segments = crate::diarization::merge_channel_segments(per_channel);
+    Ok(DiarizationResult {
+        text: crate::diarization::labeled_text(&segments),
+        segments,
+        provider: "channels".to_string(),
+    })
+}
+
 /// Run diarization on a decoded WAV file via the Python runner.
 async fn run_diarization(
     app_handle: &tauri::AppHandle,
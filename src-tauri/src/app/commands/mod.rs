@@ -5,6 +5,8 @@ pub mod hotkey;
 pub mod platform;
 pub mod post_process;
 pub mod recording;
+pub mod recovery;
+pub mod system_audio;
 pub mod transcription;
 pub mod update;
 
@@ -15,5 +17,7 @@ pub use hotkey::*;
 pub use platform::*;
 pub use post_process::*;
 pub use recording::*;
+pub use recovery::*;
+pub use system_audio::*;
 pub use transcription::*;
 pub use update::*;
@@ -329,3 +329,9 @@ pub async fn get_output_devices() -> Result<Vec<audio::AudioDevice>, String> {
     crate::log_info!("Tauri Command: get_output_devices invoked");
     audio::get_output_devices()
 }
+
+#[tauri::command]
+pub async fn get_monitor_devices() -> Result<Vec<audio::AudioDevice>, String> {
+    crate::log_info!("Tauri Command: get_monitor_devices invoked");
+    audio::get_monitor_devices()
+}
@@ -1,11 +1,144 @@
-use crate::post_process::provider_api;
+use crate::config::{PostProcessPrompt, PostProcessProvider};
+use crate::post_process::{provider_anthropic, provider_api, provider_ollama, template};
+use crate::presets::{self, ConflictPolicy, ImportSummary};
+use crate::AppState;
+use serde::Serialize;
+use tauri::Emitter;
 
+/// Sends a test sentence through the given API provider. `provider` defaults
+/// to the OpenAI-compatible API.
 #[tauri::command]
 pub async fn test_cleanup_api(
+    provider: Option<PostProcessProvider>,
     api_key: String,
     api_url: String,
     model: String,
     system_prompt: String,
 ) -> Result<String, String> {
-    provider_api::test_connection(&api_key, &api_url, &model, &system_prompt).await
+    match provider.unwrap_or(PostProcessProvider::Api) {
+        PostProcessProvider::Api => {
+            provider_api::test_connection(&api_key, &api_url, &model, &system_prompt).await
+        }
+        PostProcessProvider::Anthropic => {
+            provider_anthropic::test_connection(&api_key, &api_url, &model, &system_prompt).await
+        }
+        PostProcessProvider::Ollama => {
+            provider_ollama::test_connection(&api_key, &api_url, &model, &system_prompt).await
+        }
+        PostProcessProvider::Local => Err("The local model has no connection to test".to_string()),
+    }
+}
+
+#[derive(Serialize)]
+pub struct PromptPreview {
+    pub system_prompt: String,
+    pub user_prompt: String,
+    pub unknown_variables: Vec<String>,
+}
+
+/// Renders the selected preset's prompts with live variable values, as the
+/// next dictation would send them. The focused application is unknown here
+/// (Voquill itself has focus), so it renders as missing.
+#[tauri::command]
+pub async fn preview_post_process_prompt(
+    state: tauri::State<'_, AppState>,
+    transcript: Option<String>,
+) -> Result<PromptPreview, String> {
+    let config = state.config.lock().unwrap().clone();
+    let variables = template::PromptVariables::gather(&config, None);
+    let prompts = template::render_selected_prompts(&config, &variables);
+
+    let mut unknown_variables = template::unknown_variables(&config.resolve_post_process_prompt());
+    for name in template::unknown_variables(&config.resolve_user_prompt_template()) {
+        if !unknown_variables.contains(&name) {
+            unknown_variables.push(name);
+        }
+    }
+
+    let user_prompt = match transcript.filter(|t| !t.trim().is_empty()) {
+        Some(transcript) => prompts
+            .user_prompt_template
+            .replace("{transcript}", &transcript),
+        None => prompts.user_prompt_template,
+    };
+
+    Ok(PromptPreview {
+        system_prompt: prompts.system_prompt,
+        user_prompt,
+        unknown_variables,
+    })
+}
+
+/// Writes presets to a preset file: the given ids, or all of them.
+#[tauri::command]
+pub async fn export_post_process_presets(
+    state: tauri::State<'_, AppState>,
+    path: String,
+    ids: Option<Vec<String>>,
+) -> Result<usize, String> {
+    let prompts: Vec<_> = {
+        let config = state.config.lock().unwrap();
+        config
+            .post_process_prompts
+            .iter()
+            .filter(|p| ids.as_ref().is_none_or(|ids| ids.contains(&p.id)))
+            .cloned()
+            .collect()
+    };
+    if prompts.is_empty() {
+        return Err("No presets to export".to_string());
+    }
+    let contents = presets::export(&prompts)?;
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    crate::log_info!("Exported {} preset(s) to {}", prompts.len(), path);
+    Ok(prompts.len())
+}
+
+/// Imports a preset file, resolving presets whose id already exists with
+/// `on_conflict`.
+#[tauri::command]
+pub async fn import_post_process_presets(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    path: String,
+    on_conflict: ConflictPolicy,
+) -> Result<ImportSummary, String> {
+    update_prompts(&state, &app_handle, |prompts| {
+        presets::import_file(prompts, &path, on_conflict)
+    })
+}
+
+/// Pulls newer revisions of imported presets from the given file, or from
+/// every file presets were imported from.
+#[tauri::command]
+pub async fn update_post_process_presets(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    path: Option<String>,
+) -> Result<ImportSummary, String> {
+    update_prompts(&state, &app_handle, |prompts| {
+        presets::update(prompts, path.as_deref())
+    })
+}
+
+fn update_prompts(
+    state: &AppState,
+    app_handle: &tauri::AppHandle,
+    apply: impl FnOnce(&mut Vec<PostProcessPrompt>) -> Result<ImportSummary, String>,
+) -> Result<ImportSummary, String> {
+    let summary = {
+        let mut config = state.config.lock().unwrap();
+        let summary = apply(&mut config.post_process_prompts)?;
+        crate::config::save_config(&config).map_err(|e| format!("Failed to save config: {}", e))?;
+        summary
+    };
+    crate::log_info!(
+        "Presets: {} added, {} replaced, {} renamed, {} skipped",
+        summary.added.len(),
+        summary.replaced.len(),
+        summary.renamed.len(),
+        summary.skipped.len()
+    );
+    let _ = app_handle.emit("config-updated", ());
+    Ok(summary)
 }
@@ -8,6 +8,22 @@ use tauri::Emitter;
 pub async fn start_recording(
     state: tauri::State<'_, AppState>,
     app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    start_recording_with_mode(
+        state,
+        app_handle,
+        crate::app::recording_flow::SessionKind::Dictation(None),
+    )
+    .await
+}
+
+/// Starts a recording session of the given kind. A dictation mode in `kind`
+/// overrides the mode resolved from config for this session only (e.g. the
+/// code dictation hotkey).
+pub async fn start_recording_with_mode(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    kind: crate::app::recording_flow::SessionKind,
 ) -> Result<(), String> {
     let session_before = *state.session_state.lock().unwrap();
     crate::log_info!(
@@ -139,6 +155,7 @@ pub async fn start_recording(
             app_handle_clone,
             audio_engine,
             engine_factory,
+            kind,
         )
         .await;
 
@@ -395,22 +412,16 @@ pub async fn play_history_recording(
         file_name
     );
     let playback_device = { state.config.lock().unwrap().playback_device.clone() };
-    let recordings_dir = crate::paths::debug_recordings_dir()?;
-    let path = recordings_dir.join(&file_name);
-    let wav_bytes = if path.exists() {
-        std::fs::read(&path).map_err(|e| e.to_string())?
-    } else {
-        let debug_dir = crate::paths::debug_dir()?;
-        let alt = debug_dir.join(&file_name);
-        if alt.exists() {
-            std::fs::read(&alt).map_err(|e| e.to_string())?
-        } else {
-            return Err("Recording audio file not found".to_string());
-        }
-    };
+    let lookup = file_name.clone();
+    let wav =
+        tokio::task::spawn_blocking(move || audio::history_audio::open_recording_wav(&lookup))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("Recording audio: {}", e))?;
+    let path = wav.path().to_path_buf();
 
     let app_handle_clone = app_handle.clone();
-    let stream = audio::play_wav_file(&wav_bytes, playback_device, move || {
+    let stream = audio::play_wav_path(&path, playback_device, wav, move || {
         crate::log_info!("History audio playback finished");
         let _ = app_handle_clone.emit("history-playback-finished", ());
     })
@@ -0,0 +1,72 @@
+use crate::app::commands::file_import::{transcribe_import, ImportAudio, ImportSource};
+use crate::app::commands::system_audio::listen_audio;
+use crate::audio::conversion::finalize_spooled_capture;
+use crate::audio::spool::{self, RecoverableRecording, SpoolChannel, SpoolFile};
+use crate::diarization::DiarizationResult;
+
+/// Recordings left on disk by sessions that ended before they were
+/// transcribed, e.g. because the app crashed mid-recording.
+#[tauri::command]
+pub fn get_recoverable_recordings() -> Result<Vec<RecoverableRecording>, String> {
+    spool::recoverable_recordings()
+}
+
+/// Transcribes a recovered recording through the import pipeline and saves
+/// it to history under the source it was captured from. The recording is
+/// removed once it is transcribed and kept if transcription fails.
+#[tauri::command]
+pub async fn transcribe_recovered_recording(
+    id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<DiarizationResult, String> {
+    let spools = spool::open_recoverable(&id)?;
+    let session_uuid = uuid::Uuid::new_v4().to_string();
+    crate::log_info!(
+        "[session:{}] transcribe_recovered_recording: {}",
+        &session_uuid[..8],
+        id
+    );
+
+    let (source, input) = recovered_audio(&spools);
+    let input = input.map_err(|e| format!("Failed to read recovered recording: {}", e));
+    let result = transcribe_import(&app_handle, &session_uuid, source, input).await;
+    if result.is_err() {
+        spools.into_iter().for_each(|(_, spool)| spool.keep());
+    }
+    result
+}
+
+fn recovered_audio(
+    spools: &[(SpoolChannel, SpoolFile)],
+) -> (ImportSource, Result<ImportAudio, String>) {
+    let finalize = |channel: SpoolChannel| {
+        spools
+            .iter()
+            .find(|(c, _)| *c == channel)
+            .map(|(_, spool)| {
+                finalize_spooled_capture(spool.path(), true).map_err(|e| e.to_string())
+            })
+            .transpose()
+    };
+    let system = match finalize(SpoolChannel::System) {
+        Ok(Some(system)) => system,
+        Ok(None) => {
+            let dictation = finalize(SpoolChannel::Dictation)
+                .and_then(|wav| wav.ok_or_else(|| "Nothing was captured".to_string()));
+            return (ImportSource::Recovered, dictation.map(ImportAudio::single));
+        }
+        Err(e) => return (ImportSource::System, Err(e)),
+    };
+    match finalize(SpoolChannel::Mic) {
+        Ok(Some(mic)) => (ImportSource::Call, listen_audio(system, Some(mic))),
+        Ok(None) => (ImportSource::System, listen_audio(system, None)),
+        Err(e) => (ImportSource::Call, Err(e)),
+    }
+}
+
+#[tauri::command]
+pub fn discard_recovered_recording(id: String) -> Result<(), String> {
+    crate::log_info!("Discarding recovered recording {}", id);
+    drop(spool::open_recoverable(&id)?);
+    Ok(())
+}
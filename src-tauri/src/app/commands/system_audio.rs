@@ -0,0 +1,118 @@
+use crate::app::commands::file_import::{transcribe_import, ImportAudio, ImportSource};
+use crate::audio::system_capture::{CapturedSystemAudio, MicChannel, SystemCapture};
+use crate::audio::WhisperFile;
+use crate::diarization::DiarizationResult;
+use crate::AppState;
+use tauri::Emitter;
+
+#[tauri::command]
+pub async fn start_system_listen(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let (monitor_id, mic, max_duration) = {
+        let config = state.config.lock().unwrap();
+        let mic = config.listen_include_mic.then(|| MicChannel {
+            device_id: config.audio_device.clone(),
+            sensitivity: config.input_sensitivity,
+        });
+        (
+            config.listen_device.clone(),
+            mic,
+            std::time::Duration::from_secs(config.max_recording_duration_minutes * 60),
+        )
+    };
+    crate::log_info!(
+        "Tauri Command: start_system_listen invoked (device={:?}, mic={})",
+        monitor_id,
+        mic.is_some()
+    );
+
+    let mut guard = state.system_capture.lock().unwrap();
+    if guard.is_some() {
+        return Err("System audio is already being captured".to_string());
+    }
+    let session_uuid = uuid::Uuid::new_v4().to_string();
+    *guard = Some(SystemCapture::start(
+        session_uuid,
+        monitor_id,
+        mic,
+        max_duration,
+    )?);
+    drop(guard);
+
+    let _ = app_handle.emit("system-listen-state", true);
+    Ok(())
+}
+
+/// Stops listen mode and transcribes what was captured through the file
+/// import pipeline, saved to history with source "system". With the
+/// microphone captured alongside, each side is transcribed separately and
+/// labelled "Me" and "Remote" (source "call").
+#[tauri::command]
+pub async fn stop_system_listen(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<DiarizationResult, String> {
+    let capture = state
+        .system_capture
+        .lock()
+        .unwrap()
+        .take()
+        .ok_or("System audio capture is not running")?;
+    let _ = app_handle.emit("system-listen-state", false);
+
+    let session_uuid = capture.session_uuid().to_string();
+    crate::log_info!(
+        "[session:{}] stop_system_listen: captured {:.1}s",
+        &session_uuid[..8],
+        capture.elapsed().as_secs_f64()
+    );
+    let source = if capture.has_mic() {
+        ImportSource::Call
+    } else {
+        ImportSource::System
+    };
+    // The spools stay on disk until the transcript is in history, so a crash
+    // in between still leaves the session recoverable.
+    let (audio, _spools) = match capture.finish() {
+        Ok(CapturedSystemAudio {
+            system,
+            mic,
+            spools,
+        }) => (listen_audio(system, mic), spools),
+        Err(e) => (Err(e), Vec::new()),
+    };
+    transcribe_import(&app_handle, &session_uuid, source, audio).await
+}
+
+/// The import input for a listen session: the system channel alone, or with
+/// the microphone as a call labelled "Me" and "Remote".
+pub fn listen_audio(system: WhisperFile, mic: Option<WhisperFile>) -> Result<ImportAudio, String> {
+    let Some(mic) = mic else {
+        return Ok(ImportAudio::single(system));
+    };
+    let wav = crate::audio::conversion::mix_whisper_files(&[&mic, &system])
+        .map_err(|e| format!("Failed to mix call channels: {}", e))?;
+    Ok(ImportAudio {
+        wav,
+        channels: vec![("Me", mic), ("Remote", system)],
+    })
+}
+
+#[tauri::command]
+pub async fn cancel_system_listen(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    crate::log_info!("Tauri Command: cancel_system_listen invoked");
+    if state.system_capture.lock().unwrap().take().is_some() {
+        let _ = app_handle.emit("system-listen-state", false);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn is_system_listen_active(state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.system_capture.lock().unwrap().is_some())
+}
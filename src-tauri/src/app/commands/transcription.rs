@@ -55,10 +55,11 @@ pub async fn download_model(
     // belongs to so the first dictation reuses a warm service.
     let config = state.config.lock().unwrap().clone();
 
+    let post_process_target = config.resolve_post_process_target();
     if config.post_process_enabled
-        && config.post_process_provider == crate::config::PostProcessProvider::Local
-        && config.post_process_engine == engine_name
-        && config.post_process_model == model_size
+        && post_process_target.provider == crate::config::PostProcessProvider::Local
+        && post_process_target.engine == engine_name
+        && post_process_target.model == model_size
     {
         crate::app::bootstrap::spawn_post_process_warmup(
             state.post_process_factory.clone(),
@@ -122,21 +123,6 @@ pub async fn delete_history_item(id: u64) -> Result<(), String> {
     history::delete_history_item(id).map_err(|error| error.to_string())
 }
 
-#[tauri::command]
-pub async fn get_history_audio(file_name: String) -> Result<Vec<u8>, String> {
-    let recordings_dir = crate::paths::debug_recordings_dir()?;
-    let path = recordings_dir.join(&file_name);
-    if path.exists() {
-        return std::fs::read(&path).map_err(|e| e.to_string());
-    }
-    let debug_dir = crate::paths::debug_dir()?;
-    let alt_path = debug_dir.join(&file_name);
-    if alt_path.exists() {
-        return std::fs::read(&alt_path).map_err(|e| e.to_string());
-    }
-    Err("Audio file not found".to_string())
-}
-
 #[tauri::command]
 pub async fn unload_model(state: tauri::State<'_, AppState>) -> Result<(), String> {
     state.engine_factory.unload_all();
@@ -0,0 +1,207 @@
+use crate::app::state::{AppState, SessionState};
+use crate::audio;
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// How often a specific configured device is looked for in the device list,
+/// and how often a failed reopen is retried.
+const PRESENCE_CHECK_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Payload of the `audio-device-status` event.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct AudioDeviceStatus {
+    /// "lost", "fallback", "recovered" or "reconnected".
+    pub state: &'static str,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum EngineHealth {
+    Missing,
+    Failed(String),
+    OnPreferred,
+    OnFallback,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Preferred,
+    Default,
+}
+
+/// Decides which device the engine should be reopened on, if any.
+/// `preferred_available` is `None` when the preferred device is the system
+/// default or its presence is unknown.
+fn next_target(health: &EngineHealth, preferred_available: Option<bool>) -> Option<Target> {
+    match (health, preferred_available) {
+        (EngineHealth::Missing | EngineHealth::Failed(_), Some(false)) => Some(Target::Default),
+        (EngineHealth::Missing | EngineHealth::Failed(_), _) => Some(Target::Preferred),
+        // Removed without a stream error (e.g. the sound server moved the
+        // stream elsewhere): open the default explicitly and say so.
+        (EngineHealth::OnPreferred, Some(false)) => Some(Target::Default),
+        (EngineHealth::OnFallback, Some(true)) => Some(Target::Preferred),
+        _ => None,
+    }
+}
+
+/// Runs for the lifetime of the app and keeps the persistent engine on a
+/// working device: when the stream dies or the configured device disappears
+/// it reopens on the configured device or the system default, and moves
+/// back once the configured device returns. Each change is announced with an
+/// `audio-device-status` event. The engine is only swapped while no
+/// recording or mic test is using it.
+pub fn spawn_watchdog(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        // The configured device we fell back from, while on the default.
+        let mut fallback_from: Option<Option<String>> = None;
+        let mut reported_lost = false;
+        let mut preferred_available: Option<bool> = None;
+        let mut last_presence_check: Option<Instant> = None;
+        let mut retry_after: Option<Instant> = None;
+
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let state = app_handle.state::<AppState>();
+            let (preferred, sensitivity) = {
+                let config = state.config.lock().unwrap();
+                (config.audio_device.clone(), config.input_sensitivity)
+            };
+            // Picking a device in settings rebuilds the engine on it.
+            if fallback_from
+                .as_ref()
+                .is_some_and(|from| *from != preferred)
+            {
+                fallback_from = None;
+            }
+
+            let health = match state.audio_engine.lock().unwrap().as_ref() {
+                None => EngineHealth::Missing,
+                Some(engine) => match engine.failure() {
+                    Some(reason) => EngineHealth::Failed(reason),
+                    None if fallback_from.is_some() => EngineHealth::OnFallback,
+                    None => EngineHealth::OnPreferred,
+                },
+            };
+            // An engine that was never opened is opened on demand by the
+            // first recording; only take over once it has gone wrong.
+            if health == EngineHealth::Missing && !reported_lost && fallback_from.is_none() {
+                continue;
+            }
+
+            let specific = preferred.as_deref().is_some_and(|id| id != "default");
+            let presence_due =
+                last_presence_check.is_none_or(|at| at.elapsed() >= PRESENCE_CHECK_INTERVAL);
+            if !specific {
+                preferred_available = None;
+            } else if presence_due {
+                last_presence_check = Some(Instant::now());
+                preferred_available = audio::get_input_devices()
+                    .ok()
+                    .map(|devices| devices.iter().any(|d| Some(&d.id) == preferred.as_ref()));
+            }
+
+            if let EngineHealth::Failed(reason) = &health {
+                if !reported_lost {
+                    crate::log_warn!("Audio device lost: {}", reason);
+                    emit(&app_handle, "lost", format!("Microphone lost: {}", reason));
+                    reported_lost = true;
+                }
+            }
+
+            let Some(target) = next_target(&health, preferred_available) else {
+                continue;
+            };
+            let busy = *state.session_state.lock().unwrap() == SessionState::Recording
+                || *state.is_mic_test_active.lock().unwrap();
+            if busy || retry_after.is_some_and(|at| Instant::now() < at) {
+                continue;
+            }
+
+            let device_id = match target {
+                Target::Preferred => preferred.clone(),
+                Target::Default => None,
+            };
+            // Release the old stream before opening a new one on the same
+            // hardware.
+            state.audio_engine.lock().unwrap().take();
+            match reopen(&state, device_id, sensitivity) {
+                Ok(()) => {
+                    let label = preferred.as_deref().unwrap_or("default");
+                    let (status, message) = match (target, specific) {
+                        (Target::Default, true) => {
+                            fallback_from = Some(preferred.clone());
+                            (
+                                "fallback",
+                                format!("'{}' is unavailable; using the default microphone", label),
+                            )
+                        }
+                        _ if fallback_from.take().is_some() => {
+                            ("reconnected", format!("Reconnected to '{}'", label))
+                        }
+                        _ => ("recovered", format!("Microphone reopened on '{}'", label)),
+                    };
+                    crate::log_info!("Audio device {}: {}", status, message);
+                    emit(&app_handle, status, message);
+                    reported_lost = false;
+                    retry_after = None;
+                }
+                Err(error) => {
+                    crate::log_warn!("Audio device reopen failed: {}", error);
+                    retry_after = Some(Instant::now() + PRESENCE_CHECK_INTERVAL);
+                    if !reported_lost {
+                        emit(
+                            &app_handle,
+                            "lost",
+                            format!("No microphone available: {}", error),
+                        );
+                        reported_lost = true;
+                    }
+                }
+            }
+        }
+    });
+}
+
+fn reopen(state: &AppState, device_id: Option<String>, sensitivity: f32) -> Result<(), String> {
+    let device = audio::lookup_device(device_id)?;
+    let engine = audio::PersistentAudioEngine::new(&device, sensitivity)?;
+    *state.cached_device.lock().unwrap() = Some(device);
+    *state.audio_engine.lock().unwrap() = Some(engine);
+    Ok(())
+}
+
+fn emit(app_handle: &tauri::AppHandle, state: &'static str, message: String) {
+    let _ = app_handle.emit("audio-device-status", AudioDeviceStatus { state, message });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_engine_reopens_on_the_preferred_device_or_the_default() {
+        let failed = EngineHealth::Failed("unplugged".to_string());
+        assert_eq!(next_target(&failed, None), Some(Target::Preferred));
+        assert_eq!(next_target(&failed, Some(true)), Some(Target::Preferred));
+        assert_eq!(next_target(&failed, Some(false)), Some(Target::Default));
+        assert_eq!(
+            next_target(&EngineHealth::Missing, Some(false)),
+            Some(Target::Default)
+        );
+    }
+
+    #[test]
+    fn fallback_returns_to_the_preferred_device_when_it_reappears() {
+        assert_eq!(next_target(&EngineHealth::OnFallback, Some(false)), None);
+        assert_eq!(
+            next_target(&EngineHealth::OnFallback, Some(true)),
+            Some(Target::Preferred)
+        );
+        assert_eq!(next_target(&EngineHealth::OnPreferred, Some(true)), None);
+        assert_eq!(
+            next_target(&EngineHealth::OnPreferred, Some(false)),
+            Some(Target::Default)
+        );
+    }
+}
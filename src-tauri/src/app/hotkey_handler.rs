@@ -1,29 +1,119 @@
-use crate::app::commands::recording::{cancel_session, start_recording, stop_recording};
+use crate::app::commands::recording::{cancel_session, start_recording_with_mode, stop_recording};
+use crate::app::recording_flow::SessionKind;
 use crate::app::state::{AppState, SessionState};
-use crate::config::HotkeyMode;
+use crate::config::{Config, DictationMode, HotkeyMode};
+use tauri_plugin_global_shortcut::Shortcut;
+
+/// Which configured shortcut fired. The primary hotkey dictates in the mode
+/// resolved from config; secondary hotkeys start a session in a fixed mode
+/// or, for `Command`, transform the selected text by voice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyAction {
+    Dictate,
+    CodeDictation,
+    Command,
+}
+
+impl HotkeyAction {
+    /// Shortcut id used when binding through the Wayland GlobalShortcuts portal.
+    pub fn portal_id(self) -> &'static str {
+        match self {
+            HotkeyAction::Dictate => "record",
+            HotkeyAction::CodeDictation => "code_dictation",
+            HotkeyAction::Command => "command_mode",
+        }
+    }
+
+    pub fn portal_description(self) -> &'static str {
+        match self {
+            HotkeyAction::Dictate => "Dictation Hotkey",
+            HotkeyAction::CodeDictation => "Code Dictation Hotkey",
+            HotkeyAction::Command => "Command Mode Hotkey",
+        }
+    }
+
+    pub fn from_portal_id(id: &str) -> Option<Self> {
+        [
+            HotkeyAction::Dictate,
+            HotkeyAction::CodeDictation,
+            HotkeyAction::Command,
+        ]
+        .into_iter()
+        .find(|action| action.portal_id() == id)
+    }
+
+    fn session_kind(self) -> SessionKind {
+        match self {
+            HotkeyAction::Dictate => SessionKind::Dictation(None),
+            HotkeyAction::CodeDictation => SessionKind::Dictation(Some(DictationMode::Code)),
+            HotkeyAction::Command => SessionKind::Command,
+        }
+    }
+}
+
+/// Secondary shortcuts registered alongside the primary hotkey. Empty
+/// strings mean the shortcut is disabled; a binding already taken by an
+/// earlier hotkey is skipped.
+pub fn secondary_hotkeys(config: &Config) -> Vec<(HotkeyAction, String)> {
+    let mut hotkeys: Vec<(HotkeyAction, String)> = Vec::new();
+    for (action, hotkey) in [
+        (HotkeyAction::CodeDictation, &config.code_dictation_hotkey),
+        (HotkeyAction::Command, &config.command_mode_hotkey),
+    ] {
+        let hotkey = hotkey.trim();
+        let taken = hotkey.eq_ignore_ascii_case(config.hotkey.trim())
+            || hotkeys
+                .iter()
+                .any(|(_, existing)| existing.eq_ignore_ascii_case(hotkey));
+        if !hotkey.is_empty() && !taken {
+            hotkeys.push((action, hotkey.to_string()));
+        }
+    }
+    hotkeys
+}
+
+/// Maps a plugin shortcut event (X11, Windows) back to the action it was
+/// registered for. Anything unrecognised is treated as the primary hotkey.
+pub fn action_for_shortcut(config: &Config, shortcut: &Shortcut) -> HotkeyAction {
+    secondary_hotkeys(config)
+        .into_iter()
+        .find(|(_, hotkey)| {
+            crate::hotkey::parse_hotkey_string(hotkey)
+                .map(|parsed| parsed == *shortcut)
+                .unwrap_or(false)
+        })
+        .map(|(action, _)| action)
+        .unwrap_or(HotkeyAction::Dictate)
+}
 
 /// Single owner for hotkey press semantics across every platform backend
 /// (Wayland portal, X11, Windows):
-/// - Idle: press starts a dictation session.
-/// - Recording: press stops and transcribes in Toggle mode; ignored in
-///   HoldToTalk mode (the user is still holding the keys).
+/// - Idle: press starts a session of the action's kind (dictation in a
+///   mode, or command mode).
+/// - Recording: press stops and transcribes in Toggle and VoiceActivated
+///   modes; ignored in HoldToTalk mode (the user is still holding the keys).
 /// - Transcribing/Typing: press cancels and discards the in-flight session.
-pub async fn handle_hotkey_press(state: tauri::State<'_, AppState>, app_handle: tauri::AppHandle) {
+pub async fn handle_hotkey_press(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    action: HotkeyAction,
+) {
     let hotkey_mode = state.config.lock().unwrap().hotkey_mode.clone();
     let session = *state.session_state.lock().unwrap();
 
     crate::log_info!(
-        "Hotkey press: session_state={:?}, hotkey_mode={:?}",
+        "Hotkey press: action={:?}, session_state={:?}, hotkey_mode={:?}",
+        action,
         session,
         hotkey_mode
     );
 
     match session {
         SessionState::Idle => {
-            let _ = start_recording(state, app_handle).await;
+            let _ = start_recording_with_mode(state, app_handle, action.session_kind()).await;
         }
         SessionState::Recording => {
-            if hotkey_mode == HotkeyMode::Toggle {
+            if hotkey_mode.stops_on_press() {
                 let _ = stop_recording(state).await;
             }
         }
@@ -34,7 +124,7 @@ pub async fn handle_hotkey_press(state: tauri::State<'_, AppState>, app_handle:
 }
 
 /// Single owner for hotkey release semantics: only HoldToTalk mode stops
-/// recording on release. Toggle mode ignores releases entirely.
+/// recording on release. The other modes ignore releases entirely.
 pub async fn handle_hotkey_release(state: tauri::State<'_, AppState>) {
     let hotkey_mode = state.config.lock().unwrap().hotkey_mode.clone();
     let session = *state.session_state.lock().unwrap();
@@ -43,3 +133,74 @@ pub async fn handle_hotkey_release(state: tauri::State<'_, AppState>) {
         let _ = stop_recording(state).await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secondary_hotkeys_skips_empty_and_duplicate_bindings() {
+        let mut config = Config::default();
+        assert!(secondary_hotkeys(&config).is_empty());
+
+        config.code_dictation_hotkey = config.hotkey.to_uppercase();
+        assert!(secondary_hotkeys(&config).is_empty());
+
+        config.code_dictation_hotkey = "ctrl+alt+c".to_string();
+        assert_eq!(
+            secondary_hotkeys(&config),
+            vec![(HotkeyAction::CodeDictation, "ctrl+alt+c".to_string())]
+        );
+    }
+
+    #[test]
+    fn secondary_hotkeys_includes_command_mode_unless_taken() {
+        let mut config = Config {
+            command_mode_hotkey: "ctrl+alt+e".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            secondary_hotkeys(&config),
+            vec![(HotkeyAction::Command, "ctrl+alt+e".to_string())]
+        );
+
+        config.code_dictation_hotkey = "Ctrl+Alt+E".to_string();
+        assert_eq!(
+            secondary_hotkeys(&config),
+            vec![(HotkeyAction::CodeDictation, "Ctrl+Alt+E".to_string())]
+        );
+    }
+
+    #[test]
+    fn action_for_shortcut_matches_code_hotkey() {
+        let config = Config {
+            code_dictation_hotkey: "ctrl+alt+c".to_string(),
+            ..Default::default()
+        };
+        let code = crate::hotkey::parse_hotkey_string("ctrl+alt+c").unwrap();
+        let primary = crate::hotkey::parse_hotkey_string(&config.hotkey).unwrap();
+        assert_eq!(
+            action_for_shortcut(&config, &code),
+            HotkeyAction::CodeDictation
+        );
+        assert_eq!(
+            action_for_shortcut(&config, &primary),
+            HotkeyAction::Dictate
+        );
+    }
+
+    #[test]
+    fn portal_ids_round_trip() {
+        for action in [
+            HotkeyAction::Dictate,
+            HotkeyAction::CodeDictation,
+            HotkeyAction::Command,
+        ] {
+            assert_eq!(
+                HotkeyAction::from_portal_id(action.portal_id()),
+                Some(action)
+            );
+        }
+        assert_eq!(HotkeyAction::from_portal_id("unknown"), None);
+    }
+}
@@ -1,7 +1,9 @@
 pub mod bootstrap;
 pub mod commands;
+pub mod device_watchdog;
 pub mod hotkey_handler;
 pub mod recording_flow;
 pub mod session_log;
 pub mod state;
 pub mod status;
+pub mod voice_activation;
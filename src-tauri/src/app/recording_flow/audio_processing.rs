@@ -1,48 +1,12 @@
+use crate::audio::WhisperFile;
+use crate::config::NoiseReductionProvider;
 use crate::diarization::DiarizationResult;
 use tauri::Manager;
 
 pub fn validate_audio_duration(
-    audio_data: &[u8],
+    audio_data: &WhisperFile,
 ) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
-    if audio_data.len() < 44 {
-        return Err("Audio file too small".into());
-    }
-    let sample_rate = u32::from_le_bytes([
-        audio_data[24],
-        audio_data[25],
-        audio_data[26],
-        audio_data[27],
-    ]);
-    let channels = u16::from_le_bytes([audio_data[22], audio_data[23]]);
-    let bits_per_sample = u16::from_le_bytes([audio_data[34], audio_data[35]]);
-
-    let mut data_size = 0u32;
-    let mut pos = 36;
-    while pos + 8 <= audio_data.len() {
-        let chunk_id = &audio_data[pos..pos + 4];
-        let chunk_size = u32::from_le_bytes([
-            audio_data[pos + 4],
-            audio_data[pos + 5],
-            audio_data[pos + 6],
-            audio_data[pos + 7],
-        ]);
-        if chunk_id == b"data" {
-            data_size = chunk_size;
-            break;
-        }
-        pos += 8 + chunk_size as usize;
-        if chunk_size % 2 == 1 {
-            pos += 1;
-        }
-    }
-
-    if data_size == 0 {
-        return Err("No data chunk".into());
-    }
-    let bytes_per_sample = (bits_per_sample / 8) as u32;
-    let bytes_per_second = sample_rate * channels as u32 * bytes_per_sample;
-    let duration_seconds = data_size as f64 / bytes_per_second as f64;
-
+    let duration_seconds = audio_data.duration_secs();
     crate::log_info!("Audio duration: {:.3}s", duration_seconds);
     if duration_seconds < 0.1 {
         return Err("Audio too short".into());
@@ -89,12 +53,33 @@ pub async fn run_diarization_for_recording(
     runner.diarize(&path_str, cluster_threshold).await
 }
 
-/// Run noise reduction on captured audio via the Python runner.
+/// Run noise reduction on captured audio with the configured provider,
+/// into a new file. The native providers stream the file in-process; the
+/// Python one goes through the runner.
 pub async fn run_noise_reduction(
     app_handle: &tauri::AppHandle,
-    audio_data: &[u8],
+    audio_data: &WhisperFile,
+    provider: NoiseReductionProvider,
     noise_reduction_strength: f32,
-) -> Result<Vec<u8>, String> {
+) -> Result<WhisperFile, String> {
+    if provider == NoiseReductionProvider::Python {
+        return run_python_noise_reduction(app_handle, audio_data, noise_reduction_strength).await;
+    }
+    let path = audio_data.path().to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        crate::audio::denoise::denoise_file(&path, provider, noise_reduction_strength)
+    })
+    .await
+    .map_err(|e| format!("Noise reduction task failed: {}", e))?
+}
+
+/// Run noise reduction on captured audio via the Python runner, which reads
+/// the file in place and hands back the path of the enhanced copy.
+async fn run_python_noise_reduction(
+    app_handle: &tauri::AppHandle,
+    audio_data: &WhisperFile,
+    noise_reduction_strength: f32,
+) -> Result<WhisperFile, String> {
     let app_state = app_handle.state::<crate::AppState>();
 
     let needs_start = {
@@ -124,31 +109,8 @@ pub async fn run_noise_reduction(
     }
     .ok_or("Python runner not available")?;
 
-    let temp_dir = crate::paths::temp_dir();
-    let _ = std::fs::create_dir_all(&temp_dir);
-    let input_path = temp_dir.join(format!(
-        "noise_input_{}.wav",
-        std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_nanos()
-    ));
-
-    std::fs::write(&input_path, audio_data)
-        .map_err(|e| format!("Failed to write temp audio for noise reduction: {}", e))?;
-
-    let path_str = input_path.to_string_lossy().to_string();
-    let result = runner.enhance(&path_str, noise_reduction_strength).await;
-
-    // Clean up input file regardless of outcome
-    let _ = std::fs::remove_file(&input_path);
-
-    let enhanced_path = result?;
-    let enhanced_data = std::fs::read(&enhanced_path)
-        .map_err(|e| format!("Failed to read enhanced audio: {}", e))?;
-
-    // Clean up enhanced file
-    let _ = std::fs::remove_file(&enhanced_path);
-
-    Ok(enhanced_data)
+    let path_str = audio_data.path().to_string_lossy().to_string();
+    let enhanced_path = runner.enhance(&path_str, noise_reduction_strength).await?;
+    WhisperFile::adopt(enhanced_path.into())
+        .map_err(|e| format!("Failed to read enhanced audio: {}", e))
 }
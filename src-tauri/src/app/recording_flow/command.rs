@@ -0,0 +1,257 @@
+//! Command mode: the dictation is an instruction ("make this more formal",
+//! "translate to German") applied to the text selected in the focused
+//! application, and the result is pasted over the selection.
+
+use super::SessionContext;
+use crate::app::state::SessionState;
+use crate::config::{Config, SelectionSource};
+use crate::typing;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// How long the focused application gets to answer the copy shortcut.
+const COPY_TIMEOUT: Duration = Duration::from_millis(600);
+const COPY_POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+/// A selection and the spoken instruction to apply to it.
+pub struct Command {
+    pub selection: String,
+    pub instruction: String,
+}
+
+/// Reads the selected text, either from PRIMARY or by sending the copy
+/// shortcut. The clipboard is restored afterwards.
+pub async fn capture_selection(app_handle: &AppHandle, config: &Config) -> Result<String, String> {
+    let selection = if cfg!(target_os = "linux")
+        && config.command_selection_source == SelectionSource::PrimarySelection
+    {
+        typing::read_primary_selection()
+    } else {
+        let saved = typing::save_clipboard();
+        // Clear first so a stale clipboard is never mistaken for the selection.
+        typing::clear_clipboard();
+        let copied = app_handle
+            .state::<crate::AppState>()
+            .display_backend
+            .send_copy_shortcut(app_handle)
+            .await;
+        let selection = match copied {
+            Ok(()) => wait_for_clipboard_text().await,
+            Err(error) => {
+                crate::log_warn!("Command mode: copy shortcut failed: {}", error);
+                None
+            }
+        };
+        typing::restore_clipboard(saved);
+        selection
+    };
+
+    selection
+        .filter(|text| !text.trim().is_empty())
+        .ok_or_else(|| "No text is selected".to_string())
+}
+
+async fn wait_for_clipboard_text() -> Option<String> {
+    let deadline = Instant::now() + COPY_TIMEOUT;
+    while Instant::now() < deadline {
+        tokio::time::sleep(COPY_POLL_INTERVAL).await;
+        if let Some(text) = typing::read_clipboard().filter(|text| !text.is_empty()) {
+            return Some(text);
+        }
+    }
+    None
+}
+
+/// The selection is the text being processed, so it takes the place of
+/// `{transcript}`; the instruction is written into the template itself.
+fn user_prompt_template(instruction: &str) -> String {
+    format!(
+        "Apply the instruction to the text inside <selection> tags. Output only the resulting text.\n\nInstruction: {}\n\n<selection>\n{{transcript}}\n</selection>",
+        instruction.replace("{transcript}", "transcript")
+    )
+}
+
+/// Models drop the whitespace around a selection, which would join the
+/// replacement onto its neighbours; put the selection's back.
+fn keep_surrounding_whitespace(selection: &str, output: &str) -> String {
+    let leading = &selection[..selection.len() - selection.trim_start().len()];
+    let trailing = &selection[selection.trim_end().len()..];
+    format!("{}{}{}", leading, output.trim(), trailing)
+}
+
+async fn run_instruction(
+    app_handle: &AppHandle,
+    config: &Config,
+    command: &Command,
+) -> Result<String, String> {
+    let post_process_factory = app_handle
+        .state::<crate::AppState>()
+        .post_process_factory
+        .clone();
+    crate::app::status::emit_status_to_frontend("Processing").await;
+    let mut attempts = crate::post_process::policy::Attempts::new(&post_process_factory, config);
+    while let Some(processor) = attempts.next().await {
+        match processor
+            .post_process(
+                &command.selection,
+                &config.command_system_prompt,
+                &user_prompt_template(&command.instruction),
+                config.resolve_max_output_tokens(),
+            )
+            .await
+        {
+            Ok(output) => return Ok(output),
+            Err(e) => attempts.failed(&e),
+        }
+    }
+    // The selection stays as it was, so the failure output does not apply.
+    let report = attempts.exhausted(crate::config::PostProcessFailureOutput::Raw);
+    Err(report
+        .attempts
+        .into_iter()
+        .rev()
+        .find_map(|attempt| attempt.error)
+        .unwrap_or_else(|| "Post-processing failed".to_string()))
+}
+
+async fn paste_over_selection(
+    app_handle: &AppHandle,
+    config: &Config,
+    text: &str,
+) -> Result<(), String> {
+    let saved_clipboard = typing::save_clipboard();
+    typing::copy_to_clipboard(text).map_err(|e| e.to_string())?;
+    tokio::time::sleep(Duration::from_millis(config.paste_delay_before_ms)).await;
+    let pasted = app_handle
+        .state::<crate::AppState>()
+        .display_backend
+        .send_paste_shortcut(app_handle, config.paste_shortcut)
+        .await;
+    tokio::time::sleep(Duration::from_millis(config.paste_delay_after_ms)).await;
+    typing::restore_clipboard(saved_clipboard);
+    pasted
+}
+
+/// Runs the instruction on the selection, pastes the result over it and
+/// records the session in history.
+pub async fn apply_instruction(
+    app_handle: &AppHandle,
+    session_state: &Arc<Mutex<SessionState>>,
+    session_token: &Arc<AtomicBool>,
+    config: &Config,
+    ctx: &SessionContext<'_>,
+    transcription_engine: &str,
+    command: Command,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    crate::log_info!(
+        "[session:{}] Command mode: \"{}\" on {} selected chars",
+        &ctx.session_uuid[..8],
+        command.instruction,
+        command.selection.len()
+    );
+    let result = if command.instruction.trim().is_empty() {
+        Err("No instruction was dictated".to_string())
+    } else {
+        run_instruction(app_handle, config, &command).await
+    };
+
+    let metadata = crate::history::HistoryMetadata {
+        instruction: Some(command.instruction.clone()),
+        agc: ctx.agc,
+        ..Default::default()
+    };
+    let record = |status: &str, text: &str, error_message: Option<&str>| {
+        let _ = crate::history::add_history_item(&crate::history::NewHistoryItem {
+            session_uuid: ctx.session_uuid,
+            status,
+            text,
+            raw_text: Some(command.selection.as_str()),
+            error_message,
+            segments: None,
+            audio_file: ctx.saved_audio_file,
+            duration_secs: Some(ctx.duration_secs),
+            engine: Some(transcription_engine),
+            source: Some("command"),
+            language: Some(ctx.lang_code_str),
+            prompt_name: None,
+            metadata: Some(&metadata),
+            limit: Some(ctx.history_limit),
+        });
+        if let Some(window) = app_handle.get_webview_window("main") {
+            let _ = window.emit("history-updated", ());
+        }
+    };
+
+    if session_token.load(Ordering::SeqCst) {
+        crate::log_info!(
+            "[session:{}] Session cancelled during command; selection left as is",
+            &ctx.session_uuid[..8]
+        );
+        record("cancelled", "", Some("Session cancelled during command"));
+        return Ok(());
+    }
+
+    let output = match result {
+        Ok(output) if !output.trim().is_empty() => {
+            keep_surrounding_whitespace(&command.selection, &output)
+        }
+        Ok(_) => {
+            record("failed", "", Some("Command produced no text"));
+            return Ok(());
+        }
+        Err(error) => {
+            crate::log_warn!("Command mode failed: {}", error);
+            record(
+                "failed",
+                "",
+                Some(format!("Command failed: {}", error).as_str()),
+            );
+            return Ok(());
+        }
+    };
+
+    {
+        let mut session = session_state.lock().unwrap();
+        *session = SessionState::Typing;
+    }
+    crate::app::status::emit_status_to_frontend("Typing").await;
+
+    match paste_over_selection(app_handle, config, &output).await {
+        Ok(()) => {
+            record("success", &output, None);
+            Ok(())
+        }
+        Err(error) => {
+            crate::log_warn!("Command mode: paste failed: {}", error);
+            record(
+                "failed",
+                &output,
+                Some(format!("Could not paste the result: {}", error).as_str()),
+            );
+            Err(error.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_embeds_instruction_and_keeps_one_placeholder() {
+        let template = user_prompt_template("make it {transcript} formal");
+        assert!(template.contains("Instruction: make it transcript formal"));
+        assert_eq!(template.matches("{transcript}").count(), 1);
+    }
+
+    #[test]
+    fn test_keeps_selection_whitespace() {
+        assert_eq!(
+            keep_surrounding_whitespace("  hello there\n", "Hello there.\n"),
+            "  Hello there.\n"
+        );
+        assert_eq!(keep_surrounding_whitespace("hi", " Hi! "), "Hi!");
+    }
+}
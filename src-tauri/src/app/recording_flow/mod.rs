@@ -1,13 +1,25 @@
 pub mod audio_processing;
+pub mod command;
 pub mod output;
+pub mod streaming;
 
 use crate::app::state::SessionState;
-use crate::config::Config;
+use crate::config::{Config, DictationMode};
 use crate::{audio, engine_factory, typing};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, Emitter, Manager};
 
+/// What a recording session does with the transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionKind {
+    /// Types the dictation, optionally in a fixed mode instead of the one
+    /// resolved from config.
+    Dictation(Option<DictationMode>),
+    /// Applies the dictation as an instruction to the selected text.
+    Command,
+}
+
 pub async fn record_and_transcribe(
     config: Arc<Mutex<Config>>,
     session_state: Arc<Mutex<SessionState>>,
@@ -15,6 +27,7 @@ pub async fn record_and_transcribe(
     app_handle: AppHandle,
     audio_engine: Arc<Mutex<Option<audio::PersistentAudioEngine>>>,
     engine_factory: Arc<engine_factory::EngineFactory>,
+    kind: SessionKind,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let result = record_and_transcribe_inner(
         &config,
@@ -23,6 +36,7 @@ pub async fn record_and_transcribe(
         &app_handle,
         audio_engine,
         &engine_factory,
+        kind,
     )
     .await;
     finish_session(&app_handle, &session_state, &session_token).await;
@@ -60,17 +74,18 @@ struct SessionContext<'a> {
     lang_code_str: &'a str,
     prompt_name: Option<&'a str>,
     history_limit: usize,
+    agc: Option<crate::audio::agc::AgcReport>,
 }
 
 async fn transcribe_full_audio(
     service: &(dyn crate::transcription::TranscriptionService + Send + Sync),
-    audio_data: &[u8],
+    audio_data: &audio::WhisperFile,
     lang_code: Option<&str>,
     prompt_hint: Option<&str>,
     ctx: &SessionContext<'_>,
     app_handle: &AppHandle,
 ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    match service.transcribe(audio_data, lang_code, prompt_hint).await {
+    match crate::transcription::transcribe_file(service, audio_data, lang_code, prompt_hint).await {
         Ok(text) => {
             crate::log_info!(
                 "[session:{}] Transcription received ({}): \"{}\"",
@@ -100,6 +115,7 @@ async fn transcribe_full_audio(
                 source: Some("mic"),
                 language: Some(ctx.lang_code_str),
                 prompt_name: ctx.prompt_name,
+                metadata: None,
                 limit: Some(ctx.history_limit),
             });
             if let Some(window) = app_handle.get_webview_window("main") {
@@ -117,13 +133,15 @@ async fn record_and_transcribe_inner(
     app_handle: &AppHandle,
     audio_engine: Arc<Mutex<Option<audio::PersistentAudioEngine>>>,
     engine_factory: &Arc<engine_factory::EngineFactory>,
+    kind: SessionKind,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let session_uuid = uuid::Uuid::new_v4().to_string();
     crate::log_info!("[session:{}] Recording flow started", &session_uuid[..8]);
 
-    let (post_roll_ms, max_recording_duration, current_config, history_limit) = {
+    let (pre_roll_ms, post_roll_ms, max_recording_duration, current_config, history_limit) = {
         let config_guard = config.lock().unwrap();
         (
+            config_guard.resolve_pre_roll_ms(),
             config_guard.post_roll_ms,
             std::time::Duration::from_secs(
                 config_guard
@@ -135,16 +153,117 @@ async fn record_and_transcribe_inner(
         )
     };
     let lang_code_str = current_config.language.clone();
-    let prompt_hint = current_config.resolve_prompt_hint();
     let prompt_name = current_config.resolve_post_process_prompt_name();
 
-    let audio_data = audio::record_audio_while_flag(
+    // Focus can move while we record and transcribe, so capture the target
+    // application now (only when a prompt that may run asks for it).
+    let focused_app = if current_config.post_process_enabled
+        && kind != SessionKind::Command
+        && [
+            current_config.resolve_post_process_prompt(),
+            current_config.resolve_user_prompt_template(),
+        ]
+        .into_iter()
+        .chain(
+            current_config
+                .post_process_prompts
+                .iter()
+                .filter(|p| !p.triggers.is_empty())
+                .flat_map(|p| {
+                    [
+                        p.prompt.clone(),
+                        p.user_prompt_template.clone().unwrap_or_default(),
+                    ]
+                }),
+        )
+        .any(|prompt| crate::post_process::template::references(&prompt, "focused_app"))
+    {
+        app_handle
+            .state::<crate::AppState>()
+            .display_backend
+            .focused_application()
+    } else {
+        None
+    };
+    let dictation_mode = match kind {
+        SessionKind::Dictation(mode) => {
+            mode.unwrap_or_else(|| current_config.resolve_dictation_mode())
+        }
+        SessionKind::Command => DictationMode::Prose,
+    };
+    crate::log_info!(
+        "[session:{}] Session kind: {:?}, dictation mode: {:?}",
+        &session_uuid[..8],
+        kind,
+        dictation_mode
+    );
+
+    // Sticky continuation: a recent prose output seeds the transcription
+    // prompt and decides how this dictation joins onto it. An instruction
+    // is not a continuation of anything.
+    let sticky_continuation = current_config.continuation_enabled
+        && dictation_mode == DictationMode::Prose
+        && kind != SessionKind::Command;
+    let continuation_tail = if sticky_continuation {
+        let window = std::time::Duration::from_secs(current_config.continuation_window_secs);
+        app_handle
+            .state::<crate::AppState>()
+            .last_output_tail
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|tail| tail.within(window).map(str::to_string))
+    } else {
+        None
+    };
+    let prompt_hint = crate::continuation::extend_prompt_hint(
+        current_config.resolve_prompt_hint(),
+        continuation_tail.as_deref(),
+    );
+
+    let agc_settings = current_config
+        .agc_enabled
+        .then_some(crate::audio::agc::AgcSettings {
+            target_dbfs: current_config.agc_target_dbfs,
+            max_gain_db: current_config.agc_max_gain_db,
+        });
+    let captured = audio::record_audio_while_flag(
         session_state,
         audio_engine,
+        pre_roll_ms,
         post_roll_ms,
         max_recording_duration,
+        agc_settings,
+        audio::spool::session_spool_path(&session_uuid)?,
     )
     .await?;
+    crate::log_info!(
+        "[session:{}] Captured {:.1}s at {}Hz",
+        &session_uuid[..8],
+        captured.samples as f64 / captured.sample_rate as f64,
+        captured.sample_rate
+    );
+    // Finalizing reads the whole spool, so it runs off the async runtime.
+    let spool_path = captured.spool.path().to_path_buf();
+    let normalize = captured.agc.is_none();
+    let finalized = match tokio::task::spawn_blocking(move || {
+        audio::conversion::finalize_spooled_capture(&spool_path, normalize)
+    })
+    .await
+    {
+        Ok(finalized) => finalized,
+        Err(e) => Err(e.into()),
+    };
+    let audio_data = match finalized {
+        Ok(audio_data) => audio_data,
+        Err(e) => {
+            captured.spool.keep();
+            return Err(e);
+        }
+    };
+    // The spooled capture is removed when the session ends; if the app dies
+    // before then it is offered for recovery on the next start.
+    let _spool = captured.spool;
 
     // Capture has ended, however it ended (release, toggle stop, cancel, or
     // the max-duration auto-stop): the recording phase is over.
@@ -173,6 +292,7 @@ async fn record_and_transcribe_inner(
             source: Some("mic"),
             language: Some(&lang_code_str),
             prompt_name: prompt_name.as_deref(),
+            metadata: None,
             limit: Some(history_limit),
         });
         if let Some(window) = app_handle.get_webview_window("main") {
@@ -181,11 +301,40 @@ async fn record_and_transcribe_inner(
         return Ok(());
     }
 
-    crate::app::status::emit_status_to_frontend("Transcribing").await;
+    // Command mode reads the selection as soon as the hotkey is released,
+    // before the user has a chance to click elsewhere.
+    let selection = if kind == SessionKind::Command {
+        match command::capture_selection(app_handle, &current_config).await {
+            Ok(selection) => Some(selection),
+            Err(error) => {
+                crate::log_info!("[session:{}] Command mode: {}", &session_uuid[..8], error);
+                let _ = crate::history::add_history_item(&crate::history::NewHistoryItem {
+                    session_uuid: &session_uuid,
+                    status: "failed",
+                    text: "",
+                    raw_text: None,
+                    error_message: Some(&format!("Command mode: {}", error)),
+                    segments: None,
+                    audio_file: None,
+                    duration_secs: None,
+                    engine: None,
+                    source: Some("command"),
+                    language: Some(&lang_code_str),
+                    prompt_name: None,
+                    metadata: None,
+                    limit: Some(history_limit),
+                });
+                if let Some(window) = app_handle.get_webview_window("main") {
+                    let _ = window.emit("history-updated", ());
+                }
+                return Ok(());
+            }
+        }
+    } else {
+        None
+    };
 
-    if audio_data.is_empty() {
-        return Ok(());
-    }
+    crate::app::status::emit_status_to_frontend("Transcribing").await;
 
     let duration_secs = match audio_processing::validate_audio_duration(&audio_data) {
         Ok(d) => d,
@@ -208,6 +357,7 @@ async fn record_and_transcribe_inner(
                 source: Some("mic"),
                 language: Some(&lang_code_str),
                 prompt_name: prompt_name.as_deref(),
+                metadata: None,
                 limit: Some(history_limit),
             });
             if let Some(window) = app_handle.get_webview_window("main") {
@@ -218,30 +368,24 @@ async fn record_and_transcribe_inner(
     };
 
     let saved_audio_file = if current_config.enable_recording_logs {
-        match crate::paths::debug_recordings_dir() {
-            Ok(dir) => {
-                let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-                let file_name = format!("recording_{}_{}.wav", timestamp, &session_uuid[..8]);
-                let file_path = dir.join(&file_name);
-                if let Err(e) = std::fs::write(&file_path, &audio_data) {
-                    crate::log_warn!(
-                        "[session:{}] Failed to save debug recording: {}",
-                        &session_uuid[..8],
-                        e
-                    );
-                    None
-                } else {
-                    crate::log_info!(
-                        "[session:{}] Debug recording saved: {:?}",
-                        &session_uuid[..8],
-                        file_path
-                    );
-                    Some(file_name)
-                }
+        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+        let file_stem = format!("recording_{}_{}", timestamp, &session_uuid[..8]);
+        match audio::history_audio::save_recording(
+            &file_stem,
+            &audio_data,
+            current_config.recording_audio_format,
+        ) {
+            Ok(file_name) => {
+                crate::log_info!(
+                    "[session:{}] Debug recording saved: {}",
+                    &session_uuid[..8],
+                    file_name
+                );
+                Some(file_name)
             }
             Err(e) => {
                 crate::log_warn!(
-                    "[session:{}] Failed to get debug recordings directory: {}",
+                    "[session:{}] Failed to save debug recording: {}",
                     &session_uuid[..8],
                     e
                 );
@@ -270,6 +414,7 @@ async fn record_and_transcribe_inner(
             source: Some("mic"),
             language: Some(&lang_code_str),
             prompt_name: prompt_name.as_deref(),
+            metadata: None,
             limit: Some(history_limit),
         });
         if let Some(window) = app_handle.get_webview_window("main") {
@@ -298,16 +443,18 @@ async fn record_and_transcribe_inner(
         match audio_processing::run_noise_reduction(
             app_handle,
             &audio_data,
+            current_config.noise_reduction_provider,
             current_config.noise_reduction_strength,
         )
         .await
         {
             Ok(enhanced) => {
                 crate::log_info!(
-                    "[session:{}] Noise reduction applied ({} bytes -> {} bytes)",
+                    "[session:{}] Noise reduction applied ({:?}, {} samples -> {} samples)",
                     &session_uuid[..8],
-                    audio_data.len(),
-                    enhanced.len()
+                    current_config.noise_reduction_provider,
+                    audio_data.samples(),
+                    enhanced.samples()
                 );
                 enhanced
             }
@@ -346,6 +493,7 @@ async fn record_and_transcribe_inner(
                 source: Some("mic"),
                 language: Some(&lang_code_str),
                 prompt_name: prompt_name.as_deref(),
+                metadata: None,
                 limit: Some(history_limit),
             });
             if let Some(window) = app_handle.get_webview_window("main") {
@@ -362,142 +510,114 @@ async fn record_and_transcribe_inner(
         lang_code_str: &lang_code_str,
         prompt_name: prompt_name.as_deref(),
         history_limit,
+        agc: captured.agc,
     };
 
     // ── Transcription: per-segment or full-file ──
     let mut diar_segments: Vec<crate::diarization::Segment> = Vec::new();
     let text = if current_config.diarization_enabled_recording {
-        // Save the recorded audio to a temp file for diarization
-        let temp_dir = crate::paths::temp_dir();
-        let _ = std::fs::create_dir_all(&temp_dir);
-        let temp_path = temp_dir.join(format!(
-            "recording_{}.wav",
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_nanos()
-        ));
-        if let Err(e) = std::fs::write(&temp_path, &audio_data) {
-            crate::log_warn!("Failed to save temp audio for diarization: {}", e);
-            // Fall back to full-file transcription
-            transcribe_full_audio(
-                service.as_ref(),
-                &audio_data,
-                lang_code,
-                prompt_hint.as_deref(),
-                &session_ctx,
-                app_handle,
-            )
-            .await?
-        } else {
-            let diar_result = audio_processing::run_diarization_for_recording(
-                app_handle,
-                &temp_path,
-                current_config.diarization_cluster_threshold,
-            )
-            .await;
-            let _ = std::fs::remove_file(&temp_path);
-
-            match diar_result {
-                Ok(diar) if !diar.segments.is_empty() => {
-                    crate::log_info!(
-                        "Diarization returned {} segments from {}",
-                        diar.segments.len(),
-                        diar.provider
-                    );
+        let diar_result = audio_processing::run_diarization_for_recording(
+            app_handle,
+            audio_data.path(),
+            current_config.diarization_cluster_threshold,
+        )
+        .await;
 
-                    let mut segment_pairs: Vec<(Option<String>, String)> = Vec::new();
-
-                    for seg in &diar.segments {
-                        let start = seg.start_sec.unwrap_or(0.0);
-                        let end = seg.end_sec.unwrap_or(1000000.0);
-
-                        let seg_wav = match audio::extract_segment_wav(&audio_data, start, end) {
-                            Ok(w) => w,
-                            Err(e) => {
-                                crate::log_warn!("Failed to extract segment, skipping: {}", e);
-                                continue;
-                            }
-                        };
-
-                        let seg_text = match service
-                            .transcribe(&seg_wav, lang_code, prompt_hint.as_deref())
-                            .await
-                        {
-                            Ok(t) => t,
-                            Err(e) => {
-                                crate::log_warn!("Segment transcription failed, skipping: {}", e);
-                                continue;
-                            }
-                        };
-                        crate::log_info!(
-                            "Segment [{}]: \"{}\"",
-                            seg.speaker.as_deref().unwrap_or("?"),
-                            seg_text
-                        );
-                        segment_pairs.push((seg.speaker.clone(), seg_text));
-                    }
+        match diar_result {
+            Ok(diar) if !diar.segments.is_empty() => {
+                crate::log_info!(
+                    "Diarization returned {} segments from {}",
+                    diar.segments.len(),
+                    diar.provider
+                );
 
-                    if segment_pairs.is_empty() {
-                        String::new()
-                    } else {
-                        let unique_speakers: std::collections::HashSet<Option<&str>> =
-                            segment_pairs.iter().map(|(s, _)| s.as_deref()).collect();
-
-                        if unique_speakers.len() <= 1 {
-                            segment_pairs
-                                .iter()
-                                .map(|(_, t)| t.as_str())
-                                .collect::<Vec<_>>()
-                                .join(" ")
-                        } else {
-                            diar_segments = segment_pairs
-                                .iter()
-                                .map(|(s, t)| crate::diarization::Segment {
-                                    speaker: s.clone(),
-                                    text: t.clone(),
-                                    start_sec: None,
-                                    end_sec: None,
-                                })
-                                .collect();
-
-                            segment_pairs
-                                .iter()
-                                .map(|(s, t)| {
-                                    format!("[{}] {}", s.as_deref().unwrap_or("Speaker"), t)
-                                })
-                                .collect::<Vec<_>>()
-                                .join("\n")
+                let mut segment_pairs: Vec<(Option<String>, String)> = Vec::new();
+
+                for seg in &diar.segments {
+                    let start = seg.start_sec.unwrap_or(0.0);
+                    let end = seg.end_sec.unwrap_or(1000000.0);
+
+                    let seg_wav = match audio_data.segment_wav(start, end) {
+                        Ok(w) => w,
+                        Err(e) => {
+                            crate::log_warn!("Failed to extract segment, skipping: {}", e);
+                            continue;
                         }
-                    }
-                }
-                Ok(_) => {
-                    crate::log_warn!(
-                        "Diarization returned 0 segments — transcribing full recording"
+                    };
+
+                    let seg_text = match service
+                        .transcribe(&seg_wav, lang_code, prompt_hint.as_deref())
+                        .await
+                    {
+                        Ok(t) => t,
+                        Err(e) => {
+                            crate::log_warn!("Segment transcription failed, skipping: {}", e);
+                            continue;
+                        }
+                    };
+                    crate::log_info!(
+                        "Segment [{}]: \"{}\"",
+                        seg.speaker.as_deref().unwrap_or("?"),
+                        seg_text
                     );
-                    transcribe_full_audio(
-                        service.as_ref(),
-                        &audio_data,
-                        lang_code,
-                        prompt_hint.as_deref(),
-                        &session_ctx,
-                        app_handle,
-                    )
-                    .await?
+                    segment_pairs.push((seg.speaker.clone(), seg_text));
                 }
-                Err(e) => {
-                    crate::log_warn!("Diarization failed, transcribing full recording: {}", e);
-                    transcribe_full_audio(
-                        service.as_ref(),
-                        &audio_data,
-                        lang_code,
-                        prompt_hint.as_deref(),
-                        &session_ctx,
-                        app_handle,
-                    )
-                    .await?
+
+                if segment_pairs.is_empty() {
+                    String::new()
+                } else {
+                    let unique_speakers: std::collections::HashSet<Option<&str>> =
+                        segment_pairs.iter().map(|(s, _)| s.as_deref()).collect();
+
+                    if unique_speakers.len() <= 1 {
+                        segment_pairs
+                            .iter()
+                            .map(|(_, t)| t.as_str())
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    } else {
+                        diar_segments = segment_pairs
+                            .iter()
+                            .map(|(s, t)| crate::diarization::Segment {
+                                speaker: s.clone(),
+                                text: t.clone(),
+                                start_sec: None,
+                                end_sec: None,
+                            })
+                            .collect();
+
+                        segment_pairs
+                            .iter()
+                            .map(|(s, t)| format!("[{}] {}", s.as_deref().unwrap_or("Speaker"), t))
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    }
                 }
             }
+            Ok(_) => {
+                crate::log_warn!("Diarization returned 0 segments — transcribing full recording");
+                transcribe_full_audio(
+                    service.as_ref(),
+                    &audio_data,
+                    lang_code,
+                    prompt_hint.as_deref(),
+                    &session_ctx,
+                    app_handle,
+                )
+                .await?
+            }
+            Err(e) => {
+                crate::log_warn!("Diarization failed, transcribing full recording: {}", e);
+                transcribe_full_audio(
+                    service.as_ref(),
+                    &audio_data,
+                    lang_code,
+                    prompt_hint.as_deref(),
+                    &session_ctx,
+                    app_handle,
+                )
+                .await?
+            }
         }
     } else {
         transcribe_full_audio(
@@ -518,71 +638,263 @@ async fn record_and_transcribe_inner(
         &current_config.custom_filler_words,
     );
 
-    // Save the raw (pre-post-process) text for history display
-    let raw_text = if current_config.post_process_enabled {
+    // Snap near-miss spellings of dictionary terms to their canonical form
+    let text = crate::dictionary::apply_dictionary_correction(&text, &current_config);
+
+    if let Some(selection) = selection {
+        return command::apply_instruction(
+            app_handle,
+            session_state,
+            session_token,
+            &current_config,
+            &session_ctx,
+            service.service_name(),
+            command::Command {
+                selection,
+                instruction: text,
+            },
+        )
+        .await;
+    }
+
+    // Save the raw (pre-post-process, unmasked) text for history display
+    let raw_text = if current_config.post_process_enabled || current_config.profanity_filter_enabled
+    {
         Some(text.clone())
     } else {
         None
     };
 
+    // A leading trigger phrase selects a preset for this session only. The
+    // dictation mode was fixed when recording started and stays as it is.
+    let trigger = if current_config.post_process_enabled {
+        crate::post_process::trigger::match_trigger(&text, &current_config.post_process_prompts)
+            .map(|found| {
+                (
+                    found.prompt.id.clone(),
+                    found.prompt.name.clone(),
+                    found.text,
+                )
+            })
+    } else {
+        None
+    };
+    let (text, current_config, prompt_name) = match trigger {
+        Some((prompt_id, name, stripped)) => {
+            crate::log_info!(
+                "[session:{}] Trigger phrase selected prompt \"{}\"",
+                &session_uuid[..8],
+                name
+            );
+            let mut session_config = current_config.clone();
+            session_config.post_process_selected_prompt_id = Some(prompt_id);
+            let prompt_name = session_config.resolve_post_process_prompt_name();
+            (stripped, session_config, prompt_name)
+        }
+        None => (text, current_config, prompt_name),
+    };
+
+    // Mask before post-processing so the provider never sees masked terms
+    let text = crate::profanity::apply_profanity_filter(&text, &current_config);
+
+    // Streaming can type finished sentences while the model is still
+    // generating; that only works when nothing transforms the whole text
+    // afterwards, i.e. Prose typed through the hardware typing engine.
+    let early_typing = (current_config.post_process_streaming
+        && current_config.post_process_stream_typing
+        && dictation_mode == DictationMode::Prose
+        && current_config.output_method == crate::config::OutputMethod::Typewriter)
+        .then(|| streaming::EarlyTyping {
+            continuation_tail: continuation_tail.clone(),
+            typing_speed: current_config.typing_speed_interval,
+            hold_duration: current_config.key_press_duration_ms,
+        });
+    let mut early_typed: Option<String> = None;
+    let mut history_metadata = crate::history::HistoryMetadata {
+        agc: session_ctx.agc,
+        ..Default::default()
+    };
+
     let text = if !text.trim().is_empty() && current_config.post_process_enabled {
         crate::log_info!("Post-processing transcription...");
         let post_process_factory = app_handle
             .state::<crate::AppState>()
             .post_process_factory
             .clone();
-        match post_process_factory.get_service(&current_config).await {
-            Ok(processor) => {
-                crate::app::status::emit_status_to_frontend("Processing").await;
-                match processor
-                    .post_process(
-                        &text,
-                        &current_config.resolve_post_process_prompt(),
-                        &current_config.resolve_user_prompt_template(),
-                        current_config.resolve_max_output_tokens(),
-                    )
-                    .await
-                {
-                    Ok(cleaned) => {
-                        crate::log_info!(
-                            "Post-processed ({}): \"{}\"",
-                            processor.service_name(),
-                            cleaned
-                        );
-                        cleaned
+        crate::app::status::emit_status_to_frontend("Processing").await;
+        let variables =
+            crate::post_process::template::PromptVariables::gather(&current_config, focused_app);
+        let prompts =
+            crate::post_process::template::render_selected_prompts(&current_config, &variables);
+        // Structured presets and long dictations need the whole output
+        // before anything can be shown, so they do not stream.
+        let structured =
+            crate::post_process::structured::StructuredPreset::resolve(&current_config);
+
+        let mut attempts =
+            crate::post_process::policy::Attempts::new(&post_process_factory, &current_config);
+        let mut succeeded = None;
+        while let Some(processor) = attempts.next().await {
+            let result = if let Some(preset) = &structured {
+                crate::post_process::structured::post_process(
+                    &processor,
+                    &text,
+                    &prompts,
+                    preset,
+                    &current_config,
+                )
+                .await
+                .map(|output| {
+                    history_metadata.structured = output.value;
+                    output.text
+                })
+            } else if current_config.post_process_streaming
+                && !crate::post_process::chunked::needs_chunking(&text, &current_config)
+            {
+                let outcome = streaming::stream_post_process(
+                    app_handle,
+                    &processor,
+                    &text,
+                    &prompts,
+                    &current_config,
+                    session_token,
+                    early_typing.clone(),
+                )
+                .await;
+                early_typed = outcome.typed;
+                outcome.result
+            } else {
+                crate::post_process::chunked::post_process(
+                    &processor,
+                    &text,
+                    &prompts,
+                    &current_config,
+                    |_| {},
+                )
+                .await
+                .map(|output| {
+                    if output.chunks > 1 {
+                        history_metadata.chunks = Some(output.chunks);
                     }
-                    Err(e) => {
-                        crate::log_warn!("Post-processing failed, using raw text: {}", e);
-                        // A network error from the local sidecar means the
-                        // process is gone or unreachable; drop it so the next
-                        // dictation respawns a fresh one.
-                        if matches!(e, crate::post_process::PostProcessError::Network(_)) {
-                            post_process_factory.invalidate_local();
-                        }
-                        text
+                    output.text
+                })
+            };
+            match result {
+                Ok(cleaned) => {
+                    succeeded = Some((processor, cleaned));
+                    break;
+                }
+                Err(crate::post_process::PostProcessError::Cancelled) => break,
+                Err(e) => {
+                    attempts.failed(&e);
+                    // Text typed while streaming cannot be taken back, so
+                    // another attempt would only type it twice.
+                    if early_typed.is_some() || session_token.load(Ordering::SeqCst) {
+                        break;
                     }
                 }
             }
-            Err(e) => {
-                crate::log_warn!(
-                    "Could not create post-process service, using raw text: {}",
-                    e
+        }
+
+        match succeeded {
+            Some((processor, cleaned)) => {
+                history_metadata.post_process = Some(attempts.succeeded());
+                crate::log_info!(
+                    "Post-processed ({}): \"{}\"",
+                    processor.service_name(),
+                    cleaned
                 );
-                text
+                if early_typed.is_some() {
+                    // Already on screen, so the guardrail can only record
+                    // what it would have rejected.
+                    if current_config.resolve_guardrail_enabled() {
+                        history_metadata.guardrail =
+                            crate::post_process::guardrail::check(&text, &cleaned)
+                                .map(|reason| format!("typed while streaming: {}", reason));
+                    }
+                    cleaned
+                } else {
+                    let guarded = crate::post_process::guardrail::enforce(
+                        &processor,
+                        &text,
+                        cleaned,
+                        &prompts,
+                        &current_config,
+                    )
+                    .await;
+                    history_metadata.guardrail = guarded.note;
+                    guarded.text
+                }
+            }
+            None => {
+                let failure_output = current_config.post_process_failure_output;
+                history_metadata.post_process = Some(attempts.exhausted(failure_output));
+                match failure_output {
+                    crate::config::PostProcessFailureOutput::Raw => {
+                        crate::log_warn!("Post-processing failed, using raw text");
+                        text
+                    }
+                    crate::config::PostProcessFailureOutput::Discard => {
+                        crate::log_warn!("Post-processing failed, discarding output");
+                        String::new()
+                    }
+                }
             }
         }
     } else {
         text
     };
 
-    let text = typing::normalize_for_typing(&text);
+    // Text typed while post-processing streamed already went through the
+    // stages below piece by piece, and what is on screen cannot change.
+    // Markdown numbering only advances once the output is actually delivered.
+    let mut next_markdown_session = None;
+    let text = if let Some(typed) = &early_typed {
+        crate::log_info!(
+            "[session:{}] {} chars typed while post-processing streamed",
+            &session_uuid[..8],
+            typed.len()
+        );
+        typed.clone()
+    } else {
+        // The model can restore a masked word from context, so mask its output too
+        let text = crate::profanity::apply_profanity_filter(&text, &current_config);
+
+        let text = match dictation_mode {
+            DictationMode::Prose => text,
+            DictationMode::Code => crate::text_cleanup::apply_code_dictation(&text),
+            DictationMode::Markdown => {
+                let session = *app_handle
+                    .state::<crate::AppState>()
+                    .markdown_session
+                    .lock()
+                    .unwrap();
+                let (formatted, next) = crate::markdown::format_markdown(&text, &session);
+                next_markdown_session = Some(next);
+                formatted
+            }
+        };
+
+        let text = typing::normalize_for_typing(&text);
+
+        // Only a dictation that continues an earlier one loses a dangling
+        // period; a standalone "I went to the." is left as transcribed.
+        match continuation_tail.as_deref() {
+            Some(tail) if !text.is_empty() => {
+                let text = crate::continuation::trim_dangling_period(&text);
+                crate::continuation::continue_from(&text, Some(tail))
+            }
+            _ => text,
+        }
+    };
 
-    // Apply trailing space if configured
+    // Apply trailing space if configured. Continuation adds the separating
+    // space in front of the next dictation instead, so it owns the join.
     let (append_trailing_space, auto_submit) = {
         let config_guard = config.lock().unwrap();
         (config_guard.append_trailing_space, config_guard.auto_submit)
     };
-    let output_text = if append_trailing_space && !text.is_empty() {
+    let output_text = if append_trailing_space && !sticky_continuation && !text.is_empty() {
         format!("{} ", text)
     } else {
         text
@@ -606,6 +918,7 @@ async fn record_and_transcribe_inner(
             source: Some("mic"),
             language: Some(&lang_code_str),
             prompt_name: prompt_name.as_deref(),
+            metadata: None,
             limit: Some(history_limit),
         });
         if let Some(window) = app_handle.get_webview_window("main") {
@@ -614,6 +927,24 @@ async fn record_and_transcribe_inner(
         return Ok(());
     }
 
+    {
+        let state = app_handle.state::<crate::AppState>();
+        let mut last_output_tail = state.last_output_tail.lock().unwrap();
+        if !sticky_continuation {
+            *last_output_tail = None;
+        } else if !output_text.trim().is_empty() {
+            *last_output_tail = Some(crate::continuation::OutputTail::new(&output_text));
+        }
+    }
+
+    if let Some(next) = next_markdown_session {
+        *app_handle
+            .state::<crate::AppState>()
+            .markdown_session
+            .lock()
+            .unwrap() = next;
+    }
+
     output::deliver_output(
         app_handle,
         session_state,
@@ -628,6 +959,9 @@ async fn record_and_transcribe_inner(
             audio_file: saved_audio_file,
             duration_secs: Some(duration_secs),
             engine: Some(service.service_name().to_string()),
+            prompt_name,
+            metadata: history_metadata,
+            typed_prefix_len: early_typed.as_ref().map_or(0, String::len),
         },
     )
     .await
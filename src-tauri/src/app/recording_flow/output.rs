@@ -14,6 +14,13 @@ pub struct OutputPayload {
     pub audio_file: Option<String>,
     pub duration_secs: Option<f64>,
     pub engine: Option<String>,
+    /// The post-process preset actually used, which a trigger phrase may
+    /// have switched away from the configured one.
+    pub prompt_name: Option<String>,
+    pub metadata: history::HistoryMetadata,
+    /// Bytes at the start of `output_text` that were already typed while
+    /// post-processing streamed.
+    pub typed_prefix_len: usize,
 }
 
 pub async fn deliver_output(
@@ -32,6 +39,9 @@ pub async fn deliver_output(
         audio_file,
         duration_secs,
         engine,
+        prompt_name,
+        metadata,
+        typed_prefix_len,
     } = payload;
     let (
         typing_speed,
@@ -44,7 +54,6 @@ pub async fn deliver_output(
         paste_shortcut,
         history_limit,
         language,
-        prompt_name,
     ) = {
         let config_guard = config.lock().unwrap();
         (
@@ -58,18 +67,25 @@ pub async fn deliver_output(
             config_guard.paste_shortcut,
             config_guard.history_limit,
             config_guard.language.clone(),
-            config_guard.resolve_post_process_prompt_name(),
         )
     };
 
     if output_text.trim().is_empty() {
         crate::log_info!("Transcription was empty, skipping output delivery.");
+        let discarded = metadata
+            .post_process
+            .as_ref()
+            .is_some_and(|report| report.outcome == history::PostProcessOutcome::Discarded);
         let _ = history::add_history_item(&history::NewHistoryItem {
             session_uuid: &session_uuid,
             status: "empty",
             text: "",
             raw_text: raw_text.as_deref(),
-            error_message: Some("Transcription was empty or contained no speech"),
+            error_message: Some(if discarded {
+                "Post-processing failed; output discarded"
+            } else {
+                "Transcription was empty or contained no speech"
+            }),
             segments: None,
             audio_file: audio_file.as_deref(),
             duration_secs,
@@ -77,6 +93,7 @@ pub async fn deliver_output(
             source: Some("mic"),
             language: Some(&language),
             prompt_name: prompt_name.as_deref(),
+            metadata: Some(&metadata),
             limit: Some(history_limit),
         });
         if let Some(window) = app_handle.get_webview_window("main") {
@@ -104,6 +121,7 @@ pub async fn deliver_output(
         source: Some("mic"),
         language: Some(&language),
         prompt_name: prompt_name.as_deref(),
+        metadata: Some(&metadata),
         limit: Some(history_limit),
     });
     if let Some(window) = app_handle.get_webview_window("main") {
@@ -130,14 +148,17 @@ pub async fn deliver_output(
                     crate::log_info!("CLIPBOARD ERROR: {}", error);
                 }
             }
-            crate::log_info!("Forwarding text to hardware typing engine...");
             let state = app_handle.state::<crate::AppState>();
-            if let Err(error) = state
-                .display_backend
-                .type_text_hardware(app_handle, &output_text, typing_speed, hold_duration)
-                .await
-            {
-                crate::log_info!("TYPING ENGINE ERROR: {}", error);
+            let remaining = &output_text[typed_prefix_len..];
+            if !remaining.is_empty() {
+                crate::log_info!("Forwarding text to hardware typing engine...");
+                if let Err(error) = state
+                    .display_backend
+                    .type_text_hardware(app_handle, remaining, typing_speed, hold_duration)
+                    .await
+                {
+                    crate::log_info!("TYPING ENGINE ERROR: {}", error);
+                }
             }
             if auto_submit {
                 crate::log_info!("Auto-submitting with Enter...");
@@ -0,0 +1,271 @@
+use crate::config::Config;
+use crate::post_process::stream::PostProcessStream;
+use crate::post_process::template::RenderedPrompts;
+use crate::post_process::{PostProcessError, PostProcessService};
+use serde::Serialize;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+
+#[derive(Clone, Debug, Serialize)]
+struct PostProcessTokenPayload {
+    text: String,
+}
+
+/// Splits streamed model output into sentence-sized pieces that are safe to
+/// type before generation finishes. A piece ends at sentence punctuation
+/// (plus any closing quotes or brackets) once the following whitespace has
+/// arrived, or at a newline. Whitespace between pieces stays at the start of
+/// the next piece, so concatenating every piece reproduces the input exactly.
+#[derive(Default)]
+pub struct SentenceChunker {
+    buffer: String,
+}
+
+impl SentenceChunker {
+    pub fn push(&mut self, delta: &str) -> Vec<String> {
+        self.buffer.push_str(delta);
+        let mut chunks = Vec::new();
+        while let Some(end) = self.sentence_end() {
+            let rest = self.buffer.split_off(end);
+            chunks.push(std::mem::replace(&mut self.buffer, rest));
+        }
+        chunks
+    }
+
+    /// Whatever is left once the stream has ended.
+    pub fn finish(self) -> String {
+        self.buffer
+    }
+
+    fn sentence_end(&self) -> Option<usize> {
+        let chars: Vec<(usize, char)> = self.buffer.char_indices().collect();
+        for (i, &(offset, c)) in chars.iter().enumerate() {
+            if c == '\n' {
+                // Leading newlines belong to the next piece
+                if self.buffer[..offset].trim().is_empty() {
+                    continue;
+                }
+                return Some(offset);
+            }
+            if !matches!(c, '.' | '!' | '?') {
+                continue;
+            }
+            let mut j = i + 1;
+            while j < chars.len() && matches!(chars[j].1, '.' | '!' | '?' | '"' | '\'' | ')' | ']')
+            {
+                j += 1;
+            }
+            match chars.get(j) {
+                Some(&(end, next)) if next.is_whitespace() => return Some(end),
+                // Not enough input yet to know whether the sentence ended
+                None => return None,
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// Collapses whitespace the way `prompt::tidy_completion` does for a whole
+/// completion: a run containing a newline becomes one newline, any other run
+/// becomes one space.
+fn tidy_chunk(chunk: &str) -> String {
+    let mut output = String::with_capacity(chunk.len());
+    let mut pending: Option<bool> = None;
+    for c in chunk.chars() {
+        if c.is_whitespace() {
+            let has_newline = pending.unwrap_or(false) || c == '\n';
+            pending = Some(has_newline);
+            continue;
+        }
+        if let Some(has_newline) = pending.take() {
+            output.push(if has_newline { '\n' } else { ' ' });
+        }
+        output.push(c);
+    }
+    output
+}
+
+/// Settings for typing post-processed text while the model is still
+/// generating. Only used for Prose dictation with typewriter output, so each
+/// piece goes through the same masking, normalization and continuation steps
+/// the full text would.
+#[derive(Clone)]
+pub struct EarlyTyping {
+    pub continuation_tail: Option<String>,
+    pub typing_speed: f64,
+    pub hold_duration: u64,
+}
+
+impl EarlyTyping {
+    fn prepare(&self, chunk: &str, first: bool, last: bool, config: &Config) -> String {
+        let mut piece = tidy_chunk(chunk);
+        if first {
+            piece = piece.trim_start().to_string();
+        }
+        if last {
+            piece = piece.trim_end().to_string();
+        }
+        let piece = crate::profanity::apply_profanity_filter_to_piece(&piece, config);
+        let mut piece = crate::typing::normalize_for_typing(&piece);
+        if last && self.continuation_tail.is_some() {
+            piece = crate::continuation::trim_dangling_period(&piece);
+        }
+        if first && self.continuation_tail.is_some() && !piece.is_empty() {
+            piece = crate::continuation::continue_from(&piece, self.continuation_tail.as_deref());
+        }
+        piece
+    }
+}
+
+pub struct StreamOutcome {
+    pub result: Result<String, PostProcessError>,
+    /// Exactly what was typed during generation, when early typing was on
+    /// and produced any output. The pipeline must not type this again.
+    pub typed: Option<String>,
+}
+
+async fn type_piece(app_handle: &AppHandle, piece: &str, early: &EarlyTyping) {
+    if piece.is_empty() {
+        return;
+    }
+    let state = app_handle.state::<crate::AppState>();
+    if let Err(error) = state
+        .display_backend
+        .type_text_hardware(app_handle, piece, early.typing_speed, early.hold_duration)
+        .await
+    {
+        crate::log_info!("TYPING ENGINE ERROR: {}", error);
+    }
+}
+
+/// Runs streaming post-processing: tokens go to the overlay as they arrive
+/// and, with `early_typing`, complete sentences are typed immediately. The
+/// request stops mid-generation when `session_token` is set.
+pub async fn stream_post_process(
+    app_handle: &AppHandle,
+    processor: &Arc<dyn PostProcessService + Send + Sync>,
+    text: &str,
+    prompts: &RenderedPrompts,
+    config: &Config,
+    session_token: &Arc<AtomicBool>,
+    early_typing: Option<EarlyTyping>,
+) -> StreamOutcome {
+    let (deltas, mut receiver) = tokio::sync::mpsc::unbounded_channel::<String>();
+    let stream = PostProcessStream {
+        deltas,
+        cancel: session_token.clone(),
+    };
+
+    let producer = async {
+        // Owning the stream here closes the channel when the request ends
+        let stream = stream;
+        processor
+            .post_process_stream(
+                text,
+                &prompts.system_prompt,
+                &prompts.user_prompt_template,
+                config.resolve_max_output_tokens(),
+                &stream,
+            )
+            .await
+    };
+
+    let consumer = async {
+        let mut accumulated = String::new();
+        let mut chunker = SentenceChunker::default();
+        let mut typed = String::new();
+        while let Some(delta) = receiver.recv().await {
+            accumulated.push_str(&delta);
+            if let Some(overlay) = app_handle.get_webview_window("overlay") {
+                let _ = overlay.emit(
+                    "post-process-token",
+                    PostProcessTokenPayload {
+                        text: accumulated.clone(),
+                    },
+                );
+            }
+            if let Some(early) = &early_typing {
+                for chunk in chunker.push(&delta) {
+                    let piece = early.prepare(&chunk, typed.is_empty(), false, config);
+                    type_piece(app_handle, &piece, early).await;
+                    typed.push_str(&piece);
+                }
+            }
+        }
+        (chunker, typed)
+    };
+
+    let (result, (chunker, mut typed)) = tokio::join!(producer, consumer);
+
+    let Some(early) = early_typing else {
+        return StreamOutcome {
+            result,
+            typed: None,
+        };
+    };
+
+    // The final sentence has no following whitespace to close it, so it is
+    // typed once the stream has finished (but never after a cancel).
+    if result.is_ok() {
+        let piece = early.prepare(&chunker.finish(), typed.is_empty(), true, config);
+        type_piece(app_handle, &piece, &early).await;
+        typed.push_str(&piece);
+    }
+
+    StreamOutcome {
+        result,
+        typed: (!typed.is_empty()).then_some(typed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk_all(deltas: &[&str]) -> (Vec<String>, String) {
+        let mut chunker = SentenceChunker::default();
+        let mut chunks = Vec::new();
+        for delta in deltas {
+            chunks.extend(chunker.push(delta));
+        }
+        (chunks, chunker.finish())
+    }
+
+    #[test]
+    fn test_chunker_waits_for_whitespace_after_punctuation() {
+        let (chunks, rest) = chunk_all(&["Hello there", ".", " How", " are you?"]);
+        assert_eq!(chunks, vec!["Hello there."]);
+        assert_eq!(rest, " How are you?");
+    }
+
+    #[test]
+    fn test_chunker_keeps_decimals_and_ellipses_together() {
+        let (chunks, rest) = chunk_all(&["It costs 3.5 dollars... ", "Really."]);
+        assert_eq!(chunks, vec!["It costs 3.5 dollars..."]);
+        assert_eq!(rest, " Really.");
+    }
+
+    #[test]
+    fn test_chunker_splits_on_newlines_and_closing_quotes() {
+        let (chunks, rest) = chunk_all(&["He said \"stop.\" Then", "\nNext line"]);
+        assert_eq!(chunks, vec!["He said \"stop.\"", " Then"]);
+        assert_eq!(rest, "\nNext line");
+    }
+
+    #[test]
+    fn test_chunks_reassemble_exactly() {
+        let input = "One. Two!  Three?\n\nFour";
+        let deltas: Vec<String> = input.chars().map(|c| c.to_string()).collect();
+        let deltas: Vec<&str> = deltas.iter().map(String::as_str).collect();
+        let (chunks, rest) = chunk_all(&deltas);
+        assert_eq!(format!("{}{}", chunks.concat(), rest), input);
+    }
+
+    #[test]
+    fn test_tidy_chunk_matches_completion_tidying() {
+        assert_eq!(tidy_chunk("  Two   words"), " Two words");
+        assert_eq!(tidy_chunk(" \n\n  Next"), "\nNext");
+    }
+}
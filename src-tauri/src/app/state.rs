@@ -47,6 +47,15 @@ pub struct AppState {
     pub engine_factory: Arc<engine_factory::EngineFactory>,
     pub post_process_factory: Arc<PostProcessFactory>,
     pub python_runner: Arc<Mutex<Option<crate::python_runner::PythonRunner>>>,
+    /// Markdown list numbering and block state carried between dictations.
+    pub markdown_session: Arc<Mutex<crate::markdown::MarkdownSession>>,
+    /// Tail of the last delivered prose dictation, for sticky continuation.
+    pub last_output_tail: Arc<Mutex<Option<crate::continuation::OutputTail>>>,
+    /// Tray toggle for hands-free listening. Only consulted while the hotkey
+    /// mode is `VoiceActivated`.
+    pub voice_activation_armed: Arc<AtomicBool>,
+    /// System playback capture while listen mode is running.
+    pub system_capture: Arc<Mutex<Option<audio::system_capture::SystemCapture>>>,
 }
 
 #[derive(Clone, Debug, Default, serde::Serialize)]
@@ -82,6 +91,9 @@ impl AppState {
             if guard.take().is_some() {
                 crate::log_info!("Audio engine stopped");
             }
+            if self.system_capture.lock().unwrap().take().is_some() {
+                crate::log_info!("System capture stopped");
+            }
         }
 
         // 4. Stop any playback stream.
@@ -143,6 +155,10 @@ impl Default for AppState {
             engine_factory: Arc::new(engine_factory::EngineFactory::new()),
             post_process_factory: Arc::new(PostProcessFactory::new()),
             python_runner: Arc::new(Mutex::new(None)),
+            markdown_session: Arc::new(Mutex::new(crate::markdown::MarkdownSession::default())),
+            last_output_tail: Arc::new(Mutex::new(None)),
+            voice_activation_armed: Arc::new(AtomicBool::new(true)),
+            system_capture: Arc::new(Mutex::new(None)),
         }
     }
 }
@@ -0,0 +1,188 @@
+use crate::app::commands::recording::{start_recording_with_mode, stop_recording};
+use crate::app::recording_flow::SessionKind;
+use crate::app::state::{AppState, SessionState};
+use crate::audio::vad::{VadEvent, VadSettings, VoiceActivityDetector};
+use crate::config::{Config, HotkeyMode};
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::Manager;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+const TAP_CAPACITY: usize = 65536;
+
+fn vad_settings(config: &Config) -> VadSettings {
+    VadSettings {
+        threshold_db: config.vad_threshold_db,
+        min_speech_ms: config.vad_min_speech_ms,
+        silence_ms: config.vad_silence_ms,
+    }
+}
+
+/// Runs for the lifetime of the app and drives hands-free recording: while
+/// `HotkeyMode::VoiceActivated` is selected and armed from the tray, it
+/// watches the persistent engine's monitor tap, starts a dictation session
+/// when speech begins and stops it after the configured silence or the
+/// utterance cap. Sessions started from the hotkey are left alone.
+pub fn spawn_listener(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut tap: Option<mpsc::Receiver<f32>> = None;
+        let mut detector: Option<VoiceActivityDetector> = None;
+        let mut detector_rate = 0u32;
+        // Start time of the session this listener opened, if one is running.
+        let mut own_session: Option<tokio::time::Instant> = None;
+        let mut samples: Vec<f32> = Vec::new();
+
+        loop {
+            let state = app_handle.state::<AppState>();
+            let (active, settings, max_utterance) = {
+                let config = state.config.lock().unwrap();
+                (
+                    config.hotkey_mode == HotkeyMode::VoiceActivated
+                        && state.voice_activation_armed.load(Ordering::SeqCst),
+                    vad_settings(&config),
+                    Duration::from_secs(config.vad_max_utterance_secs),
+                )
+            };
+
+            if !active {
+                if tap.take().is_some() {
+                    detach_tap(&state);
+                    crate::log_info!("Hands-free listener paused");
+                }
+                own_session = None;
+                tokio::time::sleep(IDLE_POLL_INTERVAL).await;
+                continue;
+            }
+
+            // (Re)attach whenever the engine was rebuilt or is not up yet;
+            // a replaced engine drops our sender and disconnects the tap.
+            if tap.is_none() {
+                match attach_tap(&state) {
+                    Some((receiver, rate)) => {
+                        crate::log_info!("Hands-free listener attached at {}Hz", rate);
+                        tap = Some(receiver);
+                        if rate != detector_rate {
+                            detector = None;
+                            detector_rate = rate;
+                        }
+                    }
+                    None => {
+                        tokio::time::sleep(IDLE_POLL_INTERVAL).await;
+                        continue;
+                    }
+                }
+            }
+
+            samples.clear();
+            let mut disconnected = false;
+            if let Some(receiver) = tap.as_ref() {
+                loop {
+                    match receiver.try_recv() {
+                        Ok(sample) => samples.push(sample),
+                        Err(mpsc::TryRecvError::Empty) => break,
+                        Err(mpsc::TryRecvError::Disconnected) => {
+                            disconnected = true;
+                            break;
+                        }
+                    }
+                }
+            }
+            if disconnected {
+                crate::log_info!("Hands-free listener lost the audio engine; reattaching");
+                tap = None;
+                continue;
+            }
+
+            let vad =
+                detector.get_or_insert_with(|| VoiceActivityDetector::new(detector_rate, settings));
+            if vad.settings() != settings {
+                *vad = VoiceActivityDetector::new(detector_rate, settings);
+            }
+
+            let session = *state.session_state.lock().unwrap();
+            let busy = *state.is_mic_test_active.lock().unwrap()
+                || *state.is_configuring_hotkey.lock().unwrap();
+
+            match (session, own_session) {
+                (SessionState::Idle, _) if !busy => {
+                    own_session = None;
+                    if vad.push(&samples) == Some(VadEvent::SpeechStart) {
+                        crate::log_info!(
+                            "Hands-free: speech detected (noise floor {:.1}dB); starting session",
+                            vad.noise_floor_db()
+                        );
+                        let started = start_recording_with_mode(
+                            state.clone(),
+                            app_handle.clone(),
+                            SessionKind::Dictation(None),
+                        )
+                        .await;
+                        if started.is_ok()
+                            && *state.session_state.lock().unwrap() == SessionState::Recording
+                        {
+                            own_session = Some(tokio::time::Instant::now());
+                        } else {
+                            vad.reset();
+                        }
+                    }
+                }
+                (SessionState::Recording, Some(started_at)) => {
+                    let ended = vad.push(&samples) == Some(VadEvent::SpeechEnd);
+                    let capped = started_at.elapsed() >= max_utterance;
+                    if ended || capped {
+                        if capped {
+                            crate::log_warn!(
+                                "Hands-free: utterance reached the {:?} cap; stopping session",
+                                max_utterance
+                            );
+                        } else {
+                            crate::log_info!("Hands-free: silence detected; stopping session");
+                        }
+                        let _ = stop_recording(state.clone()).await;
+                        vad.reset();
+                        own_session = None;
+                    }
+                }
+                _ => {
+                    // Someone else owns the input (a hotkey session, the
+                    // pipeline, a mic test): listen again from scratch once
+                    // it is done.
+                    vad.reset();
+                    if session != SessionState::Recording {
+                        own_session = None;
+                    }
+                }
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+fn attach_tap(state: &AppState) -> Option<(mpsc::Receiver<f32>, u32)> {
+    let guard = state.audio_engine.lock().unwrap();
+    let engine = guard.as_ref()?;
+    let (tx, rx) = mpsc::sync_channel::<f32>(TAP_CAPACITY);
+    *engine.monitor_tx.lock().unwrap() = Some(tx);
+    Some((rx, engine.sample_rate))
+}
+
+fn detach_tap(state: &AppState) {
+    if let Some(engine) = state.audio_engine.lock().unwrap().as_ref() {
+        *engine.monitor_tx.lock().unwrap() = None;
+    }
+}
+
+/// Tray toggle: arms or disarms hands-free listening without changing the
+/// configured hotkey mode. Returns the new armed state.
+pub fn toggle_armed(state: &AppState) -> bool {
+    let armed = !state.voice_activation_armed.load(Ordering::SeqCst);
+    state.voice_activation_armed.store(armed, Ordering::SeqCst);
+    crate::log_info!(
+        "Hands-free listening {}",
+        if armed { "armed" } else { "disarmed" }
+    );
+    armed
+}
@@ -0,0 +1,259 @@
+//! Automatic gain control applied to microphone audio while it is captured.
+//!
+//! Loudness is measured in 10ms blocks and steered towards a target RMS
+//! level. Blocks that do not stand out from the tracked noise floor hold the
+//! current gain, so pauses and room noise are never pumped up to speech
+//! level. A peak limiter after the gain stage keeps boosted peaks below a
+//! fixed ceiling instead of clipping.
+
+use serde::{Deserialize, Serialize};
+
+const BLOCK_MS: f32 = 10.0;
+/// Blocks this far above the noise floor are treated as signal.
+const SIGNAL_MARGIN_DB: f32 = 6.0;
+/// Nothing quieter than this is ever treated as signal.
+const SIGNAL_FLOOR_DB: f32 = -70.0;
+const FLOOR_RISE_DB_PER_SEC: f32 = 2.0;
+/// The most the AGC ever attenuates a loud source.
+const MIN_GAIN_DB: f32 = -12.0;
+/// Loudness averaging window over signal blocks.
+const LOUDNESS_TAU_MS: f32 = 300.0;
+/// How fast gain comes down on a loud block, and goes back up after.
+const ATTACK_TAU_MS: f32 = 50.0;
+const RELEASE_TAU_MS: f32 = 400.0;
+/// Limiter ceiling, -1 dBFS.
+const LIMITER_CEILING: f32 = 0.891;
+const LIMITER_RELEASE_MS: f32 = 50.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AgcSettings {
+    pub target_dbfs: f32,
+    pub max_gain_db: f32,
+}
+
+/// What the AGC did to one capture, for the session log and history.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AgcReport {
+    pub target_dbfs: f32,
+    /// Average gain over the blocks that carried signal.
+    pub mean_gain_db: f32,
+    pub min_gain_db: f32,
+    pub max_gain_db: f32,
+    pub noise_floor_dbfs: f32,
+}
+
+pub struct Agc {
+    settings: AgcSettings,
+    block_len: usize,
+    block: Vec<f32>,
+    noise_floor_db: Option<f32>,
+    floor_rise_per_block: f32,
+    loudness_power: Option<f32>,
+    gain_db: Option<f32>,
+    applied_gain: f32,
+    limiter_env: f32,
+    limiter_release: f32,
+    gain_sum_db: f32,
+    gain_min_db: f32,
+    gain_max_db: f32,
+    signal_blocks: usize,
+}
+
+impl Agc {
+    pub fn new(sample_rate: u32, settings: AgcSettings) -> Self {
+        let block_len = ((sample_rate as f32 * BLOCK_MS / 1000.0) as usize).max(1);
+        Self {
+            settings,
+            block_len,
+            block: Vec::with_capacity(block_len),
+            noise_floor_db: None,
+            floor_rise_per_block: FLOOR_RISE_DB_PER_SEC * BLOCK_MS / 1000.0,
+            loudness_power: None,
+            gain_db: None,
+            applied_gain: 1.0,
+            limiter_env: 0.0,
+            limiter_release: (-1.0 / (sample_rate as f32 * LIMITER_RELEASE_MS / 1000.0)).exp(),
+            gain_sum_db: 0.0,
+            gain_min_db: f32::MAX,
+            gain_max_db: f32::MIN,
+            signal_blocks: 0,
+        }
+    }
+
+    /// Processes `input`, appending finished blocks to `output`. Up to one
+    /// block is held back until enough samples arrive; see `finish`.
+    pub fn process(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        for &sample in input {
+            self.block.push(sample);
+            if self.block.len() == self.block_len {
+                self.process_block(output);
+            }
+        }
+    }
+
+    /// Flushes the held-back partial block.
+    pub fn finish(&mut self, output: &mut Vec<f32>) {
+        if !self.block.is_empty() {
+            self.process_block(output);
+        }
+    }
+
+    pub fn report(&self) -> AgcReport {
+        let (mean, min, max) = if self.signal_blocks == 0 {
+            (0.0, 0.0, 0.0)
+        } else {
+            (
+                self.gain_sum_db / self.signal_blocks as f32,
+                self.gain_min_db,
+                self.gain_max_db,
+            )
+        };
+        AgcReport {
+            target_dbfs: self.settings.target_dbfs,
+            mean_gain_db: mean,
+            min_gain_db: min,
+            max_gain_db: max,
+            noise_floor_dbfs: self.noise_floor_db.unwrap_or(SIGNAL_FLOOR_DB),
+        }
+    }
+
+    fn process_block(&mut self, output: &mut Vec<f32>) {
+        let power = self.block.iter().map(|s| s * s).sum::<f32>() / self.block.len() as f32;
+        let level_db = power_to_db(power);
+        let block_ms = self.block.len() as f32 * BLOCK_MS / self.block_len as f32;
+
+        let floor = self.noise_floor_db.get_or_insert(level_db);
+        let is_signal = level_db > SIGNAL_FLOOR_DB && level_db > *floor + SIGNAL_MARGIN_DB;
+        if level_db < *floor {
+            *floor = level_db;
+        } else {
+            *floor += self.floor_rise_per_block;
+        }
+
+        if is_signal {
+            let loudness = match self.loudness_power {
+                Some(previous) => {
+                    previous + smoothing(block_ms, LOUDNESS_TAU_MS) * (power - previous)
+                }
+                None => power,
+            };
+            self.loudness_power = Some(loudness);
+            let desired = (self.settings.target_dbfs - power_to_db(loudness))
+                .clamp(MIN_GAIN_DB, self.settings.max_gain_db.max(MIN_GAIN_DB));
+            let gain_db = match self.gain_db {
+                // The first signal jumps straight to the right gain so the
+                // opening words are not left at the raw level.
+                None => desired,
+                Some(current) => {
+                    let tau = if desired < current {
+                        ATTACK_TAU_MS
+                    } else {
+                        RELEASE_TAU_MS
+                    };
+                    current + smoothing(block_ms, tau) * (desired - current)
+                }
+            };
+            self.gain_db = Some(gain_db);
+            self.gain_sum_db += gain_db;
+            self.gain_min_db = self.gain_min_db.min(gain_db);
+            self.gain_max_db = self.gain_max_db.max(gain_db);
+            self.signal_blocks += 1;
+        }
+
+        // Ramp from the previous block's gain to avoid zipper noise.
+        let target_gain = db_to_gain(self.gain_db.unwrap_or(0.0));
+        let start_gain = self.applied_gain;
+        let len = self.block.len() as f32;
+        for (i, &sample) in self.block.iter().enumerate() {
+            let gain = start_gain + (target_gain - start_gain) * (i + 1) as f32 / len;
+            let boosted = sample * gain;
+            self.limiter_env = boosted.abs().max(self.limiter_env * self.limiter_release);
+            let limited = if self.limiter_env > LIMITER_CEILING {
+                boosted * LIMITER_CEILING / self.limiter_env
+            } else {
+                boosted
+            };
+            output.push(limited);
+        }
+        self.applied_gain = target_gain;
+        self.block.clear();
+    }
+}
+
+fn smoothing(block_ms: f32, tau_ms: f32) -> f32 {
+    1.0 - (-block_ms / tau_ms).exp()
+}
+
+fn power_to_db(power: f32) -> f32 {
+    10.0 * power.max(1e-12).log10()
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    fn settings() -> AgcSettings {
+        AgcSettings {
+            target_dbfs: -20.0,
+            max_gain_db: 30.0,
+        }
+    }
+
+    fn tone(secs: f32, amplitude: f32) -> Vec<f32> {
+        let len = (RATE as f32 * secs) as usize;
+        (0..len)
+            .map(|i| amplitude * (i as f32 * 220.0 * std::f32::consts::TAU / RATE as f32).sin())
+            .collect()
+    }
+
+    fn rms_db(samples: &[f32]) -> f32 {
+        power_to_db(samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32)
+    }
+
+    fn run(agc: &mut Agc, input: &[f32]) -> Vec<f32> {
+        let mut output = Vec::new();
+        agc.process(input, &mut output);
+        agc.finish(&mut output);
+        output
+    }
+
+    #[test]
+    fn quiet_and_loud_sources_converge_on_the_target() {
+        for amplitude in [0.01, 0.3] {
+            let mut agc = Agc::new(RATE, settings());
+            let mut input = tone(0.5, 0.0005);
+            input.extend(tone(2.0, amplitude));
+            let output = run(&mut agc, &input);
+            assert_eq!(output.len(), input.len());
+            let tail = &output[output.len() - RATE as usize / 2..];
+            assert!((rms_db(tail) + 20.0).abs() < 1.5, "{}", rms_db(tail));
+            assert!(output.iter().all(|s| s.abs() <= LIMITER_CEILING + 1e-6));
+        }
+    }
+
+    #[test]
+    fn limiter_catches_peaks_after_a_quiet_passage() {
+        let mut agc = Agc::new(RATE, settings());
+        let mut input = tone(0.5, 0.0005);
+        input.extend(tone(1.0, 0.01));
+        input.extend(tone(0.2, 1.0));
+        let output = run(&mut agc, &input);
+        assert!(output.iter().all(|s| s.abs() <= LIMITER_CEILING + 1e-6));
+        assert!(agc.report().max_gain_db > 20.0);
+    }
+
+    #[test]
+    fn background_noise_alone_is_not_boosted() {
+        let mut agc = Agc::new(RATE, settings());
+        let input = tone(2.0, 0.002);
+        let output = run(&mut agc, &input);
+        assert!((rms_db(&output) - rms_db(&input)).abs() < 0.1);
+        assert_eq!(agc.report().mean_gain_db, 0.0);
+    }
+}
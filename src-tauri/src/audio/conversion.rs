@@ -1,11 +1,17 @@
+use std::path::Path;
+
 use hound::{WavSpec, WavWriter};
 use rubato::{FftFixedInOut, Resampler};
 
 use super::decode::decode_compressed_audio;
+use super::whisper_file::{WhisperFile, WhisperWriter};
+
+/// Samples per input read when mixing.
+const MIX_BLOCK_SAMPLES: usize = 1 << 16;
 
 pub fn convert_audio_file_for_whisper(
     data: &[u8],
-) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<WhisperFile, Box<dyn std::error::Error + Send + Sync>> {
     if is_wav_file(data) {
         let reader = hound::WavReader::new(std::io::Cursor::new(data))?;
         let sample_rate = reader.spec().sample_rate;
@@ -32,15 +38,24 @@ fn is_wav_file(data: &[u8]) -> bool {
 
 fn normalize_peak(samples: &[f32]) -> Vec<f32> {
     let peak = samples.iter().map(|s| s.abs()).fold(0.0f32, f32::max);
+    match normalization_gain(peak) {
+        Some(gain) => samples.iter().map(|s| s * gain).collect(),
+        None => samples.to_vec(),
+    }
+}
+
+/// Gain that brings `peak` up to full scale (at most 10x), or `None` when
+/// the audio is silent or already at full scale.
+fn normalization_gain(peak: f32) -> Option<f32> {
     if peak < f32::EPSILON {
-        return samples.to_vec();
+        return None;
     }
     let gain = (1.0 / peak).min(10.0);
     if (gain - 1.0).abs() < f32::EPSILON {
-        return samples.to_vec();
+        return None;
     }
     crate::log_info!("Normalizing audio: peak={:.6}, gain={:.2}", peak, gain);
-    samples.iter().map(|s| s * gain).collect()
+    Some(gain)
 }
 
 fn downmix_to_mono(samples: &[f32], channels: usize) -> Vec<f32> {
@@ -53,24 +68,25 @@ fn downmix_to_mono(samples: &[f32], channels: usize) -> Vec<f32> {
         .collect()
 }
 
-fn float_to_i16(sample: f32) -> i16 {
+pub(super) fn float_to_i16(sample: f32) -> i16 {
     (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
 }
 
+pub(super) fn whisper_spec() -> WavSpec {
+    WavSpec {
+        channels: 1,
+        sample_rate: 16000,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    }
+}
+
 pub fn write_whisper_wav(
     samples: &[i16],
 ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
     let mut out = Vec::new();
     {
-        let mut writer = WavWriter::new(
-            std::io::Cursor::new(&mut out),
-            WavSpec {
-                channels: 1,
-                sample_rate: 16000,
-                bits_per_sample: 16,
-                sample_format: hound::SampleFormat::Int,
-            },
-        )?;
+        let mut writer = WavWriter::new(std::io::Cursor::new(&mut out), whisper_spec())?;
         for &sample in samples {
             writer.write_sample(sample)?;
         }
@@ -79,58 +95,46 @@ pub fn write_whisper_wav(
     Ok(out)
 }
 
-/// Parse a full 16kHz mono 16-bit PCM WAV and extract a time-range segment
-/// as a valid WAV binary suitable for passing to whisper.
-///
-/// * `full_wav` — complete 16kHz mono 16-bit WAV bytes
-/// * `start_sec` — start time in seconds (clamped to 0)
-/// * `end_sec` — end time in seconds (clamped to file duration)
-///
-/// Segments shorter than 0.5s are padded with silence to avoid whisper choking
-/// on tiny fragments from diarization boundaries.
-pub fn extract_segment_wav(
-    full_wav: &[u8],
-    start_sec: f64,
-    end_sec: f64,
-) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
-    let mut reader = hound::WavReader::new(std::io::Cursor::new(full_wav))?;
-    let spec = reader.spec();
-
-    if spec.channels != 1 || spec.sample_rate != 16000 {
-        return Err(format!(
-            "Expected 16kHz mono WAV, got {}ch {}Hz",
-            spec.channels, spec.sample_rate,
-        )
-        .into());
-    }
-
-    let all_samples: Vec<i16> = reader.samples::<i16>().collect::<Result<Vec<_>, _>>()?;
-
-    let total_secs = all_samples.len() as f64 / 16000.0;
-    let start = (start_sec.max(0.0).min(total_secs) * 16000.0) as usize;
-    let end = (end_sec.max(0.0).min(total_secs) * 16000.0) as usize;
-    let end = end.min(all_samples.len());
-
-    let segment = if end <= start || (end - start) < 8000 {
-        // Pad short segments to 0.5s (8000 samples at 16kHz)
-        let mut padded = Vec::with_capacity(8000);
-        if end > start {
-            padded.extend_from_slice(&all_samples[start..end]);
+/// Mixes whisper files captured side by side (e.g. the two channels of a
+/// call) into one, padding the shorter ones with silence. The inputs are
+/// read in lockstep a block at a time.
+pub fn mix_whisper_files(files: &[&WhisperFile]) -> Result<WhisperFile, String> {
+    let mut readers = files
+        .iter()
+        .map(|file| {
+            hound::WavReader::open(file.path())
+                .map_err(|e| format!("Failed to open {:?}: {}", file.path(), e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut writer = WhisperWriter::create()?;
+    let mut mixed: Vec<i32> = Vec::with_capacity(MIX_BLOCK_SAMPLES);
+    loop {
+        mixed.clear();
+        for reader in &mut readers {
+            for (i, sample) in reader.samples::<i16>().take(MIX_BLOCK_SAMPLES).enumerate() {
+                let sample = sample.map_err(|e| format!("Failed to read audio: {}", e))? as i32;
+                match mixed.get_mut(i) {
+                    Some(slot) => *slot += sample,
+                    None => mixed.push(sample),
+                }
+            }
         }
-        padded.resize(8000, 0);
-        padded
-    } else {
-        all_samples[start..end].to_vec()
-    };
-
-    write_whisper_wav(&segment)
+        if mixed.is_empty() {
+            return writer.finish();
+        }
+        let block: Vec<i16> = mixed
+            .iter()
+            .map(|&s| s.clamp(i16::MIN as i32, i16::MAX as i32) as i16)
+            .collect();
+        writer.push_pcm(&block);
+    }
 }
 
 pub fn convert_audio_for_whisper(
     data: &[u8],
     _rate: u32,
     _chans: u16,
-) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<WhisperFile, Box<dyn std::error::Error + Send + Sync>> {
     let mut reader = hound::WavReader::new(std::io::Cursor::new(data))?;
     let spec = reader.spec();
 
@@ -186,7 +190,17 @@ pub fn convert_audio_for_whisper(
 pub fn finalize_captured_audio_for_whisper(
     samples: &[f32],
     sample_rate: u32,
-) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<WhisperFile, Box<dyn std::error::Error + Send + Sync>> {
+    finalize_captured_audio(samples, sample_rate, true)
+}
+
+/// Like `finalize_captured_audio_for_whisper`, with peak normalization
+/// optional: audio that already went through the AGC keeps its level.
+pub fn finalize_captured_audio(
+    samples: &[f32],
+    sample_rate: u32,
+    normalize: bool,
+) -> Result<WhisperFile, Box<dyn std::error::Error + Send + Sync>> {
     let resampled = if sample_rate != 16000 {
         crate::log_info!(
             "Resampling audio: {}Hz -> 16000Hz ({} samples)",
@@ -198,25 +212,220 @@ pub fn finalize_captured_audio_for_whisper(
         samples.to_vec()
     };
 
-    let normalized = normalize_peak(&resampled);
-    let peak = normalized.iter().map(|s| s.abs()).fold(0.0f32, f32::max);
-    let rms = if normalized.is_empty() {
-        0.0
+    let normalized = if normalize {
+        normalize_peak(&resampled)
     } else {
-        (normalized.iter().map(|s| s * s).sum::<f32>() / normalized.len() as f32).sqrt()
+        resampled
     };
-    crate::log_info!(
-        "Output audio: {} samples ({:.1}s at 16kHz), peak={:.4}, rms={:.6}",
-        normalized.len(),
-        normalized.len() as f64 / 16000.0,
-        peak,
-        rms,
-    );
-    let samples_i16: Vec<i16> = normalized
-        .iter()
-        .map(|&sample| float_to_i16(sample))
-        .collect();
-    write_whisper_wav(&samples_i16)
+    let mut stats = OutputStats::default();
+    stats.add(&normalized);
+    stats.log();
+    let mut writer = WhisperWriter::create()?;
+    writer.push(&normalized);
+    Ok(writer.finish()?)
+}
+
+/// Finalizes a capture spooled to disk (see `spool`) like
+/// `finalize_captured_audio`, without loading the raw capture into memory:
+/// the file is read and resampled block by block, once to find the peak when
+/// normalizing and once more to write the output. A spool cut short by a
+/// crash is read up to its last complete sample.
+pub fn finalize_spooled_capture(
+    path: &Path,
+    normalize: bool,
+) -> Result<WhisperFile, Box<dyn std::error::Error + Send + Sync>> {
+    let gain = if normalize {
+        let mut peak = 0.0f32;
+        for_each_resampled_block(path, |block| {
+            peak = block.iter().map(|s| s.abs()).fold(peak, f32::max);
+        })?;
+        normalization_gain(peak)
+    } else {
+        None
+    };
+
+    let mut writer = WhisperWriter::create()?;
+    let mut stats = OutputStats::default();
+    let mut scaled = Vec::new();
+    for_each_resampled_block(path, |block| {
+        scaled.clear();
+        scaled.extend(block.iter().map(|s| gain.map_or(*s, |gain| s * gain)));
+        stats.add(&scaled);
+        writer.push(&scaled);
+    })?;
+    let finalized = writer.finish()?;
+    stats.log();
+    Ok(finalized)
+}
+
+/// Samples read from a spool per block.
+const SPOOL_BLOCK_SAMPLES: usize = 1 << 16;
+
+/// Streams a mono spool WAV through the same resampling as
+/// `resample_audio_f32`, handing 16kHz output to `sink` a block at a time.
+/// Spools are 32-bit float; 16-bit ones come from earlier versions.
+fn for_each_resampled_block(
+    path: &Path,
+    mut sink: impl FnMut(&[f32]),
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut reader = hound::WavReader::open(path)?;
+    let spec = reader.spec();
+    let mut samples: Box<dyn Iterator<Item = f32>> =
+        match (spec.channels, spec.sample_format, spec.bits_per_sample) {
+            (1, hound::SampleFormat::Float, 32) => {
+                Box::new(reader.samples::<f32>().map_while(Result::ok))
+            }
+            (1, hound::SampleFormat::Int, 16) => Box::new(
+                reader
+                    .samples::<i16>()
+                    .map_while(Result::ok)
+                    .map(|s| s as f32 / i16::MAX as f32),
+            ),
+            _ => {
+                return Err(format!(
+                    "Unexpected spool format: {} channels, {} bits {:?}",
+                    spec.channels, spec.bits_per_sample, spec.sample_format
+                )
+                .into())
+            }
+        };
+
+    if spec.sample_rate != 16000 {
+        crate::log_info!(
+            "Resampling spooled audio: {}Hz -> 16000Hz",
+            spec.sample_rate
+        );
+    }
+    let mut resampler = StreamResampler::new(spec.sample_rate, 16000);
+    let mut block = Vec::with_capacity(SPOOL_BLOCK_SAMPLES);
+    loop {
+        block.clear();
+        block.extend(samples.by_ref().take(SPOOL_BLOCK_SAMPLES));
+        if block.is_empty() {
+            break;
+        }
+        sink(&resampler.push(&block)?);
+    }
+    sink(&resampler.finish()?);
+    Ok(())
+}
+
+/// `resample_audio_f32` for audio that arrives in blocks: each `push`
+/// returns the output that is ready so far and `finish` the rest, which
+/// together match resampling the whole input at once.
+pub struct StreamResampler {
+    from: u32,
+    to: u32,
+    resampler: Option<FftFixedInOut<f32>>,
+    chunk: Vec<Vec<f32>>,
+    input_len: usize,
+    emitted: usize,
+    /// The last chunk's output is truncated to the expected length, which
+    /// is only known once the input runs out, so each chunk is held back
+    /// until the next one arrives.
+    held: Option<Vec<f32>>,
+}
+
+impl StreamResampler {
+    pub fn new(from: u32, to: u32) -> Self {
+        let resampler =
+            (from != to).then(|| FftFixedInOut::<f32>::new(from as usize, to as usize, 1024, 1));
+        let frames_needed = resampler.as_ref().map_or(0, |r| r.nbr_frames_needed());
+        Self {
+            from,
+            to,
+            resampler,
+            chunk: vec![Vec::with_capacity(frames_needed)],
+            input_len: 0,
+            emitted: 0,
+            held: None,
+        }
+    }
+
+    pub fn push(&mut self, samples: &[f32]) -> Result<Vec<f32>, String> {
+        let Some(resampler) = &self.resampler else {
+            return Ok(samples.to_vec());
+        };
+        let frames_needed = resampler.nbr_frames_needed();
+        self.input_len += samples.len();
+        let mut output = Vec::new();
+        let mut rest = samples;
+        while !rest.is_empty() {
+            let take = (frames_needed - self.chunk[0].len()).min(rest.len());
+            self.chunk[0].extend_from_slice(&rest[..take]);
+            rest = &rest[take..];
+            if self.chunk[0].len() == frames_needed {
+                self.process_chunk(&mut output)?;
+            }
+        }
+        Ok(output)
+    }
+
+    pub fn finish(mut self) -> Result<Vec<f32>, String> {
+        let Some(resampler) = &self.resampler else {
+            return Ok(Vec::new());
+        };
+        let mut output = Vec::new();
+        if !self.chunk[0].is_empty() {
+            let frames_needed = resampler.nbr_frames_needed();
+            self.chunk[0].resize(frames_needed, 0.0);
+            self.process_chunk(&mut output)?;
+        }
+        if let Some(last) = self.held.take() {
+            let expected_len =
+                (self.input_len as f64 * self.to as f64 / self.from as f64).round() as usize;
+            let len = expected_len.saturating_sub(self.emitted).min(last.len());
+            output.extend_from_slice(&last[..len]);
+        }
+        Ok(output)
+    }
+
+    fn process_chunk(&mut self, output: &mut Vec<f32>) -> Result<(), String> {
+        let Some(resampler) = &mut self.resampler else {
+            return Ok(());
+        };
+        let resampled = resampler
+            .process(&self.chunk)
+            .map_err(|e| format!("Resampling failed: {}", e))?;
+        self.chunk[0].clear();
+        if let Some(previous) = self.held.take() {
+            self.emitted += previous.len();
+            output.extend_from_slice(&previous);
+        }
+        self.held = resampled.into_iter().next();
+        Ok(())
+    }
+}
+
+/// Level statistics of finalized audio, for the session log.
+#[derive(Default)]
+struct OutputStats {
+    len: usize,
+    peak: f32,
+    sum_squares: f32,
+}
+
+impl OutputStats {
+    fn add(&mut self, samples: &[f32]) {
+        self.len += samples.len();
+        self.peak = samples.iter().map(|s| s.abs()).fold(self.peak, f32::max);
+        self.sum_squares += samples.iter().map(|s| s * s).sum::<f32>();
+    }
+
+    fn log(&self) {
+        let rms = if self.len == 0 {
+            0.0
+        } else {
+            (self.sum_squares / self.len as f32).sqrt()
+        };
+        crate::log_info!(
+            "Output audio: {} samples ({:.1}s at 16kHz), peak={:.4}, rms={:.6}",
+            self.len,
+            self.len as f64 / 16000.0,
+            self.peak,
+            rms,
+        );
+    }
 }
 
 pub fn resample_audio_f32(samples: &[f32], from: u32, to: u32) -> Vec<f32> {
@@ -325,9 +534,8 @@ mod tests {
     #[test]
     fn finalize_captured_audio_16k_direct_path() {
         let input = vec![0.5; 16000]; // 1 second of 16kHz
-        let wav_bytes =
-            finalize_captured_audio_for_whisper(&input, 16000).expect("finalize failed");
-        let reader = hound::WavReader::new(std::io::Cursor::new(wav_bytes)).unwrap();
+        let file = finalize_captured_audio_for_whisper(&input, 16000).expect("finalize failed");
+        let reader = hound::WavReader::open(file.path()).unwrap();
         assert_eq!(reader.spec().sample_rate, 16000);
         assert_eq!(reader.spec().channels, 1);
         assert_eq!(reader.duration(), 16000);
@@ -336,14 +544,77 @@ mod tests {
     #[test]
     fn finalize_captured_audio_44100_resampled_path() {
         let input = vec![0.3; 44100]; // 1 second of 44.1kHz
-        let wav_bytes =
-            finalize_captured_audio_for_whisper(&input, 44100).expect("finalize failed");
-        let reader = hound::WavReader::new(std::io::Cursor::new(wav_bytes)).unwrap();
+        let file = finalize_captured_audio_for_whisper(&input, 44100).expect("finalize failed");
+        let reader = hound::WavReader::open(file.path()).unwrap();
         assert_eq!(reader.spec().sample_rate, 16000);
         assert_eq!(reader.spec().channels, 1);
         assert_eq!(reader.duration(), 16000);
     }
 
+    #[test]
+    fn spooled_capture_finalizes_like_the_in_memory_path() {
+        let path =
+            std::env::temp_dir().join(format!("voquill_finalize_spool_{}.wav", std::process::id()));
+        for (rate, len) in [(16000, 70000), (44100, 100_000)] {
+            let input: Vec<f32> = (0..len).map(|i| 0.02 * (i as f32 * 0.01).sin()).collect();
+            let spec = WavSpec {
+                sample_rate: rate,
+                bits_per_sample: 32,
+                sample_format: hound::SampleFormat::Float,
+                ..whisper_spec()
+            };
+            let mut writer = WavWriter::create(&path, spec).unwrap();
+            for &s in &input {
+                writer.write_sample(s).unwrap();
+            }
+            writer.finalize().unwrap();
+            for normalize in [true, false] {
+                let spooled = finalize_spooled_capture(&path, normalize).unwrap();
+                let in_memory = finalize_captured_audio(&input, rate, normalize).unwrap();
+                assert_eq!(
+                    std::fs::read(spooled.path()).unwrap(),
+                    std::fs::read(in_memory.path()).unwrap()
+                );
+            }
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn streamed_resampling_matches_the_one_shot_resampler() {
+        let input: Vec<f32> = (0..50_000).map(|i| (i as f32 * 0.013).sin()).collect();
+        for (from, to) in [
+            (44100, 16000),
+            (16000, 48000),
+            (48000, 16000),
+            (16000, 16000),
+        ] {
+            let mut resampler = StreamResampler::new(from, to);
+            let mut output = Vec::new();
+            for block in input.chunks(3001) {
+                output.extend(resampler.push(block).unwrap());
+            }
+            output.extend(resampler.finish().unwrap());
+            assert_eq!(output, resample_audio_f32(&input, from, to));
+        }
+    }
+
+    #[test]
+    fn mixing_pads_the_shorter_channel_and_saturates() {
+        let write = |samples: &[i16]| {
+            let mut writer = WhisperWriter::create().unwrap();
+            writer.push_pcm(samples);
+            writer.finish().unwrap()
+        };
+        let long = write(&[1000, 30000, -30000, 5]);
+        let short = write(&[1000, 10000]);
+        let mixed = mix_whisper_files(&[&long, &short]).unwrap();
+        assert_eq!(mixed.samples(), 4);
+        let mut reader = hound::WavReader::open(mixed.path()).unwrap();
+        let samples: Vec<i16> = reader.samples::<i16>().map(|s| s.unwrap()).collect();
+        assert_eq!(samples, [2000, i16::MAX, -30000, 5]);
+    }
+
     #[test]
     fn resample_linear_basic_interpolation() {
         let input = vec![0.0, 1.0];
@@ -1,13 +1,14 @@
 use symphonia::core::audio::SampleBuffer;
 use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::conv::ConvertibleSample;
 use symphonia::core::errors::Error as SymphoniaError;
 use symphonia::core::formats::FormatOptions;
-use symphonia::core::io::MediaSourceStream;
+use symphonia::core::io::{MediaSource, MediaSourceStream};
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
 
-pub struct DecodedAudio {
-    pub samples: Vec<f32>,
+pub struct DecodedAudio<S = f32> {
+    pub samples: Vec<S>,
     pub sample_rate: u32,
     pub channels: usize,
 }
@@ -15,8 +16,47 @@ pub struct DecodedAudio {
 pub fn decode_compressed_audio(
     data: &[u8],
 ) -> Result<DecodedAudio, Box<dyn std::error::Error + Send + Sync>> {
+    decode_audio(data)
+}
+
+/// Decodes the file at `path` to 16-bit samples a packet at a time, which
+/// round-trips 16-bit lossless formats such as FLAC exactly. `sink` gets each
+/// block of interleaved samples with the sample rate and channel count.
+pub fn decode_file_i16(
+    path: &std::path::Path,
+    sink: impl FnMut(&[i16], u32, usize) -> Result<(), Box<dyn std::error::Error + Send + Sync>>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let file = std::fs::File::open(path)?;
+    decode_stream(Box::new(file), sink)
+}
+
+fn decode_audio<S: ConvertibleSample>(
+    data: &[u8],
+) -> Result<DecodedAudio<S>, Box<dyn std::error::Error + Send + Sync>> {
     let cursor = std::io::Cursor::new(data.to_vec());
-    let media_source_stream = MediaSourceStream::new(Box::new(cursor), Default::default());
+    let mut samples: Vec<S> = Vec::new();
+    let mut format = None;
+    decode_stream(Box::new(cursor), |block, sample_rate, channels| {
+        samples.extend_from_slice(block);
+        format = Some((sample_rate, channels));
+        Ok(())
+    })?;
+
+    let Some((sample_rate, channels)) = format.filter(|_| !samples.is_empty()) else {
+        return Err("no audio samples could be decoded from file".into());
+    };
+    Ok(DecodedAudio {
+        samples,
+        sample_rate,
+        channels,
+    })
+}
+
+fn decode_stream<S: ConvertibleSample>(
+    source: Box<dyn MediaSource>,
+    mut sink: impl FnMut(&[S], u32, usize) -> Result<(), Box<dyn std::error::Error + Send + Sync>>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let media_source_stream = MediaSourceStream::new(source, Default::default());
 
     let probed = symphonia::default::get_probe().format(
         &Hint::new(),
@@ -45,8 +85,6 @@ pub fn decode_compressed_audio(
     let mut sample_rate: Option<u32> = codec_params.sample_rate;
     let mut channels: Option<usize> = codec_params.channels.map(|channels| channels.count());
 
-    let mut samples: Vec<f32> = Vec::new();
-
     loop {
         let packet = match format.next_packet() {
             Ok(packet) => packet,
@@ -68,12 +106,12 @@ pub fn decode_compressed_audio(
         match decoder.decode(&packet) {
             Ok(decoded) => {
                 let spec = *decoded.spec();
-                sample_rate = sample_rate.or(Some(spec.rate));
-                channels = channels.or(Some(spec.channels.count()));
+                let rate = *sample_rate.get_or_insert(spec.rate);
+                let count = *channels.get_or_insert(spec.channels.count());
 
-                let mut sample_buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+                let mut sample_buffer = SampleBuffer::<S>::new(decoded.capacity() as u64, spec);
                 sample_buffer.copy_interleaved_ref(decoded);
-                samples.extend_from_slice(sample_buffer.samples());
+                sink(sample_buffer.samples(), rate, count)?;
             }
             Err(SymphoniaError::DecodeError(e)) => {
                 log::warn!("Skipping undecodable audio packet: {}", e);
@@ -86,13 +124,5 @@ pub fn decode_compressed_audio(
         }
     }
 
-    if samples.is_empty() {
-        return Err("no audio samples could be decoded from file".into());
-    }
-
-    Ok(DecodedAudio {
-        samples,
-        sample_rate: sample_rate.ok_or("could not determine audio sample rate")?,
-        channels: channels.ok_or("could not determine audio channel count")?,
-    })
+    Ok(())
 }
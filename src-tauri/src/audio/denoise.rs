@@ -0,0 +1,614 @@
+//! In-process noise suppression for captured audio. Spectral gating handles
+//! the steady hum and hiss of fans and mains; the RNNoise network (via
+//! `nnnoiseless`) also handles non-stationary noise such as keyboards.
+//! Both blend with the original by `noise_reduction_strength`, so 0 leaves
+//! the audio untouched and 1 applies the full reduction.
+
+use std::collections::VecDeque;
+
+use std::path::Path;
+
+use crate::config::NoiseReductionProvider;
+
+use super::conversion::StreamResampler;
+use super::whisper_file::{for_each_block, WhisperFile, WhisperWriter, WHISPER_SAMPLE_RATE};
+
+/// STFT frame length in samples (32ms at 16kHz; a power of two for the FFT).
+const FRAME_LEN: usize = 512;
+/// 75% overlap keeps the gain changes between frames inaudible.
+const HOP_LEN: usize = FRAME_LEN / 4;
+/// Frames the noise profile is estimated from, about four seconds at 16kHz.
+const NOISE_PROFILE_FRAMES: usize = 500;
+/// The quietest share of those frames is taken to be noise only.
+const NOISE_PROFILE_FRACTION: f32 = 0.2;
+/// How far above the noise mean (in standard deviations) a bin must be to
+/// count as signal.
+const THRESHOLD_STDS: f32 = 1.5;
+/// Width of the soft knee around the threshold, in dB.
+const KNEE_DB: f32 = 3.0;
+/// The sample rate the RNNoise network was trained at.
+const RNNOISE_SAMPLE_RATE: u32 = 48000;
+
+/// Denoises a whisper file (the captured-audio format) into a new one with
+/// the given in-process provider, streaming it through a block at a time.
+/// The Python provider is not handled here.
+pub fn denoise_file(
+    input: &Path,
+    provider: NoiseReductionProvider,
+    strength: f32,
+) -> Result<WhisperFile, String> {
+    let mut output = WhisperWriter::create()?;
+    match provider {
+        NoiseReductionProvider::SpectralGate => {
+            let mut gate = SpectralGate::new(strength);
+            for_each_block(input, |block| {
+                output.push(&gate.push(block));
+                Ok(())
+            })?;
+            output.push(&gate.finish());
+        }
+        NoiseReductionProvider::RNNoise => {
+            let mut rnnoise = RnNoise::new(WHISPER_SAMPLE_RATE, strength);
+            for_each_block(input, |block| {
+                output.push(&rnnoise.push(block)?);
+                Ok(())
+            })?;
+            output.push(&rnnoise.finish()?);
+        }
+        NoiseReductionProvider::Python => {
+            return Err("The Python provider runs in the Python runner".to_string())
+        }
+    }
+    output.finish()
+}
+
+#[derive(Clone, Copy, Default)]
+struct Complex {
+    re: f32,
+    im: f32,
+}
+
+/// In-place iterative radix-2 FFT; `buf.len()` must be a power of two. The
+/// inverse transform is unscaled.
+fn fft(buf: &mut [Complex], inverse: bool) {
+    let n = buf.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            buf.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * 2.0 * std::f32::consts::PI / len as f32;
+        let (w_re, w_im) = (angle.cos(), angle.sin());
+        for start in (0..n).step_by(len) {
+            let (mut cur_re, mut cur_im) = (1.0f32, 0.0f32);
+            for k in 0..len / 2 {
+                let a = buf[start + k];
+                let b = buf[start + k + len / 2];
+                let t = Complex {
+                    re: b.re * cur_re - b.im * cur_im,
+                    im: b.re * cur_im + b.im * cur_re,
+                };
+                buf[start + k] = Complex {
+                    re: a.re + t.re,
+                    im: a.im + t.im,
+                };
+                buf[start + k + len / 2] = Complex {
+                    re: a.re - t.re,
+                    im: a.im - t.im,
+                };
+                let next_re = cur_re * w_re - cur_im * w_im;
+                cur_im = cur_re * w_im + cur_im * w_re;
+                cur_re = next_re;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+fn hann_window() -> Vec<f32> {
+    (0..FRAME_LEN)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / FRAME_LEN as f32).cos())
+        .collect()
+}
+
+/// Analysis of one STFT frame, plus its gain mask once the noise profile is
+/// known.
+struct GateFrame {
+    spectrum: Vec<Complex>,
+    levels_db: Vec<f32>,
+    energy: f32,
+    mask: Option<Vec<f32>>,
+}
+
+/// Spectral gating: estimates a per-frequency noise profile from the
+/// quietest frames near the start of the audio, then attenuates every
+/// time-frequency bin that does not rise clearly above it.
+///
+/// Audio is pushed through in blocks and comes back out with a short delay,
+/// so memory stays flat however long the recording is. Only the profiling
+/// window at the start is held until the profile is ready.
+pub struct SpectralGate {
+    strength: f32,
+    window: Vec<f32>,
+    /// Padded input from `input_start` that later frames still need.
+    input: Vec<f32>,
+    input_start: usize,
+    /// Index of the next frame to analyse.
+    next_frame: usize,
+    /// Analysed frames not yet resynthesised, oldest first.
+    pending: VecDeque<GateFrame>,
+    thresholds: Option<Vec<f32>>,
+    /// Mask of the frame resynthesised last, for smoothing the next one.
+    prev_mask: Option<Vec<f32>>,
+    /// Overlap-add accumulators, from padded position `output_start`.
+    output: Vec<f32>,
+    norm: Vec<f32>,
+    output_start: usize,
+    /// The first samples, returned as they are if the input is too short
+    /// for a single frame.
+    head: Vec<f32>,
+    received: usize,
+}
+
+impl SpectralGate {
+    pub fn new(strength: f32) -> Self {
+        Self {
+            strength: strength.clamp(0.0, 1.0),
+            window: hann_window(),
+            // Pad so the first samples sit in as many frames as the rest.
+            input: vec![0.0; FRAME_LEN],
+            input_start: 0,
+            next_frame: 0,
+            pending: VecDeque::new(),
+            thresholds: None,
+            prev_mask: None,
+            output: Vec::new(),
+            norm: Vec::new(),
+            output_start: 0,
+            head: Vec::new(),
+            received: 0,
+        }
+    }
+
+    /// Feeds samples in and returns whatever output is now final.
+    pub fn push(&mut self, samples: &[f32]) -> Vec<f32> {
+        if self.strength == 0.0 {
+            return samples.to_vec();
+        }
+        let head = (FRAME_LEN - self.head.len().min(FRAME_LEN)).min(samples.len());
+        self.head.extend_from_slice(&samples[..head]);
+        self.received += samples.len();
+        self.input.extend_from_slice(samples);
+        self.process(false)
+    }
+
+    /// Flushes the remaining output once all input has been pushed.
+    pub fn finish(mut self) -> Vec<f32> {
+        if self.strength == 0.0 {
+            return Vec::new();
+        }
+        if self.received < FRAME_LEN {
+            return self.head;
+        }
+        // Trailing padding carries the last samples through every frame.
+        self.input.resize(self.input.len() + FRAME_LEN * 2, 0.0);
+        self.process(true)
+    }
+
+    fn process(&mut self, finishing: bool) -> Vec<f32> {
+        while self.next_frame * HOP_LEN + FRAME_LEN <= self.input_start + self.input.len() {
+            let start = self.next_frame * HOP_LEN - self.input_start;
+            let frame = self.analyse(start);
+            self.pending.push_back(frame);
+            self.next_frame += 1;
+        }
+        let consumed = self.next_frame * HOP_LEN - self.input_start;
+        self.input.drain(..consumed.min(self.input.len()));
+        self.input_start += consumed;
+
+        self.update_masks(finishing);
+
+        // A frame's mask is smoothed with the next frame's, so the newest
+        // frame waits for its successor unless there is none coming.
+        let mut output = Vec::new();
+        let waiting = if finishing { 0 } else { 1 };
+        while self.pending.len() > waiting
+            && self
+                .pending
+                .iter()
+                .take(2)
+                .all(|frame| frame.mask.is_some())
+        {
+            self.resynthesise_next(&mut output);
+        }
+        output
+    }
+
+    fn analyse(&self, start: usize) -> GateFrame {
+        let mut spectrum: Vec<Complex> = self.input[start..start + FRAME_LEN]
+            .iter()
+            .zip(&self.window)
+            .map(|(s, w)| Complex { re: s * w, im: 0.0 })
+            .collect();
+        fft(&mut spectrum, false);
+        let power = |c: &Complex| c.re * c.re + c.im * c.im;
+        GateFrame {
+            levels_db: spectrum[..FRAME_LEN / 2 + 1]
+                .iter()
+                .map(|c| 10.0 * (power(c) + 1e-12).log10())
+                .collect(),
+            energy: spectrum.iter().map(power).sum(),
+            spectrum,
+            mask: None,
+        }
+    }
+
+    /// Builds the noise profile once enough frames are in, and masks every
+    /// frame it can.
+    fn update_masks(&mut self, finishing: bool) {
+        while self.thresholds.is_none() {
+            let first = self.next_frame - self.pending.len();
+            let window: Vec<usize> = (0..self.pending.len())
+                .filter(|&i| self.pending[i].mask.is_none())
+                .take(NOISE_PROFILE_FRAMES)
+                .collect();
+            if window.is_empty() || (window.len() < NOISE_PROFILE_FRAMES && !finishing) {
+                return;
+            }
+            // Frames overlapping the padding and digitally silent ones would
+            // drag the profile towards silence and inflate its spread, so
+            // only real audio counts.
+            let interior = FRAME_LEN / HOP_LEN..=self.received / HOP_LEN;
+            let mut quiet: Vec<&GateFrame> = window
+                .iter()
+                .filter(|&&i| interior.contains(&(first + i)) && self.pending[i].energy > 1e-9)
+                .map(|&i| &self.pending[i])
+                .collect();
+            if quiet.is_empty() {
+                // Nothing but silence so far: let it through and profile
+                // whatever follows.
+                for &i in &window {
+                    self.pending[i].mask = Some(vec![1.0; FRAME_LEN / 2 + 1]);
+                }
+                continue;
+            }
+            quiet.sort_by(|a, b| a.energy.total_cmp(&b.energy));
+            quiet.truncate(((quiet.len() as f32 * NOISE_PROFILE_FRACTION) as usize).max(1));
+            let n = quiet.len() as f32;
+            let thresholds = (0..FRAME_LEN / 2 + 1)
+                .map(|bin| {
+                    let mean = quiet.iter().map(|f| f.levels_db[bin]).sum::<f32>() / n;
+                    let variance = quiet
+                        .iter()
+                        .map(|f| (f.levels_db[bin] - mean).powi(2))
+                        .sum::<f32>()
+                        / n;
+                    mean + THRESHOLD_STDS * variance.sqrt()
+                })
+                .collect();
+            self.thresholds = Some(thresholds);
+        }
+
+        let Some(thresholds) = &self.thresholds else {
+            return;
+        };
+        for frame in self.pending.iter_mut().filter(|f| f.mask.is_none()) {
+            frame.mask = Some(
+                frame
+                    .levels_db
+                    .iter()
+                    .zip(thresholds)
+                    .map(|(level, threshold)| {
+                        ((level - threshold) / KNEE_DB * 0.5 + 0.5).clamp(0.0, 1.0)
+                    })
+                    .collect(),
+            );
+        }
+    }
+
+    /// Gates the oldest pending frame, adds it into the output and moves
+    /// the samples no later frame overlaps into `output`.
+    fn resynthesise_next(&mut self, output: &mut Vec<f32>) {
+        let Some(GateFrame {
+            mut spectrum,
+            mask: Some(mask),
+            ..
+        }) = self.pending.pop_front()
+        else {
+            return;
+        };
+        let frame = self.next_frame - self.pending.len() - 1;
+
+        // Average each bin with its neighbours in this and the adjacent
+        // frames so the mask has no isolated bins, which would otherwise
+        // ring as "musical noise".
+        let rows: Vec<&Vec<f32>> = self
+            .prev_mask
+            .iter()
+            .chain(Some(&mask))
+            .chain(self.pending.front().and_then(|next| next.mask.as_ref()))
+            .collect();
+        let bins = mask.len();
+        let gains: Vec<f32> = (0..bins)
+            .map(|bin| {
+                let neighbours = bin.saturating_sub(1)..(bin + 2).min(bins);
+                let sum: f32 = rows
+                    .iter()
+                    .map(|row| row[neighbours.clone()].iter().sum::<f32>())
+                    .sum();
+                sum / (rows.len() * neighbours.len()) as f32
+            })
+            .collect();
+        for bin in 0..FRAME_LEN {
+            // Bins above Nyquist mirror those below it.
+            let gain = 1.0 - self.strength * (1.0 - gains[bin.min(FRAME_LEN - bin)]);
+            spectrum[bin].re *= gain;
+            spectrum[bin].im *= gain;
+        }
+        fft(&mut spectrum, true);
+        self.prev_mask = Some(mask);
+
+        // Weighted overlap-add, normalised by the summed squared window.
+        let start = frame * HOP_LEN - self.output_start;
+        self.output.resize(start + FRAME_LEN, 0.0);
+        self.norm.resize(start + FRAME_LEN, 0.0);
+        for (i, (c, w)) in spectrum.iter().zip(&self.window).enumerate() {
+            self.output[start + i] += c.re / FRAME_LEN as f32 * w;
+            self.norm[start + i] += w * w;
+        }
+
+        let done = (frame + 1) * HOP_LEN - self.output_start;
+        let real = FRAME_LEN..FRAME_LEN + self.received;
+        output.extend(
+            self.output
+                .drain(..done)
+                .zip(self.norm.drain(..done))
+                .enumerate()
+                .filter(|(i, _)| real.contains(&(self.output_start + i)))
+                .map(|(_, (s, n))| if n > 1e-6 { s / n } else { 0.0 }),
+        );
+        self.output_start += done;
+    }
+}
+
+/// Runs the RNNoise network at its native 48kHz over audio pushed through
+/// in blocks, and mixes the result with the original by `strength`. Output
+/// comes back a little behind the input, and `finish` flushes the rest so
+/// the total matches the input length.
+pub struct RnNoise {
+    network: Network,
+    upsampler: StreamResampler,
+    downsampler: StreamResampler,
+    /// 48kHz input not yet making up a whole frame.
+    pending: Vec<f32>,
+    input_len: usize,
+    output_len: usize,
+}
+
+/// The network itself, mixing each frame's output with its input.
+struct Network {
+    strength: f32,
+    state: Box<nnnoiseless::DenoiseState<'static>>,
+    /// The last frame sent to the network, mixed once its output arrives.
+    dry: Option<Vec<f32>>,
+    in_frame: Vec<f32>,
+    out_frame: Vec<f32>,
+}
+
+impl RnNoise {
+    pub fn new(sample_rate: u32, strength: f32) -> Self {
+        let frame_len = nnnoiseless::DenoiseState::FRAME_SIZE;
+        Self {
+            network: Network {
+                strength: strength.clamp(0.0, 1.0),
+                state: nnnoiseless::DenoiseState::new(),
+                dry: None,
+                in_frame: vec![0.0; frame_len],
+                out_frame: vec![0.0; frame_len],
+            },
+            upsampler: StreamResampler::new(sample_rate, RNNOISE_SAMPLE_RATE),
+            downsampler: StreamResampler::new(RNNOISE_SAMPLE_RATE, sample_rate),
+            pending: Vec::with_capacity(frame_len),
+            input_len: 0,
+            output_len: 0,
+        }
+    }
+
+    pub fn push(&mut self, samples: &[f32]) -> Result<Vec<f32>, String> {
+        self.input_len += samples.len();
+        if self.network.strength == 0.0 {
+            self.output_len += samples.len();
+            return Ok(samples.to_vec());
+        }
+        let frame_len = nnnoiseless::DenoiseState::FRAME_SIZE;
+        let mut mixed = Vec::new();
+        for sample in self.upsampler.push(samples)? {
+            self.pending.push(sample);
+            if self.pending.len() == frame_len {
+                let frame = std::mem::replace(&mut self.pending, Vec::with_capacity(frame_len));
+                self.network.process_frame(frame, &mut mixed);
+            }
+        }
+        let output = self.downsampler.push(&mixed)?;
+        self.output_len += output.len();
+        Ok(output)
+    }
+
+    pub fn finish(self) -> Result<Vec<f32>, String> {
+        let Self {
+            mut network,
+            upsampler,
+            mut downsampler,
+            mut pending,
+            input_len,
+            output_len,
+        } = self;
+        if network.strength == 0.0 {
+            return Ok(Vec::new());
+        }
+        let mut mixed = Vec::new();
+        pending.extend(upsampler.finish()?);
+        for chunk in pending.chunks(nnnoiseless::DenoiseState::FRAME_SIZE) {
+            network.process_frame(chunk.to_vec(), &mut mixed);
+        }
+        // The network's overlap-add delays its output by one frame; a
+        // trailing silent frame flushes the last real one out.
+        network.process_frame(Vec::new(), &mut mixed);
+        let mut output = downsampler.push(&mixed)?;
+        output.extend(downsampler.finish()?);
+        // The network's delay keeps the output behind the input until here,
+        // where it is cut or padded to the input length.
+        output.resize(input_len.saturating_sub(output_len), 0.0);
+        Ok(output)
+    }
+}
+
+impl Network {
+    /// Sends one frame (zero-padded when short) through the network, and
+    /// mixes the previous frame with the output that belongs to it.
+    fn process_frame(&mut self, frame: Vec<f32>, mixed: &mut Vec<f32>) {
+        // RNNoise expects 16-bit sample magnitudes.
+        self.in_frame.fill(0.0);
+        for (dst, src) in self.in_frame.iter_mut().zip(&frame) {
+            *dst = src * i16::MAX as f32;
+        }
+        self.state
+            .process_frame(&mut self.out_frame, &self.in_frame);
+        if let Some(dry) = self.dry.replace(frame) {
+            mixed.extend(dry.iter().zip(&self.out_frame).map(|(dry, wet)| {
+                let wet = wet / i16::MAX as f32;
+                dry + self.strength * (wet - dry)
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic white-ish noise from a linear congruential generator.
+    fn noise(len: usize, amplitude: f32) -> Vec<f32> {
+        let mut state = 12345u32;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                ((state >> 16) as f32 / 32768.0 - 1.0) * amplitude
+            })
+            .collect()
+    }
+
+    fn spectral_gate(samples: &[f32], strength: f32) -> Vec<f32> {
+        let mut gate = SpectralGate::new(strength);
+        let mut output = gate.push(samples);
+        output.extend(gate.finish());
+        output
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_fft_round_trips() {
+        let input: Vec<f32> = (0..16).map(|i| (i as f32 * 0.7).sin()).collect();
+        let mut buf: Vec<Complex> = input.iter().map(|&re| Complex { re, im: 0.0 }).collect();
+        fft(&mut buf, false);
+        fft(&mut buf, true);
+        for (original, c) in input.iter().zip(&buf) {
+            assert!((original - c.re / 16.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_zero_strength_and_short_input_are_untouched() {
+        let samples = noise(4000, 0.1);
+        assert_eq!(spectral_gate(&samples, 0.0), samples);
+        assert_eq!(spectral_gate(&samples[..100], 1.0), &samples[..100]);
+    }
+
+    #[test]
+    fn test_streamed_gate_matches_one_shot_with_bounded_state() {
+        // Long enough that gating starts while audio is still coming in.
+        let rate = 16000;
+        let mut samples = noise(rate * 10, 0.05);
+        for (i, sample) in samples[rate * 6..].iter_mut().enumerate() {
+            *sample += 0.5 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / rate as f32).sin();
+        }
+
+        let mut gate = SpectralGate::new(1.0);
+        let mut streamed = Vec::new();
+        for block in samples.chunks(1000) {
+            streamed.extend(gate.push(block));
+            assert!(gate.pending.len() <= NOISE_PROFILE_FRAMES + 1000 / HOP_LEN + 1);
+        }
+        assert!(gate.pending.len() <= 2);
+        assert!(gate.input.len() < FRAME_LEN + 1000);
+        streamed.extend(gate.finish());
+
+        assert_eq!(streamed, spectral_gate(&samples, 1.0));
+        assert!(rms(&streamed[rate * 4..rate * 6]) < rms(&samples[rate * 4..rate * 6]) * 0.3);
+    }
+
+    #[test]
+    fn test_streamed_rnnoise_matches_one_shot() {
+        let samples = noise(16000 * 2 + 123, 0.1);
+        let mut network = RnNoise::new(16000, 0.7);
+        let mut streamed = Vec::new();
+        for block in samples.chunks(777) {
+            streamed.extend(network.push(block).unwrap());
+        }
+        streamed.extend(network.finish().unwrap());
+        assert_eq!(streamed.len(), samples.len());
+        let mut network = RnNoise::new(16000, 0.7);
+        let mut one_shot = network.push(&samples).unwrap();
+        one_shot.extend(network.finish().unwrap());
+        assert_eq!(streamed, one_shot);
+    }
+
+    #[test]
+    fn test_spectral_gate_removes_noise_and_keeps_tone() {
+        // One second of noise, then a second of a 440Hz tone over the same
+        // noise.
+        let rate = 16000;
+        let mut samples = noise(rate * 2, 0.05);
+        for (i, sample) in samples[rate..].iter_mut().enumerate() {
+            *sample += 0.5 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / rate as f32).sin();
+        }
+
+        let gated = spectral_gate(&samples, 1.0);
+        assert_eq!(gated.len(), samples.len());
+
+        let noise_before = rms(&samples[2000..rate - 2000]);
+        let noise_after = rms(&gated[2000..rate - 2000]);
+        assert!(
+            noise_after < noise_before * 0.3,
+            "{} -> {}",
+            noise_before,
+            noise_after
+        );
+
+        let tone_before = rms(&samples[rate + 2000..]);
+        let tone_after = rms(&gated[rate + 2000..]);
+        assert!(
+            tone_after > tone_before * 0.8,
+            "{} -> {}",
+            tone_before,
+            tone_after
+        );
+
+        let half = spectral_gate(&samples, 0.5);
+        assert!(rms(&half[2000..rate - 2000]) > noise_after);
+    }
+}
@@ -143,8 +143,10 @@ fn get_windows_audio_devices() -> Result<Vec<AudioDevice>, String> {
     Ok(devices)
 }
 
+/// Lists PulseAudio/PipeWire sources: microphones, or with `monitors` the
+/// monitor sources that capture what a sink plays.
 #[cfg(target_os = "linux")]
-fn get_linux_pulse_devices() -> Result<Vec<AudioDevice>, String> {
+fn get_linux_pulse_devices(monitors: bool) -> Result<Vec<AudioDevice>, String> {
     let mut devices = Vec::new();
     let mut handler = pulsectl::controllers::SourceController::create()
         .map_err(|e| format!("Failed to connect to PulseAudio: {}", e))?;
@@ -155,9 +157,9 @@ fn get_linux_pulse_devices() -> Result<Vec<AudioDevice>, String> {
     for source in sources {
         let name = source.name.clone().unwrap_or_default();
         let description = source.description.clone().unwrap_or_default();
-        if name.to_lowercase().contains(".monitor")
-            || description.to_lowercase().contains("monitor")
-        {
+        let is_monitor = name.to_lowercase().contains(".monitor")
+            || description.to_lowercase().contains("monitor");
+        if is_monitor != monitors {
             continue;
         }
         devices.push(AudioDevice {
@@ -173,7 +175,7 @@ pub fn get_input_devices() -> Result<Vec<AudioDevice>, String> {
     let mut final_devices = Vec::new();
     #[cfg(target_os = "linux")]
     {
-        if let Ok(devices) = get_linux_pulse_devices() {
+        if let Ok(devices) = get_linux_pulse_devices(false) {
             final_devices = devices;
         }
     }
@@ -308,6 +310,8 @@ pub fn lookup_device(target_id: Option<String>) -> Result<cpal::Device, String>
     } else {
         #[cfg(target_os = "linux")]
         {
+            // A source picked earlier must not keep steering the default.
+            std::env::remove_var("PULSE_SOURCE");
             if let Ok(devices) = host.input_devices() {
                 for dev in devices {
                     if let Ok(id) = dev.id() {
@@ -327,6 +331,73 @@ pub fn lookup_device(target_id: Option<String>) -> Result<cpal::Device, String>
     }
 }
 
+/// Sources that capture system playback: PulseAudio/PipeWire monitor sources
+/// on Linux, WASAPI loopback of render endpoints on Windows. The first entry
+/// follows the default output.
+pub fn get_monitor_devices() -> Result<Vec<AudioDevice>, String> {
+    #[cfg(target_os = "linux")]
+    let mut devices = get_linux_pulse_devices(true)?;
+
+    #[cfg(target_os = "windows")]
+    let mut devices: Vec<AudioDevice> = get_windows_output_audio_devices()?
+        .into_iter()
+        .map(|device| AudioDevice {
+            id: format!("{}{}", LOOPBACK_PREFIX, device.id),
+            label: device.label,
+            is_system_default: false,
+        })
+        .collect();
+
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    let mut devices: Vec<AudioDevice> = Vec::new();
+
+    devices.sort_by(|a, b| a.label.cmp(&b.label));
+    devices.insert(
+        0,
+        AudioDevice {
+            id: "default".to_string(),
+            label: "Default Output".to_string(),
+            is_system_default: true,
+        },
+    );
+    Ok(devices)
+}
+
+/// Id prefix for output devices captured through WASAPI loopback.
+#[cfg_attr(target_os = "linux", allow(dead_code))]
+const LOOPBACK_PREFIX: &str = "loopback:";
+
+/// Resolves a monitor id from `get_monitor_devices` to a device the
+/// persistent engine can open as an input.
+pub fn lookup_monitor_device(target_id: Option<String>) -> Result<cpal::Device, String> {
+    let target = target_id.filter(|id| id != "default");
+
+    #[cfg(target_os = "linux")]
+    {
+        // The pulse server resolves @DEFAULT_MONITOR@ to the monitor of the
+        // current default sink.
+        let id = target.unwrap_or_else(|| "pulse:@DEFAULT_MONITOR@".to_string());
+        lookup_device(Some(id))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let host = cpal::default_host();
+        match target {
+            Some(id) => {
+                let output_id = id.strip_prefix(LOOPBACK_PREFIX).unwrap_or(&id).to_string();
+                host.output_devices()
+                    .map_err(|e| e.to_string())?
+                    .find(|d| d.id().map(|id| id.1 == output_id).unwrap_or(false))
+                    .ok_or_else(|| format!("Output device '{output_id}' not found."))
+            }
+            None => host
+                .default_output_device()
+                .ok_or_else(|| "No output device available to capture.".to_string()),
+        }
+    }
+}
+
 #[cfg(target_os = "linux")]
 fn get_linux_pulse_output_devices() -> Result<Vec<AudioDevice>, String> {
     let mut devices = Vec::new();
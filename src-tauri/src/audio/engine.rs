@@ -1,16 +1,36 @@
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use cpal::traits::{DeviceTrait, StreamTrait};
 use cpal::SampleFormat;
 use ringbuf::traits::*;
 use ringbuf::{CachingCons, HeapRb};
 
+/// How much audio the pre-roll ring keeps: the longest pre-roll a session
+/// can ask for. Sessions take the most recent slice they need (see
+/// `record_audio_while_flag`).
+pub const PRE_ROLL_CAPACITY_MS: u64 = crate::config::VAD_PRE_ROLL_MS_MAX;
+
+/// A stream whose callback has not run for this long is treated as dead.
+/// Backends keep delivering (silent) buffers while a device is present, so
+/// a stall means the device went away without an error being reported.
+const STALL_TIMEOUT: Duration = Duration::from_secs(3);
+
 pub struct PersistentAudioEngine {
     pub stream: cpal::Stream,
     pub pre_roll_consumer: Arc<Mutex<CachingCons<Arc<HeapRb<f32>>>>>,
     pub recording_tx: Arc<Mutex<Option<mpsc::SyncSender<f32>>>>,
+    /// Always-on tap for listeners that watch the input without recording
+    /// it, such as the hands-free voice activity detector.
+    pub monitor_tx: Arc<Mutex<Option<mpsc::SyncSender<f32>>>>,
     pub sample_rate: u32,
     pub channels: u16,
+    /// First fatal error reported by the stream, if any.
+    stream_error: Arc<Mutex<Option<String>>>,
+    opened_at: Instant,
+    /// Milliseconds after `opened_at` of the most recent callback.
+    last_callback_ms: Arc<AtomicU64>,
 }
 
 impl PersistentAudioEngine {
@@ -23,8 +43,11 @@ impl PersistentAudioEngine {
                     "Audio Engine: Preferred stream init failed ({}); falling back to default config",
                     err
                 );
+                // Output devices have no input config; opening one as an
+                // input captures its playback (WASAPI loopback).
                 let default_config = device
                     .default_input_config()
+                    .or_else(|_| device.default_output_config())
                     .map_err(|e| format!("Failed to get default input config: {}", e))?;
                 Self::try_build_stream(device, default_config, sensitivity)
             }
@@ -46,29 +69,61 @@ impl PersistentAudioEngine {
             config.sample_format()
         );
 
-        let pre_roll_size = (sample_rate as f32 * 0.2) as usize;
+        let pre_roll_size = (sample_rate as u64 * PRE_ROLL_CAPACITY_MS / 1000) as usize;
         let pre_roll_rb = HeapRb::<f32>::new(pre_roll_size);
         let (mut pre_roll_prod, pre_roll_cons) = pre_roll_rb.split();
+        let pre_roll_consumer = Arc::new(Mutex::new(pre_roll_cons));
+        let pre_roll_consumer_clone = pre_roll_consumer.clone();
 
         let recording_tx = Arc::new(Mutex::new(None::<mpsc::SyncSender<f32>>));
         let recording_tx_clone = recording_tx.clone();
+        let monitor_tx = Arc::new(Mutex::new(None::<mpsc::SyncSender<f32>>));
+        let monitor_tx_clone = monitor_tx.clone();
+
+        let stream_error = Arc::new(Mutex::new(None::<String>));
+        let stream_error_clone = stream_error.clone();
+        let err_fn = move |err: cpal::StreamError| {
+            crate::log_info!("Audio stream error: {}", err);
+            // Under/overruns are glitches the stream recovers from; anything
+            // else means it has to be rebuilt.
+            if !matches!(err, cpal::StreamError::BufferUnderrun) {
+                if let Ok(mut guard) = stream_error_clone.lock() {
+                    guard.get_or_insert_with(|| err.to_string());
+                }
+            }
+        };
 
-        let err_fn = |err| crate::log_info!("Audio stream error: {}", err);
+        let opened_at = Instant::now();
+        let last_callback_ms = Arc::new(AtomicU64::new(0));
+        let last_callback_ms_clone = last_callback_ms.clone();
 
         let stream_config: cpal::StreamConfig = config.clone().into();
         let channels_usize = channels as usize;
 
         let mut audio_callback = move |data: &[f32], _: &cpal::InputCallbackInfo| {
+            last_callback_ms_clone.store(opened_at.elapsed().as_millis() as u64, Ordering::Relaxed);
             for frame in data.chunks(channels_usize) {
                 let sample_raw: f32 = frame.iter().sum::<f32>() / channels_usize as f32;
                 let sample = sample_raw * sensitivity;
 
-                let _ = pre_roll_prod.try_push(sample);
+                // Keep the ring rolling: drop the oldest sample when full so
+                // the pre-roll is always the audio just before a session.
+                if pre_roll_prod.try_push(sample).is_err() {
+                    if let Ok(mut cons) = pre_roll_consumer_clone.try_lock() {
+                        let _ = cons.try_pop();
+                    }
+                    let _ = pre_roll_prod.try_push(sample);
+                }
                 if let Ok(guard) = recording_tx_clone.try_lock() {
                     if let Some(tx) = guard.as_ref() {
                         let _ = tx.try_send(sample);
                     }
                 }
+                if let Ok(guard) = monitor_tx_clone.try_lock() {
+                    if let Some(tx) = guard.as_ref() {
+                        let _ = tx.try_send(sample);
+                    }
+                }
             }
         };
 
@@ -96,12 +151,28 @@ impl PersistentAudioEngine {
 
         Ok(Self {
             stream,
-            pre_roll_consumer: Arc::new(Mutex::new(pre_roll_cons)),
+            pre_roll_consumer,
             recording_tx,
+            monitor_tx,
             sample_rate,
             channels: 1, // downmixed to mono in callback
+            stream_error,
+            opened_at,
+            last_callback_ms,
         })
     }
+
+    /// Why the stream can no longer be used, if it cannot: a fatal stream
+    /// error, or no callbacks for `STALL_TIMEOUT`.
+    pub fn failure(&self) -> Option<String> {
+        if let Some(error) = self.stream_error.lock().unwrap().clone() {
+            return Some(error);
+        }
+        let last_callback = Duration::from_millis(self.last_callback_ms.load(Ordering::Relaxed));
+        let silent_for = self.opened_at.elapsed().saturating_sub(last_callback);
+        (silent_for >= STALL_TIMEOUT)
+            .then(|| format!("no audio from the device for {}s", silent_for.as_secs()))
+    }
 }
 
 fn select_preferred_config(device: &cpal::Device) -> cpal::SupportedStreamConfig {
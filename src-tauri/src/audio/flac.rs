@@ -0,0 +1,451 @@
+//! Minimal FLAC encoder for saved recordings.
+//!
+//! Only what history audio needs: 16-bit PCM, fixed-size blocks, and per
+//! channel either a constant, verbatim or fixed-predictor subframe with
+//! partitioned Rice coding of the residual. That already roughly halves the
+//! size of speech WAVs; decoding goes through symphonia like any other FLAC.
+
+use std::io::Write;
+
+const BLOCK_SIZE: usize = 4096;
+const BITS_PER_SAMPLE: u32 = 16;
+const MAX_FIXED_ORDER: usize = 4;
+const MAX_PARTITION_ORDER: u32 = 6;
+/// Largest Rice parameter the 4-bit field can hold; 15 is the escape code.
+const MAX_RICE_PARAM: u32 = 14;
+
+/// Encodes interleaved 16-bit samples pushed in blocks, writing each FLAC
+/// frame as soon as it is full. The total length goes in the header, so it
+/// must be known up front.
+pub struct FlacEncoder<W: Write> {
+    out: W,
+    sample_rate: u32,
+    channels: usize,
+    block: Vec<i16>,
+    index: u64,
+    remaining: u64,
+}
+
+impl<W: Write> FlacEncoder<W> {
+    pub fn new(mut out: W, sample_rate: u32, channels: u16, frames: u64) -> Result<Self, String> {
+        if !(1..=8).contains(&channels) {
+            return Err(format!("FLAC supports 1-8 channels, got {}", channels));
+        }
+        if sample_rate == 0 || sample_rate >= 1 << 20 {
+            return Err(format!("Unsupported FLAC sample rate {}", sample_rate));
+        }
+        let channels = channels as usize;
+
+        let mut header = BitWriter::default();
+        header.write_bytes(b"fLaC");
+        // STREAMINFO, the only (and so last) metadata block.
+        header.write(1, 1);
+        header.write(0, 7);
+        header.write(34, 24);
+        header.write(BLOCK_SIZE as u64, 16);
+        header.write(BLOCK_SIZE as u64, 16);
+        header.write(0, 24);
+        header.write(0, 24);
+        header.write(sample_rate as u64, 20);
+        header.write(channels as u64 - 1, 3);
+        header.write(BITS_PER_SAMPLE as u64 - 1, 5);
+        header.write(frames, 36);
+        // An all-zero MD5 means "not computed".
+        header.write_bytes(&[0; 16]);
+        out.write_all(&header.bytes).map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            out,
+            sample_rate,
+            channels,
+            block: Vec::with_capacity(BLOCK_SIZE * channels),
+            index: 0,
+            remaining: frames * channels as u64,
+        })
+    }
+
+    /// Appends samples. Anything past the length given to `new` is dropped.
+    pub fn push(&mut self, samples: &[i16]) -> Result<(), String> {
+        let samples = &samples[..samples.len().min(self.remaining as usize)];
+        self.remaining -= samples.len() as u64;
+        for chunk in samples.chunks(BLOCK_SIZE * self.channels) {
+            let take = (BLOCK_SIZE * self.channels - self.block.len()).min(chunk.len());
+            self.block.extend_from_slice(&chunk[..take]);
+            if self.block.len() == BLOCK_SIZE * self.channels {
+                self.write_block()?;
+            }
+            self.block.extend_from_slice(&chunk[take..]);
+        }
+        Ok(())
+    }
+
+    /// Writes the last, short block. Fails if fewer samples arrived than
+    /// the header promised.
+    pub fn finish(mut self) -> Result<W, String> {
+        if self.remaining > 0 {
+            return Err(format!("FLAC stream is {} samples short", self.remaining));
+        }
+        if !self.block.is_empty() {
+            self.write_block()?;
+        }
+        self.out.flush().map_err(|e| e.to_string())?;
+        Ok(self.out)
+    }
+
+    fn write_block(&mut self) -> Result<(), String> {
+        let channels = self.channels;
+        let block_len = self.block.len() / channels;
+        let mut frame = BitWriter::default();
+        write_frame_header(
+            &mut frame,
+            self.index,
+            block_len,
+            self.sample_rate,
+            channels,
+        );
+        let mut channel = Vec::with_capacity(block_len);
+        for ch in 0..channels {
+            channel.clear();
+            channel.extend(
+                self.block
+                    .iter()
+                    .skip(ch)
+                    .step_by(channels)
+                    .map(|&s| s as i32),
+            );
+            write_subframe(&mut frame, &channel);
+        }
+        frame.align();
+        let crc = crc16(&frame.bytes);
+        frame.write(crc as u64, 16);
+        self.out
+            .write_all(&frame.bytes)
+            .map_err(|e| e.to_string())?;
+        self.block.clear();
+        self.index += 1;
+        Ok(())
+    }
+}
+
+fn write_frame_header(
+    frame: &mut BitWriter,
+    index: u64,
+    block_len: usize,
+    sample_rate: u32,
+    channels: usize,
+) {
+    let rate_code = match sample_rate {
+        8000 => 0b0100,
+        16000 => 0b0101,
+        22050 => 0b0110,
+        24000 => 0b0111,
+        32000 => 0b1000,
+        44100 => 0b1001,
+        48000 => 0b1010,
+        96000 => 0b1011,
+        // Taken from STREAMINFO.
+        _ => 0b0000,
+    };
+    frame.write(0b11_1111_1111_1110, 14);
+    frame.write(0, 1);
+    // Fixed block size; the frame number stands in for the sample number.
+    frame.write(0, 1);
+    // Block size - 1 follows the header as 16 bits.
+    frame.write(0b0111, 4);
+    frame.write(rate_code, 4);
+    frame.write(channels as u64 - 1, 4);
+    // 16 bits per sample.
+    frame.write(0b100, 3);
+    frame.write(0, 1);
+    frame.write_bytes(&utf8_coded(index));
+    frame.write(block_len as u64 - 1, 16);
+    let crc = crc8(&frame.bytes);
+    frame.write(crc as u64, 8);
+}
+
+fn write_subframe(frame: &mut BitWriter, samples: &[i32]) {
+    if samples.iter().all(|&s| s == samples[0]) {
+        frame.write(0b0000_0000, 8);
+        frame.write_signed(samples[0] as i64, BITS_PER_SAMPLE);
+        return;
+    }
+
+    let verbatim_bits = samples.len() as u64 * BITS_PER_SAMPLE as u64;
+    let best = (0..=MAX_FIXED_ORDER.min(samples.len() - 1))
+        .map(|order| {
+            let residual = fixed_residual(samples, order);
+            let (bits, partition_order, params) = best_rice_partitioning(&residual, order);
+            (
+                bits.saturating_add(order as u64 * BITS_PER_SAMPLE as u64),
+                order,
+                residual,
+                partition_order,
+                params,
+            )
+        })
+        .min_by_key(|candidate| candidate.0);
+
+    match best {
+        Some((bits, order, residual, partition_order, params)) if bits < verbatim_bits => {
+            frame.write(0b0001_0000 | (order as u64) << 1, 8);
+            for &warm_up in &samples[..order] {
+                frame.write_signed(warm_up as i64, BITS_PER_SAMPLE);
+            }
+            // Rice coding with 4-bit parameters.
+            frame.write(0b00, 2);
+            frame.write(partition_order as u64, 4);
+            let partition_len = samples.len() >> partition_order;
+            let mut start = 0;
+            for (partition, &param) in params.iter().enumerate() {
+                let len = if partition == 0 {
+                    partition_len - order
+                } else {
+                    partition_len
+                };
+                frame.write(param as u64, 4);
+                for &r in &residual[start..start + len] {
+                    frame.write_rice(r, param);
+                }
+                start += len;
+            }
+        }
+        _ => {
+            frame.write(0b0000_0010, 8);
+            for &sample in samples {
+                frame.write_signed(sample as i64, BITS_PER_SAMPLE);
+            }
+        }
+    }
+}
+
+/// Residual of the fixed polynomial predictor of `order`, for every sample
+/// after the warm-up.
+fn fixed_residual(samples: &[i32], order: usize) -> Vec<i64> {
+    samples
+        .windows(order + 1)
+        .map(|w| {
+            let s = |back: usize| w[order - back] as i64;
+            match order {
+                0 => s(0),
+                1 => s(0) - s(1),
+                2 => s(0) - 2 * s(1) + s(2),
+                3 => s(0) - 3 * s(1) + 3 * s(2) - s(3),
+                _ => s(0) - 4 * s(1) + 6 * s(2) - 4 * s(3) + s(4),
+            }
+        })
+        .collect()
+}
+
+/// Picks the partition order and per-partition Rice parameters that code
+/// `residual` in the fewest bits. Returns (bits, partition order, params).
+fn best_rice_partitioning(residual: &[i64], order: usize) -> (u64, u32, Vec<u32>) {
+    let block_len = residual.len() + order;
+    let mut best: Option<(u64, u32, Vec<u32>)> = None;
+    for partition_order in 0..=MAX_PARTITION_ORDER {
+        let partitions = 1usize << partition_order;
+        if !block_len.is_multiple_of(partitions) || block_len / partitions <= order {
+            break;
+        }
+        let partition_len = block_len / partitions;
+        let mut bits = 6u64;
+        let mut params = Vec::with_capacity(partitions);
+        let mut start = 0;
+        for partition in 0..partitions {
+            let len = if partition == 0 {
+                partition_len - order
+            } else {
+                partition_len
+            };
+            let (param, partition_bits) = best_rice_param(&residual[start..start + len]);
+            bits += 4 + partition_bits;
+            params.push(param);
+            start += len;
+        }
+        if best
+            .as_ref()
+            .is_none_or(|(best_bits, ..)| bits < *best_bits)
+        {
+            best = Some((bits, partition_order, params));
+        }
+    }
+    best.unwrap_or((u64::MAX, 0, Vec::new()))
+}
+
+fn best_rice_param(residual: &[i64]) -> (u32, u64) {
+    let zigzag: Vec<u64> = residual.iter().map(|&r| zigzag(r)).collect();
+    (0..=MAX_RICE_PARAM)
+        .map(|param| {
+            let bits = zigzag
+                .iter()
+                .map(|&u| (u >> param) + 1 + param as u64)
+                .sum::<u64>();
+            (param, bits)
+        })
+        .min_by_key(|&(_, bits)| bits)
+        .unwrap_or((0, 0))
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+/// The "UTF-8" style variable-length coding FLAC uses for frame numbers.
+fn utf8_coded(value: u64) -> Vec<u8> {
+    if value < 0x80 {
+        return vec![value as u8];
+    }
+    let mut continuation = Vec::new();
+    let mut rest = value;
+    // Each continuation byte carries 6 bits; the lead byte holds what is
+    // left after its length prefix.
+    loop {
+        continuation.push(0x80 | (rest & 0x3f) as u8);
+        rest >>= 6;
+        let len = continuation.len() + 1;
+        if rest < (1 << (7 - len)) {
+            let prefix = !(0xffu8 >> len);
+            let mut bytes = vec![prefix | rest as u8];
+            bytes.extend(continuation.iter().rev());
+            return bytes;
+        }
+    }
+}
+
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |mut crc, &byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0u16, |mut crc, &byte| {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u64,
+    bits: u32,
+}
+
+impl BitWriter {
+    /// Writes the low `bits` bits of `value`, most significant first. At
+    /// most 48 bits at a time.
+    fn write(&mut self, value: u64, bits: u32) {
+        if bits == 0 {
+            return;
+        }
+        self.acc = (self.acc << bits) | (value & ((1 << bits) - 1));
+        self.bits += bits;
+        while self.bits >= 8 {
+            self.bits -= 8;
+            self.bytes.push((self.acc >> self.bits) as u8);
+        }
+        self.acc &= (1 << self.bits) - 1;
+    }
+
+    fn write_signed(&mut self, value: i64, bits: u32) {
+        self.write(value as u64 & ((1 << bits) - 1), bits);
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        if self.bits == 0 {
+            self.bytes.extend_from_slice(bytes);
+        } else {
+            for &byte in bytes {
+                self.write(byte as u64, 8);
+            }
+        }
+    }
+
+    fn write_rice(&mut self, value: i64, param: u32) {
+        let u = zigzag(value);
+        let mut quotient = u >> param;
+        while quotient > 32 {
+            self.write(0, 32);
+            quotient -= 32;
+        }
+        self.write(1, quotient as u32 + 1);
+        self.write(u, param);
+    }
+
+    fn align(&mut self) {
+        if self.bits > 0 {
+            self.write(0, 8 - self.bits);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes interleaved 16-bit samples as a FLAC stream in one go.
+    fn encode_flac(samples: &[i16], sample_rate: u32, channels: u16) -> Result<Vec<u8>, String> {
+        let frames = samples.len() / channels.max(1) as usize;
+        let mut out = Vec::new();
+        let mut encoder = FlacEncoder::new(&mut out, sample_rate, channels, frames as u64)?;
+        encoder.push(samples)?;
+        encoder.finish()?;
+        Ok(out)
+    }
+
+    #[test]
+    fn frame_numbers_use_flac_utf8_coding() {
+        assert_eq!(utf8_coded(0x7f), vec![0x7f]);
+        assert_eq!(utf8_coded(0x80), vec![0xc2, 0x80]);
+        assert_eq!(utf8_coded(0x7ff), vec![0xdf, 0xbf]);
+        assert_eq!(utf8_coded(0x800), vec![0xe0, 0xa0, 0x80]);
+    }
+
+    #[test]
+    fn crcs_match_the_reference_check_values() {
+        assert_eq!(crc8(b"123456789"), 0xf4);
+        assert_eq!(crc16(b"123456789"), 0xfee8);
+    }
+
+    #[test]
+    fn tonal_audio_compresses_well_below_pcm() {
+        let samples: Vec<i16> = (0..48000)
+            .map(|i| (8000.0 * (i as f32 * 0.05).sin()) as i16)
+            .collect();
+        let flac = encode_flac(&samples, 16000, 1).unwrap();
+        assert_eq!(&flac[..4], b"fLaC");
+        assert!(flac.len() < samples.len(), "{} bytes", flac.len());
+    }
+
+    #[test]
+    fn blocks_can_be_pushed_in_any_size() {
+        let samples: Vec<i16> = (0..20_001).map(|i| ((i * 31) % 2000) as i16).collect();
+        let mut encoder = FlacEncoder::new(Vec::new(), 16000, 1, samples.len() as u64).unwrap();
+        for block in samples.chunks(3000) {
+            encoder.push(block).unwrap();
+        }
+        assert_eq!(
+            encoder.finish().unwrap(),
+            encode_flac(&samples, 16000, 1).unwrap()
+        );
+
+        let mut short = FlacEncoder::new(Vec::new(), 16000, 1, 10).unwrap();
+        short.push(&[1, 2, 3]).unwrap();
+        assert!(short.finish().is_err());
+    }
+}
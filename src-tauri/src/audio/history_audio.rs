@@ -0,0 +1,315 @@
+//! Recordings saved alongside history items when recording logs are on.
+//!
+//! They are written as WAV or FLAC depending on `recording_audio_format`,
+//! and always read back as a WAV file so playback does not care which. WAVs
+//! from earlier versions are converted to FLAC in the background (see
+//! `migrate_recordings_to_flac`). Recordings can be hours long, so every
+//! conversion streams between files a block at a time.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::config::RecordingAudioFormat;
+
+use super::decode::decode_file_i16;
+use super::flac::FlacEncoder;
+use super::whisper_file::{WhisperFile, WHISPER_SAMPLE_RATE};
+
+/// Samples read from a WAV per block while converting.
+const CONVERT_BLOCK_SAMPLES: usize = 1 << 16;
+
+/// Held while converting, so overlapping migrations do not race on a file.
+static MIGRATION: Mutex<()> = Mutex::new(());
+
+/// Saves `audio` as `<file_stem>.wav` or `<file_stem>.flac` in the
+/// recordings directory and returns the file name history should reference.
+/// The audio is copied or encoded straight from disk.
+pub fn save_recording(
+    file_stem: &str,
+    audio: &WhisperFile,
+    format: RecordingAudioFormat,
+) -> Result<String, String> {
+    let dir = crate::paths::debug_recordings_dir()?;
+    let file_name = match format {
+        RecordingAudioFormat::Wav => {
+            let file_name = format!("{}.wav", file_stem);
+            std::fs::copy(audio.path(), dir.join(&file_name)).map_err(|e| e.to_string())?;
+            file_name
+        }
+        RecordingAudioFormat::Flac => {
+            let file_name = format!("{}.flac", file_stem);
+            let path = dir.join(&file_name);
+            if let Err(e) = write_flac(audio, &path) {
+                let _ = std::fs::remove_file(&path);
+                return Err(e);
+            }
+            file_name
+        }
+    };
+    Ok(file_name)
+}
+
+fn write_flac(audio: &WhisperFile, path: &Path) -> Result<(), String> {
+    let file = BufWriter::new(File::create(path).map_err(|e| e.to_string())?);
+    let mut encoder = FlacEncoder::new(file, WHISPER_SAMPLE_RATE, 1, audio.samples())?;
+    audio.for_each_pcm_block(|block| encoder.push(block))?;
+    encoder.finish()?;
+    Ok(())
+}
+
+/// A saved recording as a WAV file: the recording itself, or a decoded
+/// temporary copy of a FLAC that is removed on drop.
+pub struct RecordingWav {
+    path: PathBuf,
+    temporary: bool,
+}
+
+impl RecordingWav {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for RecordingWav {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Finds a saved recording and returns it as a WAV file, decoding FLAC to
+/// a temporary file.
+pub fn open_recording_wav(file_name: &str) -> Result<RecordingWav, String> {
+    let path = locate_recording(file_name)?.ok_or("Audio file not found")?;
+    if !is_flac(&path)? {
+        return Ok(RecordingWav {
+            path,
+            temporary: false,
+        });
+    }
+    let dir = crate::paths::temp_dir();
+    let _ = std::fs::create_dir_all(&dir);
+    let wav = RecordingWav {
+        path: dir.join(format!(
+            "recording_{}.wav",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        )),
+        temporary: true,
+    };
+    flac_to_wav(&path, wav.path())?;
+    Ok(wav)
+}
+
+/// Recordings are looked up in the recordings directory, then in the debug
+/// directory where older versions saved them. A WAV that has since been
+/// converted is found under its FLAC name.
+fn locate_recording(file_name: &str) -> Result<Option<PathBuf>, String> {
+    let dirs = [
+        crate::paths::debug_recordings_dir()?,
+        crate::paths::debug_dir()?,
+    ];
+    let converted = Path::new(file_name)
+        .extension()
+        .is_some_and(|ext| ext == "wav")
+        .then(|| Path::new(file_name).with_extension("flac"));
+    Ok(dirs
+        .iter()
+        .flat_map(|dir| {
+            std::iter::once(dir.join(file_name))
+                .chain(converted.as_ref().map(|name| dir.join(name)))
+        })
+        .find(|path| path.exists()))
+}
+
+/// Converts WAV recordings in the recordings directory to FLAC and points
+/// history at the new files. Each conversion is checked by decoding it back
+/// before the WAV is removed. Returns how many files were converted.
+pub fn migrate_recordings_to_flac() -> Result<usize, String> {
+    let _migrating = MIGRATION.lock().unwrap();
+    let dir = crate::paths::debug_recordings_dir()?;
+    let mut converted = 0;
+    for entry in std::fs::read_dir(&dir)
+        .map_err(|e| e.to_string())?
+        .flatten()
+    {
+        let wav_path = entry.path();
+        if wav_path.extension().is_none_or(|ext| ext != "wav") {
+            continue;
+        }
+        match migrate_recording(&wav_path) {
+            Ok(()) => converted += 1,
+            Err(e) => crate::log_warn!("Failed to convert {:?} to FLAC: {}", wav_path, e),
+        }
+    }
+    Ok(converted)
+}
+
+fn migrate_recording(wav_path: &Path) -> Result<(), String> {
+    let flac_path = wav_path.with_extension("flac");
+    let flac_name = flac_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or("Invalid file name")?
+        .to_string();
+    if let Err(e) = wav_to_flac(wav_path, &flac_path).and_then(|()| {
+        if same_samples(wav_path, &flac_path)? {
+            Ok(())
+        } else {
+            Err("FLAC did not decode to the original audio".to_string())
+        }
+    }) {
+        let _ = std::fs::remove_file(&flac_path);
+        return Err(e);
+    }
+    if let Err(e) = crate::history::rename_audio_file(wav_path, &flac_name) {
+        let _ = std::fs::remove_file(&flac_path);
+        return Err(format!("Failed to update history: {}", e));
+    }
+    std::fs::remove_file(wav_path).map_err(|e| e.to_string())
+}
+
+fn is_flac(path: &Path) -> Result<bool, String> {
+    let mut magic = [0; 4];
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    Ok(file.read_exact(&mut magic).is_ok() && &magic == b"fLaC")
+}
+
+/// Opens a 16-bit PCM WAV, the only kind history has ever saved.
+fn open_pcm16(path: &Path) -> Result<hound::WavReader<BufReader<File>>, String> {
+    let reader = hound::WavReader::open(path).map_err(|e| e.to_string())?;
+    let spec = reader.spec();
+    if spec.bits_per_sample != 16 || spec.sample_format != hound::SampleFormat::Int {
+        return Err(format!(
+            "Unsupported WAV format: {}-bit {:?}",
+            spec.bits_per_sample, spec.sample_format
+        ));
+    }
+    Ok(reader)
+}
+
+fn wav_to_flac(wav_path: &Path, flac_path: &Path) -> Result<(), String> {
+    let mut reader = open_pcm16(wav_path)?;
+    let spec = reader.spec();
+    let out = BufWriter::new(File::create(flac_path).map_err(|e| e.to_string())?);
+    let mut encoder = FlacEncoder::new(
+        out,
+        spec.sample_rate,
+        spec.channels,
+        reader.duration() as u64,
+    )?;
+    let mut samples = reader.samples::<i16>();
+    let mut block = Vec::with_capacity(CONVERT_BLOCK_SAMPLES);
+    loop {
+        block.clear();
+        for sample in samples.by_ref().take(CONVERT_BLOCK_SAMPLES) {
+            block.push(sample.map_err(|e| e.to_string())?);
+        }
+        if block.is_empty() {
+            break;
+        }
+        encoder.push(&block)?;
+    }
+    encoder.finish()?;
+    Ok(())
+}
+
+fn flac_to_wav(flac_path: &Path, wav_path: &Path) -> Result<(), String> {
+    let mut writer: Option<hound::WavWriter<BufWriter<File>>> = None;
+    let decoded = decode_file_i16(flac_path, |block, sample_rate, channels| {
+        let writer = match &mut writer {
+            Some(writer) => writer,
+            None => writer.insert(hound::WavWriter::create(
+                wav_path,
+                hound::WavSpec {
+                    channels: channels as u16,
+                    sample_rate,
+                    bits_per_sample: 16,
+                    sample_format: hound::SampleFormat::Int,
+                },
+            )?),
+        };
+        for &sample in block {
+            writer.write_sample(sample)?;
+        }
+        Ok(())
+    });
+    let finished = decoded.map_err(|e| e.to_string()).and_then(|()| {
+        writer
+            .ok_or_else(|| "Recording has no audio".to_string())?
+            .finalize()
+            .map_err(|e| e.to_string())
+    });
+    if finished.is_err() {
+        let _ = std::fs::remove_file(wav_path);
+    }
+    finished
+}
+
+/// Whether the FLAC decodes to exactly the WAV's samples and format,
+/// comparing both a block at a time.
+fn same_samples(wav_path: &Path, flac_path: &Path) -> Result<bool, String> {
+    let mut reader = open_pcm16(wav_path)?;
+    let spec = reader.spec();
+    let mut expected = reader.samples::<i16>();
+    let mut same = true;
+    decode_file_i16(flac_path, |block, sample_rate, channels| {
+        same &= sample_rate == spec.sample_rate && channels == spec.channels as usize;
+        for &sample in block {
+            same &= matches!(expected.next(), Some(Ok(original)) if original == sample);
+        }
+        Ok(())
+    })
+    .map_err(|e| e.to_string())?;
+    Ok(same && expected.next().is_none())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::conversion::write_whisper_wav;
+
+    #[test]
+    fn flac_round_trips_recordings_exactly() {
+        let speechlike: Vec<i16> = (0..40_000)
+            .map(|i| {
+                let t = i as f32 / 16000.0;
+                let envelope = (t * 3.0).sin().abs();
+                (envelope * 9000.0 * (t * 900.0).sin() + ((i * 7919) % 61) as f32 - 30.0) as i16
+            })
+            .collect();
+        let cases = [
+            speechlike,
+            vec![0; 5000],
+            vec![i16::MIN, i16::MAX, 0, -1, 1, i16::MAX, i16::MIN],
+            vec![1234],
+        ];
+        let dir = std::env::temp_dir().join(format!("voquill-history-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let wav_path = dir.join("original.wav");
+        let flac_path = dir.join("original.flac");
+        let decoded_path = dir.join("decoded.wav");
+        for samples in cases {
+            std::fs::write(&wav_path, write_whisper_wav(&samples).unwrap()).unwrap();
+            wav_to_flac(&wav_path, &flac_path).unwrap();
+            assert!(same_samples(&wav_path, &flac_path).unwrap());
+            flac_to_wav(&flac_path, &decoded_path).unwrap();
+            let mut decoded = open_pcm16(&decoded_path).unwrap();
+            let spec = decoded.spec();
+            let decoded: Vec<i16> = decoded.samples::<i16>().map(Result::unwrap).collect();
+            assert_eq!(
+                (decoded, spec.sample_rate, spec.channels),
+                (samples, 16000, 1)
+            );
+        }
+
+        std::fs::write(&wav_path, write_whisper_wav(&[1, 2, 3]).unwrap()).unwrap();
+        assert!(!same_samples(&wav_path, &flac_path).unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,12 +1,23 @@
+pub mod agc;
 pub mod conversion;
 pub mod decode;
+pub mod denoise;
 pub mod device;
 pub mod engine;
+pub mod flac;
+pub mod history_audio;
 pub mod playback;
 pub mod recording;
+pub mod spool;
+pub mod system_capture;
+pub mod vad;
+pub mod whisper_file;
 
-pub use conversion::{convert_audio_file_for_whisper, extract_segment_wav};
-pub use device::{get_input_devices, get_output_devices, lookup_device, AudioDevice};
+pub use conversion::convert_audio_file_for_whisper;
+pub use device::{
+    get_input_devices, get_monitor_devices, get_output_devices, lookup_device, AudioDevice,
+};
 pub use engine::PersistentAudioEngine;
-pub use playback::{play_audio, play_wav_file};
+pub use playback::{play_audio, play_wav_path};
 pub use recording::{record_audio_while_flag, record_mic_test};
+pub use whisper_file::WhisperFile;
@@ -1,9 +1,16 @@
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
 use std::sync::Arc;
 
 use cpal::traits::{DeviceTrait, StreamTrait};
 use cpal::{SampleFormat, StreamConfig};
 
-use super::conversion::resample_linear;
+use super::conversion::{resample_linear, StreamResampler};
+
+/// Frames read from a WAV file per block when streaming it to a device.
+const STREAM_READ_FRAMES: usize = 8192;
+/// Blocks decoded ahead of the output stream; a few seconds at most.
+const STREAM_QUEUE_BLOCKS: usize = 8;
 
 pub fn play_audio_on_device<F>(
     device: &cpal::Device,
@@ -100,26 +107,152 @@ where
     play_audio_on_device(&device, samples, sample_rate, on_done)
 }
 
-pub fn play_wav_file<F>(
-    wav_bytes: &[u8],
+/// Device-rate samples handed over by the reader thread of `play_wav_path`.
+struct QueuedSamples {
+    blocks: Receiver<Vec<f32>>,
+    block: Vec<f32>,
+    index: usize,
+}
+
+impl QueuedSamples {
+    /// The next sample, silence while the reader is behind, or `None` once
+    /// the reader has finished and everything was played.
+    fn next(&mut self) -> Option<f32> {
+        while self.index >= self.block.len() {
+            match self.blocks.try_recv() {
+                Ok(block) => {
+                    self.block = block;
+                    self.index = 0;
+                }
+                Err(TryRecvError::Empty) => return Some(0.0),
+                Err(TryRecvError::Disconnected) => return None,
+            }
+        }
+        self.index += 1;
+        Some(self.block[self.index - 1])
+    }
+}
+
+/// Plays a WAV file without loading it: a reader thread downmixes and
+/// resamples it a block at a time into a short queue the output stream
+/// drains. `keep` is held by the reader until the whole file is read, so a
+/// temporary file can be removed when it drops.
+pub fn play_wav_path<F, K>(
+    path: &Path,
     device_id: Option<String>,
+    keep: K,
     on_done: F,
 ) -> Result<cpal::Stream, Box<dyn std::error::Error + Send + Sync>>
 where
     F: FnOnce() + Send + 'static,
+    K: Send + 'static,
 {
-    let mut reader = hound::WavReader::new(std::io::Cursor::new(wav_bytes))?;
+    let reader = hound::WavReader::open(path)?;
     let spec = reader.spec();
-    let samples: Vec<f32> = match spec.sample_format {
-        hound::SampleFormat::Int => {
-            let max_val = (1 << (spec.bits_per_sample - 1)) as f32;
-            reader
-                .samples::<i32>()
-                .filter_map(|s| s.ok())
-                .map(|s| (s as f32 / max_val).clamp(-1.0, 1.0))
-                .collect()
+    let device = super::device::lookup_output_device(device_id)?;
+    let config = device.default_output_config()?;
+    let stream_config: StreamConfig = config.clone().into();
+    let chans = stream_config.channels as usize;
+
+    let (sender, blocks) = mpsc::sync_channel::<Vec<f32>>(STREAM_QUEUE_BLOCKS);
+    let device_rate = stream_config.sample_rate;
+    std::thread::spawn(move || {
+        let _keep = keep;
+        let mut samples: Box<dyn Iterator<Item = f32> + Send> = match spec.sample_format {
+            hound::SampleFormat::Int => {
+                let max_val = (1 << (spec.bits_per_sample - 1)) as f32;
+                Box::new(
+                    reader
+                        .into_samples::<i32>()
+                        .filter_map(|s| s.ok())
+                        .map(move |s| (s as f32 / max_val).clamp(-1.0, 1.0)),
+                )
+            }
+            hound::SampleFormat::Float => {
+                Box::new(reader.into_samples::<f32>().filter_map(|s| s.ok()))
+            }
+        };
+        let channels = (spec.channels as usize).max(1);
+        let mut resampler = StreamResampler::new(spec.sample_rate, device_rate);
+        let mut interleaved = Vec::with_capacity(STREAM_READ_FRAMES * channels);
+        loop {
+            interleaved.clear();
+            interleaved.extend(samples.by_ref().take(STREAM_READ_FRAMES * channels));
+            if interleaved.is_empty() {
+                break;
+            }
+            let mono: Vec<f32> = interleaved
+                .chunks(channels)
+                .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+                .collect();
+            match resampler.push(&mono) {
+                Ok(block) => {
+                    // The stream was dropped, so playback was stopped
+                    if sender.send(block).is_err() {
+                        return;
+                    }
+                }
+                Err(e) => {
+                    crate::log_info!("Playback error: {}", e);
+                    return;
+                }
+            }
+        }
+        match resampler.finish() {
+            Ok(tail) => {
+                let _ = sender.send(tail);
+            }
+            Err(e) => crate::log_info!("Playback error: {}", e),
         }
-        hound::SampleFormat::Float => reader.samples::<f32>().filter_map(|s| s.ok()).collect(),
+    });
+
+    let err_fn = |err| crate::log_info!("Playback error: {}", err);
+    let mut done = Some(on_done);
+    let mut queued = QueuedSamples {
+        blocks,
+        block: Vec::new(),
+        index: 0,
+    };
+
+    let stream = match config.sample_format() {
+        SampleFormat::F32 => device.build_output_stream(
+            &stream_config,
+            move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                for frame in data.chunks_mut(chans) {
+                    let s = queued.next();
+                    for out in frame.iter_mut() {
+                        *out = s.unwrap_or(0.0);
+                    }
+                    if s.is_none() {
+                        if let Some(cb) = done.take() {
+                            cb();
+                        }
+                    }
+                }
+            },
+            err_fn,
+            None,
+        )?,
+        SampleFormat::I16 => device.build_output_stream(
+            &stream_config,
+            move |data: &mut [i16], _| {
+                for frame in data.chunks_mut(chans) {
+                    let s = queued.next();
+                    for out in frame.iter_mut() {
+                        *out = (s.unwrap_or(0.0) * i16::MAX as f32) as i16;
+                    }
+                    if s.is_none() {
+                        if let Some(cb) = done.take() {
+                            cb();
+                        }
+                    }
+                }
+            },
+            err_fn,
+            None,
+        )?,
+        _ => return Err("Unsupported format".into()),
     };
-    play_audio(samples, spec.sample_rate, device_id, on_done)
+    stream.play()?;
+    Ok(stream)
 }
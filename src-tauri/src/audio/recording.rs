@@ -1,41 +1,93 @@
+use std::path::PathBuf;
 use std::sync::{mpsc, Arc, Mutex};
 
 use ringbuf::traits::Consumer;
 
 use crate::app::state::SessionState;
 
-use super::conversion::finalize_captured_audio_for_whisper;
+use super::agc::{Agc, AgcReport, AgcSettings};
 use super::engine::PersistentAudioEngine;
+use super::spool::{SpoolFile, SpoolWriter};
+
+/// Samples collected before each write to the spool.
+const SPOOL_WRITE_SAMPLES: usize = 4096;
+
+/// A finished capture, spooled to disk at the capture rate, plus what the
+/// AGC did when it was enabled. Nothing of it is held in memory; it is
+/// finalized from the spool, which stays on disk until dropped so a session
+/// that crashes before it is done can still be recovered.
+pub struct CapturedAudio {
+    pub spool: SpoolFile,
+    pub sample_rate: u32,
+    pub samples: u64,
+    pub agc: Option<AgcReport>,
+}
 
 pub async fn record_audio_while_flag(
     session_state: &Arc<Mutex<SessionState>>,
     engine: Arc<Mutex<Option<PersistentAudioEngine>>>,
+    pre_roll_ms: u64,
     post_roll_ms: u64,
     max_recording_duration: std::time::Duration,
-) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+    agc: Option<AgcSettings>,
+    spool_path: PathBuf,
+) -> Result<CapturedAudio, Box<dyn std::error::Error + Send + Sync>> {
     crate::log_info!("record_audio_while_flag: enter");
     let (tx, rx) = mpsc::sync_channel::<f32>(65536);
     let mut samples = Vec::new();
     let sample_rate;
+    let mut spool;
     {
         let mut guard = engine.lock().unwrap();
         let eng = guard.as_mut().ok_or("Audio engine not initialized")?;
         sample_rate = eng.sample_rate;
+        spool = SpoolWriter::create(spool_path, sample_rate)?;
         if let Ok(mut cons) = eng.pre_roll_consumer.lock() {
             while let Some(s) = cons.try_pop() {
                 samples.push(s);
             }
         }
+        let keep = (sample_rate as u64 * pre_roll_ms / 1000) as usize;
+        if samples.len() > keep {
+            samples.drain(..samples.len() - keep);
+        }
         *eng.recording_tx.lock().unwrap() = Some(tx);
     }
 
-    let (data_tx, data_rx) = mpsc::channel::<Vec<f32>>();
+    // The AGC runs as samples arrive, starting with the pre-roll; its output
+    // goes straight to the spool.
+    type Collected = (Result<(SpoolFile, u64), String>, Option<AgcReport>);
+    let (data_tx, data_rx) = mpsc::channel::<Collected>();
     std::thread::spawn(move || {
-        let mut all = samples;
+        let mut agc = agc.map(|settings| Agc::new(sample_rate, settings));
+        let mut block = Vec::with_capacity(SPOOL_WRITE_SAMPLES);
+        let mut processed = Vec::with_capacity(SPOOL_WRITE_SAMPLES);
+        let mut write = |block: &[f32], spool: &mut SpoolWriter, agc: &mut Option<Agc>| match agc {
+            Some(agc) => {
+                processed.clear();
+                agc.process(block, &mut processed);
+                spool.push(&processed);
+            }
+            None => spool.push(block),
+        };
+        write(&samples, &mut spool, &mut agc);
+        drop(samples);
         while let Ok(s) = rx.recv() {
-            all.push(s);
+            block.push(s);
+            if block.len() == SPOOL_WRITE_SAMPLES {
+                write(&block, &mut spool, &mut agc);
+                block.clear();
+            }
         }
-        let _ = data_tx.send(all);
+        write(&block, &mut spool, &mut agc);
+        let report = agc.as_mut().map(|agc| {
+            let mut tail = Vec::new();
+            agc.finish(&mut tail);
+            spool.push(&tail);
+            agc.report()
+        });
+        let written = spool.written();
+        let _ = data_tx.send((spool.finish().map(|file| (file, written)), report));
     });
 
     let capture_started = tokio::time::Instant::now();
@@ -63,13 +115,28 @@ pub async fn record_audio_while_flag(
     if let Some(eng) = engine.lock().unwrap().as_ref() {
         *eng.recording_tx.lock().unwrap() = None;
     }
-    let raw_samples = data_rx.recv()?;
+    let (spooled, agc_report) = data_rx.recv()?;
+    let (spool, samples) = spooled?;
     crate::log_info!(
-        "record_audio_while_flag: captured {} raw float samples at {}Hz",
-        raw_samples.len(),
+        "record_audio_while_flag: capture spooled at {}Hz",
         sample_rate
     );
-    finalize_captured_audio_for_whisper(&raw_samples, sample_rate)
+    if let Some(report) = agc_report {
+        crate::log_info!(
+            "AGC: target={:.1}dBFS, gain mean={:+.1}dB min={:+.1}dB max={:+.1}dB, noise floor={:.1}dBFS",
+            report.target_dbfs,
+            report.mean_gain_db,
+            report.min_gain_db,
+            report.max_gain_db,
+            report.noise_floor_dbfs
+        );
+    }
+    Ok(CapturedAudio {
+        spool,
+        sample_rate,
+        samples,
+        agc: agc_report,
+    })
 }
 
 pub async fn record_mic_test<F>(
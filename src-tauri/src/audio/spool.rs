@@ -0,0 +1,329 @@
+//! Disk spool for captured audio: dictation and each listen-mode channel.
+//!
+//! Samples are written to a 32-bit float mono WAV at the capture rate as
+//! they arrive instead of being collected in memory, so an hours-long recording
+//! costs disk space rather than RAM. The WAV header is rewritten at every
+//! checkpoint, so if the app dies mid-session the file is still readable up
+//! to the last checkpoint. Spools left over from such sessions are offered
+//! for recovery on the next start. Float keeps the full precision of the
+//! capture for the gain and normalization applied when it is finalized.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use hound::{SampleFormat, WavSpec, WavWriter};
+use serde::Serialize;
+
+/// Audio written between header checkpoints.
+const CHECKPOINT_MS: u64 = 1000;
+
+/// Spools owned by a live session, which must not be offered for recovery.
+static ACTIVE: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// A spool file on disk, removed when dropped unless `keep` is called.
+pub struct SpoolFile {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl SpoolFile {
+    fn claim(path: PathBuf) -> Self {
+        ACTIVE.lock().unwrap().push(path.clone());
+        Self { path, keep: false }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Leaves the file on disk so it can be recovered later.
+    pub fn keep(mut self) {
+        self.keep = true;
+    }
+}
+
+impl Drop for SpoolFile {
+    fn drop(&mut self) {
+        ACTIVE.lock().unwrap().retain(|path| *path != self.path);
+        if self.keep {
+            crate::log_info!("Keeping spooled capture for recovery: {:?}", self.path);
+        } else if let Err(e) = std::fs::remove_file(&self.path) {
+            crate::log_warn!("Failed to remove spooled capture {:?}: {}", self.path, e);
+        }
+    }
+}
+
+pub struct SpoolWriter {
+    writer: WavWriter<BufWriter<File>>,
+    file: SpoolFile,
+    checkpoint_samples: u64,
+    since_checkpoint: u64,
+    written: u64,
+    error: Option<String>,
+}
+
+impl SpoolWriter {
+    pub fn create(path: PathBuf, sample_rate: u32) -> Result<Self, String> {
+        let spec = WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 32,
+            sample_format: SampleFormat::Float,
+        };
+        let writer = WavWriter::create(&path, spec)
+            .map_err(|e| format!("Failed to create capture spool {:?}: {}", path, e))?;
+        Ok(Self {
+            writer,
+            file: SpoolFile::claim(path),
+            checkpoint_samples: (sample_rate as u64 * CHECKPOINT_MS / 1000).max(1),
+            since_checkpoint: 0,
+            written: 0,
+            error: None,
+        })
+    }
+
+    /// Appends samples. After a write error further samples are dropped and
+    /// the error is returned from `finish`.
+    pub fn push(&mut self, samples: &[f32]) {
+        if self.error.is_some() {
+            return;
+        }
+        for &sample in samples {
+            if let Err(e) = self.writer.write_sample(sample) {
+                crate::log_warn!("Capture spool write failed: {}", e);
+                self.error = Some(e.to_string());
+                return;
+            }
+        }
+        self.written += samples.len() as u64;
+        self.since_checkpoint += samples.len() as u64;
+        if self.since_checkpoint >= self.checkpoint_samples {
+            self.since_checkpoint = 0;
+            if let Err(e) = self.writer.flush() {
+                crate::log_warn!("Capture spool checkpoint failed: {}", e);
+                self.error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Samples written so far.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Completes the WAV header and hands back the file.
+    pub fn finish(self) -> Result<SpoolFile, String> {
+        let Self {
+            writer,
+            file,
+            written,
+            error,
+            ..
+        } = self;
+        if let Some(error) = error {
+            return Err(format!("Failed to spool capture: {}", error));
+        }
+        writer
+            .finalize()
+            .map_err(|e| format!("Failed to spool capture: {}", e))?;
+        crate::log_info!("Spooled {} samples to {:?}", written, file.path);
+        Ok(file)
+    }
+}
+
+/// Where the capture for a dictation session is spooled.
+pub fn session_spool_path(session_uuid: &str) -> Result<PathBuf, String> {
+    Ok(crate::paths::spool_dir()?.join(format!("{}.wav", session_uuid)))
+}
+
+/// Where one channel ("system" or "mic") of a listen session is spooled.
+pub fn channel_spool_path(session_uuid: &str, channel: &str) -> Result<PathBuf, String> {
+    Ok(crate::paths::spool_dir()?.join(format!("{}.{}.wav", session_uuid, channel)))
+}
+
+/// What a leftover spool holds, from its file name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpoolChannel {
+    Dictation,
+    System,
+    Mic,
+}
+
+impl SpoolChannel {
+    fn parse(stem: &str) -> Option<(&str, Self)> {
+        match stem.split_once('.') {
+            None => Some((stem, Self::Dictation)),
+            Some((id, "system")) => Some((id, Self::System)),
+            Some((id, "mic")) => Some((id, Self::Mic)),
+            Some(_) => None,
+        }
+    }
+}
+
+/// A session that never finished, with one spool per captured channel.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoverableRecording {
+    pub id: String,
+    pub recorded_at: String,
+    pub duration_secs: f64,
+    /// The history source it will be saved under: "mic", "system" or "call".
+    pub source: &'static str,
+}
+
+pub fn recoverable_recordings() -> Result<Vec<RecoverableRecording>, String> {
+    let dir = crate::paths::spool_dir()?;
+    let active = ACTIVE.lock().unwrap().clone();
+    let mut recordings: Vec<(RecoverableRecording, Vec<SpoolChannel>)> = Vec::new();
+    for entry in std::fs::read_dir(&dir)
+        .map_err(|e| e.to_string())?
+        .flatten()
+    {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("wav") || active.contains(&path) {
+            continue;
+        }
+        let Some((id, channel)) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(SpoolChannel::parse)
+        else {
+            continue;
+        };
+        // Crashed before the first checkpoint: nothing to recover.
+        let duration_secs = match hound::WavReader::open(&path) {
+            Ok(reader) if reader.duration() > 0 => {
+                reader.duration() as f64 / reader.spec().sample_rate as f64
+            }
+            _ => {
+                crate::log_warn!("Removing unreadable capture spool {:?}", path);
+                let _ = std::fs::remove_file(&path);
+                continue;
+            }
+        };
+        let recorded_at = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .map(|time| chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339())
+            .unwrap_or_default();
+        match recordings
+            .iter_mut()
+            .find(|(recording, _)| recording.id == id)
+        {
+            Some((recording, channels)) => {
+                recording.duration_secs = recording.duration_secs.max(duration_secs);
+                recording.recorded_at = recording.recorded_at.clone().max(recorded_at);
+                channels.push(channel);
+            }
+            None => recordings.push((
+                RecoverableRecording {
+                    id: id.to_string(),
+                    recorded_at,
+                    duration_secs,
+                    source: "mic",
+                },
+                vec![channel],
+            )),
+        }
+    }
+    let mut recordings: Vec<RecoverableRecording> = recordings
+        .into_iter()
+        .map(|(mut recording, channels)| {
+            recording.source = if !channels.contains(&SpoolChannel::System) {
+                "mic"
+            } else if channels.contains(&SpoolChannel::Mic) {
+                "call"
+            } else {
+                "system"
+            };
+            recording
+        })
+        .collect();
+    recordings.sort_by(|a, b| a.recorded_at.cmp(&b.recorded_at));
+    Ok(recordings)
+}
+
+/// Takes ownership of the spools a session left behind; dropping them
+/// deletes them.
+pub fn open_recoverable(id: &str) -> Result<Vec<(SpoolChannel, SpoolFile)>, String> {
+    let dir = crate::paths::spool_dir()?;
+    if id.is_empty() || id.contains(['.', '/', '\\']) {
+        return Err(format!("No recoverable recording '{}'", id));
+    }
+    let mut active = ACTIVE.lock().unwrap();
+    let spools: Vec<(SpoolChannel, PathBuf)> = [
+        (SpoolChannel::Dictation, format!("{}.wav", id)),
+        (SpoolChannel::System, format!("{}.system.wav", id)),
+        (SpoolChannel::Mic, format!("{}.mic.wav", id)),
+    ]
+    .into_iter()
+    .map(|(channel, name)| (channel, dir.join(name)))
+    .filter(|(_, path)| path.is_file())
+    .collect();
+    if spools.is_empty() {
+        return Err(format!("No recoverable recording '{}'", id));
+    }
+    if spools.iter().any(|(_, path)| active.contains(path)) {
+        return Err("That recording is already in use".to_string());
+    }
+    Ok(spools
+        .into_iter()
+        .map(|(channel, path)| {
+            active.push(path.clone());
+            (channel, SpoolFile { path, keep: false })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("voquill_spool_{}_{}.wav", name, std::process::id()))
+    }
+
+    #[test]
+    fn finished_spool_holds_every_sample_and_is_removed_on_drop() {
+        let path = temp_path("finished");
+        let mut writer = SpoolWriter::create(path.clone(), 8000).unwrap();
+        writer.push(&vec![0.25; 12000]);
+        writer.push(&[0.5; 10]);
+        let file = writer.finish().unwrap();
+        let reader = hound::WavReader::open(file.path()).unwrap();
+        assert_eq!(reader.duration(), 12010);
+        drop(reader);
+        drop(file);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn listen_channels_share_their_session_id() {
+        let id = "0b6f3c1e-2d4a-4f5e-9a8b-7c6d5e4f3a2b";
+        assert_eq!(SpoolChannel::parse(id), Some((id, SpoolChannel::Dictation)));
+        assert_eq!(
+            SpoolChannel::parse(&format!("{}.system", id)),
+            Some((id, SpoolChannel::System))
+        );
+        assert_eq!(
+            SpoolChannel::parse(&format!("{}.mic", id)),
+            Some((id, SpoolChannel::Mic))
+        );
+        assert_eq!(SpoolChannel::parse(&format!("{}.other", id)), None);
+    }
+
+    #[test]
+    fn interrupted_spool_is_readable_up_to_the_last_checkpoint() {
+        let path = temp_path("interrupted");
+        let mut writer = SpoolWriter::create(path.clone(), 8000).unwrap();
+        writer.push(&vec![0.25; 8000]);
+        writer.push(&vec![0.25; 100]);
+        // A crash runs no destructors and loses whatever was buffered.
+        std::mem::forget(writer);
+        let reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.duration(), 8000);
+        ACTIVE.lock().unwrap().retain(|active| *active != path);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,209 @@
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use super::conversion::finalize_spooled_capture;
+use super::device::{lookup_device, lookup_monitor_device};
+use super::engine::PersistentAudioEngine;
+use super::spool::{channel_spool_path, SpoolFile, SpoolWriter};
+use super::whisper_file::WhisperFile;
+
+/// Samples collected before each write to the spool.
+const SPOOL_WRITE_SAMPLES: usize = 4096;
+
+/// A running capture of system playback ("listen" mode), optionally with the
+/// microphone alongside it on its own channel for call transcription. Each
+/// channel owns an engine separate from the dictation engine and spools its
+/// samples to disk until `finish` or the duration cap, so a session cut
+/// short by a crash can be recovered.
+pub struct SystemCapture {
+    session_uuid: String,
+    system: Channel,
+    mic: Option<Channel>,
+    started: Instant,
+}
+
+/// What a finished capture produced, one whisper file per channel. The
+/// spools stay on disk until `spools` is dropped.
+pub struct CapturedSystemAudio {
+    pub system: WhisperFile,
+    pub mic: Option<WhisperFile>,
+    pub spools: Vec<SpoolFile>,
+}
+
+/// Microphone settings for a dual capture.
+pub struct MicChannel {
+    pub device_id: Option<String>,
+    pub sensitivity: f32,
+}
+
+struct Channel {
+    name: &'static str,
+    engine: PersistentAudioEngine,
+    collector: JoinHandle<Result<SpoolFile, String>>,
+}
+
+impl SystemCapture {
+    pub fn start(
+        session_uuid: String,
+        monitor_id: Option<String>,
+        mic: Option<MicChannel>,
+        max_duration: Duration,
+    ) -> Result<Self, String> {
+        // Opening a pulse source goes through PULSE_SOURCE, which the
+        // dictation engine also reads; put it back once the streams are open.
+        let previous_source = std::env::var("PULSE_SOURCE").ok();
+        let engines = open_engines(monitor_id, mic);
+        match previous_source {
+            Some(source) => std::env::set_var("PULSE_SOURCE", source),
+            None => std::env::remove_var("PULSE_SOURCE"),
+        }
+        let (system_engine, mic_engine) = engines?;
+
+        let system = Channel::start(&session_uuid, "system", system_engine, max_duration)?;
+        let mic = mic_engine
+            .map(|engine| Channel::start(&session_uuid, "mic", engine, max_duration))
+            .transpose()?;
+        let capture = Self {
+            session_uuid,
+            system,
+            mic,
+            started: Instant::now(),
+        };
+        crate::log_info!(
+            "System capture started at {}Hz{}",
+            capture.system.engine.sample_rate,
+            if capture.mic.is_some() {
+                " with the microphone on a separate channel"
+            } else {
+                ""
+            }
+        );
+        Ok(capture)
+    }
+
+    pub fn session_uuid(&self) -> &str {
+        &self.session_uuid
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn has_mic(&self) -> bool {
+        self.mic.is_some()
+    }
+
+    /// Stops the streams and finalizes each channel into a whisper file.
+    pub fn finish(self) -> Result<CapturedSystemAudio, String> {
+        // Stop both streams before draining either so the channels end
+        // together.
+        self.system.stop();
+        if let Some(mic) = &self.mic {
+            mic.stop();
+        }
+        let mut spools = Vec::new();
+        let system = self.system.finish()?;
+        let mic = self.mic.map(Channel::finish).transpose()?;
+        let finalized = finalize_channel(&system).and_then(|system_audio| {
+            let mic_audio = mic.as_ref().map(finalize_channel).transpose()?;
+            Ok((system_audio, mic_audio))
+        });
+        spools.push(system);
+        spools.extend(mic);
+        match finalized {
+            Ok((system, mic)) => Ok(CapturedSystemAudio {
+                system,
+                mic,
+                spools,
+            }),
+            Err(e) => {
+                spools.into_iter().for_each(SpoolFile::keep);
+                Err(e)
+            }
+        }
+    }
+}
+
+fn finalize_channel(spool: &SpoolFile) -> Result<WhisperFile, String> {
+    finalize_spooled_capture(spool.path(), true)
+        .map_err(|e| format!("Failed to finalize {:?}: {}", spool.path(), e))
+}
+
+fn open_engines(
+    monitor_id: Option<String>,
+    mic: Option<MicChannel>,
+) -> Result<(PersistentAudioEngine, Option<PersistentAudioEngine>), String> {
+    let system = lookup_monitor_device(monitor_id)
+        .and_then(|device| PersistentAudioEngine::new(&device, 1.0))?;
+    let mic = match mic {
+        Some(mic) => Some(
+            lookup_device(mic.device_id)
+                .and_then(|device| PersistentAudioEngine::new(&device, mic.sensitivity))
+                .map_err(|e| format!("Failed to open microphone: {}", e))?,
+        ),
+        None => None,
+    };
+    Ok((system, mic))
+}
+
+impl Channel {
+    fn start(
+        session_uuid: &str,
+        name: &'static str,
+        engine: PersistentAudioEngine,
+        max_duration: Duration,
+    ) -> Result<Self, String> {
+        let sample_rate = engine.sample_rate;
+        let max_samples = sample_rate as u64 * max_duration.as_secs();
+        let mut spool = SpoolWriter::create(channel_spool_path(session_uuid, name)?, sample_rate)?;
+        let (tx, rx) = mpsc::sync_channel::<f32>(65536);
+        *engine.recording_tx.lock().unwrap() = Some(tx);
+        let collector = std::thread::spawn(move || {
+            let mut block = Vec::with_capacity(SPOOL_WRITE_SAMPLES);
+            let mut received = 0u64;
+            let mut capped = false;
+            while let Ok(sample) = rx.recv() {
+                if received < max_samples {
+                    received += 1;
+                    block.push(sample);
+                    if block.len() == SPOOL_WRITE_SAMPLES {
+                        spool.push(&block);
+                        block.clear();
+                    }
+                } else if !capped {
+                    capped = true;
+                    crate::log_warn!(
+                        "System capture ({}): max duration of {:?} reached; ignoring further audio",
+                        name,
+                        max_duration
+                    );
+                }
+            }
+            spool.push(&block);
+            crate::log_info!(
+                "System capture ({}) finished: {} samples at {}Hz",
+                name,
+                received,
+                sample_rate
+            );
+            spool.finish()
+        });
+        Ok(Self {
+            name,
+            engine,
+            collector,
+        })
+    }
+
+    fn stop(&self) {
+        *self.engine.recording_tx.lock().unwrap() = None;
+    }
+
+    fn finish(self) -> Result<SpoolFile, String> {
+        drop(self.engine);
+        self.collector
+            .join()
+            .map_err(|_| format!("System capture ({}) collector panicked", self.name))?
+    }
+}
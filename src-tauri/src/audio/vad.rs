@@ -0,0 +1,288 @@
+//! Energy-based voice activity detection for hands-free recording.
+//!
+//! Audio is scored in 20ms frames against an adaptive noise floor: the floor
+//! follows quiet frames down immediately and creeps up slowly otherwise, so
+//! a fan or hum that never stops is eventually absorbed instead of holding a
+//! session open. Speech must stay above the floor for `min_speech_ms` before
+//! it counts, which rejects clicks, keyboard taps and short bumps.
+
+const FRAME_MS: u64 = 20;
+/// Frames quieter than this never count as speech, however low the floor.
+const ABSOLUTE_FLOOR_DB: f32 = -55.0;
+/// How fast the noise floor rises while the signal stays above it.
+const FLOOR_RISE_DB_PER_SEC: f32 = 1.0;
+const INITIAL_FLOOR_DB: f32 = -60.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VadSettings {
+    /// Margin above the noise floor a frame needs to count as speech.
+    pub threshold_db: f32,
+    /// Continuous speech needed before an utterance starts.
+    pub min_speech_ms: u64,
+    /// Continuous silence that ends an utterance.
+    pub silence_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VadEvent {
+    SpeechStart,
+    SpeechEnd,
+}
+
+pub struct VoiceActivityDetector {
+    settings: VadSettings,
+    frame_len: usize,
+    frame: Vec<f32>,
+    noise_floor_db: f32,
+    floor_rise_per_frame: f32,
+    in_speech: bool,
+    voiced_run_ms: u64,
+    silent_run_ms: u64,
+}
+
+impl VoiceActivityDetector {
+    pub fn new(sample_rate: u32, settings: VadSettings) -> Self {
+        let frame_len = ((sample_rate as u64 * FRAME_MS) / 1000).max(1) as usize;
+        Self {
+            settings,
+            frame_len,
+            frame: Vec::with_capacity(frame_len),
+            noise_floor_db: INITIAL_FLOOR_DB,
+            floor_rise_per_frame: FLOOR_RISE_DB_PER_SEC * FRAME_MS as f32 / 1000.0,
+            in_speech: false,
+            voiced_run_ms: 0,
+            silent_run_ms: 0,
+        }
+    }
+
+    pub fn settings(&self) -> VadSettings {
+        self.settings
+    }
+
+    pub fn in_speech(&self) -> bool {
+        self.in_speech
+    }
+
+    pub fn noise_floor_db(&self) -> f32 {
+        self.noise_floor_db
+    }
+
+    /// Forgets the current utterance but keeps the learned noise floor.
+    pub fn reset(&mut self) {
+        self.frame.clear();
+        self.in_speech = false;
+        self.voiced_run_ms = 0;
+        self.silent_run_ms = 0;
+    }
+
+    /// Feeds mono samples and returns the last speech boundary crossed, if
+    /// any. Partial frames are carried over to the next call.
+    pub fn push(&mut self, samples: &[f32]) -> Option<VadEvent> {
+        let mut event = None;
+        for &sample in samples {
+            self.frame.push(sample);
+            if self.frame.len() == self.frame_len {
+                let level_db = frame_level_db(&self.frame);
+                self.frame.clear();
+                if let Some(frame_event) = self.process_frame(level_db) {
+                    event = Some(frame_event);
+                }
+            }
+        }
+        event
+    }
+
+    fn process_frame(&mut self, level_db: f32) -> Option<VadEvent> {
+        let voiced = level_db > ABSOLUTE_FLOOR_DB
+            && level_db > self.noise_floor_db + self.settings.threshold_db;
+
+        if level_db < self.noise_floor_db {
+            self.noise_floor_db = level_db.max(-100.0);
+        } else {
+            self.noise_floor_db += self.floor_rise_per_frame;
+        }
+
+        if voiced {
+            self.voiced_run_ms += FRAME_MS;
+            self.silent_run_ms = 0;
+        } else {
+            self.silent_run_ms += FRAME_MS;
+            if !self.in_speech {
+                self.voiced_run_ms = 0;
+            }
+        }
+
+        if !self.in_speech && self.voiced_run_ms >= self.settings.min_speech_ms {
+            self.in_speech = true;
+            return Some(VadEvent::SpeechStart);
+        }
+        if self.in_speech && self.silent_run_ms >= self.settings.silence_ms {
+            self.in_speech = false;
+            self.voiced_run_ms = 0;
+            return Some(VadEvent::SpeechEnd);
+        }
+        None
+    }
+}
+
+/// Finds the speech in a finished recording, read in blocks, as
+/// `(start, end)` seconds, each widened by `padding_ms` and merged where the
+/// padding overlaps. Used to timestamp separately captured call channels
+/// without diarization.
+pub struct SpeechRegions {
+    vad: VoiceActivityDetector,
+    rate: f64,
+    padding: f64,
+    pushed: usize,
+    raw: Vec<(f64, f64)>,
+    start: Option<f64>,
+}
+
+impl SpeechRegions {
+    pub fn new(sample_rate: u32, settings: VadSettings, padding_ms: u64) -> Self {
+        Self {
+            vad: VoiceActivityDetector::new(sample_rate, settings),
+            rate: sample_rate as f64,
+            padding: padding_ms as f64 / 1000.0,
+            pushed: 0,
+            raw: Vec::new(),
+            start: None,
+        }
+    }
+
+    pub fn push(&mut self, samples: &[f32]) {
+        let min_speech = self.vad.settings.min_speech_ms as f64 / 1000.0;
+        let silence = self.vad.settings.silence_ms as f64 / 1000.0;
+        let mut rest = samples;
+        while !rest.is_empty() {
+            // Feed up to the next frame boundary at a time, so each event
+            // is timed to the frame that fired it.
+            let to_boundary = self.vad.frame_len - self.pushed % self.vad.frame_len;
+            let (frame, tail) = rest.split_at(to_boundary.min(rest.len()));
+            rest = tail;
+            self.pushed += frame.len();
+            let frame_end = self.pushed as f64 / self.rate;
+            // Events fire once the speech or silence run is long enough, so
+            // wind the boundary back to where the run began.
+            match self.vad.push(frame) {
+                Some(VadEvent::SpeechStart) => self.start = Some(frame_end - min_speech),
+                Some(VadEvent::SpeechEnd) => {
+                    if let Some(start) = self.start.take() {
+                        self.raw.push((start, frame_end - silence));
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+
+    pub fn finish(mut self) -> Vec<(f64, f64)> {
+        let duration = self.pushed as f64 / self.rate;
+        if let Some(start) = self.start {
+            self.raw.push((start, duration));
+        }
+
+        let mut regions: Vec<(f64, f64)> = Vec::new();
+        for (start, end) in self.raw {
+            let start = (start - self.padding).max(0.0);
+            let end = (end + self.padding).min(duration);
+            match regions.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => regions.push((start, end)),
+            }
+        }
+        regions
+    }
+}
+
+fn frame_level_db(frame: &[f32]) -> f32 {
+    let mean_square = frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32;
+    10.0 * mean_square.max(1e-12).log10()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 16000;
+
+    fn settings() -> VadSettings {
+        VadSettings {
+            threshold_db: 12.0,
+            min_speech_ms: 200,
+            silence_ms: 600,
+        }
+    }
+
+    fn speech_regions(samples: &[f32], padding_ms: u64) -> Vec<(f64, f64)> {
+        let mut finder = SpeechRegions::new(RATE, settings(), padding_ms);
+        finder.push(samples);
+        finder.finish()
+    }
+
+    fn tone(ms: u64, amplitude: f32) -> Vec<f32> {
+        let len = (RATE as u64 * ms / 1000) as usize;
+        (0..len)
+            .map(|i| amplitude * (i as f32 * 440.0 * std::f32::consts::TAU / RATE as f32).sin())
+            .collect()
+    }
+
+    #[test]
+    fn speech_opens_and_silence_closes_an_utterance() {
+        let mut vad = VoiceActivityDetector::new(RATE, settings());
+        assert_eq!(vad.push(&tone(1000, 0.001)), None);
+        assert_eq!(vad.push(&tone(500, 0.3)), Some(VadEvent::SpeechStart));
+        assert!(vad.in_speech());
+        assert_eq!(vad.push(&tone(300, 0.001)), None);
+        assert_eq!(vad.push(&tone(400, 0.001)), Some(VadEvent::SpeechEnd));
+        assert!(!vad.in_speech());
+    }
+
+    #[test]
+    fn short_bursts_do_not_start_an_utterance() {
+        let mut vad = VoiceActivityDetector::new(RATE, settings());
+        vad.push(&tone(1000, 0.001));
+        for _ in 0..5 {
+            assert_eq!(vad.push(&tone(100, 0.3)), None);
+            assert_eq!(vad.push(&tone(200, 0.001)), None);
+        }
+        assert!(!vad.in_speech());
+    }
+
+    #[test]
+    fn steady_background_noise_is_absorbed_into_the_floor() {
+        let mut vad = VoiceActivityDetector::new(RATE, settings());
+        let mut starts = 0;
+        let mut ends = 0;
+        for _ in 0..60 {
+            match vad.push(&tone(1000, 0.02)) {
+                Some(VadEvent::SpeechStart) => starts += 1,
+                Some(VadEvent::SpeechEnd) => ends += 1,
+                None => {}
+            }
+        }
+        assert!(starts <= 1);
+        assert_eq!(starts, ends);
+        assert!(vad.noise_floor_db() > -45.0);
+    }
+
+    #[test]
+    fn speech_regions_are_timestamped_and_padded() {
+        let mut samples = tone(1000, 0.001);
+        samples.extend(tone(1000, 0.3));
+        samples.extend(tone(1500, 0.001));
+        samples.extend(tone(500, 0.3));
+        let regions = speech_regions(&samples, 100);
+        assert_eq!(regions.len(), 2);
+        assert!((regions[0].0 - 0.9).abs() < 0.05, "{:?}", regions);
+        assert!((regions[0].1 - 2.1).abs() < 0.05, "{:?}", regions);
+        assert!((regions[1].0 - 3.4).abs() < 0.05, "{:?}", regions);
+        assert_eq!(regions[1].1, 4.0);
+
+        let mut finder = SpeechRegions::new(RATE, settings(), 100);
+        for block in samples.chunks(1234) {
+            finder.push(block);
+        }
+        assert_eq!(finder.finish(), regions);
+    }
+}
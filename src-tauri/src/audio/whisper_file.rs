@@ -0,0 +1,350 @@
+//! Whisper-ready audio on disk: a 16kHz mono 16-bit WAV in the temp
+//! directory, removed when dropped.
+//!
+//! Captures are finalized into one of these instead of into memory, and
+//! every stage after that (noise reduction, diarization, saving,
+//! transcription) reads it back a block or a window at a time. Only the
+//! WAV of a single segment or transcription window is ever held in memory,
+//! so an hours-long session costs disk space rather than RAM.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use hound::{WavReader, WavWriter};
+
+use super::conversion::{float_to_i16, whisper_spec, write_whisper_wav};
+
+pub const WHISPER_SAMPLE_RATE: u32 = 16000;
+/// Samples read per block.
+const READ_BLOCK_SAMPLES: usize = 1 << 16;
+/// Longest stretch of audio sent to the transcription service at once;
+/// five minutes is under 10MB of WAV, well inside hosted API upload limits.
+const TRANSCRIPTION_WINDOW_SECS: u64 = 300;
+/// How far back from a window's end to look for a quiet place to cut it,
+/// so a word is not split between two requests.
+const WINDOW_CUT_SEARCH_SECS: u64 = 10;
+/// Frame the quietest cut point is chosen by (20ms).
+const WINDOW_CUT_FRAME: usize = 320;
+/// Diarization segments shorter than this (0.5s) are padded with silence so
+/// whisper does not choke on tiny fragments from the boundaries.
+const MIN_SEGMENT_SAMPLES: usize = 8000;
+
+pub struct WhisperFile {
+    path: PathBuf,
+    samples: u64,
+}
+
+impl WhisperFile {
+    /// Takes ownership of a WAV written elsewhere (e.g. by the Python
+    /// runner), which must already be in the whisper format. The file is
+    /// removed when dropped, or straight away when it is not usable.
+    pub fn adopt(path: PathBuf) -> Result<Self, String> {
+        let mut file = Self { path, samples: 0 };
+        file.samples = match file.reader()? {
+            reader if reader.spec() == whisper_spec() => reader.duration() as u64,
+            reader => {
+                let spec = reader.spec();
+                return Err(format!(
+                    "Expected 16kHz mono 16-bit WAV, got {}ch {}Hz {}-bit {:?}",
+                    spec.channels, spec.sample_rate, spec.bits_per_sample, spec.sample_format,
+                ));
+            }
+        };
+        Ok(file)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    pub fn duration_secs(&self) -> f64 {
+        self.samples as f64 / WHISPER_SAMPLE_RATE as f64
+    }
+
+    /// Reads the audio as float samples, a block at a time.
+    pub fn for_each_block(
+        &self,
+        sink: impl FnMut(&[f32]) -> Result<(), String>,
+    ) -> Result<(), String> {
+        for_each_block(&self.path, sink)
+    }
+
+    /// Reads the audio as 16-bit samples, a block at a time.
+    pub fn for_each_pcm_block(
+        &self,
+        sink: impl FnMut(&[i16]) -> Result<(), String>,
+    ) -> Result<(), String> {
+        for_each_pcm_block(&self.path, sink)
+    }
+
+    /// Samples `start..end`, clamped to the file.
+    fn read_range(&self, start: u64, end: u64) -> Result<Vec<i16>, String> {
+        let end = end.min(self.samples);
+        if end <= start {
+            return Ok(Vec::new());
+        }
+        let mut reader = self.reader()?;
+        reader
+            .seek(start as u32)
+            .map_err(|e| format!("Failed to seek audio: {}", e))?;
+        reader
+            .samples::<i16>()
+            .take((end - start) as usize)
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Failed to read audio: {}", e))
+    }
+
+    /// A time range (in seconds, clamped to the file) as WAV bytes to pass
+    /// to whisper, padded with silence to at least half a second.
+    pub fn segment_wav(&self, start_sec: f64, end_sec: f64) -> Result<Vec<u8>, String> {
+        let to_sample = |sec: f64| (sec.max(0.0) * WHISPER_SAMPLE_RATE as f64) as u64;
+        let mut segment = self.read_range(to_sample(start_sec), to_sample(end_sec))?;
+        if segment.len() < MIN_SEGMENT_SAMPLES {
+            segment.resize(MIN_SEGMENT_SAMPLES, 0);
+        }
+        write_whisper_wav(&segment).map_err(|e| e.to_string())
+    }
+
+    /// Splits the audio into `(start, end)` sample ranges of at most
+    /// `TRANSCRIPTION_WINDOW_SECS`, each cut at the quietest point near its
+    /// end. Audio shorter than that is a single window.
+    pub fn transcription_windows(&self) -> Result<Vec<(u64, u64)>, String> {
+        let window = TRANSCRIPTION_WINDOW_SECS * WHISPER_SAMPLE_RATE as u64;
+        let search = WINDOW_CUT_SEARCH_SECS * WHISPER_SAMPLE_RATE as u64;
+        let mut windows = Vec::new();
+        let mut start = 0;
+        while self.samples - start > window {
+            let search_start = start + window - search;
+            let region = self.read_range(search_start, start + window)?;
+            let cut = search_start + quietest_point(&region) as u64;
+            windows.push((start, cut));
+            start = cut;
+        }
+        windows.push((start, self.samples));
+        Ok(windows)
+    }
+
+    /// Samples `start..end` as WAV bytes.
+    pub fn window_wav(&self, start: u64, end: u64) -> Result<Vec<u8>, String> {
+        write_whisper_wav(&self.read_range(start, end)?).map_err(|e| e.to_string())
+    }
+
+    fn reader(&self) -> Result<WavReader<BufReader<File>>, String> {
+        WavReader::open(&self.path).map_err(|e| format!("Failed to open {:?}: {}", self.path, e))
+    }
+}
+
+impl Drop for WhisperFile {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            crate::log_warn!("Failed to remove temp audio {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// Reads the whisper WAV at `path` as float samples, a block at a time.
+/// Blocking tasks take the path, as the file itself stays with its owner.
+pub fn for_each_block(
+    path: &Path,
+    mut sink: impl FnMut(&[f32]) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut block = Vec::with_capacity(READ_BLOCK_SAMPLES);
+    for_each_pcm_block(path, |pcm| {
+        block.clear();
+        block.extend(pcm.iter().map(|&s| s as f32 / i16::MAX as f32));
+        sink(&block)
+    })
+}
+
+fn for_each_pcm_block(
+    path: &Path,
+    mut sink: impl FnMut(&[i16]) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut reader =
+        WavReader::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+    let mut samples = reader.samples::<i16>();
+    let mut block = Vec::with_capacity(READ_BLOCK_SAMPLES);
+    loop {
+        block.clear();
+        for sample in samples.by_ref().take(READ_BLOCK_SAMPLES) {
+            block.push(sample.map_err(|e| format!("Failed to read audio: {}", e))?);
+        }
+        if block.is_empty() {
+            return Ok(());
+        }
+        sink(&block)?;
+    }
+}
+
+/// Writes a `WhisperFile` from float samples as they are produced.
+pub struct WhisperWriter {
+    writer: WavWriter<BufWriter<File>>,
+    file: WhisperFile,
+    error: Option<String>,
+}
+
+impl WhisperWriter {
+    pub fn create() -> Result<Self, String> {
+        let dir = crate::paths::temp_dir();
+        let _ = std::fs::create_dir_all(&dir);
+        let path = dir.join(format!(
+            "audio_{}.wav",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos()
+        ));
+        let writer = WavWriter::create(&path, whisper_spec())
+            .map_err(|e| format!("Failed to create temp audio {:?}: {}", path, e))?;
+        Ok(Self {
+            writer,
+            file: WhisperFile { path, samples: 0 },
+            error: None,
+        })
+    }
+
+    /// Appends float samples, clamped to full scale.
+    pub fn push(&mut self, samples: &[f32]) {
+        for &sample in samples {
+            self.push_sample(float_to_i16(sample));
+        }
+    }
+
+    /// Appends 16-bit samples.
+    pub fn push_pcm(&mut self, samples: &[i16]) {
+        for &sample in samples {
+            self.push_sample(sample);
+        }
+    }
+
+    /// After a write error further samples are dropped and the error is
+    /// returned from `finish`.
+    fn push_sample(&mut self, sample: i16) {
+        if self.error.is_some() {
+            return;
+        }
+        match self.writer.write_sample(sample) {
+            Ok(()) => self.file.samples += 1,
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    pub fn finish(self) -> Result<WhisperFile, String> {
+        let Self {
+            writer,
+            file,
+            error,
+        } = self;
+        if let Some(error) = error {
+            return Err(format!("Failed to write temp audio: {}", error));
+        }
+        writer
+            .finalize()
+            .map_err(|e| format!("Failed to write temp audio: {}", e))?;
+        Ok(file)
+    }
+}
+
+/// Offset of the middle of the quietest `WINDOW_CUT_FRAME` in `samples`.
+fn quietest_point(samples: &[i16]) -> usize {
+    samples
+        .chunks(WINDOW_CUT_FRAME)
+        .enumerate()
+        .min_by_key(|(_, frame)| frame.iter().map(|&s| (s as i64).pow(2)).sum::<i64>())
+        .map_or(samples.len(), |(index, frame)| {
+            index * WINDOW_CUT_FRAME + frame.len() / 2
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(samples: &[i16]) -> WhisperFile {
+        let mut writer = WhisperWriter::create().unwrap();
+        writer.push_pcm(samples);
+        writer.finish().unwrap()
+    }
+
+    fn read(wav: &[u8]) -> Vec<i16> {
+        let mut reader = WavReader::new(std::io::Cursor::new(wav)).unwrap();
+        reader.samples::<i16>().map(|s| s.unwrap()).collect()
+    }
+
+    #[test]
+    fn segments_are_read_by_seeking_and_padded() {
+        let samples: Vec<i16> = (0..48_000).map(|i| (i % 1000) as i16).collect();
+        let file = write(&samples);
+        assert_eq!(file.samples(), 48_000);
+        assert_eq!(file.duration_secs(), 3.0);
+        assert_eq!(
+            read(&file.segment_wav(1.0, 2.0).unwrap()),
+            samples[16_000..32_000]
+        );
+        assert_eq!(
+            read(&file.segment_wav(2.5, 9.0).unwrap()),
+            samples[40_000..]
+        );
+        let short = read(&file.segment_wav(0.0, 0.1).unwrap());
+        assert_eq!(short.len(), MIN_SEGMENT_SAMPLES);
+        assert_eq!(short[..1600], samples[..1600]);
+        assert!(short[1600..].iter().all(|&s| s == 0));
+
+        let mut blocks = Vec::new();
+        file.for_each_pcm_block(|block| {
+            blocks.extend_from_slice(block);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(blocks, samples);
+        let path = file.path().to_path_buf();
+        drop(file);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn long_audio_is_windowed_at_quiet_points() {
+        let window = (TRANSCRIPTION_WINDOW_SECS * WHISPER_SAMPLE_RATE as u64) as usize;
+        let quiet_at = window - 3 * WHISPER_SAMPLE_RATE as usize;
+        let mut samples: Vec<i16> = (0..window * 2).map(|i| (i % 200) as i16 + 100).collect();
+        samples[quiet_at..quiet_at + WINDOW_CUT_FRAME].fill(0);
+        let file = write(&samples);
+        let windows = file.transcription_windows().unwrap();
+        let cut = (quiet_at + WINDOW_CUT_FRAME / 2) as u64;
+        assert_eq!(windows[0], (0, cut));
+        assert_eq!(windows[1].0, cut);
+        assert_eq!(windows.last().unwrap().1, samples.len() as u64);
+        assert!(windows
+            .iter()
+            .all(|(start, end)| end - start <= window as u64));
+        assert_eq!(
+            read(&file.window_wav(windows[1].0, windows[1].1).unwrap()),
+            samples[windows[1].0 as usize..windows[1].1 as usize]
+        );
+
+        let short = write(&samples[..1000]);
+        assert_eq!(short.transcription_windows().unwrap(), [(0, 1000)]);
+    }
+
+    #[test]
+    fn adopting_rejects_other_formats() {
+        let file = write(&[1, 2, 3]);
+        let adopted = WhisperFile::adopt(file.path().to_path_buf()).unwrap();
+        assert_eq!(adopted.samples(), 3);
+        std::mem::forget(file);
+
+        let path = std::env::temp_dir().join(format!("voquill_adopt_{}.wav", std::process::id()));
+        let spec = hound::WavSpec {
+            sample_rate: 44100,
+            ..whisper_spec()
+        };
+        WavWriter::create(&path, spec).unwrap().finalize().unwrap();
+        assert!(WhisperFile::adopt(path.clone()).is_err());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,229 @@
+use std::time::{Duration, Instant};
+
+/// How much of the previous output is remembered. Whisper only looks at the
+/// end of its initial prompt, and casing/spacing only need the last few
+/// characters.
+const TAIL_MAX_CHARS: usize = 200;
+
+/// Words a sentence practically never ends on. When the transcriber closes a
+/// dictation with one of these plus a period ("I went to the."), the user
+/// almost certainly paused mid-sentence.
+const DANGLING_WORDS: &[&str] = &[
+    "a", "an", "the", "to", "of", "and", "or", "but", "with", "for", "from", "in", "on", "at",
+    "by", "my", "your", "our", "their", "his", "her", "its", "this", "that", "is", "are", "was",
+    "were",
+];
+
+/// The tail of the last delivered dictation and when it was delivered.
+#[derive(Debug, Clone)]
+pub struct OutputTail {
+    pub text: String,
+    pub at: Instant,
+}
+
+impl OutputTail {
+    pub fn new(output: &str) -> Self {
+        let skip = output.chars().count().saturating_sub(TAIL_MAX_CHARS);
+        Self {
+            text: output.chars().skip(skip).collect(),
+            at: Instant::now(),
+        }
+    }
+
+    /// The remembered text, or `None` once the continuation window elapsed.
+    pub fn within(&self, window: Duration) -> Option<&str> {
+        if self.at.elapsed() <= window && !self.text.trim().is_empty() {
+            Some(&self.text)
+        } else {
+            None
+        }
+    }
+}
+
+fn last_word(text: &str) -> Option<String> {
+    text.split_whitespace().last().map(|w| {
+        w.trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase()
+    })
+}
+
+fn ends_sentence(tail: &str) -> bool {
+    let trimmed = tail.trim_end_matches([' ', '\t']);
+    if trimmed.is_empty() || trimmed.ends_with('\n') {
+        return true;
+    }
+    let trimmed = trimmed.trim_end_matches(['"', '\'', ')']);
+    trimmed.ends_with(['.', '!', '?', ':'])
+}
+
+/// Drops a terminal period the transcriber added after a dangling word, so a
+/// sentence split across two dictations does not get a full stop in the
+/// middle ("I went to the." -> "I went to the").
+pub fn trim_dangling_period(text: &str) -> String {
+    let trimmed = text.trim_end();
+    let Some(without_period) = trimmed.strip_suffix('.') else {
+        return text.to_string();
+    };
+    if without_period.ends_with('.') {
+        return text.to_string();
+    }
+    match last_word(without_period) {
+        Some(word) if DANGLING_WORDS.contains(&word.as_str()) => without_period.to_string(),
+        _ => text.to_string(),
+    }
+}
+
+fn should_lowercase_first_word(word: &str) -> bool {
+    let core = word.trim_matches(|c: char| !c.is_alphanumeric());
+    let mut chars = core.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    // Leave "I", "I'm", acronyms and mixed-case names alone
+    first.is_uppercase()
+        && chars.clone().next().is_some()
+        && chars.all(|c| !c.is_uppercase())
+        && !core.starts_with("I'")
+}
+
+/// Joins a new dictation onto the previous output's tail: lowercases the
+/// transcriber's sentence-start capital when the tail stopped mid-sentence,
+/// and adds the separating space when the tail did not already end in one.
+pub fn continue_from(text: &str, tail: Option<&str>) -> String {
+    let Some(tail) = tail else {
+        return text.to_string();
+    };
+    if text.is_empty() {
+        return String::new();
+    }
+
+    let mut body = text.to_string();
+    if !ends_sentence(tail) {
+        if let Some(first_word) = body.split_whitespace().next() {
+            if should_lowercase_first_word(first_word) {
+                let offset = body.find(first_word).unwrap_or(0);
+                let mut chars = body[offset..].chars();
+                if let Some(first) = chars.next() {
+                    body = format!(
+                        "{}{}{}",
+                        &body[..offset],
+                        first.to_lowercase(),
+                        chars.as_str()
+                    );
+                }
+            }
+        }
+    }
+
+    let tail_ends_in_space = tail.ends_with(char::is_whitespace);
+    let starts_with_punctuation = body.starts_with([',', '.', ';', ':', '!', '?', ')']);
+    if tail_ends_in_space || starts_with_punctuation {
+        body
+    } else {
+        format!(" {}", body)
+    }
+}
+
+/// Appends the previous output's tail to the transcription prompt so Whisper
+/// continues the sentence instead of starting a fresh one.
+pub fn extend_prompt_hint(hint: Option<String>, tail: Option<&str>) -> Option<String> {
+    let tail = tail.map(str::trim).filter(|t| !t.is_empty());
+    match (hint, tail) {
+        (Some(hint), Some(tail)) => Some(format!("{} {}", hint, tail)),
+        (Some(hint), None) => Some(hint),
+        (None, Some(tail)) => Some(tail.to_string()),
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trim_dangling_period() {
+        assert_eq!(trim_dangling_period("I went to the."), "I went to the");
+        assert_eq!(trim_dangling_period("I went home."), "I went home.");
+        assert_eq!(trim_dangling_period("Wait for it..."), "Wait for it...");
+        assert_eq!(trim_dangling_period("Is it the?"), "Is it the?");
+    }
+
+    #[test]
+    fn test_continue_mid_sentence_lowercases_and_spaces() {
+        assert_eq!(
+            continue_from("Store today.", Some("I went to the")),
+            " store today."
+        );
+    }
+
+    #[test]
+    fn test_continue_after_sentence_keeps_capital() {
+        assert_eq!(
+            continue_from("Then we left.", Some("I went home.")),
+            " Then we left."
+        );
+    }
+
+    #[test]
+    fn test_continue_respects_existing_trailing_space() {
+        assert_eq!(
+            continue_from("Store today.", Some("I went to the ")),
+            "store today."
+        );
+    }
+
+    #[test]
+    fn test_continue_keeps_pronoun_and_acronyms() {
+        assert_eq!(
+            continue_from("I think so.", Some("and then")),
+            " I think so."
+        );
+        assert_eq!(
+            continue_from("NASA called.", Some("and then")),
+            " NASA called."
+        );
+    }
+
+    #[test]
+    fn test_continue_no_space_before_punctuation() {
+        assert_eq!(continue_from(", right?", Some("It works")), ", right?");
+    }
+
+    #[test]
+    fn test_continue_after_newline_is_fresh_line() {
+        assert_eq!(continue_from("Next item", Some("- milk\n")), "Next item");
+    }
+
+    #[test]
+    fn test_no_tail_is_passthrough() {
+        assert_eq!(continue_from("Hello.", None), "Hello.");
+    }
+
+    #[test]
+    fn test_tail_is_bounded_and_expires() {
+        let long = "x".repeat(TAIL_MAX_CHARS * 2);
+        let tail = OutputTail::new(&long);
+        assert_eq!(tail.text.chars().count(), TAIL_MAX_CHARS);
+        assert!(tail.within(Duration::from_secs(30)).is_some());
+
+        if let Some(at) = Instant::now().checked_sub(Duration::from_secs(60)) {
+            let stale = OutputTail {
+                text: "I went to the".to_string(),
+                at,
+            };
+            assert!(stale.within(Duration::from_secs(30)).is_none());
+        }
+    }
+
+    #[test]
+    fn test_extend_prompt_hint() {
+        assert_eq!(
+            extend_prompt_hint(
+                Some("Vocabulary: Voquill.".to_string()),
+                Some("I went to the")
+            ),
+            Some("Vocabulary: Voquill. I went to the".to_string())
+        );
+        assert_eq!(extend_prompt_hint(None, Some("  ")), None);
+    }
+}
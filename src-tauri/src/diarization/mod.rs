@@ -29,6 +29,28 @@ fn default_provider() -> String {
     "none".to_string()
 }
 
+/// Interleaves segments transcribed from separately captured channels (for
+/// example "Me" and "Remote" in a call) into one conversation ordered by
+/// start time. Segments without a start keep their order at the end.
+pub fn merge_channel_segments(channels: Vec<Vec<Segment>>) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = channels.into_iter().flatten().collect();
+    segments.sort_by(|a, b| {
+        a.start_sec
+            .unwrap_or(f64::MAX)
+            .total_cmp(&b.start_sec.unwrap_or(f64::MAX))
+    });
+    segments
+}
+
+/// Flat transcript with one `[Speaker] text` line per segment.
+pub fn labeled_text(segments: &[Segment]) -> String {
+    segments
+        .iter()
+        .map(|s| format!("[{}] {}", s.speaker.as_deref().unwrap_or("Speaker"), s.text))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// Trait for diarization backends. The Python runner is the initial
 /// implementation; a future Rust-native ONNX path would also implement this.
 #[allow(dead_code)]
@@ -36,3 +58,36 @@ pub trait DiarizationService: Send + Sync {
     async fn diarize(&self, audio_path: &str) -> Result<DiarizationResult, String>;
     fn service_name(&self) -> &'static str;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(speaker: &str, text: &str, start: f64) -> Segment {
+        Segment {
+            speaker: Some(speaker.to_string()),
+            text: text.to_string(),
+            start_sec: Some(start),
+            end_sec: Some(start + 1.0),
+        }
+    }
+
+    #[test]
+    fn channel_segments_interleave_by_start_time() {
+        let me = vec![
+            segment("Me", "Hi, can you hear me?", 0.5),
+            segment("Me", "Great.", 4.0),
+        ];
+        let remote = vec![segment("Remote", "Yes, loud and clear.", 2.0)];
+        let merged = merge_channel_segments(vec![me, remote]);
+        let speakers: Vec<_> = merged
+            .iter()
+            .map(|s| s.speaker.as_deref().unwrap())
+            .collect();
+        assert_eq!(speakers, ["Me", "Remote", "Me"]);
+        assert_eq!(
+            labeled_text(&merged),
+            "[Me] Hi, can you hear me?\n[Remote] Yes, loud and clear.\n[Me] Great."
+        );
+    }
+}
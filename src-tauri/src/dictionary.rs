@@ -0,0 +1,535 @@
+use std::collections::BTreeMap;
+
+/// Terms whose normalized form is shorter than this are only corrected
+/// through explicit aliases. Short words collide with ordinary vocabulary far
+/// too often for edit-distance or phonetic matching to be trustworthy.
+const MIN_FUZZY_LEN: usize = 4;
+
+/// Upper bound on how many transcribed words a single span may cover.
+const MAX_WINDOW_WORDS: usize = 5;
+
+/// Phonetic matches still need at least this much literal similarity, so a
+/// term like "Voquill" never swallows an unrelated word such as "vocal".
+const PHONETIC_EDIT_FLOOR: f64 = 0.6;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CorrectionThresholds {
+    /// Minimum normalized edit similarity (0.0–1.0) for a plain spelling match.
+    pub edit: f64,
+    /// Minimum phonetic-code similarity (0.0–1.0) for a sound-alike match.
+    pub phonetic: f64,
+}
+
+struct MatchKey<'a> {
+    canonical: &'a str,
+    key: String,
+    phonetic: String,
+    words: usize,
+}
+
+struct Token<'a> {
+    leading: &'a str,
+    core: &'a str,
+    trailing: &'a str,
+}
+
+fn split_token(raw: &str) -> Token<'_> {
+    let core_start = raw
+        .char_indices()
+        .find(|(_, c)| c.is_alphanumeric())
+        .map(|(i, _)| i)
+        .unwrap_or(raw.len());
+    let core_end = raw
+        .char_indices()
+        .rev()
+        .find(|(_, c)| c.is_alphanumeric())
+        .map(|(i, c)| i + c.len_utf8())
+        .unwrap_or(core_start);
+    Token {
+        leading: &raw[..core_start],
+        core: &raw[core_start..core_end],
+        trailing: &raw[core_end..],
+    }
+}
+
+/// Lowercases and strips everything but letters and digits, so "Node.js",
+/// "node js" and "nodejs" all compare equal.
+fn normalize_key(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+fn phonetic_group(c: char) -> Option<char> {
+    match c {
+        'b' | 'f' | 'p' | 'v' => Some('1'),
+        'c' | 'g' | 'j' | 'k' | 'q' | 's' | 'x' | 'z' => Some('2'),
+        'd' | 't' => Some('3'),
+        'l' => Some('4'),
+        'm' | 'n' => Some('5'),
+        'r' => Some('6'),
+        'a' | 'e' | 'i' | 'o' | 'u' | 'y' => Some('0'),
+        'h' | 'w' => None,
+        other if other.is_ascii_digit() => Some(other),
+        _ => Some('0'),
+    }
+}
+
+/// Soundex-style consonant skeleton of a normalized key. Unlike classic
+/// Soundex it is not truncated, and the first letter is coded like the rest so
+/// "c" and "k" openings ("cooper" / "Kubernetes") land in the same group.
+/// Vowels separate repeated consonant groups but are otherwise dropped; a
+/// leading vowel is kept as `0` so vowel-initial words only match each other.
+pub fn phonetic_code(key: &str) -> String {
+    let mut code = String::new();
+    let mut previous: Option<char> = None;
+    for c in key.chars() {
+        let Some(group) = phonetic_group(c) else {
+            continue;
+        };
+        if code.is_empty() {
+            code.push(group);
+            previous = Some(group);
+            continue;
+        }
+        if group == '0' {
+            previous = None;
+            continue;
+        }
+        if previous != Some(group) {
+            code.push(group);
+        }
+        previous = Some(group);
+    }
+    code
+}
+
+fn levenshtein(a: &[char], b: &[char]) -> usize {
+    if a.is_empty() {
+        return b.len();
+    }
+    if b.is_empty() {
+        return a.len();
+    }
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Normalized edit similarity: 1.0 for identical strings, 0.0 for strings
+/// that share nothing.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(&a, &b) as f64 / longest as f64
+}
+
+/// How well `fragment` matches its closest substring of `term` (1.0 when it
+/// appears verbatim). Skipping characters at either end of `term` is free, so
+/// "vo" and "quill" both score 1.0 against "voquill".
+fn fragment_similarity(fragment: &str, term: &str) -> f64 {
+    let fragment: Vec<char> = fragment.chars().collect();
+    let term: Vec<char> = term.chars().collect();
+    if fragment.is_empty() {
+        return 1.0;
+    }
+    let mut previous = vec![0; term.len() + 1];
+    let mut current = vec![0; term.len() + 1];
+    for (i, cf) in fragment.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, ct) in term.iter().enumerate() {
+            let cost = usize::from(cf != ct);
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    let distance = previous.iter().copied().min().unwrap_or(fragment.len());
+    1.0 - distance as f64 / fragment.len() as f64
+}
+
+fn build_match_keys<'a>(
+    dictionary: &'a [String],
+    aliases: &'a BTreeMap<String, Vec<String>>,
+) -> Vec<MatchKey<'a>> {
+    let mut keys = Vec::new();
+    for term in dictionary {
+        let canonical = term.trim();
+        let key = normalize_key(canonical);
+        if key.len() >= MIN_FUZZY_LEN {
+            keys.push(MatchKey {
+                canonical,
+                phonetic: phonetic_code(&key),
+                key,
+                words: canonical.split_whitespace().count(),
+            });
+        }
+    }
+    for (term, term_aliases) in aliases {
+        let canonical = term.trim();
+        if canonical.is_empty() {
+            continue;
+        }
+        for alias in term_aliases {
+            let key = normalize_key(alias);
+            if key.is_empty() {
+                continue;
+            }
+            keys.push(MatchKey {
+                canonical,
+                phonetic: phonetic_code(&key),
+                key,
+                words: alias.split_whitespace().count(),
+            });
+        }
+    }
+    keys
+}
+
+/// Scores a transcribed span against one dictionary key. Exact matches (after
+/// normalization) always score 1.0; everything else must clear either the
+/// edit threshold or the phonetic threshold plus a literal-similarity floor.
+fn score_span(span_key: &str, candidate: &MatchKey<'_>, thresholds: CorrectionThresholds) -> f64 {
+    if span_key == candidate.key {
+        return 1.0;
+    }
+    if span_key.len() < MIN_FUZZY_LEN || candidate.key.len() < MIN_FUZZY_LEN {
+        return 0.0;
+    }
+
+    // A fuzzy match must open with the same sound; otherwise a neighbouring
+    // word ("on kubernetes") could be folded into the replaced span.
+    let span_phonetic = phonetic_code(span_key);
+    if span_phonetic.chars().next() != candidate.phonetic.chars().next() {
+        return 0.0;
+    }
+
+    let edit = similarity(span_key, &candidate.key);
+    if edit >= thresholds.edit {
+        return edit;
+    }
+
+    let phonetic = similarity(&span_phonetic, &candidate.phonetic);
+    if phonetic >= thresholds.phonetic && edit >= PHONETIC_EDIT_FLOOR {
+        return (edit + phonetic) / 2.0;
+    }
+    0.0
+}
+
+/// Whether one transcribed word could be a piece of a split-up term, by
+/// spelling or by sound. A span of several words is only a candidate when
+/// every word in it is a fragment; otherwise an ordinary word next to the term
+/// ("check kubernetes") would be folded into the replacement.
+fn is_fragment(
+    token_key: &str,
+    candidate: &MatchKey<'_>,
+    thresholds: CorrectionThresholds,
+) -> bool {
+    let edit = fragment_similarity(token_key, &candidate.key);
+    if edit >= thresholds.edit {
+        return true;
+    }
+    let phonetic = fragment_similarity(&phonetic_code(token_key), &candidate.phonetic);
+    phonetic >= thresholds.phonetic && edit >= PHONETIC_EDIT_FLOOR
+}
+
+/// Replaces transcribed words and short word runs that closely resemble a
+/// dictionary term (or one of its aliases) with the term's canonical
+/// spelling and casing. Leading and trailing punctuation of the replaced span
+/// is preserved, and spans never cross sentence punctuation.
+pub fn correct_transcription(
+    text: &str,
+    dictionary: &[String],
+    aliases: &BTreeMap<String, Vec<String>>,
+    thresholds: CorrectionThresholds,
+) -> String {
+    let keys = build_match_keys(dictionary, aliases);
+    if keys.is_empty() || text.trim().is_empty() {
+        return text.to_string();
+    }
+
+    // One extra word lets a single term match when the recogniser splits it
+    // ("vo quill" for "Voquill").
+    let max_window = keys
+        .iter()
+        .map(|k| k.words + 1)
+        .max()
+        .unwrap_or(1)
+        .min(MAX_WINDOW_WORDS);
+
+    let raw_tokens: Vec<&str> = text.split_whitespace().collect();
+    let tokens: Vec<Token<'_>> = raw_tokens.iter().map(|t| split_token(t)).collect();
+    let mut output: Vec<String> = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let mut best: Option<(usize, &str, f64)> = None;
+        let mut span_key = String::new();
+        let mut token_keys: Vec<String> = Vec::new();
+        // Once a later word in the window is a term on its own, only an exact
+        // match of the whole span may cover it; a fuzzy one would swallow the
+        // word in front of it.
+        let mut covers_exact_word = false;
+
+        for n in 1..=max_window.min(tokens.len() - i) {
+            let token = &tokens[i + n - 1];
+            if token.core.is_empty() {
+                break;
+            }
+            if n > 1 && (!tokens[i + n - 2].trailing.is_empty() || !token.leading.is_empty()) {
+                break;
+            }
+            let token_key = normalize_key(token.core);
+            span_key.push_str(&token_key);
+            if n > 1 && keys.iter().any(|k| k.key == token_key) {
+                covers_exact_word = true;
+            }
+            token_keys.push(token_key);
+
+            for candidate in &keys {
+                let score = score_span(&span_key, candidate, thresholds);
+                if n > 1
+                    && score < 1.0
+                    && (covers_exact_word
+                        || !token_keys
+                            .iter()
+                            .all(|key| is_fragment(key, candidate, thresholds)))
+                {
+                    continue;
+                }
+                if score > 0.0 && best.is_none_or(|(_, _, s)| score > s) {
+                    best = Some((n, candidate.canonical, score));
+                }
+            }
+        }
+
+        match best {
+            Some((n, canonical, score)) => {
+                let original = tokens[i..i + n]
+                    .iter()
+                    .map(|t| t.core)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                if original != canonical {
+                    crate::log_info!(
+                        "Dictionary correction: \"{}\" -> \"{}\" (score={:.2})",
+                        original,
+                        canonical,
+                        score
+                    );
+                }
+                output.push(format!(
+                    "{}{}{}",
+                    tokens[i].leading,
+                    canonical,
+                    tokens[i + n - 1].trailing
+                ));
+                i += n;
+            }
+            None => {
+                output.push(raw_tokens[i].to_string());
+                i += 1;
+            }
+        }
+    }
+
+    output.join(" ")
+}
+
+/// Runs dictionary correction with the thresholds from `config`, or returns
+/// the text unchanged when correction is disabled.
+pub fn apply_dictionary_correction(text: &str, config: &crate::config::Config) -> String {
+    if !config.dictionary_correction_enabled {
+        return text.to_string();
+    }
+    correct_transcription(
+        text,
+        &config.dictionary,
+        &config.dictionary_aliases,
+        CorrectionThresholds {
+            edit: f64::from(config.dictionary_edit_threshold),
+            phonetic: f64::from(config.dictionary_phonetic_threshold),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULTS: CorrectionThresholds = CorrectionThresholds {
+        edit: 0.8,
+        phonetic: 0.75,
+    };
+
+    fn dict(terms: &[&str]) -> Vec<String> {
+        terms.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn test_exact_match_fixes_casing() {
+        let result = correct_transcription(
+            "I deployed it on kubernetes today.",
+            &dict(&["Kubernetes"]),
+            &BTreeMap::new(),
+            DEFAULTS,
+        );
+        assert_eq!(result, "I deployed it on Kubernetes today.");
+    }
+
+    #[test]
+    fn test_close_misspelling_is_corrected() {
+        let result = correct_transcription(
+            "Open the kubernetis dashboard",
+            &dict(&["Kubernetes"]),
+            &BTreeMap::new(),
+            DEFAULTS,
+        );
+        assert_eq!(result, "Open the Kubernetes dashboard");
+    }
+
+    #[test]
+    fn test_phonetic_match_is_corrected() {
+        let result = correct_transcription(
+            "I use vokwill for dictation",
+            &dict(&["Voquill"]),
+            &BTreeMap::new(),
+            DEFAULTS,
+        );
+        assert_eq!(result, "I use Voquill for dictation");
+    }
+
+    #[test]
+    fn test_split_words_are_joined() {
+        let result = correct_transcription(
+            "Thanks for trying vo quill.",
+            &dict(&["Voquill"]),
+            &BTreeMap::new(),
+            DEFAULTS,
+        );
+        assert_eq!(result, "Thanks for trying Voquill.");
+    }
+
+    #[test]
+    fn test_alias_replaces_phrase() {
+        let mut aliases = BTreeMap::new();
+        aliases.insert("Kubernetes".to_string(), vec!["cooper netties".to_string()]);
+        let result = correct_transcription(
+            "We run cooper netties, mostly.",
+            &dict(&["Kubernetes"]),
+            &aliases,
+            DEFAULTS,
+        );
+        assert_eq!(result, "We run Kubernetes, mostly.");
+    }
+
+    #[test]
+    fn test_unrelated_words_are_untouched() {
+        let text = "The vocal coach said the cover was fine.";
+        let result = correct_transcription(
+            text,
+            &dict(&["Voquill", "Kubernetes"]),
+            &BTreeMap::new(),
+            DEFAULTS,
+        );
+        assert_eq!(result, text);
+    }
+
+    #[test]
+    fn test_neighbouring_word_is_not_swallowed() {
+        let result = correct_transcription(
+            "the kubernetes is up",
+            &dict(&["Kubernetes"]),
+            &BTreeMap::new(),
+            DEFAULTS,
+        );
+        assert_eq!(result, "the Kubernetes is up");
+    }
+
+    #[test]
+    fn test_word_before_term_is_kept() {
+        for (text, expected) in [
+            ("please check kubernetes now", "please check Kubernetes now"),
+            ("we run some kubernetes here", "we run some Kubernetes here"),
+            ("six kubernetes clusters", "six Kubernetes clusters"),
+            ("please check kubernetis now", "please check Kubernetes now"),
+        ] {
+            let result =
+                correct_transcription(text, &dict(&["Kubernetes"]), &BTreeMap::new(), DEFAULTS);
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn test_split_words_are_joined_by_sound() {
+        let result = correct_transcription(
+            "I use vo kwill daily",
+            &dict(&["Voquill"]),
+            &BTreeMap::new(),
+            DEFAULTS,
+        );
+        assert_eq!(result, "I use Voquill daily");
+    }
+
+    #[test]
+    fn test_short_terms_only_match_aliases() {
+        let mut aliases = BTreeMap::new();
+        aliases.insert("Go".to_string(), vec!["golang".to_string()]);
+        let result =
+            correct_transcription("go write it in golang", &dict(&["Go"]), &aliases, DEFAULTS);
+        assert_eq!(result, "go write it in Go");
+    }
+
+    #[test]
+    fn test_spans_do_not_cross_punctuation() {
+        let result = correct_transcription(
+            "Say vo. Quill",
+            &dict(&["Voquill"]),
+            &BTreeMap::new(),
+            DEFAULTS,
+        );
+        assert_eq!(result, "Say vo. Quill");
+    }
+
+    #[test]
+    fn test_multi_word_term() {
+        let result = correct_transcription(
+            "I asked visual studio code to format it",
+            &dict(&["Visual Studio Code"]),
+            &BTreeMap::new(),
+            DEFAULTS,
+        );
+        assert_eq!(result, "I asked Visual Studio Code to format it");
+    }
+
+    #[test]
+    fn test_empty_dictionary_returns_input() {
+        let text = "nothing to see here";
+        assert_eq!(
+            correct_transcription(text, &[], &BTreeMap::new(), DEFAULTS),
+            text
+        );
+    }
+
+    #[test]
+    fn test_phonetic_code_groups_c_and_k() {
+        assert_eq!(phonetic_code("cooper"), phonetic_code("kooper"));
+        assert_eq!(phonetic_code("voquill"), phonetic_code("vokwill"));
+    }
+}
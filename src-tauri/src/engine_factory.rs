@@ -71,6 +71,121 @@ fn resolve_thread_count(config: &Config, key: &str) -> usize {
     max_cpus
 }
 
+fn number_setting(
+    key: &str,
+    label: &str,
+    description: &str,
+    default: f64,
+    (min, max, step): (f64, f64, f64),
+) -> EngineSetting {
+    EngineSetting {
+        key: key.to_string(),
+        label: label.to_string(),
+        description: description.to_string(),
+        setting_type: "number".to_string(),
+        default: serde_json::json!(default),
+        min: Some(min),
+        max: Some(max),
+        step: Some(step),
+        ..Default::default()
+    }
+}
+
+fn text_setting(key: &str, label: &str, description: &str, default: &str) -> EngineSetting {
+    EngineSetting {
+        key: key.to_string(),
+        label: label.to_string(),
+        description: description.to_string(),
+        setting_type: "text".to_string(),
+        default: serde_json::json!(default),
+        ..Default::default()
+    }
+}
+
+/// The `llama.*` settings read by `post_process::provider_local::LlamaOptions`.
+/// Sampling defaults mirror llama-server's own, except temperature, which
+/// defaults to greedy decoding for faithful cleanup.
+fn llama_server_settings() -> Vec<EngineSetting> {
+    vec![
+        EngineSetting {
+            key: "llama.server".to_string(),
+            label: "llama-server".to_string(),
+            description: "Which llama-server runs the model: the build Voquill downloads, one installed on your PATH, or a server you already run.".to_string(),
+            setting_type: "select".to_string(),
+            default: serde_json::json!("bundled"),
+            options: Some(vec![
+                SettingOption {
+                    value: "bundled".to_string(),
+                    label: "Downloaded by Voquill".to_string(),
+                },
+                SettingOption {
+                    value: "system".to_string(),
+                    label: "Installed on PATH".to_string(),
+                },
+                SettingOption {
+                    value: "url".to_string(),
+                    label: "Running at URL".to_string(),
+                },
+            ]),
+            ..Default::default()
+        },
+        text_setting(
+            "llama.server_url",
+            "Server URL",
+            "Address of your own llama-server when using \"Running at URL\". It keeps its own model, template and context size.",
+            crate::post_process::provider_local::DEFAULT_SERVER_URL,
+        ),
+        text_setting(
+            "llama.chat_template",
+            "Chat Template",
+            "A llama.cpp template name (e.g. chatml, llama3) or the path to a .jinja file. Leave empty to use the template built into the model.",
+            "",
+        ),
+        number_setting(
+            "llama.context_size",
+            "Context Size",
+            "Tokens of context. It must hold the transcript and the cleaned result.",
+            8192.0,
+            (512.0, 131_072.0, 512.0),
+        ),
+        number_setting(
+            "llama.temperature",
+            "Temperature",
+            "0 always picks the most likely word, which suits cleanup. Raise it for rewriting presets.",
+            0.0,
+            (0.0, 2.0, 0.05),
+        ),
+        number_setting(
+            "llama.top_p",
+            "Top P",
+            "Samples only from the most likely words whose probabilities add up to this.",
+            0.95,
+            (0.0, 1.0, 0.05),
+        ),
+        number_setting(
+            "llama.top_k",
+            "Top K",
+            "Samples only from this many most likely words. 0 disables the limit.",
+            40.0,
+            (0.0, 200.0, 1.0),
+        ),
+        number_setting(
+            "llama.min_p",
+            "Min P",
+            "Drops words less likely than this fraction of the most likely one.",
+            0.05,
+            (0.0, 1.0, 0.01),
+        ),
+        number_setting(
+            "llama.repeat_penalty",
+            "Repeat Penalty",
+            "Penalises repeated words. 1 disables it.",
+            1.0,
+            (0.0, 2.0, 0.05),
+        ),
+    ]
+}
+
 /// Returns true if the engine name indicates GPU acceleration should be used.
 /// Single owner of the "(GPU)" naming convention across all engines
 /// (transcription and post-processing).
@@ -86,7 +201,7 @@ pub struct EngineCapabilities {
     pub settings: Vec<EngineSetting>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Default)]
 pub struct EngineSetting {
     pub key: String,
     pub label: String,
@@ -95,6 +210,13 @@ pub struct EngineSetting {
     pub setting_type: String,
     pub default: serde_json::Value,
     pub options: Option<Vec<SettingOption>>,
+    /// Bounds and step for "number" settings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step: Option<f64>,
 }
 
 #[derive(Serialize)]
@@ -147,6 +269,7 @@ impl EngineFactory {
                     setting_type: "select".to_string(),
                     default: serde_json::json!("auto"),
                     options: Some(build_thread_options(max_cpus)),
+                    ..Default::default()
                 }],
             },
             "Whisper.cpp (GPU)" => EngineCapabilities {
@@ -162,8 +285,13 @@ impl EngineFactory {
                     setting_type: "select".to_string(),
                     default: serde_json::json!("auto"),
                     options: Some(build_thread_options(max_cpus)),
+                    ..Default::default()
                 }],
             },
+            "Post-Process (Local)" | "Post-Process (GPU)" => EngineCapabilities {
+                gpu_supported: engine_uses_gpu(engine_name),
+                settings: llama_server_settings(),
+            },
             _ => EngineCapabilities {
                 gpu_supported: false,
                 settings: vec![],
@@ -377,6 +505,17 @@ mod tests {
             .any(|s| s.key == "parakeet.num_threads"));
     }
 
+    #[test]
+    fn engine_capabilities_post_process() {
+        let caps = EngineFactory::engine_capabilities("Post-Process (GPU)");
+        assert!(caps.gpu_supported);
+        assert!(caps
+            .settings
+            .iter()
+            .any(|s| s.key == "llama.context_size" && s.min.is_some() && s.max.is_some()));
+        assert!(!EngineFactory::engine_capabilities("Post-Process (Local)").gpu_supported);
+    }
+
     #[test]
     fn engine_capabilities_unknown_engine() {
         let caps = EngineFactory::engine_capabilities("UnknownEngine");
@@ -391,7 +530,7 @@ mod tests {
             assert!(!setting.key.is_empty());
             assert!(!setting.label.is_empty());
             assert!(!setting.description.is_empty());
-            assert!(["number", "bool", "select"].contains(&setting.setting_type.as_str()));
+            assert!(["number", "bool", "select", "text"].contains(&setting.setting_type.as_str()));
             if setting.setting_type == "select" {
                 assert!(setting.options.is_some());
                 let options = setting.options.as_ref().unwrap();
@@ -6,6 +6,71 @@ use std::sync::{Mutex, OnceLock};
 
 use crate::diarization::Segment;
 
+/// Per-session details that do not warrant a column of their own, stored as
+/// JSON in `metadata`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HistoryMetadata {
+    /// Why the post-processing guardrail rejected the model output and what
+    /// the pipeline did instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guardrail: Option<String>,
+    /// The spoken instruction of a command mode session; `raw_text` holds
+    /// the selection it was applied to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instruction: Option<String>,
+    /// How many chunks a long transcript was post-processed in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunks: Option<usize>,
+    /// The parsed output of a structured-output preset, before it was
+    /// rendered to text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured: Option<serde_json::Value>,
+    /// Every post-processing attempt and what was output in the end, so a
+    /// raw transcript caused by a failed model is told apart from a clean one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_process: Option<PostProcessReport>,
+    /// The gain the capture AGC applied, when it was enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agc: Option<crate::audio::agc::AgcReport>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostProcessOutcome {
+    /// The configured backend produced the output.
+    Cleaned,
+    /// The fallback backend produced the output.
+    Fallback,
+    /// Every attempt failed and the raw transcript was output.
+    Raw,
+    /// Every attempt failed and nothing was output.
+    Discarded,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostProcessAttempt {
+    pub service: String,
+    /// `None` when the attempt succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostProcessReport {
+    pub outcome: PostProcessOutcome,
+    pub attempts: Vec<PostProcessAttempt>,
+}
+
+impl HistoryMetadata {
+    fn to_json(&self) -> Option<String> {
+        if *self == Self::default() {
+            return None;
+        }
+        serde_json::to_string(self).ok()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoryItem {
     pub id: u64,
@@ -31,6 +96,8 @@ pub struct HistoryItem {
     pub language: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prompt_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HistoryMetadata>,
 }
 
 pub struct NewHistoryItem<'a> {
@@ -46,6 +113,7 @@ pub struct NewHistoryItem<'a> {
     pub source: Option<&'a str>,
     pub language: Option<&'a str>,
     pub prompt_name: Option<&'a str>,
+    pub metadata: Option<&'a HistoryMetadata>,
     pub limit: Option<usize>,
 }
 
@@ -86,7 +154,8 @@ fn open_db() -> Result<Connection, Box<dyn std::error::Error>> {
             engine        TEXT,
             source        TEXT DEFAULT 'mic',
             language      TEXT,
-            prompt_name   TEXT
+            prompt_name   TEXT,
+            metadata      TEXT
         );
         CREATE VIRTUAL TABLE IF NOT EXISTS history_fts
             USING fts5(text, content='history', content_rowid='id');",
@@ -115,6 +184,8 @@ fn open_db() -> Result<Connection, Box<dyn std::error::Error>> {
         .ok();
     conn.execute_batch("ALTER TABLE history ADD COLUMN prompt_name TEXT")
         .ok();
+    conn.execute_batch("ALTER TABLE history ADD COLUMN metadata TEXT")
+        .ok();
 
     // Backfill session_uuid for legacy records without one
     if let Ok(mut stmt) =
@@ -223,9 +294,10 @@ fn sanitize_fts_query(raw: &str) -> String {
 pub fn add_history_item(item: &NewHistoryItem) -> Result<HistoryItem, Box<dyn std::error::Error>> {
     let conn = global_db().lock().unwrap();
     let timestamp = Utc::now().to_rfc3339();
+    let metadata_json = item.metadata.and_then(HistoryMetadata::to_json);
     conn.execute(
-        "INSERT INTO history (session_uuid, timestamp, status, text, raw_text, error_message, segments, audio_file, duration_secs, engine, source, language, prompt_name)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        "INSERT INTO history (session_uuid, timestamp, status, text, raw_text, error_message, segments, audio_file, duration_secs, engine, source, language, prompt_name, metadata)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
         params![
             item.session_uuid,
             timestamp,
@@ -240,6 +312,7 @@ pub fn add_history_item(item: &NewHistoryItem) -> Result<HistoryItem, Box<dyn st
             item.source.unwrap_or("mic"),
             item.language,
             item.prompt_name,
+            metadata_json,
         ],
     )?;
     let id = conn.last_insert_rowid() as u64;
@@ -284,13 +357,17 @@ pub fn add_history_item(item: &NewHistoryItem) -> Result<HistoryItem, Box<dyn st
         source: item.source.map(|s| s.to_string()),
         language: item.language.map(|s| s.to_string()),
         prompt_name: item.prompt_name.map(|s| s.to_string()),
+        metadata: item
+            .metadata
+            .filter(|m| **m != HistoryMetadata::default())
+            .cloned(),
     })
 }
 
 pub fn load_history(limit: usize) -> Result<Vec<HistoryItem>, Box<dyn std::error::Error>> {
     let conn = global_db().lock().unwrap();
     let mut stmt = conn.prepare(
-        "SELECT id, session_uuid, timestamp, status, text, raw_text, error_message, segments, audio_file, duration_secs, engine, source, language, prompt_name
+        "SELECT id, session_uuid, timestamp, status, text, raw_text, error_message, segments, audio_file, duration_secs, engine, source, language, prompt_name, metadata
          FROM history ORDER BY id DESC LIMIT ?",
     )?;
     let items = stmt
@@ -314,6 +391,9 @@ pub fn load_history(limit: usize) -> Result<Vec<HistoryItem>, Box<dyn std::error
                 source: row.get(11)?,
                 language: row.get(12)?,
                 prompt_name: row.get(13)?,
+                metadata: row
+                    .get::<_, Option<String>>(14)?
+                    .and_then(|s| serde_json::from_str(&s).ok()),
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
@@ -331,7 +411,7 @@ pub fn search_history(
     let conn = global_db().lock().unwrap();
     let like_query = format!("%{}%", query.trim());
     let mut stmt = conn.prepare(
-        "SELECT h.id, h.session_uuid, h.timestamp, h.status, h.text, h.raw_text, h.error_message, h.segments, h.audio_file, h.duration_secs, h.engine, h.source, h.language, h.prompt_name
+        "SELECT h.id, h.session_uuid, h.timestamp, h.status, h.text, h.raw_text, h.error_message, h.segments, h.audio_file, h.duration_secs, h.engine, h.source, h.language, h.prompt_name, h.metadata
          FROM history h
          WHERE h.id IN (SELECT rowid FROM history_fts WHERE history_fts MATCH ?1)
             OR (h.error_message IS NOT NULL AND h.error_message LIKE ?2)
@@ -359,6 +439,9 @@ pub fn search_history(
                 source: row.get(11)?,
                 language: row.get(12)?,
                 prompt_name: row.get(13)?,
+                metadata: row
+                    .get::<_, Option<String>>(14)?
+                    .and_then(|s| serde_json::from_str(&s).ok()),
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
@@ -387,6 +470,24 @@ pub fn clear_audio_file_references() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Points items that reference `old_file` (by name or by full path) at
+/// `new_file_name`, e.g. after a recording was converted to another format.
+pub fn rename_audio_file(
+    old_file: &std::path::Path,
+    new_file_name: &str,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let old_name = old_file
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or("Invalid audio file name")?;
+    let conn = global_db().lock().unwrap();
+    let updated = conn.execute(
+        "UPDATE history SET audio_file = ?1 WHERE audio_file = ?2 OR audio_file = ?3",
+        params![new_file_name, old_name, old_file.to_string_lossy()],
+    )?;
+    Ok(updated)
+}
+
 pub fn clear_history() -> Result<(), Box<dyn std::error::Error>> {
     let conn = global_db().lock().unwrap();
     if let Ok(mut stmt) =
@@ -430,7 +531,8 @@ mod tests {
                 engine        TEXT,
                 source        TEXT DEFAULT 'mic',
                 language      TEXT,
-                prompt_name   TEXT
+                prompt_name   TEXT,
+                metadata      TEXT
             );
             CREATE VIRTUAL TABLE history_fts
                 USING fts5(text, content='history', content_rowid='id');
@@ -606,4 +708,25 @@ mod tests {
             .unwrap();
         assert_eq!(count_after, 0);
     }
+
+    #[test]
+    fn test_metadata_stored_as_json_only_when_set() {
+        assert_eq!(HistoryMetadata::default().to_json(), None);
+
+        let metadata = HistoryMetadata {
+            guardrail: Some("used raw transcript: output was empty".to_string()),
+            ..Default::default()
+        };
+        let conn = setup_test_db();
+        conn.execute(
+            "INSERT INTO history (session_uuid, timestamp, text, metadata) VALUES (?1, ?2, ?3, ?4)",
+            params!["uuid-1", "2026-10-18T00:00:00Z", "Hi", metadata.to_json()],
+        )
+        .unwrap();
+        let stored: Option<String> = conn
+            .query_row("SELECT metadata FROM history", [], |r| r.get(0))
+            .unwrap();
+        let parsed: HistoryMetadata = serde_json::from_str(&stored.unwrap()).unwrap();
+        assert_eq!(parsed, metadata);
+    }
 }
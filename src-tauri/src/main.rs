@@ -48,16 +48,21 @@ mod app;
 mod archive;
 mod audio;
 mod config;
+mod continuation;
 mod diarization;
+mod dictionary;
 mod engine_factory;
 mod history;
 mod hotkey;
 mod local_whisper;
+mod markdown;
 mod model_manager;
 mod parakeet;
 mod paths;
 pub mod platform;
 mod post_process;
+mod presets;
+mod profanity;
 mod python_runner;
 mod sidecar;
 mod text_cleanup;
@@ -154,7 +159,7 @@ fn main() {
         .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(
             tauri_plugin_global_shortcut::Builder::new()
-                .with_handler(|app, _shortcut, event| {
+                .with_handler(|app, shortcut, event| {
                     // Ignore plugin hotkeys on Wayland, use Portal instead
                     if std::env::var("WAYLAND_DISPLAY").is_ok() {
                         return;
@@ -162,11 +167,20 @@ fn main() {
                     let app_handle = app.clone();
                     let pressed =
                         event.state() == tauri_plugin_global_shortcut::ShortcutState::Pressed;
+                    let action = {
+                        let state = app.state::<AppState>();
+                        let config = state.config.lock().unwrap();
+                        app::hotkey_handler::action_for_shortcut(&config, shortcut)
+                    };
                     tauri::async_runtime::spawn(async move {
                         let state = app_handle.state::<AppState>();
                         if pressed {
-                            app::hotkey_handler::handle_hotkey_press(state, app_handle.clone())
-                                .await;
+                            app::hotkey_handler::handle_hotkey_press(
+                                state,
+                                app_handle.clone(),
+                                action,
+                            )
+                            .await;
                         } else {
                             app::hotkey_handler::handle_hotkey_release(state).await;
                         }
@@ -188,7 +202,6 @@ fn main() {
             search_history,
             clear_history,
             delete_history_item,
-            get_history_audio,
             check_hotkey_status,
             manual_register_hotkey,
             configure_hotkey,
@@ -198,6 +211,7 @@ fn main() {
             quit_application,
             get_audio_devices,
             get_output_devices,
+            get_monitor_devices,
             start_mic_test,
             stop_mic_test,
             stop_mic_playback,
@@ -215,7 +229,18 @@ fn main() {
             download_model,
             preload_transcription_engine,
             transcribe_audio_file,
+            start_system_listen,
+            stop_system_listen,
+            cancel_system_listen,
+            is_system_listen_active,
+            get_recoverable_recordings,
+            transcribe_recovered_recording,
+            discard_recovered_recording,
             test_cleanup_api,
+            preview_post_process_prompt,
+            export_post_process_presets,
+            import_post_process_presets,
+            update_post_process_presets,
             get_linux_setup_status,
             request_audio_permission,
             request_input_permission,
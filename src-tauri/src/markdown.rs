@@ -0,0 +1,338 @@
+//! Deterministic Markdown formatting for note-taking dictation. Spoken
+//! structure commands ("heading", "bullet point", "numbered item", "new
+//! paragraph") become Markdown blocks without involving an LLM, so the result
+//! is instant and repeatable.
+
+/// Block kinds produced by structure commands. `Text` is prose that was not
+/// introduced by any command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockKind {
+    Text,
+    Paragraph,
+    LineBreak,
+    Heading(u8),
+    Bullet,
+    Numbered,
+}
+
+/// Formatting state carried from one dictation to the next so numbered lists
+/// keep counting and new blocks are separated from what was typed before.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarkdownSession {
+    pub next_number: u32,
+    pub last_block: Option<BlockKind>,
+}
+
+impl Default for MarkdownSession {
+    fn default() -> Self {
+        Self {
+            next_number: 1,
+            last_block: None,
+        }
+    }
+}
+
+enum Command {
+    Block(BlockKind),
+    RestartNumbering,
+}
+
+/// Where a command phrase is recognised. Phrases that are also ordinary
+/// prose ("we are heading home", "draw a new line") only count at the start of
+/// a sentence.
+#[derive(PartialEq, Eq)]
+enum Placement {
+    Anywhere,
+    SentenceStart,
+}
+
+const COMMANDS: &[(&[&str], Command, Placement)] = &[
+    (
+        &["new", "paragraph"],
+        Command::Block(BlockKind::Paragraph),
+        Placement::Anywhere,
+    ),
+    (
+        &["new", "line"],
+        Command::Block(BlockKind::LineBreak),
+        Placement::SentenceStart,
+    ),
+    (
+        &["heading", "one"],
+        Command::Block(BlockKind::Heading(1)),
+        Placement::Anywhere,
+    ),
+    (
+        &["heading", "two"],
+        Command::Block(BlockKind::Heading(2)),
+        Placement::Anywhere,
+    ),
+    (
+        &["heading", "three"],
+        Command::Block(BlockKind::Heading(3)),
+        Placement::Anywhere,
+    ),
+    (
+        &["heading"],
+        Command::Block(BlockKind::Heading(1)),
+        Placement::SentenceStart,
+    ),
+    (
+        &["subheading"],
+        Command::Block(BlockKind::Heading(2)),
+        Placement::Anywhere,
+    ),
+    (
+        &["bullet", "point"],
+        Command::Block(BlockKind::Bullet),
+        Placement::Anywhere,
+    ),
+    (
+        &["numbered", "item"],
+        Command::Block(BlockKind::Numbered),
+        Placement::Anywhere,
+    ),
+    (
+        &["restart", "numbering"],
+        Command::RestartNumbering,
+        Placement::Anywhere,
+    ),
+];
+
+fn word_key(raw: &str) -> String {
+    raw.trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+fn ends_clause(raw: &str) -> bool {
+    let core_end = raw.trim_end_matches(|c: char| !c.is_alphanumeric()).len();
+    raw[core_end..]
+        .chars()
+        .any(|c| matches!(c, '.' | ',' | '?' | '!' | ';' | ':'))
+}
+
+fn ends_sentence(raw: &str) -> bool {
+    let core_end = raw.trim_end_matches(|c: char| !c.is_alphanumeric()).len();
+    raw[core_end..]
+        .chars()
+        .any(|c| matches!(c, '.' | '?' | '!'))
+}
+
+fn match_command(words: &[&str], start: usize) -> Option<(usize, &'static Command)> {
+    let sentence_start = start == 0 || ends_sentence(words[start - 1]);
+    COMMANDS
+        .iter()
+        .filter(|(phrase, _, placement)| {
+            (sentence_start || *placement == Placement::Anywhere)
+                && words.len() >= start + phrase.len()
+                && phrase.iter().enumerate().all(|(offset, expected)| {
+                    let raw = words[start + offset];
+                    // Punctuation inside a multi-word phrase means the words
+                    // belong to different sentences and are not a command.
+                    word_key(raw) == *expected && (offset + 1 == phrase.len() || !ends_clause(raw))
+                })
+        })
+        .max_by_key(|(phrase, _, _)| phrase.len())
+        .map(|(phrase, command, _)| (phrase.len(), command))
+}
+
+/// Cleans the text that follows a command: drops the punctuation the
+/// transcriber attached to the command word (", milk") and capitalizes the
+/// first letter.
+fn tidy_block_content(words: &[&str], strip_terminal_period: bool) -> String {
+    let joined = words.join(" ");
+    let trimmed = joined.trim_start_matches(|c: char| !c.is_alphanumeric());
+    let trimmed = if strip_terminal_period {
+        trimmed.trim_end_matches('.')
+    } else {
+        trimmed
+    };
+    let mut chars = trimmed.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn block_separator(previous: Option<BlockKind>, next: BlockKind) -> &'static str {
+    match (previous, next) {
+        (None, _) => "",
+        (_, BlockKind::LineBreak) => "\n",
+        (Some(BlockKind::Bullet), BlockKind::Bullet) => "\n",
+        (Some(BlockKind::Numbered), BlockKind::Numbered) => "\n",
+        (_, BlockKind::Text) => " ",
+        _ => "\n\n",
+    }
+}
+
+/// Applies Markdown structure commands to a transcript and returns the
+/// formatted text together with the session state to carry into the next
+/// dictation. Numbered items keep counting across dictations until a heading
+/// or "restart numbering" resets the list.
+pub fn format_markdown(text: &str, session: &MarkdownSession) -> (String, MarkdownSession) {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut next = *session;
+    // `None` marks a "restart numbering" command, which has no content.
+    let mut blocks: Vec<(Option<BlockKind>, Vec<&str>)> = Vec::new();
+    let mut i = 0;
+
+    while i < words.len() {
+        match match_command(&words, i) {
+            Some((consumed, Command::Block(kind))) => {
+                blocks.push((Some(*kind), Vec::new()));
+                i += consumed;
+            }
+            Some((consumed, Command::RestartNumbering)) => {
+                blocks.push((None, Vec::new()));
+                i += consumed;
+            }
+            None => {
+                match blocks.last_mut() {
+                    Some((Some(_), content)) => content.push(words[i]),
+                    _ => blocks.push((Some(BlockKind::Text), vec![words[i]])),
+                }
+                i += 1;
+            }
+        }
+    }
+
+    let mut output = String::new();
+    for (kind, content) in blocks {
+        let Some(kind) = kind else {
+            next.next_number = 1;
+            // A blank line keeps the restarted list from merging into the
+            // previous one.
+            if next.last_block == Some(BlockKind::Numbered) {
+                next.last_block = Some(BlockKind::Paragraph);
+            }
+            continue;
+        };
+        output.push_str(block_separator(next.last_block, kind));
+        match kind {
+            BlockKind::Heading(level) => {
+                next.next_number = 1;
+                output.push_str(&"#".repeat(level as usize));
+                output.push(' ');
+                output.push_str(&tidy_block_content(&content, true));
+            }
+            BlockKind::Bullet => {
+                output.push_str("- ");
+                output.push_str(&tidy_block_content(&content, false));
+            }
+            BlockKind::Numbered => {
+                output.push_str(&format!("{}. ", next.next_number));
+                next.next_number += 1;
+                output.push_str(&tidy_block_content(&content, false));
+            }
+            BlockKind::Paragraph | BlockKind::LineBreak => {
+                output.push_str(&tidy_block_content(&content, false));
+            }
+            BlockKind::Text => {
+                output.push_str(&content.join(" "));
+            }
+        }
+        next.last_block = Some(kind);
+    }
+
+    (output, next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format_fresh(text: &str) -> String {
+        format_markdown(text, &MarkdownSession::default()).0
+    }
+
+    #[test]
+    fn test_plain_text_is_unchanged() {
+        assert_eq!(
+            format_fresh("Just a normal sentence."),
+            "Just a normal sentence."
+        );
+    }
+
+    #[test]
+    fn test_heading_levels() {
+        assert_eq!(format_fresh("Heading. Project update."), "# Project update");
+        assert_eq!(format_fresh("heading two risks"), "## Risks");
+        assert_eq!(format_fresh("Subheading next steps"), "## Next steps");
+    }
+
+    #[test]
+    fn test_bullet_points_share_a_list() {
+        assert_eq!(
+            format_fresh("Bullet point, buy milk. Bullet point, call Sam."),
+            "- Buy milk.\n- Call Sam."
+        );
+    }
+
+    #[test]
+    fn test_numbered_items_count_up() {
+        assert_eq!(
+            format_fresh("numbered item first numbered item second"),
+            "1. First\n2. Second"
+        );
+    }
+
+    #[test]
+    fn test_numbering_continues_across_dictations() {
+        let (first, session) =
+            format_markdown("Numbered item, one thing.", &MarkdownSession::default());
+        assert_eq!(first, "1. One thing.");
+        let (second, session) = format_markdown("Numbered item, another.", &session);
+        assert_eq!(second, "\n2. Another.");
+        assert_eq!(session.next_number, 3);
+    }
+
+    #[test]
+    fn test_heading_resets_numbering() {
+        let session = MarkdownSession {
+            next_number: 4,
+            last_block: Some(BlockKind::Numbered),
+        };
+        let (text, session) = format_markdown("heading notes numbered item first", &session);
+        assert_eq!(text, "\n\n# Notes\n\n1. First");
+        assert_eq!(session.next_number, 2);
+    }
+
+    #[test]
+    fn test_restart_numbering() {
+        let session = MarkdownSession {
+            next_number: 7,
+            last_block: Some(BlockKind::Numbered),
+        };
+        let (text, _) = format_markdown("restart numbering numbered item fresh", &session);
+        assert_eq!(text, "\n\n1. Fresh");
+    }
+
+    #[test]
+    fn test_new_paragraph_and_new_line() {
+        assert_eq!(
+            format_fresh("First thought. New paragraph. Second thought. New line. Third."),
+            "First thought.\n\nSecond thought.\nThird."
+        );
+    }
+
+    #[test]
+    fn test_command_split_by_punctuation_is_prose() {
+        assert_eq!(
+            format_fresh("Hit the bullet. Point taken."),
+            "Hit the bullet. Point taken."
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_commands_mid_sentence_are_prose() {
+        assert_eq!(format_fresh("We are heading home"), "We are heading home");
+        assert_eq!(
+            format_fresh("Draw a new line here. Then stop."),
+            "Draw a new line here. Then stop."
+        );
+        assert_eq!(
+            format_fresh("We are heading home. Heading two, plans."),
+            "We are heading home.\n\n## Plans"
+        );
+    }
+}
@@ -62,6 +62,15 @@ pub fn debug_recordings_dir() -> Result<PathBuf, String> {
     Ok(dir)
 }
 
+/// Captures being recorded, spooled to disk (see `audio::spool`). Kept under
+/// the app root rather than the temp dir so a session interrupted by a crash
+/// or power loss survives a reboot and can be recovered.
+pub fn spool_dir() -> Result<PathBuf, String> {
+    let dir = app_root()?.join("spool");
+    fs::create_dir_all(&dir).map_err(|error| error.to_string())?;
+    Ok(dir)
+}
+
 /// Temporary directory for runtime artifacts (diarization temp WAV files, etc.).
 /// Cleaned up by the OS across reboots.
 pub fn temp_dir() -> PathBuf {
@@ -12,6 +12,7 @@ const XK_SHIFT_L: i32 = 0xFFE1;
 const XK_CONTROL_L: i32 = 0xFFE3;
 const XK_INSERT: i32 = 0xFF63;
 const XK_V: i32 = 0x76;
+const XK_C: i32 = 0x63;
 
 pub struct WaylandTypeRequest {
     pub text: String,
@@ -27,6 +28,9 @@ pub enum WaylandInputRequest {
         shortcut: crate::config::PasteShortcut,
         response: tokio::sync::oneshot::Sender<Result<(), String>>,
     },
+    SendCopyShortcut {
+        response: tokio::sync::oneshot::Sender<Result<(), String>>,
+    },
 }
 
 pub type WaylandInputSender = tokio::sync::mpsc::UnboundedSender<WaylandInputRequest>;
@@ -260,6 +264,16 @@ pub async fn establish_input_session(
                             }
                             let _ = response.send(res);
                         }
+                        WaylandInputRequest::SendCopyShortcut { response } => {
+                            let res = send_copy_shortcut_over_portal(
+                                &current_remote_desktop,
+                                &current_session,
+                            ).await;
+                            if let Err(e) = &res {
+                                crate::log_warn!("[Event Loop] SendCopyShortcut portal call failed: {}", e);
+                            }
+                            let _ = response.send(res);
+                        }
                     }
                 }
             }
@@ -397,6 +411,41 @@ pub async fn send_paste_shortcut(
     }
 }
 
+pub async fn send_copy_shortcut(app_handle: &tauri::AppHandle) -> Result<(), String> {
+    let sender = {
+        let state = app_handle.state::<AppState>();
+        let sender_lock = state.wayland_input_sender.lock().unwrap();
+        sender_lock.clone()
+    }
+    .ok_or_else(|| {
+        "Wayland input emulation is not active. Complete input setup to enable copy.".to_string()
+    })?;
+
+    let (response_sender, response_receiver) = tokio::sync::oneshot::channel();
+    sender
+        .send(WaylandInputRequest::SendCopyShortcut {
+            response: response_sender,
+        })
+        .map_err(|_| "Wayland input emulation session is unavailable.".to_string())?;
+    response_receiver
+        .await
+        .map_err(|e| format!("send_copy_shortcut: response channel closed: {}", e))?
+}
+
+async fn send_copy_shortcut_over_portal(
+    remote_desktop: &RemoteDesktop<'_>,
+    session: &ashpd::desktop::Session<'_, RemoteDesktop<'_>>,
+) -> Result<(), String> {
+    let hold = Duration::from_millis(50);
+    send_key(remote_desktop, session, XK_CONTROL_L, KeyState::Pressed).await?;
+    tokio::time::sleep(hold).await;
+    send_key(remote_desktop, session, XK_C, KeyState::Pressed).await?;
+    tokio::time::sleep(hold).await;
+    send_key(remote_desktop, session, XK_C, KeyState::Released).await?;
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    send_key(remote_desktop, session, XK_CONTROL_L, KeyState::Released).await
+}
+
 async fn send_key(
     remote_desktop: &RemoteDesktop<'_>,
     session: &ashpd::desktop::Session<'_, RemoteDesktop<'_>>,
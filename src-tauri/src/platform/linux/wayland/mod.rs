@@ -40,6 +40,10 @@ impl InputSimulation for WaylandBackend {
     ) -> Result<(), String> {
         input::send_paste_shortcut(app_handle, shortcut).await
     }
+
+    async fn send_copy_shortcut(&self, app_handle: &tauri::AppHandle) -> Result<(), String> {
+        input::send_copy_shortcut(app_handle).await
+    }
 }
 
 #[async_trait]
@@ -82,4 +86,10 @@ impl WindowManagement for WaylandBackend {
     ) -> Result<(), String> {
         overlay::position_overlay_window(window, pixels_from_bottom)
     }
+
+    /// Wayland deliberately hides which client has focus, and no portal
+    /// exposes it.
+    fn focused_application(&self) -> Option<String> {
+        None
+    }
 }
@@ -1,3 +1,4 @@
+use crate::app::hotkey_handler::HotkeyAction;
 use crate::app::state::AppState;
 use ashpd::desktop::global_shortcuts::{GlobalShortcuts, NewShortcut};
 use tauri::Emitter;
@@ -10,12 +11,20 @@ pub(crate) async fn bind_record_shortcut(
     proxy: &GlobalShortcuts<'_>,
     session: &ashpd::desktop::Session<'_, GlobalShortcuts<'_>>,
     normalized_trigger: &str,
+    secondary_hotkeys: &[(HotkeyAction, String)],
 ) -> Result<String, String> {
-    let shortcut = NewShortcut::new(RECORD_SHORTCUT_ID, "Dictation Hotkey")
-        .preferred_trigger(Some(normalized_trigger));
+    let mut shortcuts = vec![NewShortcut::new(RECORD_SHORTCUT_ID, "Dictation Hotkey")
+        .preferred_trigger(Some(normalized_trigger))];
+    for (action, hotkey) in secondary_hotkeys {
+        let secondary_trigger = normalize_wayland_trigger(hotkey);
+        shortcuts.push(
+            NewShortcut::new(action.portal_id(), action.portal_description())
+                .preferred_trigger(Some(secondary_trigger.as_str())),
+        );
+    }
 
     let bind_result = proxy
-        .bind_shortcuts(session, &[shortcut], None)
+        .bind_shortcuts(session, &shortcuts, None)
         .await
         .map_err(|error| format!("Failed to call portal BindShortcuts: {error}"))?;
 
@@ -85,7 +94,7 @@ pub async fn try_open_linux_portal_shortcut_configuration(
     let normalized_trigger = normalize_wayland_trigger(preferred_hotkey);
     let has_record_shortcut = has_bound_record_shortcut(&proxy, &session).await?;
     if !has_record_shortcut {
-        bind_record_shortcut(&proxy, &session, &normalized_trigger).await?;
+        bind_record_shortcut(&proxy, &session, &normalized_trigger, &[]).await?;
     }
 
     let configure_result = proxy
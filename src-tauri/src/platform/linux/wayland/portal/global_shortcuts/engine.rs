@@ -1,3 +1,4 @@
+use crate::app::hotkey_handler::HotkeyAction;
 use crate::app::state::SessionState;
 use crate::config::HotkeyMode;
 use crate::platform::linux::wayland::portal::capabilities::detect_global_shortcuts_capabilities;
@@ -58,9 +59,13 @@ pub async fn start_linux_portal_hotkey_engine(
         capabilities.supports_configure_shortcuts
     );
 
-    let (shortcuts_token, hotkey_str) = {
+    let (shortcuts_token, hotkey_str, secondary_hotkeys) = {
         let config = state.config.lock().unwrap();
-        (config.shortcuts_token.clone(), config.hotkey.clone())
+        (
+            config.shortcuts_token.clone(),
+            config.hotkey.clone(),
+            crate::app::hotkey_handler::secondary_hotkeys(&config),
+        )
     };
 
     let proxy = GlobalShortcuts::new()
@@ -84,8 +89,13 @@ pub async fn start_linux_portal_hotkey_engine(
     let mut active_trigger = String::new();
 
     if matches!(flow, GlobalShortcutsFlow::BindNew) {
-        active_trigger =
-            bind_record_shortcut(&proxy, &session, normalized_trigger.as_str()).await?;
+        active_trigger = bind_record_shortcut(
+            &proxy,
+            &session,
+            normalized_trigger.as_str(),
+            &secondary_hotkeys,
+        )
+        .await?;
 
         if active_trigger.is_empty() {
             let _ = session.close().await;
@@ -146,8 +156,13 @@ pub async fn start_linux_portal_hotkey_engine(
                 "Restoring shortcut binding in current session using trigger='{}'",
                 preferred_trigger
             );
-            active_trigger =
-                bind_record_shortcut(&proxy, &session, preferred_trigger.as_str()).await?;
+            active_trigger = bind_record_shortcut(
+                &proxy,
+                &session,
+                preferred_trigger.as_str(),
+                &secondary_hotkeys,
+            )
+            .await?;
         }
 
         if active_trigger.is_empty() {
@@ -198,6 +213,11 @@ pub async fn start_linux_portal_hotkey_engine(
             tokio::time::interval(tokio::time::Duration::from_millis(REPEAT_WATCHDOG_TICK_MS));
         repeat_watchdog.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
         let _ = repeat_watchdog.tick().await;
+        // Secondary shortcuts (e.g. code dictation) get a simpler latch than
+        // the record shortcut: an activation counts as a new press only after
+        // the same silence gap that separates portal heartbeats.
+        let mut secondary_last_activation: std::collections::HashMap<String, tokio::time::Instant> =
+            std::collections::HashMap::new();
 
         loop {
             tokio::select! {
@@ -230,6 +250,26 @@ pub async fn start_linux_portal_hotkey_engine(
                                 hotkey_mode
                             );
 
+                            if let Some(action) = HotkeyAction::from_portal_id(&shortcut_id)
+                                .filter(|action| *action != HotkeyAction::Dictate)
+                            {
+                                let is_genuine_press = secondary_last_activation
+                                    .insert(shortcut_id.clone(), activation_now)
+                                    .map(|previous_activation| {
+                                        activation_now.duration_since(previous_activation) >= repeat_silence_timeout
+                                    })
+                                    .unwrap_or(true);
+                                if is_genuine_press {
+                                    crate::log_info!(
+                                        "Portal: {:?} Hotkey Pressed (session_state={:?}) -> handle_hotkey_press",
+                                        action,
+                                        session_state
+                                    );
+                                    crate::app::hotkey_handler::handle_hotkey_press(state, app_handle_for_task.clone(), action).await;
+                                }
+                                continue;
+                            }
+
                             if shortcut_id != RECORD_SHORTCUT_ID {
                                 crate::log_info!(
                                     "Portal Activated ignored: id='{}', shortcut_pressed={} ",
@@ -291,7 +331,7 @@ pub async fn start_linux_portal_hotkey_engine(
                                                 "Portal: Hotkey Pressed (session_state={:?}) -> handle_hotkey_press",
                                                 session_state
                                             );
-                                            crate::app::hotkey_handler::handle_hotkey_press(state, app_handle_for_task.clone()).await;
+                                            crate::app::hotkey_handler::handle_hotkey_press(state, app_handle_for_task.clone(), HotkeyAction::Dictate).await;
                                         } else {
                                             last_activation_at = Some(activation_now);
                                             crate::log_info!(
@@ -301,12 +341,12 @@ pub async fn start_linux_portal_hotkey_engine(
                                         }
                                     }
                                 },
-                                HotkeyMode::Toggle => {
+                                HotkeyMode::Toggle | HotkeyMode::VoiceActivated => {
                                     if !shortcut_pressed {
                                         shortcut_pressed = true;
                                         last_activation_at = Some(activation_now);
                                         crate::log_info!("Portal: Hotkey Pressed (toggle mode) -> handle_hotkey_press");
-                                        crate::app::hotkey_handler::handle_hotkey_press(state, app_handle_for_task.clone()).await;
+                                        crate::app::hotkey_handler::handle_hotkey_press(state, app_handle_for_task.clone(), HotkeyAction::Dictate).await;
                                     } else {
                                         last_activation_at = Some(activation_now);
                                         crate::log_info!("Portal Activated in toggle mode while pressed: heartbeat; ignoring");
@@ -348,6 +388,8 @@ pub async fn start_linux_portal_hotkey_engine(
                                 repeated_activation_count = 0;
                                 last_activation_at = None;
 
+                                crate::app::hotkey_handler::handle_hotkey_release(state).await;
+                            } else if secondary_last_activation.remove(&shortcut_id).is_some() {
                                 crate::app::hotkey_handler::handle_hotkey_release(state).await;
                             } else {
                                 crate::log_info!(
@@ -374,7 +416,7 @@ pub async fn start_linux_portal_hotkey_engine(
                     let state = app_handle_for_task.state::<AppState>();
                     let hotkey_mode = state.config.lock().unwrap().hotkey_mode.clone();
 
-                    if hotkey_mode == HotkeyMode::Toggle {
+                    if hotkey_mode.stops_on_press() {
                         if shortcut_pressed {
                             let silence_expired = last_activation_at
                                 .map(|last_activation| {
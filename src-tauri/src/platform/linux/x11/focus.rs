@@ -0,0 +1,59 @@
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{AtomEnum, ConnectionExt};
+
+/// Application owning the focused X11 window, from its WM_CLASS class name
+/// (e.g. "firefox"), falling back to the window title.
+pub fn focused_application() -> Option<String> {
+    let (connection, screen_num) = x11rb::connect(None).ok()?;
+    let root = connection.setup().roots.get(screen_num)?.root;
+    let active_atom = connection
+        .intern_atom(false, b"_NET_ACTIVE_WINDOW")
+        .ok()?
+        .reply()
+        .ok()?
+        .atom;
+    let window = connection
+        .get_property(false, root, active_atom, AtomEnum::WINDOW, 0, 1)
+        .ok()?
+        .reply()
+        .ok()?
+        .value32()?
+        .next()
+        .filter(|window| *window != 0)?;
+
+    // WM_CLASS holds "instance\0class\0"; the class is the readable app name
+    let class = connection
+        .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 256)
+        .ok()
+        .and_then(|cookie| cookie.reply().ok())
+        .and_then(|reply| {
+            reply
+                .value
+                .split(|byte| *byte == 0)
+                .rfind(|part| !part.is_empty())
+                .map(|part| String::from_utf8_lossy(part).to_string())
+        });
+    if class.is_some() {
+        return class;
+    }
+
+    let name_atom = connection
+        .intern_atom(false, b"_NET_WM_NAME")
+        .ok()?
+        .reply()
+        .ok()?
+        .atom;
+    let utf8_atom = connection
+        .intern_atom(false, b"UTF8_STRING")
+        .ok()?
+        .reply()
+        .ok()?
+        .atom;
+    let title = connection
+        .get_property(false, window, name_atom, utf8_atom, 0, 1024)
+        .ok()?
+        .reply()
+        .ok()?;
+    let title = String::from_utf8_lossy(&title.value).trim().to_string();
+    (!title.is_empty()).then_some(title)
+}
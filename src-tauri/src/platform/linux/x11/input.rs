@@ -238,6 +238,33 @@ pub fn send_paste_shortcut(
     result
 }
 
+pub fn send_copy_shortcut() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let (connection, _screen_num) = RustConnection::connect(None)?;
+    let keyboard_map = load_keyboard_map(&connection)?;
+    let c_key = resolve_keysym_keycode(&keyboard_map, 'c' as u32)
+        .ok_or_else(|| "Failed to resolve keycode for 'c'".to_string())?;
+    crate::log_info!("[X11] send_copy_shortcut: sending Ctrl+C via XTest");
+
+    // Safety clear: release modifiers first to clear any latent modifier state
+    send_key_event(&connection, keyboard_map.ctrl_keycode, false)?;
+    send_key_event(&connection, keyboard_map.shift_keycode, false)?;
+    connection.flush()?;
+    thread::sleep(Duration::from_millis(10));
+
+    send_key_event(&connection, keyboard_map.ctrl_keycode, true)?;
+    connection.flush()?;
+    thread::sleep(Duration::from_millis(50));
+
+    send_key_event(&connection, c_key.keycode, true)?;
+    send_key_event(&connection, c_key.keycode, false)?;
+    connection.flush()?;
+    thread::sleep(Duration::from_millis(50));
+
+    send_key_event(&connection, keyboard_map.ctrl_keycode, false)?;
+    connection.flush()?;
+    Ok(())
+}
+
 pub fn type_text_hardware(
     text: &str,
     typing_speed_interval: f64,
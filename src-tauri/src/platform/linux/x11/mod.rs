@@ -1,3 +1,4 @@
+pub mod focus;
 pub mod input;
 pub mod overlay;
 pub mod permissions;
@@ -39,6 +40,10 @@ impl InputSimulation for X11Backend {
     ) -> Result<(), String> {
         input::send_paste_shortcut(shortcut).map_err(|error| error.to_string())
     }
+
+    async fn send_copy_shortcut(&self, _app_handle: &tauri::AppHandle) -> Result<(), String> {
+        input::send_copy_shortcut().map_err(|error| error.to_string())
+    }
 }
 
 #[async_trait]
@@ -91,4 +96,8 @@ impl WindowManagement for X11Backend {
     ) -> Result<(), String> {
         overlay::position_overlay_window(window, pixels_from_bottom)
     }
+
+    fn focused_application(&self) -> Option<String> {
+        focus::focused_application()
+    }
 }
@@ -2,10 +2,13 @@ use tauri::Manager;
 use tauri_plugin_global_shortcut::GlobalShortcutExt;
 
 pub async fn start_x11_hotkey_engine(app_handle: tauri::AppHandle) -> Result<(), String> {
-    let hotkey_str = {
+    let (hotkey_str, secondary_hotkeys) = {
         let state = app_handle.state::<crate::AppState>();
         let config = state.config.lock().unwrap();
-        config.hotkey.clone()
+        (
+            config.hotkey.clone(),
+            crate::app::hotkey_handler::secondary_hotkeys(&config),
+        )
     };
 
     if hotkey_str.is_empty() {
@@ -28,5 +31,27 @@ pub async fn start_x11_hotkey_engine(app_handle: tauri::AppHandle) -> Result<(),
 
     crate::log_info!("X11 global hotkey registered: {}", hotkey_str);
 
+    // Secondary hotkeys are best-effort: a conflict must not take down the
+    // primary dictation hotkey that was just registered.
+    for (action, secondary) in secondary_hotkeys {
+        let registered = crate::hotkey::parse_hotkey_string(&secondary)
+            .map_err(|error| error.to_string())
+            .and_then(|shortcut| {
+                app_handle
+                    .global_shortcut()
+                    .register(shortcut)
+                    .map_err(|error| error.to_string())
+            });
+        match registered {
+            Ok(()) => crate::log_info!("X11 {:?} hotkey registered: {}", action, secondary),
+            Err(error) => crate::log_warn!(
+                "Failed to register X11 {:?} hotkey '{}': {}",
+                action,
+                secondary,
+                error
+            ),
+        }
+    }
+
     Ok(())
 }
@@ -18,6 +18,9 @@ pub trait InputSimulation: Send + Sync {
         app_handle: &AppHandle,
         shortcut: PasteShortcut,
     ) -> Result<(), String>;
+
+    /// Sends Ctrl+C so the focused application copies its selection.
+    async fn send_copy_shortcut(&self, app_handle: &AppHandle) -> Result<(), String>;
 }
 
 #[async_trait]
@@ -39,6 +42,9 @@ pub trait WindowManagement: Send + Sync {
         window: &WebviewWindow,
         pixels_from_bottom: i32,
     ) -> Result<(), String>;
+    /// Name of the application that currently has keyboard focus, when the
+    /// platform exposes it.
+    fn focused_application(&self) -> Option<String>;
 }
 
 pub trait DisplayBackend:
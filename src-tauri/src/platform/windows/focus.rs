@@ -0,0 +1,50 @@
+use windows::core::PWSTR;
+use windows::Win32::Foundation::CloseHandle;
+use windows::Win32::System::Threading::{
+    OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetForegroundWindow, GetWindowTextW, GetWindowThreadProcessId,
+};
+
+/// Application owning the foreground window, from its executable name
+/// (e.g. "OUTLOOK"), falling back to the window title.
+pub fn focused_application() -> Option<String> {
+    // SAFETY: plain Win32 queries on the foreground window; the process
+    // handle is closed before returning and buffers outlive every call.
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_invalid() {
+            return None;
+        }
+
+        let mut process_id = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut process_id as *mut u32));
+        if process_id != 0 {
+            if let Ok(process) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id) {
+                let mut buffer = [0u16; 1024];
+                let mut length = buffer.len() as u32;
+                let queried = QueryFullProcessImageNameW(
+                    process,
+                    PROCESS_NAME_WIN32,
+                    PWSTR(buffer.as_mut_ptr()),
+                    &mut length,
+                )
+                .is_ok();
+                let _ = CloseHandle(process);
+                if queried {
+                    let path = String::from_utf16_lossy(&buffer[..length as usize]);
+                    if let Some(stem) = std::path::Path::new(&path).file_stem() {
+                        return Some(stem.to_string_lossy().to_string());
+                    }
+                }
+            }
+        }
+
+        let mut title = [0u16; 512];
+        let length = GetWindowTextW(hwnd, &mut title);
+        let title = String::from_utf16_lossy(&title[..length.max(0) as usize]);
+        let title = title.trim();
+        (!title.is_empty()).then(|| title.to_string())
+    }
+}
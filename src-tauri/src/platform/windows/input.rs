@@ -144,6 +144,27 @@ pub fn send_paste_shortcut(
     Ok(())
 }
 
+pub fn send_copy_shortcut() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+    crate::log_info!("[Windows] Sending copy shortcut");
+    unsafe {
+        // Safety clear: release modifiers first to clear any latent modifier state
+        emit_vk(VK_CONTROL, false);
+        emit_vk(VK_SHIFT, false);
+        emit_vk(VK_MENU, false);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+
+        emit_vk(VK_CONTROL, true);
+        std::thread::sleep(std::time::Duration::from_millis(15));
+        emit_vk(VIRTUAL_KEY('C' as u16), true);
+        std::thread::sleep(std::time::Duration::from_millis(25));
+        emit_vk(VIRTUAL_KEY('C' as u16), false);
+        std::thread::sleep(std::time::Duration::from_millis(15));
+        emit_vk(VK_CONTROL, false);
+    }
+    Ok(())
+}
+
 fn is_extended_key(vk: VIRTUAL_KEY) -> bool {
     matches!(
         vk,
@@ -2,10 +2,13 @@ use tauri::Manager;
 use tauri_plugin_global_shortcut::GlobalShortcutExt;
 
 pub async fn start_windows_hotkey_engine(app_handle: tauri::AppHandle) -> Result<(), String> {
-    let hotkey_string = {
+    let (hotkey_string, secondary_hotkeys) = {
         let state = app_handle.state::<crate::AppState>();
         let config = state.config.lock().unwrap();
-        config.hotkey.clone()
+        (
+            config.hotkey.clone(),
+            crate::app::hotkey_handler::secondary_hotkeys(&config),
+        )
     };
 
     crate::log_info!("Re-registering hotkey: {}", hotkey_string);
@@ -26,5 +29,27 @@ pub async fn start_windows_hotkey_engine(app_handle: tauri::AppHandle) -> Result
         }
     }
 
+    // Secondary hotkeys are best-effort: a conflict must not take down the
+    // primary dictation hotkey that was just registered.
+    for (action, secondary) in secondary_hotkeys {
+        match crate::hotkey::parse_hotkey_string(&secondary) {
+            Ok(shortcut) => {
+                if let Err(e) = app_handle.global_shortcut().register(shortcut) {
+                    crate::log_warn!(
+                        "Failed to register {:?} hotkey '{}': {}",
+                        action,
+                        secondary,
+                        e
+                    );
+                } else {
+                    crate::log_info!("{:?} hotkey registered: {}", action, secondary);
+                }
+            }
+            Err(e) => {
+                crate::log_warn!("Failed to parse {:?} hotkey '{}': {}", action, secondary, e);
+            }
+        }
+    }
+
     Ok(())
 }
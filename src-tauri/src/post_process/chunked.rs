@@ -0,0 +1,445 @@
+//! Map-reduce post-processing for transcripts too long for one request. The
+//! text is split on segment (line) or sentence boundaries, each chunk is
+//! processed on its own with the end of the previous chunk as read-only
+//! context, and the results are joined in order. Presets with a reduce
+//! prompt (summaries) then combine the partial results into one.
+
+use super::template::RenderedPrompts;
+use super::{PostProcessError, PostProcessService};
+use crate::config::Config;
+use futures_util::{stream, StreamExt};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Rounds of combining partial results before giving up on getting them
+/// into a single request; each round normally shrinks them a lot.
+const MAX_REDUCE_ROUNDS: usize = 3;
+
+const REDUCE_USER_TEMPLATE: &str = "The text inside <parts> tags is a series of results, one for each consecutive part of a long transcript. Combine them into a single result and output only that.\n\n<parts>\n{transcript}\n</parts>";
+
+/// Emitted after every finished request. `stage` is "map" while chunks are
+/// processed and "reduce" while partial results are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ChunkProgress {
+    pub stage: &'static str,
+    pub completed: usize,
+    pub total: usize,
+}
+
+pub struct ChunkedOutput {
+    pub text: String,
+    /// Number of map requests; 1 when the text fit in a single request.
+    pub chunks: usize,
+}
+
+/// Whether `text` is over the configured chunk size. Sizes are measured in
+/// bytes, so non-Latin scripts get proportionally smaller chunks.
+pub fn needs_chunking(text: &str, config: &Config) -> bool {
+    config.post_process_chunking_enabled && text.len() > config.post_process_chunk_chars
+}
+
+/// Lines when the text has them (diarized "[Speaker] ..." segments),
+/// otherwise sentences. Each unit keeps its trailing separator.
+fn units(text: &str) -> Vec<&str> {
+    if text.trim().contains('\n') {
+        return text.split_inclusive('\n').collect();
+    }
+    let mut units = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((_, c)) = chars.next() {
+        if !matches!(c, '.' | '!' | '?') {
+            continue;
+        }
+        let mut end = None;
+        while let Some(&(i, next)) = chars.peek() {
+            if !next.is_whitespace() {
+                break;
+            }
+            end = Some(i + next.len_utf8());
+            chars.next();
+        }
+        if let Some(end) = end {
+            units.push(&text[start..end]);
+            start = end;
+        }
+    }
+    if start < text.len() {
+        units.push(&text[start..]);
+    }
+    units
+}
+
+/// Cuts a unit longer than `max_chars` at word boundaries (or anywhere, for
+/// a single enormous word).
+fn split_oversized(unit: &str, max_chars: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = unit;
+    while rest.len() > max_chars {
+        let mut limit = max_chars;
+        while !rest.is_char_boundary(limit) {
+            limit -= 1;
+        }
+        let cut = rest[..limit]
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(limit);
+        pieces.push(&rest[..cut]);
+        rest = &rest[cut..];
+    }
+    if !rest.is_empty() {
+        pieces.push(rest);
+    }
+    pieces
+}
+
+/// Packs whole units into chunks of at most `max_chars`.
+pub fn split_into_chunks(text: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for unit in units(text)
+        .into_iter()
+        .flat_map(|unit| split_oversized(unit, max_chars))
+    {
+        if !current.is_empty() && current.len() + unit.len() > max_chars {
+            chunks.push(std::mem::take(&mut current));
+        }
+        current.push_str(unit);
+    }
+    chunks.push(current);
+    chunks
+        .into_iter()
+        .map(|chunk| chunk.trim().to_string())
+        .filter(|chunk| !chunk.is_empty())
+        .collect()
+}
+
+/// The last `overlap_chars` of a chunk, starting at a word boundary.
+fn overlap_tail(chunk: &str, overlap_chars: usize) -> &str {
+    if overlap_chars == 0 {
+        return "";
+    }
+    if chunk.len() <= overlap_chars {
+        return chunk;
+    }
+    let mut start = chunk.len() - overlap_chars;
+    while !chunk.is_char_boundary(start) {
+        start += 1;
+    }
+    let tail = &chunk[start..];
+    match tail.find(char::is_whitespace) {
+        Some(space) => tail[space..].trim_start(),
+        None => tail,
+    }
+}
+
+/// Prepends the overlap as context the model must not reproduce, so chunk
+/// outputs can simply be concatenated.
+fn chunk_template(user_prompt_template: &str, context: &str) -> String {
+    if context.is_empty() {
+        return user_prompt_template.to_string();
+    }
+    format!(
+        "For context only, this is how the previous part of the transcript ended. Do not include it in your output:\n<previous>\n{}\n</previous>\n\n{}",
+        context.replace("{transcript}", "transcript"),
+        user_prompt_template
+    )
+}
+
+/// Packs partial results into groups that fit one reduce request. A result
+/// larger than `max_chars` forms a group of its own.
+fn group_parts(parts: &[String], max_chars: usize) -> Vec<String> {
+    let mut groups: Vec<String> = Vec::new();
+    for part in parts {
+        match groups.last_mut() {
+            Some(group) if group.len() + 2 + part.len() <= max_chars => {
+                group.push_str("\n\n");
+                group.push_str(part);
+            }
+            _ => groups.push(part.clone()),
+        }
+    }
+    groups
+}
+
+/// Runs one request per `(text, user_prompt_template)`, at most `parallel`
+/// at a time, and returns the outputs in input order.
+async fn run_all(
+    processor: &Arc<dyn PostProcessService + Send + Sync>,
+    requests: &[(String, String)],
+    system_prompt: &str,
+    max_output_tokens: u32,
+    parallel: usize,
+    stage: &'static str,
+    on_progress: &(impl Fn(ChunkProgress) + Send + Sync),
+) -> Result<Vec<String>, PostProcessError> {
+    let total = requests.len();
+    on_progress(ChunkProgress {
+        stage,
+        completed: 0,
+        total,
+    });
+    let mut results = stream::iter(requests.iter().map(|(text, template)| {
+        processor.post_process(text, system_prompt, template, max_output_tokens)
+    }))
+    .buffered(parallel.max(1));
+
+    let mut outputs = Vec::with_capacity(total);
+    while let Some(result) = results.next().await {
+        outputs.push(result?);
+        on_progress(ChunkProgress {
+            stage,
+            completed: outputs.len(),
+            total,
+        });
+    }
+    Ok(outputs)
+}
+
+/// Post-processes `text` in one request when it fits, and map-reduce style
+/// when it does not. A failed chunk fails the whole run, so callers fall
+/// back to the unprocessed text exactly as for a single request.
+pub async fn post_process(
+    processor: &Arc<dyn PostProcessService + Send + Sync>,
+    text: &str,
+    prompts: &RenderedPrompts,
+    config: &Config,
+    on_progress: impl Fn(ChunkProgress) + Send + Sync,
+) -> Result<ChunkedOutput, PostProcessError> {
+    let max_output_tokens = config.resolve_max_output_tokens();
+    if !needs_chunking(text, config) {
+        let text = processor
+            .post_process(
+                text,
+                &prompts.system_prompt,
+                &prompts.user_prompt_template,
+                max_output_tokens,
+            )
+            .await?;
+        return Ok(ChunkedOutput { text, chunks: 1 });
+    }
+
+    let chunk_chars = config.post_process_chunk_chars;
+    let chunks = split_into_chunks(text, chunk_chars);
+    crate::log_info!(
+        "Post-processing {} chars in {} chunks ({} at a time)",
+        text.len(),
+        chunks.len(),
+        config.post_process_parallel_chunks
+    );
+    let requests: Vec<(String, String)> = chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let context = match i {
+                0 => "",
+                _ => overlap_tail(&chunks[i - 1], config.post_process_chunk_overlap_chars),
+            };
+            (
+                chunk.clone(),
+                chunk_template(&prompts.user_prompt_template, context),
+            )
+        })
+        .collect();
+    let mut parts = run_all(
+        processor,
+        &requests,
+        &prompts.system_prompt,
+        max_output_tokens,
+        config.post_process_parallel_chunks,
+        "map",
+        &on_progress,
+    )
+    .await?;
+
+    let Some(reduce_prompt) = config.resolve_reduce_prompt() else {
+        let separator = if text.trim().contains('\n') {
+            "\n"
+        } else {
+            " "
+        };
+        return Ok(ChunkedOutput {
+            text: parts.join(separator),
+            chunks: chunks.len(),
+        });
+    };
+
+    for round in 1..=MAX_REDUCE_ROUNDS {
+        let requests: Vec<(String, String)> = group_parts(&parts, chunk_chars)
+            .into_iter()
+            .map(|group| (group, REDUCE_USER_TEMPLATE.to_string()))
+            .collect();
+        crate::log_info!(
+            "Reduce round {}: {} partial results in {} requests",
+            round,
+            parts.len(),
+            requests.len()
+        );
+        parts = run_all(
+            processor,
+            &requests,
+            &reduce_prompt,
+            max_output_tokens,
+            config.post_process_parallel_chunks,
+            "reduce",
+            &on_progress,
+        )
+        .await?;
+        if parts.len() == 1 {
+            break;
+        }
+    }
+    Ok(ChunkedOutput {
+        text: parts.join("\n\n"),
+        chunks: chunks.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    /// Echoes the text back in upper case, tagging reduce requests.
+    struct Echo;
+
+    #[async_trait]
+    impl PostProcessService for Echo {
+        async fn post_process(
+            &self,
+            text: &str,
+            _system_prompt: &str,
+            user_prompt_template: &str,
+            _max_output_tokens: u32,
+        ) -> Result<String, PostProcessError> {
+            if user_prompt_template == REDUCE_USER_TEMPLATE {
+                return Ok(format!("SUMMARY({})", text.lines().count()));
+            }
+            Ok(text.to_uppercase())
+        }
+        fn service_name(&self) -> &'static str {
+            "echo"
+        }
+    }
+
+    fn chunking_config(chunk_chars: usize) -> Config {
+        Config {
+            post_process_chunk_chars: chunk_chars,
+            post_process_chunk_overlap_chars: 20,
+            post_process_parallel_chunks: 3,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_splits_on_sentence_boundaries() {
+        let text = "One two three. Four five six! Seven eight nine? Ten eleven twelve.";
+        let chunks = split_into_chunks(text, 32);
+        assert_eq!(
+            chunks,
+            vec![
+                "One two three. Four five six!",
+                "Seven eight nine?",
+                "Ten eleven twelve."
+            ]
+        );
+        assert_eq!(chunks.join(" "), text);
+    }
+
+    #[test]
+    fn test_splits_diarized_text_on_lines() {
+        let text = "[A] hello there\n[B] general kenobi\n[A] you are a bold one";
+        assert_eq!(
+            split_into_chunks(text, 40),
+            vec![
+                "[A] hello there\n[B] general kenobi".to_string(),
+                "[A] you are a bold one".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cuts_oversized_sentence_at_words() {
+        let chunks = split_into_chunks("alpha beta gamma delta epsilon", 12);
+        assert_eq!(chunks, vec!["alpha beta", "gamma delta", "epsilon"]);
+    }
+
+    #[test]
+    fn test_overlap_starts_at_word_boundary() {
+        assert_eq!(overlap_tail("the quick brown fox", 8), "fox");
+        assert_eq!(overlap_tail("short", 8), "short");
+        assert_eq!(overlap_tail("anything", 0), "");
+    }
+
+    #[tokio::test]
+    async fn test_map_keeps_order_and_reports_progress() {
+        let processor: Arc<dyn PostProcessService + Send + Sync> = Arc::new(Echo);
+        let prompts = RenderedPrompts {
+            system_prompt: "clean".to_string(),
+            user_prompt_template: "{transcript}".to_string(),
+        };
+        let progress = Mutex::new(Vec::new());
+        let text = "first sentence here. second sentence here. third sentence here.";
+        let output = post_process(&processor, text, &prompts, &chunking_config(25), |p| {
+            progress.lock().unwrap().push(p)
+        })
+        .await
+        .unwrap();
+        assert_eq!(output.chunks, 3);
+        assert_eq!(output.text, text.to_uppercase());
+        let progress = progress.into_inner().unwrap();
+        assert_eq!(
+            progress.last(),
+            Some(&ChunkProgress {
+                stage: "map",
+                completed: 3,
+                total: 3
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reduce_combines_partial_results() {
+        let processor: Arc<dyn PostProcessService + Send + Sync> = Arc::new(Echo);
+        let prompts = RenderedPrompts {
+            system_prompt: "summarise".to_string(),
+            user_prompt_template: "{transcript}".to_string(),
+        };
+        let mut config = chunking_config(25);
+        config.post_process_prompts = vec![crate::config::PostProcessPrompt {
+            id: "summary".to_string(),
+            name: "Summary".to_string(),
+            reduce_prompt: Some("Merge the summaries.".to_string()),
+            ..Default::default()
+        }];
+        config.post_process_selected_prompt_id = Some("summary".to_string());
+        let text = "first sentence here. second sentence here. third sentence here.";
+        let output = post_process(&processor, text, &prompts, &config, |_| {})
+            .await
+            .unwrap();
+        // Three partial results are combined in rounds of 3 -> 2 -> 1.
+        assert_eq!(output.text, "SUMMARY(3)");
+    }
+
+    #[tokio::test]
+    async fn test_short_text_is_one_request() {
+        let processor: Arc<dyn PostProcessService + Send + Sync> = Arc::new(Echo);
+        let prompts = RenderedPrompts {
+            system_prompt: String::new(),
+            user_prompt_template: "{transcript}".to_string(),
+        };
+        let output = post_process(
+            &processor,
+            "hi there",
+            &prompts,
+            &chunking_config(25),
+            |_| panic!("no progress for a single request"),
+        )
+        .await
+        .unwrap();
+        assert_eq!((output.text.as_str(), output.chunks), ("HI THERE", 1));
+    }
+}
@@ -1,30 +1,53 @@
-use crate::config::Config;
+use crate::config::{Config, PostProcessTarget};
+use crate::post_process::policy::TimedService;
+use crate::post_process::provider_anthropic::AnthropicPostProcessService;
 use crate::post_process::provider_api::APIPostProcessService;
-use crate::post_process::provider_local::SidecarPostProcess;
+use crate::post_process::provider_local::{LlamaOptions, LlamaServer, SidecarPostProcess};
+use crate::post_process::provider_ollama::OllamaPostProcessService;
 use crate::post_process::PostProcessService;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 
+/// How many local sidecars stay warm at once. Each llama-server holds its
+/// model in memory, so this only covers presets alternating between two
+/// local models; a third evicts the least recently used.
+const MAX_CACHED_LOCAL_SERVICES: usize = 2;
+
 /// Fingerprint of the configuration a cached local sidecar was started with.
-/// Any change tears the sidecar down and starts a fresh one. The system
-/// prompt is deliberately excluded: it is request-scoped, so editing it must
-/// not restart the server.
+/// A request whose fingerprint matches no cached sidecar starts a fresh one.
+/// The system prompt is deliberately excluded: it is request-scoped, so
+/// editing it must not restart the server. The llama options (server,
+/// template, context size, sampling) are included.
 #[derive(PartialEq)]
 struct LocalFingerprint {
     engine: String,
     model: String,
     threads: String,
+    options: LlamaOptions,
 }
 
-/// The cached local sidecar service paired with its configuration fingerprint.
-type CachedLocalService = Arc<Mutex<Option<(LocalFingerprint, Arc<SidecarPostProcess>)>>>;
+impl LocalFingerprint {
+    fn of(target: &PostProcessTarget, options: &LlamaOptions) -> Self {
+        Self {
+            engine: target.engine.clone(),
+            model: target.model.clone(),
+            threads: target.threads.clone(),
+            options: options.clone(),
+        }
+    }
+}
+
+/// Cached local sidecar services paired with their configuration
+/// fingerprints, most recently used first.
+type CachedLocalServices = Arc<Mutex<Vec<(LocalFingerprint, Arc<SidecarPostProcess>)>>>;
 
 /// Single owner of post-process service lifecycle. Local sidecar services are
-/// expensive to start (process spawn + GGUF model load), so the most recent
-/// one is cached and reused across dictations, mirroring `EngineFactory`'s
-/// model cache for transcription.
+/// expensive to start (process spawn + GGUF model load), so recent ones are
+/// cached and reused across dictations, mirroring `EngineFactory`'s model
+/// cache for transcription. Presets may each pick their own backend, so
+/// switching presets does not throw away a warm sidecar.
 pub struct PostProcessFactory {
-    cached_local: CachedLocalService,
+    cached_local: CachedLocalServices,
     /// Serializes sidecar builds so a warm-up racing a dictation (or a second
     /// warm-up) cannot spawn duplicate llama-server processes.
     build_lock: tokio::sync::Mutex<()>,
@@ -44,7 +67,7 @@ impl Default for PostProcessFactory {
 impl PostProcessFactory {
     pub fn new() -> Self {
         Self {
-            cached_local: Arc::new(Mutex::new(None)),
+            cached_local: Arc::new(Mutex::new(Vec::new())),
             build_lock: tokio::sync::Mutex::new(()),
             generation: AtomicU64::new(0),
             gpu_tested: AtomicBool::new(false),
@@ -63,37 +86,56 @@ impl PostProcessFactory {
         self.last_gpu_error.lock().unwrap().clone()
     }
 
-    /// Returns the post-process service for the current config. Local
-    /// services come from the cache when the configuration is unchanged; API
-    /// services are cheap and stateless, so they are constructed per call.
+    /// Returns the post-process service for the selected preset's backend,
+    /// with the configured request timeout applied (see `policy`).
     pub async fn get_service(
         &self,
         config: &Config,
     ) -> Result<Arc<dyn PostProcessService + Send + Sync>, String> {
-        match config.post_process_provider {
+        let target = config.resolve_post_process_target();
+        let timeout = config.resolve_post_process_timeout(&target.provider);
+        let service = self
+            .build_service(target, LlamaOptions::from_config(config))
+            .await?;
+        Ok(Arc::new(TimedService::new(service, timeout)))
+    }
+
+    /// Local services come from the cache when one was started with the same
+    /// configuration; API services (OpenAI-compatible, Anthropic, Ollama) are
+    /// cheap and stateless, so they are constructed per call.
+    async fn build_service(
+        &self,
+        target: PostProcessTarget,
+        options: LlamaOptions,
+    ) -> Result<Arc<dyn PostProcessService + Send + Sync>, String> {
+        match target.provider {
             crate::config::PostProcessProvider::Api => Ok(Arc::new(APIPostProcessService {
-                api_key: config.post_process_api_key.clone(),
-                api_url: config.post_process_api_url.clone(),
-                model: config.post_process_api_model.clone(),
+                api_key: target.api_key,
+                api_url: target.api_url,
+                model: target.api_model,
+            })),
+            crate::config::PostProcessProvider::Anthropic => {
+                Ok(Arc::new(AnthropicPostProcessService {
+                    api_key: target.api_key,
+                    api_url: target.api_url,
+                    model: target.api_model,
+                }))
+            }
+            crate::config::PostProcessProvider::Ollama => Ok(Arc::new(OllamaPostProcessService {
+                api_key: target.api_key,
+                api_url: target.api_url,
+                model: target.api_model,
             })),
             crate::config::PostProcessProvider::Local => {
-                let fingerprint = LocalFingerprint {
-                    engine: config.post_process_engine.clone(),
-                    model: config.post_process_model.clone(),
-                    threads: config.post_process_threads.clone(),
-                };
-
-                {
-                    let guard = self.cached_local.lock().unwrap();
-                    if let Some((cached_fingerprint, service)) = guard.as_ref() {
-                        if *cached_fingerprint == fingerprint {
-                            crate::log_info!("Reusing warm llama-server post-process service");
-                            return Ok(service.clone());
-                        }
-                    }
+                let fingerprint = LocalFingerprint::of(&target, &options);
+
+                if let Some(service) = self.cached(&fingerprint) {
+                    crate::log_info!("Reusing warm llama-server post-process service");
+                    return Ok(service);
                 }
 
-                if crate::engine_factory::engine_uses_gpu(&config.post_process_engine) {
+                let spawns_server = !matches!(options.server, LlamaServer::Url(_));
+                if spawns_server && crate::engine_factory::engine_uses_gpu(&target.engine) {
                     self.gpu_tested.store(true, Ordering::SeqCst);
                 }
 
@@ -103,14 +145,9 @@ impl PostProcessFactory {
 
                 // Double-checked: a racing caller may have populated the
                 // cache while we waited for the build lock.
-                {
-                    let guard = self.cached_local.lock().unwrap();
-                    if let Some((cached_fingerprint, service)) = guard.as_ref() {
-                        if *cached_fingerprint == fingerprint {
-                            crate::log_info!("Reusing warm llama-server post-process service");
-                            return Ok(service.clone());
-                        }
-                    }
+                if let Some(service) = self.cached(&fingerprint) {
+                    crate::log_info!("Reusing warm llama-server post-process service");
+                    return Ok(service);
                 }
 
                 // Spawning the sidecar takes seconds. Capture the generation
@@ -119,9 +156,10 @@ impl PostProcessFactory {
                 let generation = self.generation.load(Ordering::SeqCst);
                 let service = Arc::new(
                     SidecarPostProcess::new(
-                        &config.post_process_engine,
-                        &config.post_process_model,
-                        &config.post_process_threads,
+                        &target.engine,
+                        &target.model,
+                        &target.threads,
+                        &options,
                         self.last_gpu_error.clone(),
                     )
                     .await
@@ -137,20 +175,37 @@ impl PostProcessFactory {
                 }
 
                 let mut guard = self.cached_local.lock().unwrap();
-                *guard = Some((fingerprint, service.clone()));
+                guard.insert(0, (fingerprint, service.clone()));
+                if guard.len() > MAX_CACHED_LOCAL_SERVICES {
+                    // Dropping the evicted entry kills its llama-server once
+                    // any in-flight request holding it finishes.
+                    guard.truncate(MAX_CACHED_LOCAL_SERVICES);
+                    crate::log_info!("Evicted least recently used llama-server sidecar");
+                }
                 Ok(service)
             }
         }
     }
 
-    /// Drops the cached local sidecar, killing the llama-server process, so
-    /// the next request starts a fresh one. Also invalidates any in-flight
+    /// Looks up a cached sidecar and marks it most recently used.
+    fn cached(&self, fingerprint: &LocalFingerprint) -> Option<Arc<SidecarPostProcess>> {
+        let mut guard = self.cached_local.lock().unwrap();
+        let index = guard.iter().position(|(cached, _)| cached == fingerprint)?;
+        let entry = guard.remove(index);
+        let service = entry.1.clone();
+        guard.insert(0, entry);
+        Some(service)
+    }
+
+    /// Drops every cached local sidecar, killing the llama-server processes,
+    /// so the next request starts a fresh one. Also invalidates any in-flight
     /// build via the generation counter. Used after failures, when local
     /// post-processing is disabled, and on factory reset.
     pub fn invalidate_local(&self) {
         self.generation.fetch_add(1, Ordering::SeqCst);
         let mut guard = self.cached_local.lock().unwrap();
-        if guard.take().is_some() {
+        if !guard.is_empty() {
+            guard.clear();
             crate::log_info!("Post-process sidecar cache invalidated");
         }
     }
@@ -161,7 +216,9 @@ impl PostProcessFactory {
         if !config.post_process_enabled {
             return;
         }
-        if config.post_process_provider != crate::config::PostProcessProvider::Local {
+        if config.resolve_post_process_target().provider
+            != crate::config::PostProcessProvider::Local
+        {
             return;
         }
         crate::log_info!("Post-process preload: starting llama-server warm-up");
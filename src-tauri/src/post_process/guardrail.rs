@@ -0,0 +1,324 @@
+//! Validation after post-processing. Small local models sometimes reply to
+//! a dictated question, summarise it, or refuse, despite the prompt telling
+//! them not to. The checks here compare the output with the text that was
+//! sent and reject output that no longer looks like a cleaned transcript.
+
+use super::template::RenderedPrompts;
+use super::{PostProcessError, PostProcessService};
+use crate::config::{Config, GuardrailAction};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Cleanup removes fillers and fixes words, but never halves the text...
+const MIN_LENGTH_RATIO: f32 = 0.5;
+/// ...nor doubles it.
+const MAX_LENGTH_RATIO: f32 = 2.0;
+/// Share of the transcript's distinct words that must survive cleanup.
+const MIN_TOKEN_OVERLAP: f32 = 0.5;
+/// Below this many words the ratios swing too much to mean anything.
+const MIN_WORDS_FOR_RATIOS: usize = 6;
+
+/// Openings typical of a chat reply rather than a transcript. Only counted
+/// when the transcript itself does not open the same way ("Sure, I'll send
+/// it tomorrow" is a perfectly good dictation).
+const ANSWER_OPENERS: &[&str] = &[
+    "sure",
+    "certainly",
+    "of course",
+    "absolutely",
+    "here is",
+    "here's",
+    "here are",
+    "i'm sorry",
+    "i am sorry",
+    "sorry, but",
+    "i can't",
+    "i cannot",
+    "i can not",
+    "i'm unable",
+    "i am unable",
+    "as an ai",
+    "i'd be happy",
+    "i would be happy",
+    "great question",
+    "the answer is",
+    "unfortunately, i",
+];
+
+/// Appended to the system prompt for the retry.
+const RETRY_REMINDER: &str = "Your previous output did not preserve the transcript. Output the transcript itself, cleaned up. Do not answer it, summarise it, comment on it or refuse it.";
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|word| word.trim_matches('\'').to_lowercase())
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+fn answer_opener(text: &str) -> Option<&'static str> {
+    let opening = text
+        .trim_start_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+        .replace('\u{2019}', "'");
+    ANSWER_OPENERS.iter().copied().find(|opener| {
+        opening.starts_with(opener)
+            && opening[opener.len()..]
+                .chars()
+                .next()
+                .is_none_or(|c| !c.is_alphanumeric())
+    })
+}
+
+/// Returns why `output` does not look like a cleaned version of `input`, or
+/// `None` when it passes.
+pub fn check(input: &str, output: &str) -> Option<String> {
+    if output.trim().is_empty() {
+        return Some("output was empty".to_string());
+    }
+
+    if let Some(opener) = answer_opener(output) {
+        if answer_opener(input) != Some(opener) {
+            return Some(format!("output opens like a reply (\"{}\")", opener));
+        }
+    }
+
+    if input.contains('?') && !output.contains('?') {
+        return Some("the transcript's questions are missing from the output".to_string());
+    }
+
+    let input_words = words(input);
+    let output_words = words(output);
+    if input_words.len() < MIN_WORDS_FOR_RATIOS {
+        return None;
+    }
+
+    let ratio = output_words.len() as f32 / input_words.len() as f32;
+    if ratio < MIN_LENGTH_RATIO {
+        return Some(format!(
+            "output is {:.0}% of the transcript's length",
+            ratio * 100.0
+        ));
+    }
+    if ratio > MAX_LENGTH_RATIO {
+        return Some(format!("output is {:.1}x the transcript's length", ratio));
+    }
+
+    let distinct: HashSet<&str> = input_words.iter().map(String::as_str).collect();
+    let kept: HashSet<&str> = output_words.iter().map(String::as_str).collect();
+    let overlap = distinct.intersection(&kept).count() as f32 / distinct.len() as f32;
+    if overlap < MIN_TOKEN_OVERLAP {
+        return Some(format!(
+            "only {:.0}% of the transcript's words were kept",
+            overlap * 100.0
+        ));
+    }
+
+    None
+}
+
+/// The text to use after the guardrail, plus a note for history when the
+/// model output was rejected.
+pub struct Guarded {
+    pub text: String,
+    pub note: Option<String>,
+}
+
+/// Checks `output` against `input` and, when it drifted, retries once (if
+/// configured) and otherwise falls back to `input`. The retry is chunked like
+/// the first attempt, so a long transcript never goes out in one request.
+pub async fn enforce(
+    processor: &Arc<dyn PostProcessService + Send + Sync>,
+    input: &str,
+    output: String,
+    prompts: &RenderedPrompts,
+    config: &Config,
+) -> Guarded {
+    if !config.resolve_guardrail_enabled() {
+        return Guarded {
+            text: output,
+            note: None,
+        };
+    }
+    let Some(reason) = check(input, &output) else {
+        return Guarded {
+            text: output,
+            note: None,
+        };
+    };
+    crate::log_warn!("Post-process guardrail rejected output: {}", reason);
+
+    if config.post_process_guardrail_action == GuardrailAction::Retry {
+        let prompts = RenderedPrompts {
+            system_prompt: format!("{}\n\n{}", prompts.system_prompt.trim_end(), RETRY_REMINDER),
+            user_prompt_template: prompts.user_prompt_template.clone(),
+        };
+        let retried: Result<String, PostProcessError> =
+            super::chunked::post_process(processor, input, &prompts, config, |_| {})
+                .await
+                .map(|output| output.text);
+        match retried {
+            Ok(retried) => match check(input, &retried) {
+                None => {
+                    crate::log_info!("Post-process guardrail: retry passed");
+                    return Guarded {
+                        text: retried,
+                        note: Some(format!("retried after: {}", reason)),
+                    };
+                }
+                Some(retry_reason) => {
+                    crate::log_warn!("Post-process guardrail: retry rejected: {}", retry_reason);
+                    return Guarded {
+                        text: input.to_string(),
+                        note: Some(format!(
+                            "used raw transcript: {}; retry: {}",
+                            reason, retry_reason
+                        )),
+                    };
+                }
+            },
+            Err(e) => {
+                crate::log_warn!("Post-process guardrail: retry failed: {}", e);
+                return Guarded {
+                    text: input.to_string(),
+                    note: Some(format!(
+                        "used raw transcript: {}; retry failed: {}",
+                        reason, e
+                    )),
+                };
+            }
+        }
+    }
+
+    Guarded {
+        text: input.to_string(),
+        note: Some(format!("used raw transcript: {}", reason)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    /// Echoes the text back and records how long each request was.
+    #[derive(Default)]
+    struct Recorder {
+        request_chars: Mutex<Vec<usize>>,
+    }
+
+    #[async_trait]
+    impl PostProcessService for Recorder {
+        async fn post_process(
+            &self,
+            text: &str,
+            _system_prompt: &str,
+            _user_prompt_template: &str,
+            _max_output_tokens: u32,
+        ) -> Result<String, PostProcessError> {
+            self.request_chars.lock().unwrap().push(text.len());
+            Ok(text.to_string())
+        }
+        fn service_name(&self) -> &'static str {
+            "recorder"
+        }
+    }
+
+    #[test]
+    fn test_accepts_ordinary_cleanup() {
+        assert_eq!(
+            check(
+                "so i think we should um ship the build on friday and tell the team",
+                "So I think we should ship the build on Friday and tell the team."
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rejects_answered_question() {
+        let reason = check(
+            "What is the capital of France?",
+            "The capital of France is Paris.",
+        )
+        .unwrap();
+        assert!(reason.contains("questions"));
+
+        let reason = check("can you check the logs", "Sure! Here are the logs.").unwrap();
+        assert!(reason.contains("sure"));
+    }
+
+    #[test]
+    fn test_opener_in_transcript_is_allowed() {
+        assert_eq!(
+            check(
+                "sure, i'll send it tomorrow",
+                "Sure, I'll send it tomorrow."
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rejects_summary_and_rambling_reply() {
+        let input = "we talked about the roadmap for the next quarter and agreed that the \
+                     search rewrite comes first followed by the billing migration and then \
+                     the mobile release";
+        let reason = check(input, "Roadmap: search, billing, mobile.").unwrap();
+        assert!(reason.contains("length"));
+
+        let reason = check(
+            "please write a short note to the landlord about the broken heater",
+            "Dear landlord, I hope this message finds you well. I am writing to let \
+             you know that the heater in my apartment has stopped working and it is \
+             getting quite cold. Could you arrange a repair? Kind regards.",
+        )
+        .unwrap();
+        assert!(reason.contains("length"));
+    }
+
+    #[test]
+    fn test_rejects_low_overlap() {
+        let reason = check(
+            "remind me to call the dentist about moving tuesday's appointment",
+            "You should phone your orthodontist regarding rescheduling that visit.",
+        )
+        .unwrap();
+        assert!(reason.contains("words were kept"));
+    }
+
+    #[tokio::test]
+    async fn test_retry_of_long_transcript_is_chunked() {
+        let recorder = Arc::new(Recorder::default());
+        let processor: Arc<dyn PostProcessService + Send + Sync> = recorder.clone();
+        let prompts = RenderedPrompts {
+            system_prompt: "clean".to_string(),
+            user_prompt_template: "{transcript}".to_string(),
+        };
+        let config = Config {
+            post_process_guardrail_enabled: true,
+            post_process_guardrail_action: GuardrailAction::Retry,
+            post_process_chunking_enabled: true,
+            post_process_chunk_chars: 60,
+            post_process_chunk_overlap_chars: 0,
+            ..Default::default()
+        };
+        let input = "we shipped the build on friday. the team tested it over the weekend. \
+                     nothing broke on monday. we will keep the release going this week.";
+
+        let guarded = enforce(
+            &processor,
+            input,
+            "Sure! Here is the summary.".to_string(),
+            &prompts,
+            &config,
+        )
+        .await;
+
+        assert_eq!(guarded.text, input);
+        assert!(guarded.note.unwrap().starts_with("retried after"));
+        let requests = recorder.request_chars.lock().unwrap();
+        assert!(requests.len() > 1);
+        assert!(requests.iter().all(|&chars| chars <= 60));
+    }
+}
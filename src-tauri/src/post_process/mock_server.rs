@@ -0,0 +1,89 @@
+//! One-shot HTTP server for provider tests. It answers a single request with
+//! a canned response and records what the client sent.
+
+use serde_json::Value;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::oneshot;
+
+pub struct RecordedRequest {
+    pub head: String,
+    pub body: Value,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<String> {
+        self.head.lines().skip(1).find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim()
+                .eq_ignore_ascii_case(name)
+                .then(|| value.trim().to_string())
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        self.head.split_whitespace().nth(1).unwrap_or("")
+    }
+}
+
+pub struct MockServer {
+    pub url: String,
+    request: oneshot::Receiver<RecordedRequest>,
+}
+
+impl MockServer {
+    /// Serves `body` with the given status line (e.g. `"200 OK"`) at
+    /// `http://127.0.0.1:<port>{path}`.
+    pub async fn respond(path: &str, status: &str, content_type: &str, body: &str) -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}{}", listener.local_addr().unwrap(), path);
+        let response = format!(
+            "HTTP/1.1 {}\r\ncontent-type: {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            status,
+            content_type,
+            body.len(),
+            body
+        );
+        let (sender, request) = oneshot::channel();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut received = Vec::new();
+            let mut buffer = [0u8; 4096];
+            let (head, body) = loop {
+                let read = socket.read(&mut buffer).await.unwrap();
+                received.extend_from_slice(&buffer[..read]);
+                let Some(end) = received.windows(4).position(|w| w == b"\r\n\r\n") else {
+                    assert!(read > 0, "connection closed before the request head");
+                    continue;
+                };
+                let head = String::from_utf8_lossy(&received[..end]).to_string();
+                let length = head
+                    .lines()
+                    .find_map(|line| {
+                        let (key, value) = line.split_once(':')?;
+                        key.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().ok())?
+                    })
+                    .unwrap_or(0);
+                let body = &received[end + 4..];
+                if body.len() >= length || read == 0 {
+                    break (head, String::from_utf8_lossy(body).to_string());
+                }
+            };
+            socket.write_all(response.as_bytes()).await.unwrap();
+            let _ = sender.send(RecordedRequest {
+                head,
+                body: serde_json::from_str(&body).unwrap_or(Value::Null),
+            });
+        });
+        Self { url, request }
+    }
+
+    pub async fn json(path: &str, status: &str, body: Value) -> Self {
+        Self::respond(path, status, "application/json", &body.to_string()).await
+    }
+
+    /// The request the server received.
+    pub async fn request(self) -> RecordedRequest {
+        self.request.await.expect("mock server received no request")
+    }
+}
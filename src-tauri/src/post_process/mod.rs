@@ -4,6 +4,8 @@ use async_trait::async_trait;
 pub enum PostProcessError {
     Network(String),
     Api(String),
+    /// The session was cancelled while a streaming request was in flight.
+    Cancelled,
 }
 
 impl std::fmt::Display for PostProcessError {
@@ -11,6 +13,7 @@ impl std::fmt::Display for PostProcessError {
         match self {
             Self::Network(e) => write!(f, "Network error: {}", e),
             Self::Api(e) => write!(f, "API error: {}", e),
+            Self::Cancelled => write!(f, "Cancelled"),
         }
     }
 }
@@ -26,10 +29,52 @@ pub trait PostProcessService: Send + Sync {
         user_prompt_template: &str,
         max_output_tokens: u32,
     ) -> Result<String, PostProcessError>;
+    /// Like `post_process`, but forwards tokens to `stream` while the model
+    /// is still generating. Providers without streaming support deliver the
+    /// whole result as a single delta.
+    async fn post_process_stream(
+        &self,
+        text: &str,
+        system_prompt: &str,
+        user_prompt_template: &str,
+        max_output_tokens: u32,
+        stream: &stream::PostProcessStream,
+    ) -> Result<String, PostProcessError> {
+        let result = self
+            .post_process(text, system_prompt, user_prompt_template, max_output_tokens)
+            .await?;
+        let _ = stream.deltas.send(result.clone());
+        Ok(result)
+    }
+    /// Like `post_process`, but constrains the output to a JSON schema or
+    /// grammar. Providers that cannot constrain output rely on the prompt.
+    async fn post_process_constrained(
+        &self,
+        text: &str,
+        system_prompt: &str,
+        user_prompt_template: &str,
+        max_output_tokens: u32,
+        constraint: &structured::OutputConstraint,
+    ) -> Result<String, PostProcessError> {
+        let _ = constraint;
+        self.post_process(text, system_prompt, user_prompt_template, max_output_tokens)
+            .await
+    }
     fn service_name(&self) -> &'static str;
 }
 
+pub mod chunked;
 pub mod factory;
+pub mod guardrail;
+#[cfg(test)]
+mod mock_server;
+pub mod policy;
 pub mod prompt;
+pub mod provider_anthropic;
 pub mod provider_api;
 pub mod provider_local;
+pub mod provider_ollama;
+pub mod stream;
+pub mod structured;
+pub mod template;
+pub mod trigger;
@@ -0,0 +1,438 @@
+//! What happens when post-processing fails: every request runs under a
+//! timeout (for streams, a limit on the silence between tokens), a failed
+//! backend is retried, and once its retries are used up
+//! the fallback backend (if any) gets the same treatment. Each attempt is
+//! recorded so history can tell a cleaned transcript from a raw one.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use super::factory::PostProcessFactory;
+use super::stream::PostProcessStream;
+use super::structured::OutputConstraint;
+use super::{PostProcessError, PostProcessService};
+use crate::config::{Config, PostProcessFailureOutput, PostProcessProvider};
+use crate::history::{PostProcessAttempt, PostProcessOutcome, PostProcessReport};
+
+/// Delay before the first retry; it doubles for every further one.
+const RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Fails `future` with a network error once `timeout` has passed. A timeout
+/// counts as a network error so a hung local sidecar gets restarted.
+pub async fn with_timeout<T>(
+    timeout: Duration,
+    future: impl std::future::Future<Output = Result<T, PostProcessError>>,
+) -> Result<T, PostProcessError> {
+    tokio::time::timeout(timeout, future)
+        .await
+        .unwrap_or_else(|_| {
+            Err(PostProcessError::Network(format!(
+                "Timed out after {}s",
+                timeout.as_secs()
+            )))
+        })
+}
+
+/// A service whose every request runs under `timeout`. The factory wraps all
+/// services in one, so providers need no timeouts of their own.
+///
+/// Streams are only timed out while no tokens arrive: a long answer that
+/// keeps generating is not cut off halfway through, after part of it may
+/// already have been typed.
+pub struct TimedService {
+    inner: Arc<dyn PostProcessService + Send + Sync>,
+    timeout: Duration,
+}
+
+impl TimedService {
+    pub fn new(inner: Arc<dyn PostProcessService + Send + Sync>, timeout: Duration) -> Self {
+        Self { inner, timeout }
+    }
+}
+
+#[async_trait]
+impl PostProcessService for TimedService {
+    async fn post_process(
+        &self,
+        text: &str,
+        system_prompt: &str,
+        user_prompt_template: &str,
+        max_output_tokens: u32,
+    ) -> Result<String, PostProcessError> {
+        with_timeout(
+            self.timeout,
+            self.inner
+                .post_process(text, system_prompt, user_prompt_template, max_output_tokens),
+        )
+        .await
+    }
+
+    async fn post_process_stream(
+        &self,
+        text: &str,
+        system_prompt: &str,
+        user_prompt_template: &str,
+        max_output_tokens: u32,
+        stream: &PostProcessStream,
+    ) -> Result<String, PostProcessError> {
+        let (deltas, mut received) = tokio::sync::mpsc::unbounded_channel();
+        let relay = PostProcessStream {
+            deltas,
+            cancel: stream.cancel.clone(),
+        };
+        let request = self.inner.post_process_stream(
+            text,
+            system_prompt,
+            user_prompt_template,
+            max_output_tokens,
+            &relay,
+        );
+        tokio::pin!(request);
+        loop {
+            tokio::select! {
+                result = &mut request => {
+                    while let Ok(delta) = received.try_recv() {
+                        let _ = stream.deltas.send(delta);
+                    }
+                    return result;
+                }
+                Some(delta) = received.recv() => {
+                    let _ = stream.deltas.send(delta);
+                }
+                _ = tokio::time::sleep(self.timeout) => {
+                    return Err(PostProcessError::Network(format!(
+                        "Timed out after {}s without output",
+                        self.timeout.as_secs()
+                    )));
+                }
+            }
+        }
+    }
+
+    async fn post_process_constrained(
+        &self,
+        text: &str,
+        system_prompt: &str,
+        user_prompt_template: &str,
+        max_output_tokens: u32,
+        constraint: &OutputConstraint,
+    ) -> Result<String, PostProcessError> {
+        with_timeout(
+            self.timeout,
+            self.inner.post_process_constrained(
+                text,
+                system_prompt,
+                user_prompt_template,
+                max_output_tokens,
+                constraint,
+            ),
+        )
+        .await
+    }
+
+    fn service_name(&self) -> &'static str {
+        self.inner.service_name()
+    }
+}
+
+/// Names a backend whose service could not even be created.
+fn backend_name(config: &Config) -> &'static str {
+    match config.resolve_post_process_target().provider {
+        PostProcessProvider::Local => "Post-Process (Local)",
+        PostProcessProvider::Api => "Post-Process (API)",
+        PostProcessProvider::Anthropic => "Post-Process (Anthropic)",
+        PostProcessProvider::Ollama => "Post-Process (Ollama)",
+    }
+}
+
+/// Hands out the services to try, in order: the configured backend, its
+/// retries, then the fallback backend and its retries. The caller reports
+/// each failure through `failed` and finishes with `succeeded` or
+/// `exhausted` to get the history record.
+pub struct Attempts<'a> {
+    factory: &'a PostProcessFactory,
+    backends: Vec<Config>,
+    backend: usize,
+    tries: u32,
+    retries: u32,
+    attempts: Vec<PostProcessAttempt>,
+    current: Option<(&'static str, PostProcessProvider, Instant)>,
+}
+
+impl<'a> Attempts<'a> {
+    pub fn new(factory: &'a PostProcessFactory, config: &Config) -> Self {
+        Self {
+            factory,
+            backends: std::iter::once(config.clone())
+                .chain(config.post_process_fallback_config())
+                .collect(),
+            backend: 0,
+            tries: 0,
+            retries: config.post_process_retries,
+            attempts: Vec::new(),
+            current: None,
+        }
+    }
+
+    /// The next service to try, or `None` once every attempt is used up.
+    /// Waits out the retry backoff first; a service that cannot be created
+    /// counts as a failed attempt.
+    pub async fn next(&mut self) -> Option<Arc<dyn PostProcessService + Send + Sync>> {
+        loop {
+            if self.tries > self.retries {
+                self.backend += 1;
+                self.tries = 0;
+                if self.backend < self.backends.len() {
+                    crate::log_warn!(
+                        "Post-processing falling back to {}",
+                        backend_name(&self.backends[self.backend])
+                    );
+                }
+            }
+            let config = self.backends.get(self.backend)?;
+            if self.tries > 0 {
+                tokio::time::sleep(RETRY_BACKOFF * 2u32.pow(self.tries - 1)).await;
+                crate::log_info!("Retrying post-processing (attempt {})", self.tries + 1);
+            }
+            self.tries += 1;
+
+            let started = Instant::now();
+            match self.factory.get_service(config).await {
+                Ok(service) => {
+                    self.current = Some((
+                        service.service_name(),
+                        config.resolve_post_process_target().provider,
+                        started,
+                    ));
+                    return Some(service);
+                }
+                Err(error) => {
+                    crate::log_warn!("Could not create post-process service: {}", error);
+                    self.attempts.push(PostProcessAttempt {
+                        service: backend_name(config).to_string(),
+                        error: Some(error),
+                        elapsed_ms: started.elapsed().as_millis() as u64,
+                    });
+                }
+            }
+        }
+    }
+
+    fn record(&mut self, error: Option<String>) {
+        if let Some((service, _, started)) = self.current.take() {
+            self.attempts.push(PostProcessAttempt {
+                service: service.to_string(),
+                error,
+                elapsed_ms: started.elapsed().as_millis() as u64,
+            });
+        }
+    }
+
+    /// Records a failed attempt with the service last handed out.
+    pub fn failed(&mut self, error: &PostProcessError) {
+        crate::log_warn!("Post-processing attempt failed: {}", error);
+        // A network error from the local sidecar means the process is gone,
+        // unreachable or hung; drop it so the next attempt respawns it.
+        // Other backends failing says nothing about the sidecar.
+        let local = matches!(self.current, Some((_, PostProcessProvider::Local, _)));
+        if local && matches!(error, PostProcessError::Network(_)) {
+            self.factory.invalidate_local();
+        }
+        self.record(Some(error.to_string()));
+    }
+
+    /// The record for history after the service last handed out succeeded.
+    pub fn succeeded(mut self) -> PostProcessReport {
+        self.record(None);
+        PostProcessReport {
+            outcome: if self.backend == 0 {
+                PostProcessOutcome::Cleaned
+            } else {
+                PostProcessOutcome::Fallback
+            },
+            attempts: self.attempts,
+        }
+    }
+
+    /// The record for history after every attempt failed.
+    pub fn exhausted(self, output: PostProcessFailureOutput) -> PostProcessReport {
+        PostProcessReport {
+            outcome: match output {
+                PostProcessFailureOutput::Raw => PostProcessOutcome::Raw,
+                PostProcessFailureOutput::Discard => PostProcessOutcome::Discarded,
+            },
+            attempts: self.attempts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct SlowService(Duration);
+
+    #[async_trait]
+    impl PostProcessService for SlowService {
+        async fn post_process(
+            &self,
+            text: &str,
+            _system_prompt: &str,
+            _user_prompt_template: &str,
+            _max_output_tokens: u32,
+        ) -> Result<String, PostProcessError> {
+            tokio::time::sleep(self.0).await;
+            Ok(text.to_uppercase())
+        }
+
+        fn service_name(&self) -> &'static str {
+            "Slow"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timeout_fails_slow_requests_as_network_errors() {
+        let slow = TimedService::new(
+            Arc::new(SlowService(Duration::from_secs(10))),
+            Duration::from_millis(50),
+        );
+        let error = slow
+            .post_process("hi", "", "{transcript}", 0)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, PostProcessError::Network(ref m) if m.starts_with("Timed out")));
+
+        let quick = TimedService::new(
+            Arc::new(SlowService(Duration::ZERO)),
+            Duration::from_secs(5),
+        );
+        assert_eq!(
+            quick
+                .post_process("hi", "", "{transcript}", 0)
+                .await
+                .unwrap(),
+            "HI"
+        );
+        assert_eq!(quick.service_name(), "Slow");
+    }
+
+    /// Streams `tokens` words, pausing `gap` before each one.
+    struct TrickleService {
+        tokens: usize,
+        gap: Duration,
+    }
+
+    #[async_trait]
+    impl PostProcessService for TrickleService {
+        async fn post_process(
+            &self,
+            _text: &str,
+            _system_prompt: &str,
+            _user_prompt_template: &str,
+            _max_output_tokens: u32,
+        ) -> Result<String, PostProcessError> {
+            unreachable!()
+        }
+
+        async fn post_process_stream(
+            &self,
+            _text: &str,
+            _system_prompt: &str,
+            _user_prompt_template: &str,
+            _max_output_tokens: u32,
+            stream: &PostProcessStream,
+        ) -> Result<String, PostProcessError> {
+            let mut output = String::new();
+            for _ in 0..self.tokens {
+                tokio::time::sleep(self.gap).await;
+                output.push_str("word ");
+                let _ = stream.deltas.send("word ".to_string());
+            }
+            Ok(output)
+        }
+
+        fn service_name(&self) -> &'static str {
+            "Trickle"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_timeout_only_counts_silence() {
+        let (deltas, mut received) = tokio::sync::mpsc::unbounded_channel();
+        let stream = PostProcessStream {
+            deltas,
+            cancel: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+        };
+
+        // Takes far longer than the timeout overall, but never goes quiet.
+        let steady = TimedService::new(
+            Arc::new(TrickleService {
+                tokens: 10,
+                gap: Duration::from_millis(30),
+            }),
+            Duration::from_millis(200),
+        );
+        let output = steady
+            .post_process_stream("hi", "", "{transcript}", 0, &stream)
+            .await
+            .unwrap();
+        assert_eq!(output, "word ".repeat(10));
+        let mut forwarded = String::new();
+        while let Ok(delta) = received.try_recv() {
+            forwarded.push_str(&delta);
+        }
+        assert_eq!(forwarded, output);
+
+        let stalled = TimedService::new(
+            Arc::new(TrickleService {
+                tokens: 2,
+                gap: Duration::from_secs(10),
+            }),
+            Duration::from_millis(50),
+        );
+        let error = stalled
+            .post_process_stream("hi", "", "{transcript}", 0, &stream)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, PostProcessError::Network(ref m) if m.starts_with("Timed out")));
+    }
+
+    #[tokio::test]
+    async fn test_retries_then_records_every_failure() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!(
+            "http://{}/v1/chat/completions",
+            listener.local_addr().unwrap()
+        );
+        drop(listener);
+        let config = Config {
+            post_process_provider: PostProcessProvider::Api,
+            post_process_api_url: url,
+            post_process_retries: 1,
+            ..Default::default()
+        };
+        let factory = PostProcessFactory::new();
+        let mut attempts = Attempts::new(&factory, &config);
+
+        let mut tried = 0;
+        while let Some(service) = attempts.next().await {
+            tried += 1;
+            let error = service
+                .post_process("hi", "", "{transcript}", 0)
+                .await
+                .unwrap_err();
+            attempts.failed(&error);
+        }
+        assert_eq!(tried, 2);
+
+        let report = attempts.exhausted(PostProcessFailureOutput::Discard);
+        assert_eq!(report.outcome, PostProcessOutcome::Discarded);
+        assert_eq!(report.attempts.len(), 2);
+        assert!(report
+            .attempts
+            .iter()
+            .all(|attempt| attempt.service == "Post-Process (API)" && attempt.error.is_some()));
+    }
+}
@@ -1,9 +1,11 @@
+use super::PostProcessError;
+
 pub fn build_post_process_messages(
     text: &str,
     system_prompt: &str,
     user_prompt_template: &str,
 ) -> Vec<serde_json::Value> {
-    let user_content = user_prompt_template.replace("{transcript}", text);
+    let user_content = user_message(text, user_prompt_template);
     vec![
         serde_json::json!({
             "role": "system",
@@ -16,9 +18,73 @@ pub fn build_post_process_messages(
     ]
 }
 
+pub fn user_message(text: &str, user_prompt_template: &str) -> String {
+    user_prompt_template.replace("{transcript}", text)
+}
+
+/// Sends a non-streaming request and returns the parsed JSON response. On an
+/// error status, `error_detail` extracts the provider's error message from
+/// the body; without one the raw body is reported.
+pub async fn send_json_request(
+    request: reqwest::RequestBuilder,
+    error_detail: fn(&serde_json::Value) -> Option<String>,
+) -> Result<serde_json::Value, PostProcessError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| PostProcessError::Network(format!("Request failed: {}", e)))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "unknown".to_string());
+        let detail = serde_json::from_str(&body_text)
+            .ok()
+            .and_then(|body| error_detail(&body))
+            .unwrap_or(body_text);
+        return Err(PostProcessError::Api(format!(
+            "API returned {}: {}",
+            status, detail
+        )));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| PostProcessError::Network(format!("Failed to parse response: {}", e)))
+}
+
+/// Sends a non-streaming chat completion request and returns the tidied
+/// content of the first choice.
+pub async fn send_chat_request(
+    request: reqwest::RequestBuilder,
+) -> Result<String, PostProcessError> {
+    let data = send_json_request(request, |_| None).await?;
+
+    let content = data["choices"][0]["message"]["content"]
+        .as_str()
+        .ok_or_else(|| PostProcessError::Api("No content in response".to_string()))?;
+
+    Ok(tidy_completion(content))
+}
+
 pub fn max_output_tokens(input_text: &str, config_tokens: u32) -> usize {
     if config_tokens > 0 {
         return config_tokens as usize;
     }
     (input_text.len()).clamp(256, 8192)
 }
+
+/// Trims the completion and collapses runs of whitespace within each line,
+/// dropping blank lines.
+pub fn tidy_completion(content: &str) -> String {
+    content
+        .trim()
+        .lines()
+        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
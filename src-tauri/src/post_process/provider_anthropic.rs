@@ -0,0 +1,346 @@
+use async_trait::async_trait;
+use serde_json::Value;
+
+use super::stream::PostProcessStream;
+use super::structured::OutputConstraint;
+use super::{PostProcessError, PostProcessService};
+
+pub const DEFAULT_API_URL: &str = "https://api.anthropic.com/v1/messages";
+const API_VERSION: &str = "2023-06-01";
+
+/// Post-processing through Anthropic's native Messages API, which differs
+/// from the OpenAI shape in its authentication headers, a top-level system
+/// prompt, and a response made of content blocks.
+pub struct AnthropicPostProcessService {
+    pub api_key: String,
+    pub api_url: String,
+    pub model: String,
+}
+
+impl AnthropicPostProcessService {
+    fn messages_request(
+        &self,
+        text: &str,
+        system_prompt: &str,
+        user_prompt_template: &str,
+        max_output_tokens: u32,
+        stream: bool,
+    ) -> reqwest::RequestBuilder {
+        let mut body = serde_json::json!({
+            "model": self.model,
+            "messages": [{
+                "role": "user",
+                "content": super::prompt::user_message(text, user_prompt_template),
+            }],
+            "max_tokens": super::prompt::max_output_tokens(text, max_output_tokens),
+            "temperature": 0.0,
+            "stream": stream,
+        });
+        // The API rejects an empty system prompt rather than ignoring it.
+        if !system_prompt.trim().is_empty() {
+            body["system"] = Value::String(system_prompt.to_string());
+        }
+
+        let api_url = if self.api_url.trim().is_empty() {
+            DEFAULT_API_URL
+        } else {
+            self.api_url.trim()
+        };
+        reqwest::Client::new()
+            .post(api_url)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", API_VERSION)
+            .json(&body)
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<String, PostProcessError> {
+        let data = super::prompt::send_json_request(request, error_detail).await?;
+        response_text(&data)
+    }
+}
+
+/// `{"type": "error", "error": {"type": "...", "message": "..."}}`, used both
+/// as an error response body and as a stream event.
+fn error_detail(body: &Value) -> Option<String> {
+    let error = body.get("error")?;
+    let message = error["message"].as_str()?;
+    Some(match error["type"].as_str() {
+        Some(kind) => format!("{}: {}", kind, message),
+        None => message.to_string(),
+    })
+}
+
+/// Joins the text blocks of a Messages API response.
+fn response_text(data: &Value) -> Result<String, PostProcessError> {
+    if data["stop_reason"] == "max_tokens" {
+        crate::log_warn!("Anthropic response was cut off at the output token limit");
+    }
+    let content: String = data["content"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|block| block["type"] == "text")
+        .filter_map(|block| block["text"].as_str())
+        .collect();
+    if content.trim().is_empty() {
+        return Err(PostProcessError::Api("No content in response".to_string()));
+    }
+    Ok(super::prompt::tidy_completion(&content))
+}
+
+/// Extracts the text from one Messages API stream event. Only
+/// `content_block_delta` events carry text; `error` events abort the stream.
+fn stream_delta(data: &str) -> Result<Option<String>, PostProcessError> {
+    let event: Value = serde_json::from_str(data)
+        .map_err(|e| PostProcessError::Api(format!("Malformed stream event: {}", e)))?;
+    match event["type"].as_str() {
+        Some("content_block_delta") => Ok(event["delta"]["text"]
+            .as_str()
+            .filter(|text| !text.is_empty())
+            .map(str::to_string)),
+        Some("error") => Err(PostProcessError::Api(
+            error_detail(&event).unwrap_or_else(|| event.to_string()),
+        )),
+        _ => Ok(None),
+    }
+}
+
+#[async_trait]
+impl PostProcessService for AnthropicPostProcessService {
+    async fn post_process(
+        &self,
+        text: &str,
+        system_prompt: &str,
+        user_prompt_template: &str,
+        max_output_tokens: u32,
+    ) -> Result<String, PostProcessError> {
+        self.send(self.messages_request(
+            text,
+            system_prompt,
+            user_prompt_template,
+            max_output_tokens,
+            false,
+        ))
+        .await
+    }
+
+    async fn post_process_constrained(
+        &self,
+        text: &str,
+        system_prompt: &str,
+        user_prompt_template: &str,
+        max_output_tokens: u32,
+        constraint: &OutputConstraint,
+    ) -> Result<String, PostProcessError> {
+        self.send(self.messages_request(
+            text,
+            &constraint.apply_to_system_prompt(system_prompt),
+            user_prompt_template,
+            max_output_tokens,
+            false,
+        ))
+        .await
+    }
+
+    async fn post_process_stream(
+        &self,
+        text: &str,
+        system_prompt: &str,
+        user_prompt_template: &str,
+        max_output_tokens: u32,
+        stream: &PostProcessStream,
+    ) -> Result<String, PostProcessError> {
+        let request = self.messages_request(
+            text,
+            system_prompt,
+            user_prompt_template,
+            max_output_tokens,
+            true,
+        );
+        super::stream::send_streaming_request_with(request, stream, stream_delta).await
+    }
+
+    fn service_name(&self) -> &'static str {
+        "Post-Process (Anthropic)"
+    }
+}
+
+pub async fn test_connection(
+    api_key: &str,
+    api_url: &str,
+    model: &str,
+    system_prompt: &str,
+) -> Result<String, String> {
+    let service = AnthropicPostProcessService {
+        api_key: api_key.to_string(),
+        api_url: api_url.to_string(),
+        model: model.to_string(),
+    };
+    let default_template = crate::config::Config::default().post_process_user_prompt_template;
+    super::policy::with_timeout(
+        std::time::Duration::from_secs(30),
+        service.post_process(
+            "This is a test um sentence",
+            system_prompt,
+            &default_template,
+            256,
+        ),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::mock_server::MockServer;
+    use super::*;
+    use serde_json::json;
+
+    fn service(url: &str) -> AnthropicPostProcessService {
+        AnthropicPostProcessService {
+            api_key: "sk-ant-test".to_string(),
+            api_url: url.to_string(),
+            model: "claude-test".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_and_response_mapping() {
+        let server = MockServer::json(
+            "/v1/messages",
+            "200 OK",
+            json!({
+                "type": "message",
+                "content": [
+                    {"type": "text", "text": "Hello, "},
+                    {"type": "tool_use", "id": "x", "name": "n", "input": {}},
+                    {"type": "text", "text": "world."}
+                ],
+                "stop_reason": "end_turn"
+            }),
+        )
+        .await;
+
+        let result = service(&server.url)
+            .post_process("hello world", "Clean up.", "<t>{transcript}</t>", 100)
+            .await
+            .unwrap();
+        assert_eq!(result, "Hello, world.");
+
+        let request = server.request().await;
+        assert_eq!(request.path(), "/v1/messages");
+        assert_eq!(request.header("x-api-key").as_deref(), Some("sk-ant-test"));
+        assert_eq!(
+            request.header("anthropic-version").as_deref(),
+            Some(API_VERSION)
+        );
+        assert_eq!(request.header("authorization"), None);
+        assert_eq!(request.body["system"], "Clean up.");
+        assert_eq!(request.body["model"], "claude-test");
+        assert_eq!(request.body["max_tokens"], 100);
+        assert_eq!(
+            request.body["messages"],
+            json!([{"role": "user", "content": "<t>hello world</t>"}])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_response_is_reported() {
+        let server = MockServer::json(
+            "/v1/messages",
+            "401 Unauthorized",
+            json!({
+                "type": "error",
+                "error": {"type": "authentication_error", "message": "invalid x-api-key"}
+            }),
+        )
+        .await;
+
+        let error = test_connection("bad", &server.url, "claude-test", "Clean up.")
+            .await
+            .unwrap_err();
+        assert!(error.contains("401"), "{}", error);
+        assert!(
+            error.contains("authentication_error: invalid x-api-key"),
+            "{}",
+            error
+        );
+    }
+
+    #[tokio::test]
+    async fn test_schema_goes_into_system_prompt_and_empty_system_is_omitted() {
+        let server = MockServer::json(
+            "/v1/messages",
+            "200 OK",
+            json!({"content": [{"type": "text", "text": "{\"a\": 1}"}]}),
+        )
+        .await;
+        let constraint = OutputConstraint::JsonSchema(json!({"type": "object"}));
+        service(&server.url)
+            .post_process_constrained("x", "Extract.", "{transcript}", 0, &constraint)
+            .await
+            .unwrap();
+        let system = server.request().await.body["system"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(system.starts_with("Extract."));
+        assert!(system.contains(r#"{"type":"object"}"#));
+
+        let server = MockServer::json(
+            "/v1/messages",
+            "200 OK",
+            json!({"content": [{"type": "text", "text": "ok"}]}),
+        )
+        .await;
+        service(&server.url)
+            .post_process("x", " ", "{transcript}", 0)
+            .await
+            .unwrap();
+        assert!(server.request().await.body.get("system").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_streaming_events() {
+        let events = [
+            json!({"type": "message_start", "message": {"content": []}}),
+            json!({"type": "content_block_start", "index": 0, "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Hi"}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": " there."}}),
+            json!({"type": "message_stop"}),
+        ];
+        let body: String = events
+            .iter()
+            .map(|event| {
+                format!(
+                    "event: {}\ndata: {}\n\n",
+                    event["type"].as_str().unwrap(),
+                    event
+                )
+            })
+            .collect();
+        let server =
+            MockServer::respond("/v1/messages", "200 OK", "text/event-stream", &body).await;
+
+        let (deltas, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let stream = PostProcessStream {
+            deltas,
+            cancel: Default::default(),
+        };
+        let result = service(&server.url)
+            .post_process_stream("hi there", "Clean up.", "{transcript}", 0, &stream)
+            .await
+            .unwrap();
+        assert_eq!(result, "Hi there.");
+        assert_eq!(receiver.recv().await.as_deref(), Some("Hi"));
+        assert_eq!(server.request().await.body["stream"], true);
+
+        let error = stream_delta(
+            r#"{"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}"#,
+        )
+        .unwrap_err();
+        assert!(
+            matches!(error, PostProcessError::Api(ref message) if message == "overloaded_error: Overloaded")
+        );
+    }
+}
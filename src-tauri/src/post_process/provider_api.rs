@@ -1,6 +1,8 @@
 use async_trait::async_trait;
 use serde_json::Value;
 
+use super::stream::PostProcessStream;
+use super::structured::OutputConstraint;
 use super::{PostProcessError, PostProcessService};
 
 pub struct APIPostProcessService {
@@ -9,64 +11,93 @@ pub struct APIPostProcessService {
     pub model: String,
 }
 
-#[async_trait]
-impl PostProcessService for APIPostProcessService {
-    async fn post_process(
+impl APIPostProcessService {
+    fn chat_request(
         &self,
         text: &str,
         system_prompt: &str,
         user_prompt_template: &str,
         max_output_tokens: u32,
-    ) -> Result<String, PostProcessError> {
+        stream: bool,
+        constraint: Option<&OutputConstraint>,
+    ) -> reqwest::RequestBuilder {
         let messages =
             super::prompt::build_post_process_messages(text, system_prompt, user_prompt_template);
 
-        let body = serde_json::json!({
+        let mut body = serde_json::json!({
             "model": self.model,
             "messages": messages,
             "max_tokens": super::prompt::max_output_tokens(text, max_output_tokens),
             "temperature": 0.0,
+            "stream": stream,
         });
+        if let Some(constraint) = constraint {
+            constraint.apply_to_openai(&mut body);
+        }
 
-        let client = reqwest::Client::new();
-        let response = client
+        reqwest::Client::new()
             .post(&self.api_url)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .json(&body)
-            .timeout(std::time::Duration::from_secs(30))
-            .send()
-            .await
-            .map_err(|e| PostProcessError::Network(format!("Request failed: {}", e)))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "unknown".to_string());
-            return Err(PostProcessError::Api(format!(
-                "API returned {}: {}",
-                status, body_text
-            )));
-        }
+    }
+}
 
-        let data: Value = response
-            .json()
-            .await
-            .map_err(|e| PostProcessError::Network(format!("Failed to parse response: {}", e)))?;
-
-        let cleaned = data["choices"][0]["message"]["content"]
-            .as_str()
-            .ok_or_else(|| PostProcessError::Api("No content in response".to_string()))?
-            .trim();
-        let cleaned = cleaned
-            .lines()
-            .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
-            .filter(|l| !l.is_empty())
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        Ok(cleaned)
+#[async_trait]
+impl PostProcessService for APIPostProcessService {
+    async fn post_process(
+        &self,
+        text: &str,
+        system_prompt: &str,
+        user_prompt_template: &str,
+        max_output_tokens: u32,
+    ) -> Result<String, PostProcessError> {
+        super::prompt::send_chat_request(self.chat_request(
+            text,
+            system_prompt,
+            user_prompt_template,
+            max_output_tokens,
+            false,
+            None,
+        ))
+        .await
+    }
+
+    async fn post_process_constrained(
+        &self,
+        text: &str,
+        system_prompt: &str,
+        user_prompt_template: &str,
+        max_output_tokens: u32,
+        constraint: &OutputConstraint,
+    ) -> Result<String, PostProcessError> {
+        super::prompt::send_chat_request(self.chat_request(
+            text,
+            system_prompt,
+            user_prompt_template,
+            max_output_tokens,
+            false,
+            Some(constraint),
+        ))
+        .await
+    }
+
+    async fn post_process_stream(
+        &self,
+        text: &str,
+        system_prompt: &str,
+        user_prompt_template: &str,
+        max_output_tokens: u32,
+        stream: &PostProcessStream,
+    ) -> Result<String, PostProcessError> {
+        let request = self.chat_request(
+            text,
+            system_prompt,
+            user_prompt_template,
+            max_output_tokens,
+            true,
+            None,
+        );
+        super::stream::send_streaming_request(request, stream).await
     }
 
     fn service_name(&self) -> &'static str {
@@ -118,16 +149,9 @@ pub async fn test_connection(
         .await
         .map_err(|e| format!("Failed to parse response: {}", e))?;
 
-    let cleaned = data["choices"][0]["message"]["content"]
+    let content = data["choices"][0]["message"]["content"]
         .as_str()
-        .ok_or_else(|| "No content in response".to_string())?
-        .trim();
-    let cleaned = cleaned
-        .lines()
-        .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
-        .filter(|l| !l.is_empty())
-        .collect::<Vec<_>>()
-        .join("\n");
-
-    Ok(cleaned)
+        .ok_or_else(|| "No content in response".to_string())?;
+
+    Ok(super::prompt::tidy_completion(content))
 }
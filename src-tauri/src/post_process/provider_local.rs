@@ -1,7 +1,9 @@
+use crate::config::Config;
 use crate::model_manager::ModelManager;
+use crate::post_process::stream::PostProcessStream;
+use crate::post_process::structured::OutputConstraint;
 use crate::post_process::{PostProcessError, PostProcessService};
 use async_trait::async_trait;
-use serde_json::Value;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
@@ -12,13 +14,101 @@ const BINARY_VERSION: &str = "b10331";
 const PORT_START: u16 = 6101;
 const PORT_END: u16 = 6200;
 const STARTUP_TIMEOUT: Duration = Duration::from_secs(120);
-const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// Cleanup must reproduce the full transcript, so the context has to hold
+/// input + output for a max-length dictation (see prompt::max_output_tokens).
+const DEFAULT_CONTEXT_SIZE: u32 = 8192;
+const CONTEXT_SIZE_MIN: u32 = 512;
+const CONTEXT_SIZE_MAX: u32 = 131_072;
+pub const DEFAULT_SERVER_URL: &str = "http://127.0.0.1:8080";
+
+/// Which llama-server runs the model.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LlamaServer {
+    /// The release Voquill downloads into its models directory.
+    Bundled,
+    /// A `llama-server` already installed on `PATH`.
+    System,
+    /// A server the user runs themselves; Voquill only sends requests, so
+    /// the model, template and context size are whatever it was started with.
+    Url(String),
+}
+
+/// Sampling parameters sent with every request. Unset ones are left to the
+/// server's defaults; temperature defaults to greedy decoding.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Sampling {
+    pub temperature: f64,
+    pub top_p: Option<f64>,
+    pub top_k: Option<u64>,
+    pub min_p: Option<f64>,
+    pub repeat_penalty: Option<f64>,
+}
+
+/// Local post-processing settings from the `llama.*` keys of
+/// `engine_config`. Part of the factory fingerprint, so changing any of them
+/// starts a fresh sidecar.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlamaOptions {
+    pub server: LlamaServer,
+    /// A built-in llama.cpp template name (e.g. `chatml`) or a path to a
+    /// Jinja template file. `None` uses the template embedded in the GGUF.
+    pub chat_template: Option<String>,
+    pub context_size: u32,
+    pub sampling: Sampling,
+}
+
+impl LlamaOptions {
+    pub fn from_config(config: &Config) -> Self {
+        let engine_config = config.engine_config.as_ref();
+        let get = |key: &str| engine_config.and_then(|ec| ec.get(key));
+        let text = |key: &str| {
+            get(key)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        // Values arrive as numbers from number fields and as strings from
+        // hand-edited config files.
+        let number = |key: &str| {
+            get(key).and_then(|v| {
+                v.as_f64()
+                    .or_else(|| v.as_str().and_then(|s| s.trim().parse().ok()))
+            })
+        };
+
+        let server = match text("llama.server").as_deref() {
+            Some("system") => LlamaServer::System,
+            Some("url") => LlamaServer::Url(
+                text("llama.server_url").unwrap_or_else(|| DEFAULT_SERVER_URL.to_string()),
+            ),
+            _ => LlamaServer::Bundled,
+        };
+
+        Self {
+            server,
+            chat_template: text("llama.chat_template"),
+            context_size: number("llama.context_size").map_or(DEFAULT_CONTEXT_SIZE, |n| {
+                (n as u32).clamp(CONTEXT_SIZE_MIN, CONTEXT_SIZE_MAX)
+            }),
+            sampling: Sampling {
+                temperature: number("llama.temperature").map_or(0.0, |t| t.clamp(0.0, 2.0)),
+                top_p: number("llama.top_p").map(|p| p.clamp(0.0, 1.0)),
+                top_k: number("llama.top_k").map(|k| k.max(0.0) as u64),
+                min_p: number("llama.min_p").map(|p| p.clamp(0.0, 1.0)),
+                repeat_penalty: number("llama.repeat_penalty").map(|p| p.clamp(0.0, 2.0)),
+            },
+        }
+    }
+}
 
 pub struct SidecarPostProcess {
-    port: u16,
+    base_url: String,
+    /// `None` when talking to a server the user runs themselves.
     _process: Arc<Mutex<Option<Child>>>,
-    model_size: String,
+    model: String,
     use_gpu: bool,
+    sampling: Sampling,
 }
 
 fn resolve_post_process_threads(threads_setting: &str) -> usize {
@@ -36,19 +126,98 @@ fn resolve_post_process_threads(threads_setting: &str) -> usize {
     max_cpus
 }
 
+/// Whether `model` names a GGUF file rather than a catalog model.
+pub fn is_custom_model(model: &str) -> bool {
+    model.to_ascii_lowercase().ends_with(".gguf")
+}
+
+/// The GGUF file to load: a downloaded catalog model, or any `.gguf` file
+/// when `model` is a path to one.
+fn resolve_model_path(engine_name: &str, model: &str) -> Result<PathBuf, PostProcessError> {
+    if is_custom_model(model) {
+        let path = PathBuf::from(model);
+        if !path.is_file() {
+            return Err(PostProcessError::Api(format!(
+                "Model file not found: {}",
+                path.display()
+            )));
+        }
+        return Ok(path);
+    }
+
+    let mgr = ModelManager::new()
+        .map_err(|e| PostProcessError::Api(format!("Failed to init model manager: {}", e)))?;
+
+    let catalog_model = ModelManager::find_model(engine_name, model).ok_or_else(|| {
+        PostProcessError::Api(format!(
+            "Model '{}' not found in catalog for engine '{}'",
+            model, engine_name
+        ))
+    })?;
+
+    let model_path = mgr.get_model_path(&catalog_model);
+    if !model_path.exists() {
+        return Err(PostProcessError::Api(format!(
+            "Model not downloaded: {}. Download it from Settings first.",
+            catalog_model.label
+        )));
+    }
+    Ok(model_path)
+}
+
+/// The model name sent with requests: the catalog size, or the file name of
+/// a custom GGUF.
+fn request_model_name(model: &str) -> String {
+    if is_custom_model(model) {
+        if let Some(stem) = std::path::Path::new(model).file_stem() {
+            return stem.to_string_lossy().to_string();
+        }
+    }
+    model.to_string()
+}
+
+/// llama-server arguments for a chat template: a built-in template name, or
+/// a Jinja file (which needs the Jinja engine enabled).
+fn chat_template_args(chat_template: &str) -> Vec<String> {
+    let is_file =
+        chat_template.ends_with(".jinja") || std::path::Path::new(chat_template).is_file();
+    if is_file {
+        vec![
+            "--jinja".to_string(),
+            "--chat-template-file".to_string(),
+            chat_template.to_string(),
+        ]
+    } else {
+        vec!["--chat-template".to_string(), chat_template.to_string()]
+    }
+}
+
+/// Accepts a server address with or without the OpenAI path suffix.
+fn server_base_url(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/');
+    let url = url.strip_suffix("/v1/chat/completions").unwrap_or(url);
+    let url = url.strip_suffix("/v1").unwrap_or(url);
+    url.to_string()
+}
+
 impl SidecarPostProcess {
-    /// Starts the llama-server sidecar for the given post-process engine. GPU
-    /// engines try the Vulkan build first and fall back to the CPU build,
-    /// recording the reason in `last_gpu_error` (the same fallback contract
-    /// as whisper GPU transcription).
+    /// Starts the llama-server sidecar for the given post-process engine, or
+    /// connects to the user's own server. GPU engines try the Vulkan build
+    /// first and fall back to the CPU build, recording the reason in
+    /// `last_gpu_error` (the same fallback contract as whisper GPU
+    /// transcription).
     pub async fn new(
         engine_name: &str,
-        model_size: &str,
+        model: &str,
         threads_setting: &str,
+        options: &LlamaOptions,
         last_gpu_error: Arc<std::sync::Mutex<Option<String>>>,
     ) -> Result<Self, PostProcessError> {
+        if let LlamaServer::Url(url) = &options.server {
+            return Self::connect(url, model, options).await;
+        }
         if crate::engine_factory::engine_uses_gpu(engine_name) {
-            match Self::start(engine_name, model_size, threads_setting, true).await {
+            match Self::start(engine_name, model, threads_setting, options, true).await {
                 Ok(service) => {
                     *last_gpu_error.lock().unwrap() = None;
                     return Ok(service);
@@ -62,53 +231,69 @@ impl SidecarPostProcess {
                 }
             }
         }
-        Self::start(engine_name, model_size, threads_setting, false).await
+        Self::start(engine_name, model, threads_setting, options, false).await
+    }
+
+    async fn connect(
+        url: &str,
+        model: &str,
+        options: &LlamaOptions,
+    ) -> Result<Self, PostProcessError> {
+        let base_url = server_base_url(url);
+        if !check_health(&base_url).await {
+            return Err(PostProcessError::Api(format!(
+                "No llama-server is responding at {}",
+                base_url
+            )));
+        }
+        crate::log_info!("Using llama-server at {}", base_url);
+        Ok(Self {
+            base_url,
+            _process: Arc::new(Mutex::new(None)),
+            model: request_model_name(model),
+            use_gpu: false,
+            sampling: options.sampling.clone(),
+        })
     }
 
     async fn start(
         engine_name: &str,
-        model_size: &str,
+        model: &str,
         threads_setting: &str,
+        options: &LlamaOptions,
         use_gpu: bool,
     ) -> Result<Self, PostProcessError> {
-        let binary_path = crate::sidecar::ensure_binary(
-            binary_dir(use_gpu)?,
-            &binary_name(),
-            download_spec(use_gpu)?,
-        )
-        .await
-        .map_err(|e| PostProcessError::Api(e.to_string()))?;
+        let binary_path = match options.server {
+            LlamaServer::System => {
+                crate::sidecar::find_on_path(&binary_name()).ok_or_else(|| {
+                    PostProcessError::Api(format!("{} was not found on PATH", binary_name()))
+                })?
+            }
+            _ => crate::sidecar::ensure_binary(
+                binary_dir(use_gpu)?,
+                &binary_name(),
+                download_spec(use_gpu)?,
+            )
+            .await
+            .map_err(|e| PostProcessError::Api(e.to_string()))?,
+        };
         let port = crate::sidecar::find_free_port(PORT_START, PORT_END)
             .await
             .map_err(|e| PostProcessError::Api(e.to_string()))?;
 
-        let mgr = ModelManager::new()
-            .map_err(|e| PostProcessError::Api(format!("Failed to init model manager: {}", e)))?;
-
-        let model = ModelManager::find_model(engine_name, model_size).ok_or_else(|| {
-            PostProcessError::Api(format!(
-                "Model '{}' not found in catalog for engine '{}'",
-                model_size, engine_name
-            ))
-        })?;
-
-        let model_path = mgr.get_model_path(&model);
-        if !model_path.exists() {
-            return Err(PostProcessError::Api(format!(
-                "Model not downloaded: {}. Download it from Settings first.",
-                model.label
-            )));
-        }
+        let model_path = resolve_model_path(engine_name, model)?;
 
         let num_threads = resolve_post_process_threads(threads_setting);
         crate::log_info!(
-            "Starting llama-server with model: {} (gpu={}, threads={})",
+            "Starting {} with model: {} (gpu={}, threads={}, context={})",
+            binary_path.display(),
             model_path.display(),
             use_gpu,
-            num_threads
+            num_threads,
+            options.context_size
         );
 
-        let args = vec![
+        let mut args = vec![
             "-m".to_string(),
             model_path.to_string_lossy().to_string(),
             "--host".to_string(),
@@ -119,26 +304,28 @@ impl SidecarPostProcess {
             if use_gpu { "99" } else { "0" }.to_string(),
             "-t".to_string(),
             num_threads.to_string(),
-            // Cleanup must reproduce the full transcript, so the context has
-            // to hold input + output for a max-length dictation (see
-            // prompt::max_output_tokens).
             "-c".to_string(),
-            "8192".to_string(),
+            options.context_size.to_string(),
         ];
+        if let Some(chat_template) = &options.chat_template {
+            args.extend(chat_template_args(chat_template));
+        }
         let mut child = crate::sidecar::spawn_sidecar(&binary_path, &args)
             .map_err(|e| PostProcessError::Api(format!("Failed to spawn llama-server: {}", e)))?;
 
+        let base_url = format!("http://127.0.0.1:{}", port);
         let stderr = child.stderr.take().unwrap();
-        let ready = wait_for_ready(stderr, port, STARTUP_TIMEOUT).await;
+        let ready = wait_for_ready(stderr, &base_url, STARTUP_TIMEOUT).await;
 
         match ready {
             Ok(()) => {
                 crate::log_info!("llama-server started on port {} (gpu={})", port, use_gpu);
                 Ok(Self {
-                    port,
+                    base_url,
                     _process: Arc::new(Mutex::new(Some(child))),
-                    model_size: model_size.to_string(),
+                    model: request_model_name(model),
                     use_gpu,
+                    sampling: options.sampling.clone(),
                 })
             }
             Err(e) => {
@@ -152,64 +339,105 @@ impl SidecarPostProcess {
     }
 }
 
-#[async_trait]
-impl PostProcessService for SidecarPostProcess {
-    async fn post_process(
+impl SidecarPostProcess {
+    fn chat_request(
         &self,
         text: &str,
         system_prompt: &str,
         user_prompt_template: &str,
         max_output_tokens: u32,
-    ) -> Result<String, PostProcessError> {
+        stream: bool,
+        constraint: Option<&OutputConstraint>,
+    ) -> reqwest::RequestBuilder {
         let messages =
             super::prompt::build_post_process_messages(text, system_prompt, user_prompt_template);
 
-        let body = serde_json::json!({
-            "model": self.model_size,
+        let mut body = serde_json::json!({
+            "model": self.model,
             "messages": messages,
             "max_tokens": super::prompt::max_output_tokens(text, max_output_tokens),
-            "temperature": 0.0,
+            "temperature": self.sampling.temperature,
+            "stream": stream,
         });
-
-        let url = format!("http://127.0.0.1:{}/v1/chat/completions", self.port);
-        let client = reqwest::Client::new();
-        let response = client
-            .post(&url)
-            .json(&body)
-            .timeout(REQUEST_TIMEOUT)
-            .send()
-            .await
-            .map_err(|e| PostProcessError::Network(format!("Request failed: {}", e)))?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "unknown".to_string());
-            return Err(PostProcessError::Api(format!(
-                "API returned {}: {}",
-                status, body_text
-            )));
+        let sampling = [
+            ("top_p", self.sampling.top_p.map(serde_json::Value::from)),
+            ("top_k", self.sampling.top_k.map(serde_json::Value::from)),
+            ("min_p", self.sampling.min_p.map(serde_json::Value::from)),
+            (
+                "repeat_penalty",
+                self.sampling.repeat_penalty.map(serde_json::Value::from),
+            ),
+        ];
+        for (key, value) in sampling {
+            if let Some(value) = value {
+                body[key] = value;
+            }
+        }
+        if let Some(constraint) = constraint {
+            constraint.apply_to_llama_server(&mut body);
         }
 
-        let data: Value = response
-            .json()
-            .await
-            .map_err(|e| PostProcessError::Network(format!("Failed to parse response: {}", e)))?;
-
-        let cleaned = data["choices"][0]["message"]["content"]
-            .as_str()
-            .ok_or_else(|| PostProcessError::Api("No content in response".to_string()))?
-            .trim();
-        let cleaned = cleaned
-            .lines()
-            .map(|l| l.split_whitespace().collect::<Vec<_>>().join(" "))
-            .filter(|l| !l.is_empty())
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        Ok(cleaned)
+        let url = format!("{}/v1/chat/completions", self.base_url);
+        reqwest::Client::new().post(&url).json(&body)
+    }
+}
+
+#[async_trait]
+impl PostProcessService for SidecarPostProcess {
+    async fn post_process(
+        &self,
+        text: &str,
+        system_prompt: &str,
+        user_prompt_template: &str,
+        max_output_tokens: u32,
+    ) -> Result<String, PostProcessError> {
+        super::prompt::send_chat_request(self.chat_request(
+            text,
+            system_prompt,
+            user_prompt_template,
+            max_output_tokens,
+            false,
+            None,
+        ))
+        .await
+    }
+
+    async fn post_process_constrained(
+        &self,
+        text: &str,
+        system_prompt: &str,
+        user_prompt_template: &str,
+        max_output_tokens: u32,
+        constraint: &OutputConstraint,
+    ) -> Result<String, PostProcessError> {
+        super::prompt::send_chat_request(self.chat_request(
+            text,
+            system_prompt,
+            user_prompt_template,
+            max_output_tokens,
+            false,
+            Some(constraint),
+        ))
+        .await
+    }
+
+    async fn post_process_stream(
+        &self,
+        text: &str,
+        system_prompt: &str,
+        user_prompt_template: &str,
+        max_output_tokens: u32,
+        stream: &PostProcessStream,
+    ) -> Result<String, PostProcessError> {
+        let request = self.chat_request(
+            text,
+            system_prompt,
+            user_prompt_template,
+            max_output_tokens,
+            true,
+            None,
+        );
+        super::stream::send_streaming_request(request, stream).await
     }
 
     fn service_name(&self) -> &'static str {
@@ -277,7 +505,7 @@ fn archive_name(use_gpu: bool) -> Result<&'static str, PostProcessError> {
 
 async fn wait_for_ready(
     stderr: tokio::process::ChildStderr,
-    port: u16,
+    base_url: &str,
     timeout: Duration,
 ) -> Result<(), String> {
     use tokio::io::AsyncBufReadExt;
@@ -300,7 +528,7 @@ async fn wait_for_ready(
                 }
             }
             Ok(Ok(None)) => {
-                let health = check_health(port).await;
+                let health = check_health(base_url).await;
                 if health {
                     return Ok(());
                 }
@@ -308,7 +536,7 @@ async fn wait_for_ready(
             }
             Ok(Err(e)) => return Err(format!("Error reading stderr: {}", e)),
             Err(_) => {
-                let health = check_health(port).await;
+                let health = check_health(base_url).await;
                 if health {
                     return Ok(());
                 }
@@ -316,7 +544,7 @@ async fn wait_for_ready(
         }
     }
 
-    let health = check_health(port).await;
+    let health = check_health(base_url).await;
     if health {
         return Ok(());
     }
@@ -326,10 +554,82 @@ async fn wait_for_ready(
     ))
 }
 
-async fn check_health(port: u16) -> bool {
-    let url = format!("http://127.0.0.1:{}/health", port);
+async fn check_health(base_url: &str) -> bool {
+    let url = format!("{}/health", base_url);
     match reqwest::get(&url).await {
         Ok(r) => r.status().is_success(),
         Err(_) => false,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options_default_to_bundled_server_and_greedy_sampling() {
+        let options = LlamaOptions::from_config(&Config::default());
+        assert_eq!(options.server, LlamaServer::Bundled);
+        assert_eq!(options.chat_template, None);
+        assert_eq!(options.context_size, DEFAULT_CONTEXT_SIZE);
+        assert_eq!(options.sampling, Sampling::default());
+    }
+
+    #[test]
+    fn test_options_read_engine_config() {
+        let config = Config {
+            engine_config: Some(serde_json::json!({
+                "whisper.num_threads": "4",
+                "llama.server": "url",
+                "llama.server_url": " http://192.168.1.5:8080/v1/ ",
+                "llama.chat_template": "chatml",
+                "llama.context_size": "1000000",
+                "llama.temperature": 0.7,
+                "llama.top_k": 20,
+                "llama.min_p": "",
+            })),
+            ..Default::default()
+        };
+        let options = LlamaOptions::from_config(&config);
+        assert_eq!(
+            options.server,
+            LlamaServer::Url("http://192.168.1.5:8080/v1/".to_string())
+        );
+        assert_eq!(options.chat_template.as_deref(), Some("chatml"));
+        assert_eq!(options.context_size, CONTEXT_SIZE_MAX);
+        assert_eq!(options.sampling.temperature, 0.7);
+        assert_eq!(options.sampling.top_k, Some(20));
+        assert_eq!(options.sampling.min_p, None);
+        assert_ne!(options, LlamaOptions::from_config(&Config::default()));
+    }
+
+    #[test]
+    fn test_custom_models_and_server_urls() {
+        assert!(is_custom_model("/models/Mistral-7B-Q4.GGUF"));
+        assert!(!is_custom_model("qwen2.5-1.5b-instruct"));
+        assert_eq!(request_model_name("/models/mistral.gguf"), "mistral");
+        assert_eq!(
+            request_model_name("qwen2.5-1.5b-instruct"),
+            "qwen2.5-1.5b-instruct"
+        );
+        assert!(matches!(
+            resolve_model_path("Post-Process (Local)", "/nonexistent/model.gguf"),
+            Err(PostProcessError::Api(ref m)) if m.starts_with("Model file not found")
+        ));
+
+        assert_eq!(
+            server_base_url("http://localhost:8080/v1/chat/completions"),
+            "http://localhost:8080"
+        );
+        assert_eq!(
+            server_base_url("http://localhost:8080/"),
+            "http://localhost:8080"
+        );
+
+        assert_eq!(chat_template_args("llama3"), ["--chat-template", "llama3"]);
+        assert_eq!(
+            chat_template_args("/templates/mine.jinja"),
+            ["--jinja", "--chat-template-file", "/templates/mine.jinja"]
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use async_trait::async_trait;
+use serde_json::Value;
+
+use super::structured::OutputConstraint;
+use super::{PostProcessError, PostProcessService};
+
+pub const DEFAULT_API_URL: &str = "http://localhost:11434/api/chat";
+
+/// Post-processing through Ollama's native `/api/chat` endpoint, which
+/// unlike its OpenAI-compatible endpoint accepts a JSON schema as `format`
+/// and reports errors as `{"error": "..."}`.
+pub struct OllamaPostProcessService {
+    /// Only sent when set, for Ollama instances behind an authenticating
+    /// proxy.
+    pub api_key: String,
+    pub api_url: String,
+    pub model: String,
+}
+
+/// Accepts either the full endpoint or just the server address
+/// (`http://host:11434`), which is how Ollama URLs are usually written.
+fn chat_url(api_url: &str) -> String {
+    let api_url = api_url.trim().trim_end_matches('/');
+    if api_url.is_empty() {
+        DEFAULT_API_URL.to_string()
+    } else if api_url.ends_with("/api/chat") {
+        api_url.to_string()
+    } else {
+        format!("{}/api/chat", api_url)
+    }
+}
+
+impl OllamaPostProcessService {
+    fn chat_request(
+        &self,
+        text: &str,
+        system_prompt: &str,
+        user_prompt_template: &str,
+        max_output_tokens: u32,
+        constraint: Option<&OutputConstraint>,
+    ) -> reqwest::RequestBuilder {
+        let messages =
+            super::prompt::build_post_process_messages(text, system_prompt, user_prompt_template);
+
+        let mut body = serde_json::json!({
+            "model": self.model,
+            "messages": messages,
+            "stream": false,
+            "options": {
+                "temperature": 0.0,
+                "num_predict": super::prompt::max_output_tokens(text, max_output_tokens),
+            },
+        });
+        if let Some(constraint) = constraint {
+            constraint.apply_to_ollama(&mut body);
+        }
+
+        let mut request = reqwest::Client::new()
+            .post(chat_url(&self.api_url))
+            .json(&body);
+        if !self.api_key.trim().is_empty() {
+            request = request.header("Authorization", format!("Bearer {}", self.api_key.trim()));
+        }
+        request
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<String, PostProcessError> {
+        let data = super::prompt::send_json_request(request, error_detail)
+            .await
+            .map_err(|e| match e {
+                PostProcessError::Network(message) => PostProcessError::Network(format!(
+                    "{} (is Ollama running at {}?)",
+                    message,
+                    chat_url(&self.api_url)
+                )),
+                PostProcessError::Api(message) if message.contains("not found") => {
+                    PostProcessError::Api(format!(
+                        "{} (run `ollama pull {}` first)",
+                        message, self.model
+                    ))
+                }
+                other => other,
+            })?;
+        response_text(&data)
+    }
+}
+
+fn error_detail(body: &Value) -> Option<String> {
+    body["error"].as_str().map(str::to_string)
+}
+
+fn response_text(data: &Value) -> Result<String, PostProcessError> {
+    if let Some(error) = error_detail(data) {
+        return Err(PostProcessError::Api(error));
+    }
+    if data["done_reason"] == "length" {
+        crate::log_warn!("Ollama response was cut off at the output token limit");
+    }
+    let content = data["message"]["content"]
+        .as_str()
+        .filter(|content| !content.trim().is_empty())
+        .ok_or_else(|| PostProcessError::Api("No content in response".to_string()))?;
+    Ok(super::prompt::tidy_completion(content))
+}
+
+#[async_trait]
+impl PostProcessService for OllamaPostProcessService {
+    async fn post_process(
+        &self,
+        text: &str,
+        system_prompt: &str,
+        user_prompt_template: &str,
+        max_output_tokens: u32,
+    ) -> Result<String, PostProcessError> {
+        self.send(self.chat_request(
+            text,
+            system_prompt,
+            user_prompt_template,
+            max_output_tokens,
+            None,
+        ))
+        .await
+    }
+
+    async fn post_process_constrained(
+        &self,
+        text: &str,
+        system_prompt: &str,
+        user_prompt_template: &str,
+        max_output_tokens: u32,
+        constraint: &OutputConstraint,
+    ) -> Result<String, PostProcessError> {
+        self.send(self.chat_request(
+            text,
+            system_prompt,
+            user_prompt_template,
+            max_output_tokens,
+            Some(constraint),
+        ))
+        .await
+    }
+
+    fn service_name(&self) -> &'static str {
+        "Post-Process (Ollama)"
+    }
+}
+
+pub async fn test_connection(
+    api_key: &str,
+    api_url: &str,
+    model: &str,
+    system_prompt: &str,
+) -> Result<String, String> {
+    let service = OllamaPostProcessService {
+        api_key: api_key.to_string(),
+        api_url: api_url.to_string(),
+        model: model.to_string(),
+    };
+    let default_template = crate::config::Config::default().post_process_user_prompt_template;
+    super::policy::with_timeout(
+        std::time::Duration::from_secs(30),
+        service.post_process(
+            "This is a test um sentence",
+            system_prompt,
+            &default_template,
+            256,
+        ),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::mock_server::MockServer;
+    use super::*;
+    use serde_json::json;
+
+    fn service(url: &str, api_key: &str) -> OllamaPostProcessService {
+        OllamaPostProcessService {
+            api_key: api_key.to_string(),
+            api_url: url.to_string(),
+            model: "llama3.2".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_chat_url_accepts_server_address() {
+        assert_eq!(chat_url(""), DEFAULT_API_URL);
+        assert_eq!(
+            chat_url("http://gpu-box:11434/"),
+            "http://gpu-box:11434/api/chat"
+        );
+        assert_eq!(
+            chat_url("http://gpu-box:11434/api/chat"),
+            "http://gpu-box:11434/api/chat"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_and_response_mapping() {
+        let server = MockServer::json(
+            "/api/chat",
+            "200 OK",
+            json!({
+                "model": "llama3.2",
+                "message": {"role": "assistant", "content": "  Hello,   world. "},
+                "done": true,
+                "done_reason": "stop"
+            }),
+        )
+        .await;
+        let base_url = server.url.trim_end_matches("/api/chat").to_string();
+
+        let result = service(&base_url, "")
+            .post_process("hello world", "Clean up.", "{transcript}", 50)
+            .await
+            .unwrap();
+        assert_eq!(result, "Hello, world.");
+
+        let request = server.request().await;
+        assert_eq!(request.path(), "/api/chat");
+        assert_eq!(request.header("authorization"), None);
+        assert_eq!(request.body["stream"], false);
+        assert_eq!(request.body["options"]["num_predict"], 50);
+        assert_eq!(request.body["messages"][0]["role"], "system");
+        assert_eq!(request.body["messages"][1]["content"], "hello world");
+        assert!(request.body.get("format").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_schema_is_sent_as_format_with_optional_auth() {
+        let server = MockServer::json(
+            "/api/chat",
+            "200 OK",
+            json!({"message": {"role": "assistant", "content": "{\"a\": 1}"}, "done": true}),
+        )
+        .await;
+        let constraint = OutputConstraint::JsonSchema(json!({"type": "object"}));
+        service(&server.url, "proxy-token")
+            .post_process_constrained("x", "Extract.", "{transcript}", 0, &constraint)
+            .await
+            .unwrap();
+
+        let request = server.request().await;
+        assert_eq!(request.body["format"], json!({"type": "object"}));
+        assert_eq!(
+            request.header("authorization").as_deref(),
+            Some("Bearer proxy-token")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_model_error_suggests_pull() {
+        let server = MockServer::json(
+            "/api/chat",
+            "404 Not Found",
+            json!({"error": "model \"llama3.2\" not found, try pulling it first"}),
+        )
+        .await;
+        let error = test_connection("", &server.url, "llama3.2", "Clean up.")
+            .await
+            .unwrap_err();
+        assert!(error.contains("404"), "{}", error);
+        assert!(error.contains("ollama pull llama3.2"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_server_is_a_network_error() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let error = service(&url, "")
+            .post_process("x", "Clean up.", "{transcript}", 0)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, PostProcessError::Network(_)));
+        assert!(error.to_string().contains("is Ollama running"));
+    }
+}
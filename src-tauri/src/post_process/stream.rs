@@ -0,0 +1,230 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use serde_json::Value;
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::PostProcessError;
+
+/// How often a pending request checks the cancel flag. Dropping the response
+/// closes the connection, which makes llama-server stop generating.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Where a streaming post-process request sends its tokens, and the flag
+/// that aborts it mid-generation (the dictation session's cancel token).
+pub struct PostProcessStream {
+    pub deltas: UnboundedSender<String>,
+    pub cancel: Arc<AtomicBool>,
+}
+
+async fn cancelled(flag: &AtomicBool) {
+    while !flag.load(Ordering::SeqCst) {
+        tokio::time::sleep(CANCEL_POLL_INTERVAL).await;
+    }
+}
+
+/// Splits a Server-Sent Events byte stream into `data:` payloads. Bytes are
+/// buffered until a full line arrives, so multi-byte characters split across
+/// network chunks decode correctly.
+#[derive(Default)]
+pub struct SseDecoder {
+    buffer: Vec<u8>,
+}
+
+impl SseDecoder {
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(bytes);
+        let mut payloads = Vec::new();
+        while let Some(newline) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=newline).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\r', '\n']);
+            if let Some(data) = line.strip_prefix("data:") {
+                payloads.push(data.trim_start().to_string());
+            }
+        }
+        payloads
+    }
+}
+
+/// Extracts the content delta from one OpenAI-style `chat.completion.chunk`.
+/// Role-only and finish chunks carry no content and yield `None`.
+pub fn delta_content(data: &str) -> Result<Option<String>, PostProcessError> {
+    let value: Value = serde_json::from_str(data)
+        .map_err(|e| PostProcessError::Api(format!("Malformed stream chunk: {}", e)))?;
+    if let Some(error) = value.get("error") {
+        let message = error["message"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| error.to_string());
+        return Err(PostProcessError::Api(message));
+    }
+    Ok(value["choices"][0]["delta"]["content"]
+        .as_str()
+        .filter(|content| !content.is_empty())
+        .map(str::to_string))
+}
+
+/// Sends a chat completion request with `"stream": true` already in its body
+/// and forwards every content delta to `stream` as it arrives. Returns the
+/// full tidied completion, or `Cancelled` as soon as the cancel flag is set.
+pub async fn send_streaming_request(
+    request: reqwest::RequestBuilder,
+    stream: &PostProcessStream,
+) -> Result<String, PostProcessError> {
+    send_streaming_request_with(request, stream, delta_content).await
+}
+
+/// `send_streaming_request` for providers whose events are not OpenAI
+/// chunks: `delta` extracts the text from one event's `data:` payload.
+pub async fn send_streaming_request_with(
+    request: reqwest::RequestBuilder,
+    stream: &PostProcessStream,
+    delta: fn(&str) -> Result<Option<String>, PostProcessError>,
+) -> Result<String, PostProcessError> {
+    let response = tokio::select! {
+        response = request.send() => response
+            .map_err(|e| PostProcessError::Network(format!("Request failed: {}", e)))?,
+        _ = cancelled(&stream.cancel) => return Err(PostProcessError::Cancelled),
+    };
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "unknown".to_string());
+        return Err(PostProcessError::Api(format!(
+            "API returned {}: {}",
+            status, body_text
+        )));
+    }
+
+    let mut body = response.bytes_stream();
+    let mut decoder = SseDecoder::default();
+    let mut content = String::new();
+    'read: loop {
+        let next = tokio::select! {
+            next = body.next() => next,
+            _ = cancelled(&stream.cancel) => return Err(PostProcessError::Cancelled),
+        };
+        let Some(chunk) = next else {
+            break;
+        };
+        let chunk =
+            chunk.map_err(|e| PostProcessError::Network(format!("Stream interrupted: {}", e)))?;
+        for data in decoder.push(&chunk) {
+            if data == "[DONE]" {
+                break 'read;
+            }
+            if let Some(delta) = delta(&data)? {
+                content.push_str(&delta);
+                let _ = stream.deltas.send(delta);
+            }
+        }
+    }
+
+    if content.trim().is_empty() {
+        return Err(PostProcessError::Api(
+            "No content in streamed response".to_string(),
+        ));
+    }
+    Ok(super::prompt::tidy_completion(&content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decoder_handles_split_lines_and_utf8() {
+        let mut decoder = SseDecoder::default();
+        let event = "data: {\"choices\":[{\"delta\":{\"content\":\"caf\u{e9}\"}}]}\n\n";
+        let bytes = event.as_bytes();
+        // Split inside the two-byte "é"
+        let split = event.find('\u{e9}').unwrap() + 1;
+        assert!(decoder.push(&bytes[..split]).is_empty());
+        let payloads = decoder.push(&bytes[split..]);
+        assert_eq!(payloads.len(), 1);
+        assert_eq!(
+            delta_content(&payloads[0]).unwrap(),
+            Some("caf\u{e9}".to_string())
+        );
+    }
+
+    #[test]
+    fn test_decoder_ignores_comments_and_crlf() {
+        let mut decoder = SseDecoder::default();
+        let payloads = decoder.push(b": keep-alive\r\n\r\ndata: [DONE]\r\n\r\n");
+        assert_eq!(payloads, vec!["[DONE]".to_string()]);
+    }
+
+    #[test]
+    fn test_delta_content_skips_role_and_finish_chunks() {
+        assert_eq!(
+            delta_content(r#"{"choices":[{"delta":{"role":"assistant"}}]}"#).unwrap(),
+            None
+        );
+        assert_eq!(
+            delta_content(r#"{"choices":[{"delta":{},"finish_reason":"stop"}]}"#).unwrap(),
+            None
+        );
+    }
+
+    /// Serves one streamed token and then stalls, like a model still
+    /// generating, so the request can only finish through cancellation.
+    async fn stalling_server() -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!(
+            "http://{}/v1/chat/completions",
+            listener.local_addr().unwrap()
+        );
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 4096];
+            let _ = socket.read(&mut request).await;
+            let event = "data: {\"choices\":[{\"delta\":{\"content\":\"Hello\"}}]}\n\n";
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ntransfer-encoding: chunked\r\n\r\n{:x}\r\n{}\r\n",
+                event.len(),
+                event
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            tokio::time::sleep(Duration::from_secs(30)).await;
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_cancel_stops_stream_mid_generation() {
+        let url = stalling_server().await;
+        let (deltas, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let stream = PostProcessStream {
+            deltas,
+            cancel: cancel.clone(),
+        };
+
+        let request = reqwest::Client::new().post(&url).body("{}");
+        let pending = tokio::spawn(async move { send_streaming_request(request, &stream).await });
+
+        assert_eq!(receiver.recv().await.as_deref(), Some("Hello"));
+        cancel.store(true, Ordering::SeqCst);
+        let result = tokio::time::timeout(Duration::from_secs(5), pending)
+            .await
+            .expect("cancel did not stop the stream")
+            .unwrap();
+        assert!(matches!(result, Err(PostProcessError::Cancelled)));
+    }
+
+    #[test]
+    fn test_delta_content_surfaces_stream_errors() {
+        let error = delta_content(r#"{"error":{"message":"context length exceeded"}}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("context length exceeded"));
+    }
+}
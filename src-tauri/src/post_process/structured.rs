@@ -0,0 +1,341 @@
+//! Structured-output presets. A preset can declare a JSON schema or a GBNF
+//! grammar that constrains what the model may generate; the JSON it returns
+//! is rendered to text through the preset's output template, and the parsed
+//! value is kept for history.
+
+use super::template::RenderedPrompts;
+use super::{PostProcessError, PostProcessService};
+use crate::config::{Config, PostProcessPrompt};
+use serde_json::Value;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputConstraint {
+    JsonSchema(Value),
+    Grammar(String),
+}
+
+impl OutputConstraint {
+    /// The preset's constraint. A schema takes precedence over a grammar; a
+    /// schema that is not valid JSON is logged and ignored.
+    pub fn for_prompt(prompt: &PostProcessPrompt) -> Option<Self> {
+        let non_empty = |field: &Option<String>| {
+            field
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        if let Some(schema) = non_empty(&prompt.json_schema) {
+            match serde_json::from_str(&schema) {
+                Ok(schema) => return Some(Self::JsonSchema(schema)),
+                Err(e) => crate::log_warn!(
+                    "Ignoring invalid JSON schema on prompt \"{}\": {}",
+                    prompt.name,
+                    e
+                ),
+            }
+        }
+        non_empty(&prompt.grammar).map(Self::Grammar)
+    }
+
+    /// llama-server takes the schema or grammar as top-level request fields.
+    pub fn apply_to_llama_server(&self, body: &mut Value) {
+        match self {
+            Self::JsonSchema(schema) => body["json_schema"] = schema.clone(),
+            Self::Grammar(grammar) => body["grammar"] = Value::String(grammar.clone()),
+        }
+    }
+
+    /// OpenAI-compatible APIs take a schema through `response_format`. They
+    /// have no GBNF support, so a grammar is left to the prompt.
+    pub fn apply_to_openai(&self, body: &mut Value) {
+        match self {
+            Self::JsonSchema(schema) => {
+                body["response_format"] = serde_json::json!({
+                    "type": "json_schema",
+                    "json_schema": { "name": "output", "schema": schema },
+                });
+            }
+            Self::Grammar(_) => {
+                crate::log_warn!(
+                    "API providers do not support GBNF grammars; relying on the prompt"
+                )
+            }
+        }
+    }
+
+    /// Ollama's native API takes a schema as `format`. Like OpenAI-compatible
+    /// APIs it has no GBNF support.
+    pub fn apply_to_ollama(&self, body: &mut Value) {
+        match self {
+            Self::JsonSchema(schema) => body["format"] = schema.clone(),
+            Self::Grammar(_) => {
+                crate::log_warn!("Ollama does not support GBNF grammars; relying on the prompt")
+            }
+        }
+    }
+
+    /// The Anthropic Messages API cannot constrain decoding, so the schema is
+    /// spelled out in the system prompt instead.
+    pub fn apply_to_system_prompt(&self, system_prompt: &str) -> String {
+        match self {
+            Self::JsonSchema(schema) => format!(
+                "{}\n\nRespond with only a JSON value matching this JSON schema, with no other text:\n{}",
+                system_prompt.trim_end(),
+                schema
+            ),
+            Self::Grammar(_) => {
+                crate::log_warn!("Anthropic does not support GBNF grammars; relying on the prompt");
+                system_prompt.to_string()
+            }
+        }
+    }
+}
+
+/// The selected preset's structured-output settings, if it has any.
+pub struct StructuredPreset {
+    pub constraint: OutputConstraint,
+    pub template: Option<String>,
+}
+
+impl StructuredPreset {
+    pub fn resolve(config: &Config) -> Option<Self> {
+        let prompt = config.selected_post_process_prompt()?;
+        Some(Self {
+            constraint: OutputConstraint::for_prompt(prompt)?,
+            template: prompt
+                .output_template
+                .clone()
+                .filter(|template| !template.trim().is_empty()),
+        })
+    }
+}
+
+pub struct StructuredOutput {
+    pub text: String,
+    /// The model output parsed as JSON; `None` when it was not JSON (a
+    /// grammar need not describe JSON), in which case `text` is the output.
+    pub value: Option<Value>,
+}
+
+/// Models behind an API sometimes wrap JSON in a Markdown code fence even
+/// when asked not to.
+fn parse_json(output: &str) -> Option<Value> {
+    let trimmed = output.trim();
+    let unfenced = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|inner| inner.strip_suffix("```"))
+        .unwrap_or(trimmed);
+    serde_json::from_str(unfenced.trim()).ok()
+}
+
+fn lookup<'a>(scopes: &[&'a Value], path: &str) -> Option<&'a Value> {
+    if path == "." {
+        return scopes.last().copied();
+    }
+    scopes.iter().rev().find_map(|scope| {
+        path.split('.').try_fold(*scope, |value, key| match value {
+            Value::Object(map) => map.get(key),
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        })
+    })
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items.iter().map(display).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
+
+fn is_tag(tag: &str) -> bool {
+    tag == "."
+        || (!tag.is_empty()
+            && tag
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '.'))
+}
+
+fn render_in(template: &str, scopes: &[&Value]) -> String {
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        output.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let Some(close) = after.find('}') else {
+            rest = &rest[open..];
+            break;
+        };
+        let tag = &after[..close];
+        rest = &after[close + 1..];
+
+        if let Some(name) = tag.strip_prefix('#').filter(|name| is_tag(name)) {
+            let end_tag = format!("{{/{}}}", name);
+            let (body, remainder) = match rest.find(&end_tag) {
+                Some(end) => (&rest[..end], &rest[end + end_tag.len()..]),
+                None => (rest, ""),
+            };
+            rest = remainder;
+            let items: Vec<&Value> = match lookup(scopes, name) {
+                Some(Value::Array(items)) => items.iter().collect(),
+                Some(Value::Null | Value::Bool(false)) | None => Vec::new(),
+                Some(Value::String(text)) if text.is_empty() => Vec::new(),
+                Some(value) => vec![value],
+            };
+            for item in items {
+                let mut inner = scopes.to_vec();
+                inner.push(item);
+                output.push_str(&render_in(body, &inner));
+            }
+        } else if is_tag(tag) {
+            if let Some(value) = lookup(scopes, tag) {
+                output.push_str(&display(value));
+            }
+        } else {
+            // Not a placeholder (e.g. literal braces); keep it as written.
+            output.push('{');
+            output.push_str(tag);
+            output.push('}');
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Renders `value` through `template`. `{field}` inserts a value (dotted
+/// paths reach into objects, lists are joined with commas),
+/// `{#list}...{/list}` repeats its body for every item with `{.}` standing
+/// for the item, and for a single value shows the body only when it is set.
+pub fn render(template: &str, value: &Value) -> String {
+    render_in(template, &[value])
+}
+
+fn to_output(raw: String, template: Option<&str>) -> StructuredOutput {
+    match parse_json(&raw) {
+        Some(value) => StructuredOutput {
+            text: match template {
+                Some(template) => render(template, &value).trim().to_string(),
+                None => serde_json::to_string_pretty(&value).unwrap_or(raw),
+            },
+            value: Some(value),
+        },
+        None => {
+            crate::log_warn!("Structured output was not JSON; using it as text");
+            StructuredOutput {
+                text: raw,
+                value: None,
+            }
+        }
+    }
+}
+
+/// Runs the preset in a single constrained request. Structured presets
+/// produce one value for the whole transcript, so they neither stream nor
+/// split long transcripts into chunks.
+pub async fn post_process(
+    processor: &Arc<dyn PostProcessService + Send + Sync>,
+    text: &str,
+    prompts: &RenderedPrompts,
+    preset: &StructuredPreset,
+    config: &Config,
+) -> Result<StructuredOutput, PostProcessError> {
+    let raw = processor
+        .post_process_constrained(
+            text,
+            &prompts.system_prompt,
+            &prompts.user_prompt_template,
+            config.resolve_max_output_tokens(),
+            &preset.constraint,
+        )
+        .await?;
+    Ok(to_output(raw, preset.template.as_deref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_schema_wins_over_grammar_and_invalid_schema_is_ignored() {
+        let mut prompt = PostProcessPrompt {
+            json_schema: Some(r#"{"type":"object"}"#.to_string()),
+            grammar: Some("root ::= \"yes\"".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            OutputConstraint::for_prompt(&prompt),
+            Some(OutputConstraint::JsonSchema(json!({"type": "object"})))
+        );
+
+        prompt.json_schema = Some("{not json".to_string());
+        assert_eq!(
+            OutputConstraint::for_prompt(&prompt),
+            Some(OutputConstraint::Grammar("root ::= \"yes\"".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_request_bodies() {
+        let schema = OutputConstraint::JsonSchema(json!({"type": "object"}));
+        let mut body = json!({"model": "m"});
+        schema.apply_to_llama_server(&mut body);
+        assert_eq!(body["json_schema"], json!({"type": "object"}));
+
+        let mut body = json!({"model": "m"});
+        schema.apply_to_openai(&mut body);
+        assert_eq!(body["response_format"]["type"], "json_schema");
+        assert_eq!(
+            body["response_format"]["json_schema"]["schema"],
+            json!({"type": "object"})
+        );
+
+        let mut body = json!({"model": "m"});
+        OutputConstraint::Grammar("root ::= \"a\"".to_string()).apply_to_llama_server(&mut body);
+        assert_eq!(body["grammar"], "root ::= \"a\"");
+    }
+
+    #[test]
+    fn test_render_fields_and_sections() {
+        let value = json!({
+            "subject": "Launch",
+            "body": "We ship Friday.",
+            "items": [{"owner": "Ana", "task": "notes"}, {"owner": "Bo", "task": "demo"}],
+            "tags": ["a", "b"],
+            "cc": null
+        });
+        assert_eq!(
+            render("Subject: {subject}\n\n{body}", &value),
+            "Subject: Launch\n\nWe ship Friday."
+        );
+        assert_eq!(
+            render("{#items}- {owner}: {task}\n{/items}", &value),
+            "- Ana: notes\n- Bo: demo\n"
+        );
+        assert_eq!(render("{tags} {#tags}[{.}]{/tags}", &value), "a, b [a][b]");
+        assert_eq!(render("{#cc}Cc: {cc}{/cc}{items.1.owner}", &value), "Bo");
+        assert_eq!(
+            render("keep {literal braces}", &value),
+            "keep {literal braces}"
+        );
+    }
+
+    #[test]
+    fn test_output_parses_fenced_json_and_passes_through_text() {
+        let output = to_output(
+            "```json\n{\"subject\": \"Hi\"}\n```".to_string(),
+            Some("Re: {subject}"),
+        );
+        assert_eq!(output.text, "Re: Hi");
+        assert_eq!(output.value, Some(json!({"subject": "Hi"})));
+
+        let output = to_output("plain yes".to_string(), Some("{x}"));
+        assert_eq!(output.text, "plain yes");
+        assert_eq!(output.value, None);
+    }
+}
@@ -0,0 +1,238 @@
+//! Variables for post-processing prompts. Both the system prompt and the user
+//! prompt template may reference `{date}`, `{time}`, `{language}`,
+//! `{focused_app}`, `{clipboard}`, `{previous_transcript}` and
+//! `{dictionary}`; `{transcript}` is substituted per request by
+//! `prompt::build_post_process_messages`.
+//!
+//! Rules:
+//! - `{name|fallback}` renders `fallback` when the value is missing or empty.
+//! - A line using a missing variable without a fallback is dropped, so
+//!   "Clipboard: {clipboard}" disappears instead of dangling. Lines holding
+//!   `{transcript}` are never dropped; the variable renders empty there.
+//! - Unknown names (and anything that is not a plain `{identifier}`, such as
+//!   JSON examples) are left untouched.
+
+use crate::config::Config;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::Serialize;
+
+static VARIABLE_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{([a-z_]+)(?:\|([^{}\n]*))?\}").unwrap());
+
+pub const TRANSCRIPT_VARIABLE: &str = "transcript";
+
+/// Every variable the engine knows, in the order shown to users.
+pub const VARIABLES: &[&str] = &[
+    "date",
+    "time",
+    "language",
+    "focused_app",
+    "clipboard",
+    "previous_transcript",
+    "dictionary",
+];
+
+/// Values for prompt variables. `None` means the value is unavailable (not
+/// captured, empty clipboard, no earlier dictation, ...).
+#[derive(Debug, Clone, Default)]
+pub struct PromptVariables {
+    pub date: Option<String>,
+    pub time: Option<String>,
+    pub language: Option<String>,
+    pub focused_app: Option<String>,
+    pub clipboard: Option<String>,
+    pub previous_transcript: Option<String>,
+    pub dictionary: Option<String>,
+}
+
+impl PromptVariables {
+    /// Collects values for the selected preset. The clipboard and history are
+    /// only read when a prompt actually references them. `focused_app` is
+    /// captured by the caller when the dictation starts, before focus can
+    /// move.
+    pub fn gather(config: &Config, focused_app: Option<String>) -> Self {
+        let prompts = [
+            config.resolve_post_process_prompt(),
+            config.resolve_user_prompt_template(),
+        ];
+        let referenced = |name: &str| prompts.iter().any(|prompt| references(prompt, name));
+
+        let now = chrono::Local::now();
+        Self {
+            date: Some(now.format("%Y-%m-%d").to_string()),
+            time: Some(now.format("%H:%M").to_string()),
+            language: (config.language != "auto").then(|| config.language.clone()),
+            focused_app,
+            clipboard: if referenced("clipboard") {
+                crate::typing::save_clipboard()
+            } else {
+                None
+            },
+            previous_transcript: if referenced("previous_transcript") {
+                previous_transcript()
+            } else {
+                None
+            },
+            dictionary: (!config.dictionary.is_empty()).then(|| config.dictionary.join(", ")),
+        }
+    }
+
+    fn value(&self, name: &str) -> Option<Option<&str>> {
+        let value = match name {
+            "date" => &self.date,
+            "time" => &self.time,
+            "language" => &self.language,
+            "focused_app" => &self.focused_app,
+            "clipboard" => &self.clipboard,
+            "previous_transcript" => &self.previous_transcript,
+            "dictionary" => &self.dictionary,
+            _ => return None,
+        };
+        Some(value.as_deref().map(str::trim).filter(|v| !v.is_empty()))
+    }
+}
+
+/// The most recent successful dictation or import in history.
+fn previous_transcript() -> Option<String> {
+    crate::history::load_history(10)
+        .ok()?
+        .into_iter()
+        .find(|item| item.status == "success" && !item.text.trim().is_empty())
+        .map(|item| item.text.trim().to_string())
+}
+
+/// Whether `template` uses the variable `name`.
+pub fn references(template: &str, name: &str) -> bool {
+    VARIABLE_PATTERN
+        .captures_iter(template)
+        .any(|caps| &caps[1] == name)
+}
+
+/// Names in `{...}` that look like variables but are not known, so the UI
+/// can point out typos such as `{clipbaord}`.
+pub fn unknown_variables(template: &str) -> Vec<String> {
+    let mut unknown: Vec<String> = VARIABLE_PATTERN
+        .captures_iter(template)
+        .map(|caps| caps[1].to_string())
+        .filter(|name| name != TRANSCRIPT_VARIABLE && !VARIABLES.contains(&name.as_str()))
+        .collect();
+    unknown.dedup();
+    unknown
+}
+
+/// Renders every known variable in `template`, leaving `{transcript}` in
+/// place for `build_post_process_messages`.
+pub fn render(template: &str, variables: &PromptVariables) -> String {
+    let mut lines = Vec::new();
+    for line in template.split('\n') {
+        let keeps_transcript = references(line, TRANSCRIPT_VARIABLE);
+        let mut missing = false;
+        let rendered = VARIABLE_PATTERN.replace_all(line, |caps: &Captures| {
+            let Some(value) = variables.value(&caps[1]) else {
+                return caps[0].to_string();
+            };
+            match (value, caps.get(2)) {
+                (Some(value), _) => value.to_string(),
+                (None, Some(fallback)) => fallback.as_str().to_string(),
+                (None, None) => {
+                    missing = true;
+                    String::new()
+                }
+            }
+        });
+        if missing && !keeps_transcript {
+            continue;
+        }
+        lines.push(rendered.into_owned());
+    }
+    lines.join("\n")
+}
+
+/// The selected preset's prompts with variables rendered.
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderedPrompts {
+    pub system_prompt: String,
+    pub user_prompt_template: String,
+}
+
+pub fn render_selected_prompts(config: &Config, variables: &PromptVariables) -> RenderedPrompts {
+    RenderedPrompts {
+        system_prompt: render(&config.resolve_post_process_prompt(), variables),
+        user_prompt_template: render(&config.resolve_user_prompt_template(), variables),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> PromptVariables {
+        PromptVariables {
+            date: Some("2026-10-18".to_string()),
+            language: Some("en-GB".to_string()),
+            clipboard: Some("  Hola, ¿cómo estás?  ".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_renders_known_variables() {
+        assert_eq!(
+            render("Today is {date}. Spelling: {language}.", &sample()),
+            "Today is 2026-10-18. Spelling: en-GB."
+        );
+        assert_eq!(
+            render("Thread:\n{clipboard}", &sample()),
+            "Thread:\nHola, ¿cómo estás?"
+        );
+    }
+
+    #[test]
+    fn test_missing_value_drops_line() {
+        assert_eq!(
+            render(
+                "Clean this up.\nActive app: {focused_app}\nBe brief.",
+                &sample()
+            ),
+            "Clean this up.\nBe brief."
+        );
+    }
+
+    #[test]
+    fn test_fallback_used_when_missing() {
+        assert_eq!(
+            render("App: {focused_app|unknown}", &sample()),
+            "App: unknown"
+        );
+        assert_eq!(render("Lang: {language|auto}", &sample()), "Lang: en-GB");
+    }
+
+    #[test]
+    fn test_unknown_variables_and_json_untouched() {
+        let template = r#"Reply as {"text": "..."} using {nickname}."#;
+        assert_eq!(render(template, &sample()), template);
+    }
+
+    #[test]
+    fn test_transcript_line_is_kept() {
+        assert_eq!(
+            render("{previous_transcript} {transcript}", &sample()),
+            " {transcript}"
+        );
+    }
+
+    #[test]
+    fn test_unknown_variables() {
+        assert_eq!(
+            unknown_variables("{clipbaord} {date} {transcript} {mood|calm}"),
+            vec!["clipbaord".to_string(), "mood".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_references() {
+        assert!(references("Use {clipboard|nothing}", "clipboard"));
+        assert!(!references("Use the clipboard", "clipboard"));
+    }
+}
@@ -0,0 +1,124 @@
+//! Spoken preset selection. A transcript that opens with one of a preset's
+//! trigger phrases ("Email: ...", "As a bullet list, ...") runs on that
+//! preset for the one session, with the phrase removed from the text.
+
+use crate::config::PostProcessPrompt;
+use regex::Regex;
+
+/// Separators a transcriber may put between a trigger and the dictation.
+const TRAILING_SEPARATORS: &str = r"[\s:;,.!?\-–—]*";
+
+pub struct TriggerMatch<'a> {
+    pub prompt: &'a PostProcessPrompt,
+    /// The transcript with the trigger phrase stripped.
+    pub text: String,
+}
+
+/// Matches the trigger words case-insensitively at the start of the text,
+/// with any punctuation or spacing between words ("e-mail" matches
+/// "E mail,").
+fn trigger_pattern(trigger: &str) -> Option<Regex> {
+    let words: Vec<String> = trigger
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(regex::escape)
+        .collect();
+    if words.is_empty() {
+        return None;
+    }
+    Regex::new(&format!(
+        r"(?i)^[^\p{{L}}\p{{N}}]*{}\b{}",
+        words.join(r"[^\p{L}\p{N}]+"),
+        TRAILING_SEPARATORS
+    ))
+    .ok()
+}
+
+fn capitalize_first(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Finds the preset whose trigger opens `text`. The longest trigger wins, so
+/// "email reply" beats "email". A trigger with nothing after it is not a
+/// match: the user said the word itself rather than selecting a preset.
+pub fn match_trigger<'a>(text: &str, prompts: &'a [PostProcessPrompt]) -> Option<TriggerMatch<'a>> {
+    let mut candidates: Vec<(&PostProcessPrompt, &str)> = prompts
+        .iter()
+        .flat_map(|prompt| {
+            prompt
+                .triggers
+                .iter()
+                .map(move |trigger| (prompt, trigger.trim()))
+        })
+        .filter(|(_, trigger)| !trigger.is_empty())
+        .collect();
+    candidates.sort_by_key(|(_, trigger)| std::cmp::Reverse(trigger.len()));
+
+    candidates.into_iter().find_map(|(prompt, trigger)| {
+        let found = trigger_pattern(trigger)?.find(text)?;
+        let rest = text[found.end()..].trim();
+        (!rest.is_empty()).then(|| TriggerMatch {
+            prompt,
+            text: capitalize_first(rest),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompts() -> Vec<PostProcessPrompt> {
+        vec![
+            PostProcessPrompt {
+                id: "email".to_string(),
+                name: "Email".to_string(),
+                triggers: vec!["email".to_string(), "e-mail".to_string()],
+                ..Default::default()
+            },
+            PostProcessPrompt {
+                id: "bullets".to_string(),
+                name: "Bullet List".to_string(),
+                triggers: vec!["as a bullet list".to_string()],
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_strips_trigger_and_separator() {
+        let prompts = prompts();
+        let found = match_trigger("Email: hi Bob, the build is green.", &prompts).unwrap();
+        assert_eq!(found.prompt.id, "email");
+        assert_eq!(found.text, "Hi Bob, the build is green.");
+
+        let found = match_trigger("As a bullet list, milk, eggs and bread", &prompts).unwrap();
+        assert_eq!(found.prompt.id, "bullets");
+        assert_eq!(found.text, "Milk, eggs and bread");
+    }
+
+    #[test]
+    fn test_tolerates_transcriber_punctuation() {
+        let prompts = prompts();
+        let found = match_trigger("E mail. Thanks for the update", &prompts).unwrap();
+        assert_eq!(found.prompt.id, "email");
+        assert_eq!(found.text, "Thanks for the update");
+    }
+
+    #[test]
+    fn test_requires_whole_words_at_start() {
+        let prompts = prompts();
+        assert!(match_trigger("Emails are piling up.", &prompts).is_none());
+        assert!(match_trigger("Send the email: tomorrow", &prompts).is_none());
+    }
+
+    #[test]
+    fn test_bare_trigger_is_not_a_match() {
+        let prompts = prompts();
+        assert!(match_trigger("Email.", &prompts).is_none());
+    }
+}
@@ -0,0 +1,427 @@
+//! Shareable post-processing presets. A preset file is versioned JSON that
+//! bundles any number of presets:
+//!
+//! ```json
+//! { "format": "voquill-presets", "version": 1, "presets": [ { "id": "...", ... } ] }
+//! ```
+//!
+//! Each preset has the fields of `PostProcessPrompt`, so everything a preset
+//! can configure (prompts, template, token limit, structured output, model
+//! overrides) travels with it. API keys, endpoints and providers never do: a
+//! shared file must not be able to point the user's own key, or their
+//! transcripts, at a server they did not choose. Presets are matched by `id`
+//! when a file is imported into an existing set.
+
+use crate::config::{PostProcessPrompt, PresetMetadata};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const FORMAT: &str = "voquill-presets";
+pub const FORMAT_VERSION: u32 = 1;
+
+const BUILTIN_PRESETS: &str = include_str!("../presets/builtin.json");
+
+/// What to do with an incoming preset whose `id` already exists.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the existing preset.
+    Skip,
+    /// Overwrite the existing preset, keeping only its local backend
+    /// (provider, endpoint and API key).
+    Replace,
+    /// Import the preset under a new id next to the existing one.
+    KeepBoth,
+    /// Overwrite the existing preset only when the incoming revision is
+    /// higher. Used to update presets from their source file.
+    Newer,
+}
+
+/// Names of the presets an import touched, by what happened to them.
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct ImportSummary {
+    pub added: Vec<String>,
+    pub replaced: Vec<String>,
+    pub renamed: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
+impl ImportSummary {
+    fn extend(&mut self, other: ImportSummary) {
+        self.added.extend(other.added);
+        self.replaced.extend(other.replaced);
+        self.renamed.extend(other.renamed);
+        self.skipped.extend(other.skipped);
+    }
+}
+
+/// The presets that ship with Voquill, in the same format as shared files.
+pub fn builtin() -> Vec<PostProcessPrompt> {
+    parse(BUILTIN_PRESETS).expect("built-in preset file is valid")
+}
+
+/// Reads a preset file. Files written by a newer Voquill are rejected rather
+/// than half-understood.
+pub fn parse(contents: &str) -> Result<Vec<PostProcessPrompt>, String> {
+    let file: Value =
+        serde_json::from_str(contents).map_err(|e| format!("Invalid preset file: {}", e))?;
+    if file["format"] != FORMAT {
+        return Err("Not a Voquill preset file".to_string());
+    }
+    let version = file["version"]
+        .as_u64()
+        .ok_or_else(|| "Preset file has no version".to_string())?;
+    if version == 0 || version > FORMAT_VERSION as u64 {
+        return Err(format!(
+            "Preset file version {} is not supported (this version of Voquill reads up to {})",
+            version, FORMAT_VERSION
+        ));
+    }
+
+    let entries = file["presets"]
+        .as_array()
+        .ok_or_else(|| "Preset file has no presets".to_string())?;
+    let mut presets: Vec<PostProcessPrompt> = Vec::with_capacity(entries.len());
+    for (index, entry) in entries.iter().enumerate() {
+        let mut preset: PostProcessPrompt = serde_json::from_value(entry.clone())
+            .map_err(|e| format!("Preset {} is invalid: {}", index + 1, e))?;
+        if preset.id.trim().is_empty() {
+            return Err(format!("Preset {} has no id", index + 1));
+        }
+        if presets.iter().any(|p| p.id == preset.id) {
+            return Err(format!(
+                "Preset id \"{}\" appears more than once",
+                preset.id
+            ));
+        }
+        if preset.api_url.is_some() || preset.provider.is_some() {
+            crate::log_warn!(
+                "Preset \"{}\" sets its own provider or endpoint; keeping the local backend",
+                preset.id
+            );
+        }
+        strip_local_fields(&mut preset);
+        presets.push(preset);
+    }
+    Ok(presets)
+}
+
+/// Clears what only makes sense on this machine: the backend the preset
+/// talks to, its API key and the file it was imported from.
+fn strip_local_fields(preset: &mut PostProcessPrompt) {
+    preset.provider = None;
+    preset.api_url = None;
+    preset.api_key = None;
+    if let Some(metadata) = &mut preset.metadata {
+        metadata.source = None;
+    }
+}
+
+/// Writes `presets` as a preset file, leaving out the local backend, API
+/// keys, local-only metadata and unset fields.
+pub fn export(presets: &[PostProcessPrompt]) -> Result<String, String> {
+    let presets = presets
+        .iter()
+        .map(|preset| {
+            let mut preset = preset.clone();
+            strip_local_fields(&mut preset);
+            let mut value = serde_json::to_value(&preset).map_err(|e| e.to_string())?;
+            if let Value::Object(fields) = &mut value {
+                fields.retain(|_, field| match field {
+                    Value::Null => false,
+                    Value::Array(items) => !items.is_empty(),
+                    _ => true,
+                });
+            }
+            Ok(value)
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    serde_json::to_string_pretty(&serde_json::json!({
+        "format": FORMAT,
+        "version": FORMAT_VERSION,
+        "presets": presets,
+    }))
+    .map_err(|e| e.to_string())
+}
+
+fn revision(preset: &PostProcessPrompt) -> u32 {
+    preset.metadata.as_ref().map_or(0, |m| m.revision)
+}
+
+fn unused_id(existing: &[PostProcessPrompt], id: &str) -> String {
+    let taken = |candidate: &str| existing.iter().any(|p| p.id == candidate);
+    let mut candidate = format!("{}-imported", id);
+    let mut n = 2;
+    while taken(&candidate) {
+        candidate = format!("{}-imported-{}", id, n);
+        n += 1;
+    }
+    candidate
+}
+
+/// Merges `incoming` into `existing` by id. `source` is recorded on every
+/// preset taken from the file so `update` can find it again.
+pub fn merge(
+    existing: &mut Vec<PostProcessPrompt>,
+    incoming: Vec<PostProcessPrompt>,
+    policy: ConflictPolicy,
+    source: Option<&str>,
+) -> ImportSummary {
+    let mut summary = ImportSummary::default();
+    for mut preset in incoming {
+        if let Some(source) = source {
+            preset
+                .metadata
+                .get_or_insert_with(PresetMetadata::default)
+                .source = Some(source.to_string());
+        }
+        let Some(index) = existing.iter().position(|p| p.id == preset.id) else {
+            summary.added.push(preset.name.clone());
+            existing.push(preset);
+            continue;
+        };
+        let replace = match policy {
+            ConflictPolicy::Skip => false,
+            ConflictPolicy::Replace => true,
+            ConflictPolicy::Newer => revision(&preset) > revision(&existing[index]),
+            ConflictPolicy::KeepBoth => {
+                preset.id = unused_id(existing, &preset.id);
+                preset.name = format!("{} (imported)", preset.name);
+                summary.renamed.push(preset.name.clone());
+                existing.push(preset);
+                continue;
+            }
+        };
+        if replace {
+            preset.provider = existing[index].provider.take();
+            preset.api_url = existing[index].api_url.take();
+            preset.api_key = existing[index].api_key.take();
+            summary.replaced.push(preset.name.clone());
+            existing[index] = preset;
+        } else {
+            summary.skipped.push(preset.name.clone());
+        }
+    }
+    summary
+}
+
+/// Imports the preset file at `path`.
+pub fn import_file(
+    existing: &mut Vec<PostProcessPrompt>,
+    path: &str,
+    policy: ConflictPolicy,
+) -> Result<ImportSummary, String> {
+    let contents =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let incoming = parse(&contents)?;
+    Ok(merge(existing, incoming, policy, Some(path)))
+}
+
+/// Updates presets from their source files: the given one, or every file a
+/// preset was imported from. Only newer revisions replace existing presets;
+/// presets new to a file are added. A source that cannot be read is an
+/// error only when it was asked for explicitly.
+pub fn update(
+    existing: &mut Vec<PostProcessPrompt>,
+    path: Option<&str>,
+) -> Result<ImportSummary, String> {
+    let sources: Vec<String> = match path {
+        Some(path) => vec![path.to_string()],
+        None => {
+            let mut sources = Vec::new();
+            for source in existing
+                .iter()
+                .filter_map(|p| p.metadata.as_ref()?.source.clone())
+            {
+                if !sources.contains(&source) {
+                    sources.push(source);
+                }
+            }
+            sources
+        }
+    };
+
+    let mut summary = ImportSummary::default();
+    for source in sources {
+        match import_file(existing, &source, ConflictPolicy::Newer) {
+            Ok(result) => summary.extend(result),
+            Err(e) if path.is_none() => {
+                crate::log_warn!("Skipping preset source {}: {}", source, e)
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preset(id: &str, revision: u32) -> PostProcessPrompt {
+        PostProcessPrompt {
+            id: id.to_string(),
+            name: id.to_uppercase(),
+            prompt: format!("{} prompt r{}", id, revision),
+            metadata: Some(PresetMetadata {
+                revision,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_builtin_presets_parse() {
+        let presets = builtin();
+        assert!(presets.iter().any(|p| p.id == "pirate"));
+    }
+
+    #[test]
+    fn test_export_round_trips_without_secrets() {
+        let mut shared = preset("email", 3);
+        shared.api_key = Some("sk-secret".to_string());
+        shared.triggers = vec!["email".to_string()];
+        shared.metadata.as_mut().unwrap().source = Some("/home/me/presets.json".to_string());
+
+        let file = export(&[shared.clone()]).unwrap();
+        assert!(!file.contains("sk-secret"));
+        assert!(!file.contains("/home/me"));
+        assert!(!file.contains("null"));
+
+        let parsed = parse(&file).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].prompt, shared.prompt);
+        assert_eq!(parsed[0].triggers, shared.triggers);
+        assert_eq!(parsed[0].api_key, None);
+        assert_eq!(revision(&parsed[0]), 3);
+    }
+
+    #[test]
+    fn test_parse_rejects_foreign_newer_and_duplicate_files() {
+        assert!(parse(r#"{"presets": []}"#).is_err());
+        assert!(
+            parse(r#"{"format": "voquill-presets", "version": 99, "presets": []}"#)
+                .unwrap_err()
+                .contains("99")
+        );
+        let duplicate = r#"{"format": "voquill-presets", "version": 1, "presets": [
+            {"id": "a", "name": "A", "prompt": "x"}, {"id": "a", "name": "B", "prompt": "y"}
+        ]}"#;
+        assert!(parse(duplicate).unwrap_err().contains("more than once"));
+    }
+
+    #[test]
+    fn test_import_keeps_the_local_backend() {
+        let file = r#"{"format": "voquill-presets", "version": 1, "presets": [
+            {"id": "team", "name": "Team", "prompt": "x", "provider": "API",
+             "api_url": "https://attacker.example/v1", "api_model": "gpt-4o"}
+        ]}"#;
+        let mut config = crate::config::Config {
+            post_process_api_key: "sk-mine".to_string(),
+            ..Default::default()
+        };
+        merge(
+            &mut config.post_process_prompts,
+            parse(file).unwrap(),
+            ConflictPolicy::Skip,
+            None,
+        );
+        config.post_process_selected_prompt_id = Some("team".to_string());
+
+        let target = config.resolve_post_process_target();
+        assert_eq!(target.api_url, config.post_process_api_url);
+        assert_eq!(target.provider, config.post_process_provider);
+        assert_eq!(target.api_key, "sk-mine");
+        assert_eq!(target.api_model, "gpt-4o");
+    }
+
+    #[test]
+    fn test_conflict_policies() {
+        let mut local = preset("email", 2);
+        local.api_key = Some("sk-local".to_string());
+        local.api_url = Some("https://local.example/v1".to_string());
+        let base = vec![local, preset("notes", 1)];
+
+        let mut existing = base.clone();
+        let summary = merge(
+            &mut existing,
+            vec![preset("email", 5), preset("todo", 1)],
+            ConflictPolicy::Skip,
+            None,
+        );
+        assert_eq!(summary.added, vec!["TODO"]);
+        assert_eq!(summary.skipped, vec!["EMAIL"]);
+        assert_eq!(existing[0].prompt, "email prompt r2");
+
+        let mut existing = base.clone();
+        merge(
+            &mut existing,
+            vec![preset("email", 1)],
+            ConflictPolicy::Replace,
+            Some("/shared/team.json"),
+        );
+        assert_eq!(existing[0].prompt, "email prompt r1");
+        assert_eq!(existing[0].api_key.as_deref(), Some("sk-local"));
+        assert_eq!(
+            existing[0].api_url.as_deref(),
+            Some("https://local.example/v1")
+        );
+        assert_eq!(
+            existing[0].metadata.as_ref().unwrap().source.as_deref(),
+            Some("/shared/team.json")
+        );
+
+        let mut existing = base.clone();
+        let summary = merge(
+            &mut existing,
+            vec![preset("email", 1), preset("notes", 2)],
+            ConflictPolicy::Newer,
+            None,
+        );
+        assert_eq!(summary.replaced, vec!["NOTES"]);
+        assert_eq!(summary.skipped, vec!["EMAIL"]);
+
+        let mut existing = base;
+        merge(
+            &mut existing,
+            vec![preset("email", 9), preset("email-imported", 1)],
+            ConflictPolicy::KeepBoth,
+            None,
+        );
+        let ids: Vec<&str> = existing.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "email",
+                "notes",
+                "email-imported",
+                "email-imported-imported"
+            ]
+        );
+        assert_eq!(existing[2].name, "EMAIL (imported)");
+    }
+
+    #[test]
+    fn test_update_reads_every_recorded_source() {
+        let dir = std::env::temp_dir().join(format!("voquill-presets-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("team.json");
+        let path_str = path.to_str().unwrap();
+        std::fs::write(&path, export(&[preset("email", 1)]).unwrap()).unwrap();
+
+        let mut existing = Vec::new();
+        import_file(&mut existing, path_str, ConflictPolicy::Skip).unwrap();
+
+        std::fs::write(
+            &path,
+            export(&[preset("email", 2), preset("todo", 1)]).unwrap(),
+        )
+        .unwrap();
+        let summary = update(&mut existing, None).unwrap();
+        assert_eq!(summary.replaced, vec!["EMAIL"]);
+        assert_eq!(summary.added, vec!["TODO"]);
+        assert_eq!(existing[0].prompt, "email prompt r2");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
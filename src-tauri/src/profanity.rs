@@ -0,0 +1,415 @@
+//! Optional masking of profanity and user-listed sensitive terms, for people
+//! who dictate into shared channels or get the occasional misheard swear word
+//! from the transcriber. Runs after `clean_transcription`; history keeps the
+//! unmasked text as `raw_text`.
+
+use crate::config::{Config, ProfanityMaskStyle};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::sync::Mutex;
+
+static MULTI_SPACE_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"[ \t]{2,}").unwrap());
+static SPACE_BEFORE_PUNCTUATION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[ \t]+([,.;:!?])").unwrap());
+
+/// Pattern for the last (language, custom words) pair seen. Streamed pieces
+/// and import segments are filtered one at a time with the same settings, so
+/// the alternation is compiled once per settings change, not once per call.
+static CACHED_PATTERN: Mutex<Option<CachedPattern>> = Mutex::new(None);
+
+struct CachedPattern {
+    language: String,
+    custom_words: Vec<String>,
+    pattern: Option<Regex>,
+}
+
+/// Built-in word lists keyed by base language code. Inflected forms are
+/// listed explicitly so matching stays whole-word and never hits substrings
+/// ("class", "Scunthorpe").
+const BUILTIN_WORDS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "fuck",
+            "fucks",
+            "fucked",
+            "fucker",
+            "fuckers",
+            "fucking",
+            "motherfucker",
+            "motherfuckers",
+            "shit",
+            "shits",
+            "shitty",
+            "bullshit",
+            "asshole",
+            "assholes",
+            "bitch",
+            "bitches",
+            "bastard",
+            "bastards",
+            "cunt",
+            "cunts",
+            "dick",
+            "dickhead",
+            "prick",
+            "piss",
+            "pissed",
+            "wanker",
+            "twat",
+            "bollocks",
+            "goddamn",
+            "damn",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "mierda",
+            "joder",
+            "jodido",
+            "coño",
+            "cabrón",
+            "cabrones",
+            "puta",
+            "puto",
+            "putas",
+            "gilipollas",
+            "pendejo",
+            "pendejos",
+            "carajo",
+            "chingar",
+            "chingada",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "merde", "putain", "connard", "connards", "connasse", "salope", "enculé", "enculés",
+            "bordel", "foutre", "chier",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "scheiße",
+            "scheisse",
+            "scheiß",
+            "arschloch",
+            "arschlöcher",
+            "fotze",
+            "wichser",
+            "hurensohn",
+            "verdammt",
+            "fick",
+            "ficken",
+            "miststück",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "cazzo",
+            "merda",
+            "stronzo",
+            "stronzi",
+            "vaffanculo",
+            "puttana",
+            "minchia",
+            "coglione",
+            "fottuto",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "merda", "porra", "caralho", "puta", "foda", "foder", "fodido", "cacete", "buceta",
+        ],
+    ),
+    (
+        "nl",
+        &[
+            "kut",
+            "klote",
+            "godverdomme",
+            "kanker",
+            "lul",
+            "klootzak",
+            "tering",
+            "hoer",
+        ],
+    ),
+];
+
+/// Built-in words for a configured language ("en-GB" -> "en"). "auto" and
+/// unknown codes use every list, since the spoken language is not known.
+fn builtin_words(language: &str) -> Vec<&'static str> {
+    let base = language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or("")
+        .to_lowercase();
+    let matching: Vec<&'static str> = BUILTIN_WORDS
+        .iter()
+        .filter(|(code, _)| *code == base)
+        .flat_map(|(_, words)| words.iter().copied())
+        .collect();
+    if matching.is_empty() {
+        BUILTIN_WORDS
+            .iter()
+            .flat_map(|(_, words)| words.iter().copied())
+            .collect()
+    } else {
+        matching
+    }
+}
+
+fn build_pattern(terms: &[String]) -> Option<Regex> {
+    let mut alternatives: Vec<String> = terms
+        .iter()
+        .map(|term| term.trim())
+        .filter(|term| !term.is_empty())
+        .map(|term| {
+            term.split_whitespace()
+                .map(regex::escape)
+                .collect::<Vec<_>>()
+                .join(r"\s+")
+        })
+        .collect();
+    if alternatives.is_empty() {
+        return None;
+    }
+    // Longest first so "motherfucker" wins over "fucker" at the same position
+    alternatives.sort_by_key(|alt| std::cmp::Reverse(alt.len()));
+    alternatives.dedup();
+    Regex::new(&format!(r"(?i)\b(?:{})\b", alternatives.join("|"))).ok()
+}
+
+fn mask_term(term: &str, style: ProfanityMaskStyle) -> String {
+    let mut kept_first = false;
+    term.chars()
+        .map(|c| {
+            if !c.is_alphanumeric() {
+                return c;
+            }
+            if style == ProfanityMaskStyle::FirstLetter && !kept_first {
+                kept_first = true;
+                return c;
+            }
+            '*'
+        })
+        .collect()
+}
+
+/// Masks every whole-word occurrence of `terms` in `text` using `style`.
+/// Removal also tidies the spacing left behind.
+pub fn mask_terms(text: &str, terms: &[String], style: ProfanityMaskStyle) -> String {
+    match build_pattern(terms) {
+        Some(pattern) => mask_with(text, &pattern, style),
+        None => text.to_string(),
+    }
+}
+
+fn mask_with(text: &str, pattern: &Regex, style: ProfanityMaskStyle) -> String {
+    if !pattern.is_match(text) {
+        return text.to_string();
+    }
+
+    match style {
+        ProfanityMaskStyle::Remove => {
+            let removed = pattern.replace_all(text, "");
+            let collapsed = MULTI_SPACE_PATTERN.replace_all(&removed, " ");
+            let tidied = SPACE_BEFORE_PUNCTUATION.replace_all(&collapsed, "$1");
+            tidied
+                .trim_start_matches([' ', '\t', ',', ';', ':'])
+                .trim()
+                .to_string()
+        }
+        _ => pattern
+            .replace_all(text, |caps: &regex::Captures| mask_term(&caps[0], style))
+            .to_string(),
+    }
+}
+
+fn configured_pattern(config: &Config) -> Option<Regex> {
+    let mut cache = CACHED_PATTERN
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(cached) = cache.as_ref() {
+        if cached.language == config.language
+            && cached.custom_words == config.profanity_custom_words
+        {
+            return cached.pattern.clone();
+        }
+    }
+
+    let mut terms: Vec<String> = builtin_words(&config.language)
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    terms.extend(config.profanity_custom_words.iter().cloned());
+    let pattern = build_pattern(&terms);
+    *cache = Some(CachedPattern {
+        language: config.language.clone(),
+        custom_words: config.profanity_custom_words.clone(),
+        pattern: pattern.clone(),
+    });
+    pattern
+}
+
+/// Applies the configured masking filter, or returns the text unchanged when
+/// it is disabled.
+pub fn apply_profanity_filter(text: &str, config: &Config) -> String {
+    if !config.profanity_filter_enabled || text.is_empty() {
+        return text.to_string();
+    }
+    let Some(pattern) = configured_pattern(config) else {
+        return text.to_string();
+    };
+
+    let masked = mask_with(text, &pattern, config.profanity_mask_style);
+    if masked != text {
+        crate::log_info!("Masked sensitive terms ({:?})", config.profanity_mask_style);
+    }
+    masked
+}
+
+/// `apply_profanity_filter` for one piece of a longer text typed as it
+/// streams in. The whitespace around the piece separates it from its
+/// neighbours, so removal only tidies the inside and keeps the edges.
+pub fn apply_profanity_filter_to_piece(piece: &str, config: &Config) -> String {
+    let core = piece.trim();
+    if core.is_empty() {
+        return piece.to_string();
+    }
+    let start = piece.len() - piece.trim_start().len();
+    let masked = apply_profanity_filter(core, config);
+    if masked.is_empty() {
+        return String::new();
+    }
+    format!(
+        "{}{}{}",
+        &piece[..start],
+        masked,
+        &piece[start + core.len()..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(list: &[&str]) -> Vec<String> {
+        list.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn test_asterisks_mask_whole_words_only() {
+        let terms = words(&["shit", "ass"]);
+        assert_eq!(
+            mask_terms(
+                "Oh Shit, the class passed.",
+                &terms,
+                ProfanityMaskStyle::Asterisks
+            ),
+            "Oh ****, the class passed."
+        );
+    }
+
+    #[test]
+    fn test_first_letter_style() {
+        let terms = words(&["fucking"]);
+        assert_eq!(
+            mask_terms(
+                "This fucking build.",
+                &terms,
+                ProfanityMaskStyle::FirstLetter
+            ),
+            "This f****** build."
+        );
+    }
+
+    #[test]
+    fn test_removal_tidies_spacing() {
+        let terms = words(&["damn"]);
+        assert_eq!(
+            mask_terms(
+                "Damn, that is a damn good idea.",
+                &terms,
+                ProfanityMaskStyle::Remove
+            ),
+            "that is a good idea."
+        );
+        assert_eq!(
+            mask_terms("It broke damn.", &terms, ProfanityMaskStyle::Remove),
+            "It broke."
+        );
+    }
+
+    #[test]
+    fn test_multi_word_custom_term() {
+        let terms = words(&["project falcon"]);
+        assert_eq!(
+            mask_terms(
+                "Status of Project  Falcon is green.",
+                &terms,
+                ProfanityMaskStyle::Asterisks
+            ),
+            "Status of *******  ****** is green."
+        );
+    }
+
+    #[test]
+    fn test_longest_term_wins() {
+        let terms = words(&["fucker", "motherfucker"]);
+        assert_eq!(
+            mask_terms("You motherfucker", &terms, ProfanityMaskStyle::FirstLetter),
+            "You m***********"
+        );
+    }
+
+    #[test]
+    fn test_builtin_words_follow_language() {
+        assert!(builtin_words("en-GB").contains(&"bollocks"));
+        assert!(!builtin_words("en-GB").contains(&"merde"));
+        assert!(builtin_words("fr").contains(&"merde"));
+        assert!(builtin_words("auto").contains(&"merde"));
+        assert!(builtin_words("auto").contains(&"shit"));
+    }
+
+    #[test]
+    fn test_filter_disabled_is_passthrough() {
+        let config = Config::default();
+        assert_eq!(apply_profanity_filter("well shit", &config), "well shit");
+    }
+
+    #[test]
+    fn test_filter_uses_builtin_and_custom_words() {
+        let config = Config {
+            profanity_filter_enabled: true,
+            profanity_custom_words: words(&["Hooli"]),
+            language: "en-US".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            apply_profanity_filter("Hooli shipped shit again.", &config),
+            "***** shipped **** again."
+        );
+    }
+
+    #[test]
+    fn test_removal_keeps_the_spacing_between_streamed_pieces() {
+        let config = Config {
+            profanity_filter_enabled: true,
+            profanity_mask_style: ProfanityMaskStyle::Remove,
+            language: "en-US".to_string(),
+            ..Default::default()
+        };
+        let pieces = ["That is done.", " Damn, next one is shit.", " Last."];
+        let typed: String = pieces
+            .iter()
+            .map(|piece| apply_profanity_filter_to_piece(piece, &config))
+            .collect();
+        assert_eq!(typed, "That is done. next one is. Last.");
+    }
+}
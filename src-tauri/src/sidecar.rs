@@ -123,6 +123,14 @@ async fn download_and_extract(
     Ok(())
 }
 
+/// Returns `binary_name` from the first `PATH` directory that contains it,
+/// for users who run their own build of a sidecar instead of the download.
+pub fn find_on_path(binary_name: &str) -> Option<PathBuf> {
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .map(|dir| dir.join(binary_name))
+        .find(|candidate| candidate.is_file())
+}
+
 /// Returns the first TCP port in `[start, end]` with nothing listening on
 /// localhost.
 pub async fn find_free_port(start: u16, end: u16) -> anyhow::Result<u16> {
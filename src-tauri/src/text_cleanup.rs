@@ -145,6 +145,319 @@ pub fn clean_transcription(
     result
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IdentifierCase {
+    Camel,
+    Pascal,
+    Snake,
+    Kebab,
+    ScreamingSnake,
+}
+
+const CASING_COMMANDS: &[(&[&str], IdentifierCase)] = &[
+    (&["camel", "case"], IdentifierCase::Camel),
+    (&["pascal", "case"], IdentifierCase::Pascal),
+    (&["snake", "case"], IdentifierCase::Snake),
+    (&["kebab", "case"], IdentifierCase::Kebab),
+    (
+        &["screaming", "snake", "case"],
+        IdentifierCase::ScreamingSnake,
+    ),
+    (&["screaming", "snake"], IdentifierCase::ScreamingSnake),
+    (&["constant", "case"], IdentifierCase::ScreamingSnake),
+];
+
+/// Spoken symbol names for code dictation: (words, text, space before, space
+/// after). A space is only emitted between two pieces when the left one wants
+/// a space after it and the right one wants a space before it.
+const CODE_SYMBOLS: &[(&[&str], &str, bool, bool)] = &[
+    (&["arrow"], "->", true, true),
+    (&["fat", "arrow"], "=>", true, true),
+    (&["double", "colon"], "::", false, false),
+    (&["colon"], ":", false, true),
+    (&["semicolon"], ";", false, true),
+    (&["comma"], ",", false, true),
+    (&["dot"], ".", false, false),
+    (&["open", "brace"], "{", true, true),
+    (&["close", "brace"], "}", true, true),
+    (&["open", "paren"], "(", false, false),
+    (&["open", "parenthesis"], "(", false, false),
+    (&["close", "paren"], ")", false, true),
+    (&["close", "parenthesis"], ")", false, true),
+    (&["open", "bracket"], "[", false, false),
+    (&["close", "bracket"], "]", false, true),
+    (&["open", "angle"], "<", false, false),
+    (&["close", "angle"], ">", false, true),
+    (&["equals"], "=", true, true),
+    (&["double", "equals"], "==", true, true),
+    (&["triple", "equals"], "===", true, true),
+    (&["not", "equals"], "!=", true, true),
+    (&["plus", "equals"], "+=", true, true),
+    (&["minus", "equals"], "-=", true, true),
+    (&["less", "than"], "<", true, true),
+    (&["greater", "than"], ">", true, true),
+    (&["less", "than", "or", "equal"], "<=", true, true),
+    (&["greater", "than", "or", "equal"], ">=", true, true),
+    (&["plus"], "+", true, true),
+    (&["plus", "plus"], "++", false, true),
+    (&["minus"], "-", true, true),
+    (&["star"], "*", true, true),
+    (&["asterisk"], "*", true, true),
+    (&["slash"], "/", false, false),
+    (&["backslash"], "\\", false, false),
+    (&["percent"], "%", true, true),
+    (&["double", "ampersand"], "&&", true, true),
+    (&["ampersand"], "&", true, false),
+    (&["double", "pipe"], "||", true, true),
+    (&["pipe"], "|", true, true),
+    (&["bang"], "!", true, false),
+    (&["question", "mark"], "?", false, true),
+    (&["underscore"], "_", false, false),
+    (&["dash"], "-", false, false),
+    (&["hash"], "#", true, false),
+    (&["dollar"], "$", true, false),
+    (&["caret"], "^", true, true),
+    (&["tilde"], "~", true, false),
+    (&["at", "sign"], "@", true, false),
+    (&["quote"], "\"", true, false),
+    (&["end", "quote"], "\"", false, true),
+    (&["single", "quote"], "'", true, false),
+    (&["end", "single", "quote"], "'", false, true),
+    (&["backtick"], "`", false, false),
+    (&["new", "line"], "\n", false, false),
+    (&["tab"], "\t", false, false),
+    (&["space"], " ", false, false),
+];
+
+const CODE_LITERAL_COMMAND: &str = "literal";
+const CODE_SPELL_COMMAND: &str = "spell";
+
+struct CodeToken {
+    /// Lowercased core used to match commands and symbol names.
+    key: String,
+    /// Core with the original casing, used when the word is emitted verbatim.
+    core: String,
+    /// Whisper ended a clause after this word (trailing `.`, `,`, `?` ...).
+    ends_clause: bool,
+}
+
+enum CodePiece {
+    Text(String),
+    Symbol(&'static str, bool, bool),
+}
+
+fn tokenize_for_code(text: &str) -> Vec<CodeToken> {
+    text.split_whitespace()
+        .map(|raw| {
+            let core = raw.trim_matches(|c: char| !c.is_alphanumeric());
+            if core.is_empty() {
+                // Whisper occasionally writes a symbol itself ("=" or "->");
+                // keep it verbatim instead of dropping it.
+                return CodeToken {
+                    key: String::new(),
+                    core: raw.to_string(),
+                    ends_clause: false,
+                };
+            }
+            let trailing = &raw[raw.rfind(core).unwrap_or(0) + core.len()..];
+            CodeToken {
+                key: core.to_lowercase(),
+                core: core.to_string(),
+                ends_clause: trailing
+                    .chars()
+                    .any(|c| matches!(c, '.' | ',' | '?' | '!' | ';' | ':')),
+            }
+        })
+        .collect()
+}
+
+fn phrase_matches(tokens: &[CodeToken], start: usize, words: &[&str]) -> bool {
+    tokens.len() >= start + words.len()
+        && words.iter().enumerate().all(|(offset, word)| {
+            let token = &tokens[start + offset];
+            // Whisper punctuation inside a multi-word phrase breaks it
+            token.key == *word && (offset + 1 == words.len() || !token.ends_clause)
+        })
+}
+
+fn match_casing_command(tokens: &[CodeToken], start: usize) -> Option<(usize, IdentifierCase)> {
+    CASING_COMMANDS
+        .iter()
+        .filter(|(words, _)| phrase_matches(tokens, start, words))
+        .max_by_key(|(words, _)| words.len())
+        .map(|(words, case)| (words.len(), *case))
+}
+
+fn match_code_symbol(
+    tokens: &[CodeToken],
+    start: usize,
+) -> Option<(usize, &'static str, bool, bool)> {
+    CODE_SYMBOLS
+        .iter()
+        .filter(|(words, ..)| phrase_matches(tokens, start, words))
+        .max_by_key(|(words, ..)| words.len())
+        .map(|(words, text, before, after)| (words.len(), *text, *before, *after))
+}
+
+fn is_code_command_start(tokens: &[CodeToken], index: usize) -> bool {
+    let key = tokens[index].key.as_str();
+    key.is_empty()
+        || key == CODE_LITERAL_COMMAND
+        || key == CODE_SPELL_COMMAND
+        || match_casing_command(tokens, index).is_some()
+        || match_code_symbol(tokens, index).is_some()
+}
+
+fn capitalize_word(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+fn format_identifier(words: &[String], case: IdentifierCase) -> String {
+    match case {
+        IdentifierCase::Camel => words
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                if i == 0 {
+                    w.to_lowercase()
+                } else {
+                    capitalize_word(w)
+                }
+            })
+            .collect(),
+        IdentifierCase::Pascal => words.iter().map(|w| capitalize_word(w)).collect(),
+        IdentifierCase::Snake => words
+            .iter()
+            .map(|w| w.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("_"),
+        IdentifierCase::Kebab => words
+            .iter()
+            .map(|w| w.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("-"),
+        IdentifierCase::ScreamingSnake => words
+            .iter()
+            .map(|w| w.to_uppercase())
+            .collect::<Vec<_>>()
+            .join("_"),
+    }
+}
+
+fn is_title_case(word: &str) -> bool {
+    let mut chars = word.chars();
+    chars.next().is_some_and(char::is_uppercase) && chars.all(|c| !c.is_uppercase())
+}
+
+/// Turns a spoken code dictation into source text. Supports identifier
+/// casing commands ("camel case user id" -> `userId`), spoken symbol names
+/// ("double colon", "open brace"), `literal <word>` to emit a command word as
+/// plain text, and `spell a b c` to type single characters back to back.
+/// Sentence punctuation inserted by the transcriber is dropped, and a
+/// clause-initial capital it added ("Return") is lowered.
+pub fn apply_code_dictation(text: &str) -> String {
+    let tokens = tokenize_for_code(text);
+    let mut pieces: Vec<CodePiece> = Vec::new();
+    let mut clause_start = true;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = &tokens[i];
+
+        if token.key.is_empty() {
+            pieces.push(CodePiece::Text(token.core.clone()));
+            i += 1;
+            continue;
+        }
+
+        if token.key == CODE_LITERAL_COMMAND && i + 1 < tokens.len() {
+            pieces.push(CodePiece::Text(tokens[i + 1].core.clone()));
+            clause_start = tokens[i + 1].ends_clause;
+            i += 2;
+            continue;
+        }
+
+        if token.key == CODE_SPELL_COMMAND {
+            let mut spelled = String::new();
+            let mut next = i + 1;
+            while next < tokens.len() && tokens[next].key.chars().count() == 1 {
+                spelled.push_str(&tokens[next].key);
+                next += 1;
+            }
+            if !spelled.is_empty() {
+                pieces.push(CodePiece::Text(spelled));
+                clause_start = tokens[next - 1].ends_clause;
+                i = next;
+                continue;
+            }
+        }
+
+        if let Some((consumed, case)) = match_casing_command(&tokens, i) {
+            let mut words: Vec<String> = Vec::new();
+            let mut next = i + consumed;
+            if !tokens[next - 1].ends_clause {
+                while next < tokens.len() && !is_code_command_start(&tokens, next) {
+                    words.extend(
+                        tokens[next]
+                            .core
+                            .split(|c: char| !c.is_alphanumeric())
+                            .filter(|part| !part.is_empty())
+                            .map(str::to_string),
+                    );
+                    next += 1;
+                    if tokens[next - 1].ends_clause {
+                        break;
+                    }
+                }
+            }
+            if !words.is_empty() {
+                pieces.push(CodePiece::Text(format_identifier(&words, case)));
+            }
+            clause_start = tokens[next - 1].ends_clause;
+            i = next;
+            continue;
+        }
+
+        if let Some((consumed, symbol, before, after)) = match_code_symbol(&tokens, i) {
+            pieces.push(CodePiece::Symbol(symbol, before, after));
+            clause_start = tokens[i + consumed - 1].ends_clause;
+            i += consumed;
+            continue;
+        }
+
+        let word = if clause_start && is_title_case(&token.core) {
+            token.core.to_lowercase()
+        } else {
+            token.core.clone()
+        };
+        pieces.push(CodePiece::Text(word));
+        clause_start = token.ends_clause;
+        i += 1;
+    }
+
+    let mut output = String::new();
+    let mut previous_wants_space = false;
+    for piece in pieces {
+        let (text, before, after) = match piece {
+            CodePiece::Text(text) => (text, true, true),
+            CodePiece::Symbol(text, before, after) => (text.to_string(), before, after),
+        };
+        if !output.is_empty() && previous_wants_space && before {
+            output.push(' ');
+        }
+        output.push_str(&text);
+        previous_wants_space = after;
+    }
+    output
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -290,4 +603,67 @@ mod tests {
         let input = "Hello, world! This is a test: 123 items.";
         assert_eq!(normalize_transcription_output(input), input);
     }
+
+    #[test]
+    fn test_code_dictation_casing_commands() {
+        assert_eq!(apply_code_dictation("camel case user id"), "userId");
+        assert_eq!(
+            apply_code_dictation("Pascal case http client."),
+            "HttpClient"
+        );
+        assert_eq!(
+            apply_code_dictation("snake case max retries"),
+            "max_retries"
+        );
+        assert_eq!(
+            apply_code_dictation("kebab case main nav bar"),
+            "main-nav-bar"
+        );
+        assert_eq!(
+            apply_code_dictation("screaming snake case max retries"),
+            "MAX_RETRIES"
+        );
+    }
+
+    #[test]
+    fn test_code_dictation_casing_stops_at_symbol() {
+        assert_eq!(
+            apply_code_dictation("Let camel case user id equals snake case max retries."),
+            "let userId = max_retries"
+        );
+    }
+
+    #[test]
+    fn test_code_dictation_casing_stops_at_clause_punctuation() {
+        assert_eq!(
+            apply_code_dictation("snake case user name, value"),
+            "user_name value"
+        );
+    }
+
+    #[test]
+    fn test_code_dictation_symbols() {
+        assert_eq!(
+            apply_code_dictation("std double colon vec double colon new open paren close paren"),
+            "std::vec::new()"
+        );
+        assert_eq!(
+            apply_code_dictation("fn main open paren close paren arrow result open brace"),
+            "fn main() -> result {"
+        );
+    }
+
+    #[test]
+    fn test_code_dictation_literal_and_spell() {
+        assert_eq!(apply_code_dictation("literal arrow"), "arrow");
+        assert_eq!(apply_code_dictation("spell X, Y, Z."), "xyz");
+    }
+
+    #[test]
+    fn test_code_dictation_plain_words_pass_through() {
+        assert_eq!(
+            apply_code_dictation("Return result dot unwrap open paren close paren"),
+            "return result.unwrap()"
+        );
+    }
 }
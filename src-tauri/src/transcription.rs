@@ -2,6 +2,8 @@ use async_trait::async_trait;
 use reqwest::multipart;
 use serde_json::Value;
 
+use crate::audio::whisper_file::{WhisperFile, WHISPER_SAMPLE_RATE};
+
 #[derive(Debug)]
 pub enum TranscriptionError {
     Network(String),
@@ -32,6 +34,45 @@ pub trait TranscriptionService {
     fn service_name(&self) -> &'static str;
 }
 
+/// Transcribes a whisper file one window at a time (see
+/// `WhisperFile::transcription_windows`), so only one window's WAV is in
+/// memory however long the recording is, and joins the text.
+pub async fn transcribe_file(
+    service: &(dyn TranscriptionService + Send + Sync),
+    audio: &WhisperFile,
+    language: Option<&str>,
+    prompt: Option<&str>,
+) -> Result<String, TranscriptionError> {
+    let windows = audio
+        .transcription_windows()
+        .map_err(TranscriptionError::Audio)?;
+    if let [(start, end)] = windows[..] {
+        let wav = audio
+            .window_wav(start, end)
+            .map_err(TranscriptionError::Audio)?;
+        return service.transcribe(&wav, language, prompt).await;
+    }
+
+    let mut parts = Vec::new();
+    for (index, &(start, end)) in windows.iter().enumerate() {
+        crate::log_info!(
+            "Transcribing window {}/{} ({:.1}s-{:.1}s)",
+            index + 1,
+            windows.len(),
+            start as f64 / WHISPER_SAMPLE_RATE as f64,
+            end as f64 / WHISPER_SAMPLE_RATE as f64
+        );
+        let wav = audio
+            .window_wav(start, end)
+            .map_err(TranscriptionError::Audio)?;
+        let text = service.transcribe(&wav, language, prompt).await?;
+        if !text.trim().is_empty() {
+            parts.push(text.trim().to_string());
+        }
+    }
+    Ok(parts.join(" "))
+}
+
 pub struct APITranscriptionService {
     pub api_key: String,
     pub api_url: String,
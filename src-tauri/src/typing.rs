@@ -28,8 +28,37 @@ pub fn copy_to_clipboard(text: &str) -> Result<(), Box<dyn std::error::Error + S
     Ok(())
 }
 
+pub fn read_clipboard() -> Option<String> {
+    Clipboard::new().ok().and_then(|mut c| c.get_text().ok())
+}
+
+pub fn clear_clipboard() {
+    if let Err(e) = Clipboard::new().and_then(|mut c| c.clear()) {
+        crate::log_warn!("Failed to clear clipboard: {}", e);
+    }
+}
+
+/// Reads the PRIMARY selection, i.e. the text currently highlighted. Only
+/// Linux has one; elsewhere this returns `None`.
+pub fn read_primary_selection() -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        use arboard::{GetExtLinux, LinuxClipboardKind};
+        Clipboard::new()
+            .ok()?
+            .get()
+            .clipboard(LinuxClipboardKind::Primary)
+            .text()
+            .ok()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
 pub fn save_clipboard() -> Option<String> {
-    let saved = Clipboard::new().ok().and_then(|mut c| c.get_text().ok());
+    let saved = read_clipboard();
     match &saved {
         Some(text) => {
             crate::log_info!("Saved clipboard content ({} chars)", text.len());
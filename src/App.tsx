@@ -177,6 +177,9 @@ function App() {
     onMicVolume: audioSetup.setMicVolume,
     onDownloadProgress: (progress) => { configHook.setDownloadProgress(progress); },
     onPostProcessGpuStatusChanged: () => { void gpuHook.refreshPostProcessGpuStatus(); },
+    onAudioDeviceStatus: (payload) => {
+      showToast(payload.message, payload.state === 'lost' ? 'error' : payload.state === 'fallback' ? 'info' : 'success');
+    },
     // Focus is the natural re-probe boundary: external changes that affect
     // readiness (models deleted, mic unplugged, permissions revoked) happen
     // while the app is unfocused.
@@ -336,6 +339,7 @@ function App() {
           onTestCleanupApi={() => {
             const cfg = configHook.config;
             invoke('test_cleanup_api', {
+              provider: cfg.post_process_provider,
               apiKey: cfg.post_process_api_key,
               apiUrl: cfg.post_process_api_url,
               model: cfg.post_process_api_model,
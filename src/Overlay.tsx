@@ -5,13 +5,18 @@ import { listen } from '@tauri-apps/api/event';
 import { StatusIndicator } from './components/StatusIndicator.tsx';
 import { tokens } from './design-tokens.ts';
 import { useDictationStatus } from './hooks/useDictationStatus.ts';
-import type { StatusUpdatePayload } from './types.ts';
+import type { AudioDeviceStatusPayload, HotkeyMode, PostProcessTokenPayload, StatusUpdatePayload } from './types.ts';
 
-type HotkeyMode = 'HoldToTalk' | 'Toggle';
+// Only the end of the streamed text fits under the indicator
+const STREAM_PREVIEW_CHARS = 48;
 
 function Overlay() {
   const dictationStatus = useDictationStatus({ accept: ['Recording', 'Transcribing', 'Error'] });
   const hotkeyMode = useSignal<HotkeyMode>('HoldToTalk');
+  const streamPreview = useSignal('');
+  // Set while the mic is lost or replaced by the default, so a session
+  // started in that state says which microphone it is hearing.
+  const deviceWarning = useSignal('');
   const hasTauriRuntime = typeof window !== 'undefined' && '__TAURI_INTERNALS__' in (window as Window & { __TAURI_INTERNALS__?: unknown });
   const isPreviewMode = !hasTauriRuntime;
 
@@ -22,6 +27,8 @@ function Overlay() {
 
     let unlistenStatus: null | (() => void) = null;
     let unlistenConfig: null | (() => void) = null;
+    let unlistenTokens: null | (() => void) = null;
+    let unlistenDevice: null | (() => void) = null;
 
     const loadHotkeyMode = async () => {
       try {
@@ -40,7 +47,20 @@ function Overlay() {
         });
 
         unlistenStatus = await listen<string | StatusUpdatePayload>('status-update', (event) => {
-          dictationStatus.handleStatusUpdate(event.payload);
+          const nextStatus = dictationStatus.handleStatusUpdate(event.payload);
+          if (nextStatus === 'Recording' || nextStatus === 'Error') {
+            streamPreview.value = '';
+          }
+        });
+
+        unlistenTokens = await listen<PostProcessTokenPayload>('post-process-token', (event) => {
+          const text = event.payload.text.replace(/\s+/g, ' ').trim();
+          streamPreview.value = text.length > STREAM_PREVIEW_CHARS ? `…${text.slice(-STREAM_PREVIEW_CHARS)}` : text;
+        });
+
+        unlistenDevice = await listen<AudioDeviceStatusPayload>('audio-device-status', (event) => {
+          const { state } = event.payload;
+          deviceWarning.value = state === 'lost' ? 'microphone lost' : state === 'fallback' ? 'using default mic' : '';
         });
       } catch (error) {
         console.error('Failed to setup overlay event listeners:', error);
@@ -56,6 +76,12 @@ function Overlay() {
       if (unlistenConfig) {
         unlistenConfig();
       }
+      if (unlistenTokens) {
+        unlistenTokens();
+      }
+      if (unlistenDevice) {
+        unlistenDevice();
+      }
     };
   }, [isPreviewMode]);
 
@@ -97,7 +123,7 @@ function Overlay() {
 
   return (
     <div style={{ display: 'flex', flexDirection: 'column', alignItems: 'center', justifyContent: 'flex-end', width: '100%', height: '100%', backgroundColor: 'transparent', padding: '16px 20px' }}>
-      <StatusIndicator status={dictationStatus.status.value} size={44} fixedWidth={180} subtitle={dictationStatus.status.value === 'Recording' && hotkeyMode.value === 'Toggle' ? 'press again to stop' : undefined} />
+      <StatusIndicator status={dictationStatus.status.value} size={44} fixedWidth={180} subtitle={dictationStatus.status.value === 'Recording' && deviceWarning.value ? deviceWarning.value : dictationStatus.status.value === 'Recording' && hotkeyMode.value === 'Toggle' ? 'press again to stop' : dictationStatus.status.value === 'Recording' && hotkeyMode.value === 'VoiceActivated' ? 'stops when you pause' : dictationStatus.status.value === 'Transcribing' && streamPreview.value ? streamPreview.value : undefined} />
     </div>
   );
 }
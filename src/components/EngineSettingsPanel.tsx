@@ -1,7 +1,7 @@
 import { NumberField } from './NumberField.tsx';
 import { SelectField } from './SelectField.tsx';
 import { Switch } from './Switch.tsx';
-import { helperTextStyle } from '../theme/ui-primitives.ts';
+import { helperTextStyle, inputBaseStyle } from '../theme/ui-primitives.ts';
 import { tokens } from '../design-tokens.ts';
 import type { EngineCapabilities } from '../types.ts';
 
@@ -64,8 +64,19 @@ function renderSettingControl(
         <NumberField
           value={Number(currentValue) || 0}
           onChange={(val) => onChange(setting.key, val)}
-          min={1}
-          max={64}
+          min={setting.min ?? 1}
+          max={setting.max ?? 64}
+          step={setting.step}
+        />
+      );
+    case 'text':
+      return (
+        <input
+          style={inputBaseStyle}
+          type="text"
+          value={String(currentValue ?? '')}
+          onChange={(e: Event) => onChange(setting.key, (e.target as HTMLInputElement).value)}
+          aria-label={setting.label}
         />
       );
     case 'bool':
@@ -95,6 +95,7 @@ export function MainLayout(props: MainLayoutProps) {
             dictationStatus={props.dictationStatus}
             onToggleOutputMethod={props.onToggleOutputMethod}
             onToggleDiarization={(enabled) => props.onUpdateConfig('diarization_enabled_files', enabled)}
+            onToggleListenMic={(enabled) => props.onUpdateConfig('listen_include_mic', enabled)}
             hasUpdateAvailable={props.updateResult?.updateAvailable === true}
             onOpenUpdateModal={props.onOpenUpdateModal}
             onCopyToClipboard={props.onCopyToClipboard}
@@ -10,14 +10,14 @@ interface ModeOption<T> {
 
 interface ModeSwitcherProps<T> {
   value: T;
-  options: [ModeOption<T>, ModeOption<T>];
+  options: ModeOption<T>[];
   onToggle: (value: T) => void;
   className?: string;
 }
 
 export function ModeSwitcher<T extends string>({ value, options, onToggle, className = "" }: ModeSwitcherProps<T>) {
-  const activeIndex = options.findIndex(opt => opt.value === value);
-  const sliderTransform = activeIndex === 0 ? 'translateX(0)' : 'translateX(100%)';
+  const activeIndex = Math.max(0, options.findIndex(opt => opt.value === value));
+  const sliderTransform = `translateX(${activeIndex * 100}%)`;
 
   return (
     <div className={className} style={{ display: 'flex' }}>
@@ -38,7 +38,7 @@ export function ModeSwitcher<T extends string>({ value, options, onToggle, class
             position: 'absolute',
             top: '4px',
             left: '4px',
-            width: 'calc(50% - 4px)',
+            width: `calc((100% - 8px) / ${options.length})`,
             height: 'calc(100% - 8px)',
             background: 'linear-gradient(135deg, #5865f2 0%, #4338ca 100%)',
             borderRadius: '22px',
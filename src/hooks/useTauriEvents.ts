@@ -1,7 +1,7 @@
 import { useEffect } from 'preact/hooks';
 import { useSignal } from '@preact/signals';
 import { listen } from '@tauri-apps/api/event';
-import { HotkeyBindingState, ModelDownloadProgress, StatusUpdatePayload } from '../types.ts';
+import { AudioDeviceStatusPayload, HotkeyBindingState, ModelDownloadProgress, StatusUpdatePayload } from '../types.ts';
 
 interface UseTauriEventsOptions {
   onSetupStatus: (payload: string) => void;
@@ -14,6 +14,7 @@ interface UseTauriEventsOptions {
   onMicVolume: (volume: number) => void;
   onDownloadProgress: (progress: ModelDownloadProgress) => void;
   onPostProcessGpuStatusChanged: () => void;
+  onAudioDeviceStatus: (payload: AudioDeviceStatusPayload) => void;
   onFocus: () => void;
   onHashChange: () => void;
 }
@@ -53,6 +54,9 @@ export function useTauriEvents(options: UseTauriEventsOptions) {
     const unlistenPostProcessGpuStatus = listen('post-process-gpu-status-changed', () => {
       latest.value.onPostProcessGpuStatusChanged();
     });
+    const unlistenAudioDeviceStatus = listen<AudioDeviceStatusPayload>('audio-device-status', (event) => {
+      latest.value.onAudioDeviceStatus(event.payload);
+    });
 
     const onFocus = () => latest.value.onFocus();
     const onHashChange = () => latest.value.onHashChange();
@@ -72,6 +76,7 @@ export function useTauriEvents(options: UseTauriEventsOptions) {
       unlistenMicVolume.then((fn) => fn());
       unlistenDownloadProgress.then((fn) => fn());
       unlistenPostProcessGpuStatus.then((fn) => fn());
+      unlistenAudioDeviceStatus.then((fn) => fn());
     };
   }, []);
 }
\ No newline at end of file
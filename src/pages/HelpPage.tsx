@@ -1,20 +1,21 @@
 import { Card } from '../components/Card.tsx';
 import { tabPanelPaddedStyle, tabPanelStyle } from '../theme/ui-primitives.ts';
 import { tokens } from '../design-tokens.ts';
+import type { HotkeyMode } from '../types.ts';
 
 interface HelpPageProps {
   config: {
     transcription_mode: 'API' | 'Local';
     local_model_size: string;
     hotkey: string;
-    hotkey_mode: 'HoldToTalk' | 'Toggle';
+    hotkey_mode: HotkeyMode;
   };
   modelStatus: Record<string, boolean>;
   isSystemManagedShortcut: boolean;
 }
 
 export function HelpPage({ config, modelStatus, isSystemManagedShortcut }: HelpPageProps) {
-  const isToggleMode = config.hotkey_mode === 'Toggle';
+  const isToggleMode = config.hotkey_mode !== 'HoldToTalk';
 
   const howToSteps = [
     config.transcription_mode === 'Local'
@@ -33,6 +34,9 @@ export function HelpPage({ config, modelStatus, isSystemManagedShortcut }: HelpP
     isToggleMode
       ? <>Press it again to stop and transcribe.</>
       : <>Release keys to transcribe and type.</>,
+    ...(config.hotkey_mode === 'VoiceActivated'
+      ? [<>In <strong style={{ color: '#ffffff' }}>Hands-Free</strong> mode you can also just start talking; a pause ends the recording. Toggle it from the tray.</>]
+      : []),
   ];
 
   return (
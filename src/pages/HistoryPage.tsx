@@ -2,6 +2,7 @@ import { useEffect } from 'preact/hooks';
 import { IconCopy, IconPlayerPause, IconPlayerPlay, IconSearch, IconTrash, IconX } from '@tabler/icons-preact';
 import { useSignal } from '@preact/signals';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { tokens } from '../design-tokens.ts';
 import type { HistoryItem } from '../types.ts';
 import { getSpeakerColor } from '../speakerColors.ts';
@@ -162,13 +163,23 @@ function renderItemText(item: HistoryItem, searchQuery: string, isRaw: boolean)
 export function HistoryPage({ history, searchQuery, searchResults, onCopyToClipboard, onSearch, onDelete }: HistoryPageProps) {
   const showRaw = useSignal<Set<number>>(new Set());
   const playingAudioId = useSignal<number | null>(null);
-  const currentAudio = useSignal<HTMLAudioElement | null>(null);
 
   useEffect(() => {
+    // Recordings play natively, streamed from disk, so long ones never
+    // have to be loaded into the page.
+    let unlisten: (() => void) | undefined;
+    let cancelled = false;
+    listen('history-playback-finished', () => {
+      playingAudioId.value = null;
+    }).then((fn) => {
+      if (cancelled) { fn(); return; }
+      unlisten = fn;
+    });
     return () => {
-      if (currentAudio.value) {
-        currentAudio.value.pause();
-        currentAudio.value = null;
+      cancelled = true;
+      unlisten?.();
+      if (playingAudioId.value !== null) {
+        invoke('stop_history_recording').catch(() => {});
       }
     };
   }, []);
@@ -187,44 +198,19 @@ export function HistoryPage({ history, searchQuery, searchResults, onCopyToClipb
     if (!item.audio_file) return;
 
     if (playingAudioId.value === item.id) {
-      if (currentAudio.value) {
-        currentAudio.value.pause();
-        currentAudio.value = null;
-      }
       playingAudioId.value = null;
+      await invoke('stop_history_recording').catch((err) => {
+        console.error('Failed to stop history audio:', err);
+      });
       return;
     }
 
     try {
-      if (currentAudio.value) {
-        currentAudio.value.pause();
-        currentAudio.value = null;
-      }
-
-      const rawBytes = await invoke<number[]>('get_history_audio', { fileName: item.audio_file });
-      const u8 = new Uint8Array(rawBytes);
-      const blob = new Blob([u8], { type: 'audio/wav' });
-      const url = URL.createObjectURL(blob);
-      const audio = new Audio(url);
-
-      audio.onended = () => {
-        playingAudioId.value = null;
-        currentAudio.value = null;
-        URL.revokeObjectURL(url);
-      };
-      audio.onerror = () => {
-        playingAudioId.value = null;
-        currentAudio.value = null;
-        URL.revokeObjectURL(url);
-      };
-
-      await audio.play();
       playingAudioId.value = item.id;
-      currentAudio.value = audio;
+      await invoke('play_history_recording', { fileName: item.audio_file });
     } catch (err) {
       console.error('Failed to play history audio:', err);
       playingAudioId.value = null;
-      currentAudio.value = null;
     }
   };
 
@@ -312,8 +298,26 @@ export function HistoryPage({ history, searchQuery, searchResults, onCopyToClipb
                   </div>
                   <div style={{ display: 'flex', alignItems: 'center', flexWrap: 'wrap', marginTop: '6px', gap: tokens.spacing.xs }}>
                     {statusBadge}
-                    {item.source === 'file' && (
+                    {(item.source === 'file' || item.source === 'system' || item.source === 'call') && (
+                      <span
+                        style={{
+                          display: 'inline-flex',
+                          alignItems: 'center',
+                          padding: '1px 5px',
+                          borderRadius: '4px',
+                          fontSize: tokens.typography.sizeXs,
+                          fontWeight: 600,
+                          background: 'rgba(255, 255, 255, 0.08)',
+                          border: '1px solid rgba(255, 255, 255, 0.15)',
+                          color: tokens.colors.textSecondary,
+                        }}
+                      >
+                        {item.source === 'call' ? 'Call' : item.source === 'system' ? 'System Audio' : 'File'}
+                      </span>
+                    )}
+                    {item.source === 'command' && (
                       <span
+                        title={item.metadata?.instruction ? `Instruction: ${item.metadata.instruction}` : undefined}
                         style={{
                           display: 'inline-flex',
                           alignItems: 'center',
@@ -326,7 +330,7 @@ export function HistoryPage({ history, searchQuery, searchResults, onCopyToClipb
                           color: tokens.colors.textSecondary,
                         }}
                       >
-                        File
+                        Command
                       </span>
                     )}
                     <div style={{ fontSize: tokens.typography.sizeXs, color: tokens.colors.textMuted }}>
@@ -352,6 +356,45 @@ export function HistoryPage({ history, searchQuery, searchResults, onCopyToClipb
                         • {item.prompt_name}
                       </div>
                     )}
+                    {item.metadata?.chunks && (
+                      <div style={{ fontSize: tokens.typography.sizeXs, color: tokens.colors.textMuted }}>
+                        • {item.metadata.chunks} chunks
+                      </div>
+                    )}
+                    {item.metadata?.structured !== undefined && item.metadata?.structured !== null && (
+                      <div
+                        title={JSON.stringify(item.metadata.structured, null, 2)}
+                        style={{ fontSize: tokens.typography.sizeXs, color: tokens.colors.textMuted }}
+                      >
+                        • Structured
+                      </div>
+                    )}
+                    {item.metadata?.post_process && item.metadata.post_process.outcome !== 'cleaned' && (
+                      <div
+                        title={item.metadata.post_process.attempts
+                          .map((attempt) => `${attempt.service}: ${attempt.error || 'OK'} (${(attempt.elapsed_ms / 1000).toFixed(1)}s)`)
+                          .join('\n')}
+                        style={{ fontSize: tokens.typography.sizeXs, color: item.metadata.post_process.outcome === 'fallback' ? tokens.colors.textMuted : '#f1c40f' }}
+                      >
+                        • {item.metadata.post_process.outcome === 'fallback' ? 'Fallback Provider' : 'Not Cleaned (Post-Processing Failed)'}
+                      </div>
+                    )}
+                    {item.metadata?.agc && (
+                      <div
+                        title={`AGC gain ${item.metadata.agc.min_gain_db.toFixed(1)} to ${item.metadata.agc.max_gain_db.toFixed(1)} dB (target ${item.metadata.agc.target_dbfs.toFixed(0)} dBFS, noise floor ${item.metadata.agc.noise_floor_dbfs.toFixed(0)} dBFS)`}
+                        style={{ fontSize: tokens.typography.sizeXs, color: tokens.colors.textMuted }}
+                      >
+                        • AGC {item.metadata.agc.mean_gain_db >= 0 ? '+' : ''}{item.metadata.agc.mean_gain_db.toFixed(1)} dB
+                      </div>
+                    )}
+                    {item.metadata?.guardrail && (
+                      <div
+                        title={`Guardrail: ${item.metadata.guardrail}`}
+                        style={{ fontSize: tokens.typography.sizeXs, color: '#f1c40f' }}
+                      >
+                        • Guardrail
+                      </div>
+                    )}
                     {item.audio_file && (
                       <button
                         onClick={() => void togglePlayAudio(item)}
@@ -1,8 +1,9 @@
-import { IconAlertCircle, IconBrandGithub, IconHeart, IconUpload, IconCopy, IconUser } from '@tabler/icons-preact';
+import { IconAlertCircle, IconBrandGithub, IconHeart, IconUpload, IconCopy, IconUser, IconHeadphones, IconPlayerStop, IconRestore } from '@tabler/icons-preact';
 import { open } from '@tauri-apps/plugin-shell';
 import { open as openFileDialog } from '@tauri-apps/plugin-dialog';
 import { useSignal } from '@preact/signals';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { getCurrentWindow } from '@tauri-apps/api/window';
 import { Card } from '../components/Card.tsx';
 import { ModeSwitcher } from '../components/ModeSwitcher.tsx';
@@ -13,7 +14,7 @@ import { BouncingDots } from '../components/BouncingDots.tsx';
 import { tabPanelPaddedStyle, tabPanelStyle } from '../theme/ui-primitives.ts';
 import { tokens } from '../design-tokens.ts';
 import { useEffect } from 'preact/hooks';
-import type { Config, DictationStatus, Segment } from '../types.ts';
+import type { ChunkProgress, Config, DictationStatus, RecoverableRecording, Segment } from '../types.ts';
 import { getSpeakerColor } from '../speakerColors.ts';
 
 interface HomePageProps {
@@ -22,6 +23,7 @@ interface HomePageProps {
   config: Config;
   onToggleOutputMethod: (method: 'Typewriter' | 'Clipboard') => void;
   onToggleDiarization: (enabled: boolean) => void;
+  onToggleListenMic: (enabled: boolean) => void;
   hasUpdateAvailable: boolean;
   onOpenUpdateModal: () => void;
   onCopyToClipboard: (text: string) => void;
@@ -41,6 +43,7 @@ export function HomePage({
   config,
   onToggleOutputMethod,
   onToggleDiarization,
+  onToggleListenMic,
   hasUpdateAvailable,
   onOpenUpdateModal,
   onCopyToClipboard,
@@ -50,6 +53,48 @@ export function HomePage({
   const importResult = useSignal<TranscribeResult | null>(null);
   const importError = useSignal<string>('');
   const isDragOver = useSignal(false);
+  const importProgress = useSignal<ChunkProgress | null>(null);
+  const isListening = useSignal(false);
+  const recoverable = useSignal<RecoverableRecording[]>([]);
+
+  useEffect(() => {
+    invoke<RecoverableRecording[]>('get_recoverable_recordings').then((recordings) => {
+      recoverable.value = recordings;
+    }).catch((e) => console.error('Failed to list recoverable recordings:', e));
+  }, []);
+
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    let cancelled = false;
+    invoke<boolean>('is_system_listen_active').then((active) => {
+      if (!cancelled) isListening.value = active;
+    }).catch(() => {});
+    listen<boolean>('system-listen-state', (event) => {
+      isListening.value = event.payload;
+    }).then((fn) => {
+      if (cancelled) { fn(); return; }
+      unlisten = fn;
+    });
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, []);
+
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    let cancelled = false;
+    listen<ChunkProgress>('file-import-progress', (event) => {
+      importProgress.value = event.payload;
+    }).then((fn) => {
+      if (cancelled) { fn(); return; }
+      unlisten = fn;
+    });
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, []);
 
   useEffect(() => {
     let unlisten: (() => void) | undefined;
@@ -97,6 +142,7 @@ export function HomePage({
 
   const transcribeFile = async (filePath: string) => {
     importStatus.value = 'transcribing';
+    importProgress.value = null;
     importResult.value = null;
     importError.value = '';
     try {
@@ -109,6 +155,75 @@ export function HomePage({
     }
   };
 
+  const startListening = async () => {
+    try {
+      await invoke('start_system_listen');
+      isListening.value = true;
+    } catch (e) {
+      importError.value = `Failed to capture system audio: ${e}`;
+      importStatus.value = 'error';
+    }
+  };
+
+  const stopListening = async () => {
+    isListening.value = false;
+    importStatus.value = 'transcribing';
+    importProgress.value = null;
+    importResult.value = null;
+    importError.value = '';
+    try {
+      const result = await invoke<TranscribeResult>('stop_system_listen');
+      importResult.value = result;
+      importStatus.value = 'done';
+    } catch (e) {
+      importError.value = `Transcription failed: ${e}`;
+      importStatus.value = 'error';
+    }
+  };
+
+  const transcribeRecovered = async (id: string) => {
+    recoverable.value = recoverable.value.filter((r) => r.id !== id);
+    importStatus.value = 'transcribing';
+    importProgress.value = null;
+    importResult.value = null;
+    importError.value = '';
+    try {
+      const result = await invoke<TranscribeResult>('transcribe_recovered_recording', { id });
+      importResult.value = result;
+      importStatus.value = 'done';
+    } catch (e) {
+      importError.value = `Transcription failed: ${e}`;
+      importStatus.value = 'error';
+      invoke<RecoverableRecording[]>('get_recoverable_recordings').then((recordings) => {
+        recoverable.value = recordings;
+      }).catch(() => {});
+    }
+  };
+
+  const discardRecovered = async (id: string) => {
+    recoverable.value = recoverable.value.filter((r) => r.id !== id);
+    try {
+      await invoke('discard_recovered_recording', { id });
+    } catch (e) {
+      console.error('Failed to discard recovered recording:', e);
+    }
+  };
+
+  const formatRecoveredDuration = (secs: number) => {
+    const minutes = Math.floor(secs / 60);
+    const seconds = Math.floor(secs % 60);
+    return `${minutes}:${seconds.toString().padStart(2, '0')}`;
+  };
+
+  const cancelListening = async () => {
+    isListening.value = false;
+    try {
+      await invoke('cancel_system_listen');
+    } catch (e) {
+      console.error('Failed to cancel system audio capture:', e);
+    }
+  };
+
   const copyLabeledText = () => {
     const result = importResult.value;
     if (!result) return;
@@ -162,7 +277,7 @@ export function HomePage({
                       marginTop: '4px',
                       letterSpacing: '0.01em',
                     }}>
-                      {config.hotkey_mode === 'Toggle' ? 'Press ' : 'Hold '}
+                      {config.hotkey_mode === 'HoldToTalk' ? 'Hold ' : 'Press '}
                       <span style={{ color: '#818cf8', fontWeight: 600 }}>
                         {config.hotkey}
                       </span>
@@ -214,7 +329,7 @@ export function HomePage({
         </div>
 
         <Card
-          onClick={handleFilePick}
+          onClick={isListening.value ? undefined : handleFilePick}
           style={{
             padding: '14px',
             boxShadow: isDragOver.value ? tokens.shadows.accent : undefined,
@@ -270,11 +385,141 @@ export function HomePage({
                   Labels each speaker
                 </span>
               </div>
+              <div style={{ display: 'flex', alignItems: 'center', gap: '6px', borderTop: '1px solid rgba(255,255,255,0.06)', paddingTop: '8px' }} onClick={(e) => e.stopPropagation()}>
+                {isListening.value ? (
+                  <>
+                    <span style={{ flex: 1, fontSize: tokens.typography.sizeXs, color: tokens.colors.accentPrimary, fontWeight: 500 }}>
+                      Listening to system audio...
+                    </span>
+                    <button
+                      onClick={stopListening}
+                      style={{
+                        background: 'rgba(88, 101, 242, 0.18)',
+                        border: 'none',
+                        padding: '4px 10px',
+                        borderRadius: '6px',
+                        color: '#ffffff',
+                        cursor: 'pointer',
+                        fontSize: tokens.typography.sizeXs,
+                        display: 'flex',
+                        alignItems: 'center',
+                        gap: '4px',
+                      }}
+                    >
+                      <IconPlayerStop size={13} />
+                      Stop & Transcribe
+                    </button>
+                    <button
+                      onClick={cancelListening}
+                      style={{
+                        background: 'rgba(255,255,255,0.08)',
+                        border: 'none',
+                        padding: '4px 10px',
+                        borderRadius: '6px',
+                        color: tokens.colors.textSecondary,
+                        cursor: 'pointer',
+                        fontSize: tokens.typography.sizeXs,
+                      }}
+                    >
+                      Cancel
+                    </button>
+                  </>
+                ) : (
+                  <>
+                    <button
+                      onClick={startListening}
+                      title="Capture what is playing on this computer, then transcribe it with speaker labels"
+                      style={{
+                        background: 'transparent',
+                        border: 'none',
+                        padding: 0,
+                        color: '#e2e8f0',
+                        cursor: 'pointer',
+                        fontSize: tokens.typography.sizeXs,
+                        fontWeight: 500,
+                        display: 'flex',
+                        alignItems: 'center',
+                        gap: '4px',
+                      }}
+                    >
+                      <IconHeadphones size={13} style={{ color: '#94a3b8' }} />
+                      Listen to system audio
+                    </button>
+                    <label
+                      title="Record your microphone on its own channel and label the call as Me and Remote"
+                      style={{
+                        marginLeft: 'auto',
+                        fontSize: tokens.typography.sizeXs,
+                        color: '#94a3b8',
+                        cursor: 'pointer',
+                        display: 'flex',
+                        alignItems: 'center',
+                        gap: '4px',
+                        userSelect: 'none',
+                      }}
+                    >
+                      <input
+                        type="checkbox"
+                        checked={config.listen_include_mic}
+                        onChange={(e) => { e.stopPropagation(); onToggleListenMic((e.target as HTMLInputElement).checked); }}
+                        style={{ accentColor: tokens.colors.accentPrimary, cursor: 'pointer' }}
+                      />
+                      Include my mic
+                    </label>
+                  </>
+                )}
+              </div>
+              {recoverable.value.map((recording) => (
+                <div
+                  key={recording.id}
+                  style={{ display: 'flex', alignItems: 'center', gap: '6px', borderTop: '1px solid rgba(255,255,255,0.06)', paddingTop: '8px' }}
+                  onClick={(e) => e.stopPropagation()}
+                >
+                  <IconRestore size={13} style={{ color: '#f59e0b', flexShrink: 0 }} />
+                  <span
+                    title="This recording was interrupted before it could be transcribed"
+                    style={{ flex: 1, minWidth: 0, fontSize: tokens.typography.sizeXs, color: '#e2e8f0' }}
+                  >
+                    Unfinished {recording.source === 'mic' ? 'recording' : recording.source === 'call' ? 'call' : 'listen session'} from {new Date(recording.recorded_at).toLocaleString()} ({formatRecoveredDuration(recording.duration_secs)})
+                  </span>
+                  <button
+                    onClick={() => transcribeRecovered(recording.id)}
+                    disabled={isListening.value}
+                    style={{
+                      background: 'rgba(88, 101, 242, 0.18)',
+                      border: 'none',
+                      padding: '4px 10px',
+                      borderRadius: '6px',
+                      color: '#ffffff',
+                      cursor: 'pointer',
+                      fontSize: tokens.typography.sizeXs,
+                    }}
+                  >
+                    Transcribe
+                  </button>
+                  <button
+                    onClick={() => discardRecovered(recording.id)}
+                    style={{
+                      background: 'rgba(255,255,255,0.08)',
+                      border: 'none',
+                      padding: '4px 10px',
+                      borderRadius: '6px',
+                      color: tokens.colors.textSecondary,
+                      cursor: 'pointer',
+                      fontSize: tokens.typography.sizeXs,
+                    }}
+                  >
+                    Discard
+                  </button>
+                </div>
+              ))}
             </div>
           )}
           {importStatus.value === 'transcribing' && (
             <div style={{ fontSize: tokens.typography.sizeSm, color: tokens.colors.accentPrimary }}>
-              Transcribing...
+              {importProgress.value
+                ? `${importProgress.value.stage === 'map' ? 'Post-processing part' : 'Combining results'} ${Math.min(importProgress.value.completed + 1, importProgress.value.total)} of ${importProgress.value.total}...`
+                : 'Transcribing...'}
             </div>
           )}
           {importStatus.value === 'done' && importResult.value && (
@@ -13,6 +13,7 @@ import { SettingRow } from '../components/SettingRow.tsx';
 import { helperTextStyle, inputBaseStyle, selectWrapperStyle, tabPanelStyle } from '../theme/ui-primitives.ts';
 import { tokens } from '../design-tokens.ts';
 import { API_KEY_PLACEHOLDER, type ReadinessStatus } from '../readiness.ts';
+import type { PostProcessProvider } from '../types.ts';
 import type { AudioDevice, DownloadPhase, GpuStatus } from '../types.ts';
 
 interface ModelInfo {
@@ -49,7 +50,7 @@ interface SetupConfig {
   input_sensitivity: number;
   openai_api_key: string;
   post_process_enabled: boolean;
-  post_process_provider: 'Local' | 'API';
+  post_process_provider: PostProcessProvider;
   post_process_model: string;
 }
 
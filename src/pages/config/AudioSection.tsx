@@ -1,8 +1,12 @@
 import { IconRefresh } from '@tabler/icons-preact';
+import { useSignal } from '@preact/signals';
+import { invoke } from '@tauri-apps/api/core';
+import { useEffect } from 'preact/hooks';
 import { ConfigField } from '../../components/ConfigField.tsx';
 import { Switch } from '../../components/Switch.tsx';
 import { Button } from '../../components/Button.tsx';
 import { SliderField } from '../../components/SliderField.tsx';
+import { NumberField } from '../../components/NumberField.tsx';
 import { MicSetupPanel } from '../../components/MicSetupPanel.tsx';
 import { SelectField } from '../../components/SelectField.tsx';
 import type { AudioDevice, Config } from '../../types.ts';
@@ -36,6 +40,20 @@ export function AudioSection({
   stopMicTest,
   stopMicPlayback,
 }: AudioSectionProps) {
+  const availableMonitors = useSignal<AudioDevice[]>([]);
+
+  const loadMonitors = async () => {
+    try {
+      availableMonitors.value = await invoke<AudioDevice[]>('get_monitor_devices');
+    } catch (error) {
+      console.error('Failed to load system audio sources:', error);
+    }
+  };
+
+  useEffect(() => {
+    loadMonitors();
+  }, []);
+
   return (
     <>
       <ConfigField label="Microphone" description="Choose the input device for recording your voice.">
@@ -66,6 +84,20 @@ export function AudioSection({
         </div>
       </ConfigField>
 
+      <ConfigField label="System Audio Source" description="Choose the monitor (loopback) source captured by Listen to system audio on the home page, for transcribing calls and videos playing on this computer.">
+        <div style={selectWrapperStyle}>
+          <SelectField
+            value={config.listen_device || 'default'}
+            options={availableMonitors.value.map((monitor) => ({ value: monitor.id, label: monitor.label }))}
+            onChange={(nextMonitorId) => updateConfig('listen_device', nextMonitorId)}
+            ariaLabel="System Audio Source"
+          />
+          <Button variant="icon" onClick={loadMonitors} title="Refresh System Audio Sources">
+            <IconRefresh size={16} />
+          </Button>
+        </div>
+      </ConfigField>
+
       <ConfigField label="Mic Test & Sensitivity" description="Adjust capture gain and verify your microphone playback.">
         <MicSetupPanel
           inputSensitivity={config.input_sensitivity}
@@ -78,11 +110,41 @@ export function AudioSection({
         />
       </ConfigField>
 
-      <ConfigField label="Noise Reduction" description="Reduce background noise from your microphone using spectral gating. Improves transcription accuracy in noisy environments.">
+      <ConfigField label="Automatic Gain Control" description="Levels your voice to a consistent loudness while recording, so quiet headsets and loud USB mics transcribe alike. A limiter prevents clipping.">
+        <div style={{ display: 'flex', flexDirection: 'column', gap: tokens.spacing.sm, width: '100%' }}>
+          <Switch name="Automatic Gain Control" checked={config.agc_enabled} onChange={(checked) => updateConfig('agc_enabled', checked)} />
+          {config.agc_enabled && (
+            <div style={{ display: 'flex', gap: tokens.spacing.sm, width: '100%' }}>
+              <div style={{ display: 'flex', flexDirection: 'column', gap: tokens.spacing.xs, flex: 1 }}>
+                <div style={{ fontSize: tokens.typography.sizeXs, color: tokens.colors.textMuted, textAlign: 'left' }}>Target loudness (dBFS)</div>
+                <NumberField value={config.agc_target_dbfs} onChange={(value) => updateConfig('agc_target_dbfs', value)} min={-40} max={-6} step={1} />
+              </div>
+              <div style={{ display: 'flex', flexDirection: 'column', gap: tokens.spacing.xs, flex: 1 }}>
+                <div style={{ fontSize: tokens.typography.sizeXs, color: tokens.colors.textMuted, textAlign: 'left' }}>Maximum boost (dB)</div>
+                <NumberField value={config.agc_max_gain_db} onChange={(value) => updateConfig('agc_max_gain_db', value)} min={0} max={40} step={1} />
+              </div>
+            </div>
+          )}
+        </div>
+      </ConfigField>
+
+      <ConfigField label="Noise Reduction" description="Reduce background noise from your microphone before transcription. Improves transcription accuracy in noisy environments.">
         <div style={{ display: 'flex', flexDirection: 'column', gap: tokens.spacing.sm, width: '100%' }}>
           <Switch name="Noise Reduction" checked={config.noise_reduction_enabled} onChange={(checked) => updateConfig('noise_reduction_enabled', checked)} />
           {config.noise_reduction_enabled && (
             <div style={{ display: 'flex', flexDirection: 'column', gap: tokens.spacing.xs, width: '100%' }}>
+              <div style={selectWrapperStyle}>
+                <SelectField
+                  value={config.noise_reduction_provider}
+                  options={[
+                    { value: 'SpectralGate', label: 'Spectral Gating (steady noise, fastest)' },
+                    { value: 'RNNoise', label: 'RNNoise (changing noise like typing)' },
+                    { value: 'Python', label: 'Python noisereduce (slower, needs Python runner)' },
+                  ]}
+                  onChange={(value) => updateConfig('noise_reduction_provider', value)}
+                  ariaLabel="Noise reduction method"
+                />
+              </div>
               <div style={{ fontSize: tokens.typography.sizeXs, color: tokens.colors.textMuted, textAlign: 'left' }}>
                 Strength: {Math.round(config.noise_reduction_strength * 100)}%
               </div>
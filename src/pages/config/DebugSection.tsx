@@ -54,7 +54,7 @@ export function DebugSection({
         </div>
       </ConfigField>
 
-      <ConfigField label="Save Recording Logs" description="Save raw audio from recordings and file imports as WAV files in the debug folder for troubleshooting.">
+      <ConfigField label="Save Recording Logs" description="Save raw audio from recordings and file imports in the debug folder for troubleshooting and playback from history.">
         <div style={{ display: 'flex', alignItems: 'center', justifyContent: 'space-between', width: '100%' }}>
           <Switch name="Save Recording Logs" checked={config.enable_recording_logs} onChange={(checked) => updateConfig('enable_recording_logs', checked)} />
           <div style={{ display: 'flex', gap: tokens.spacing.xs }}>
@@ -64,7 +64,7 @@ export function DebugSection({
               pill
               style={configGhostPillStyle}
               onClick={async () => {
-                if (await confirm('Delete all recorded WAV files?')) {
+                if (await confirm('Delete all saved recordings?')) {
                   try {
                     const count = await invoke<number>('clear_recording_logs');
                     showToast?.(`Deleted ${count} recording file(s)`, 'success');
@@ -81,6 +81,20 @@ export function DebugSection({
         </div>
       </ConfigField>
 
+      <ConfigField label="Recording Format" description="FLAC is lossless and about half the size of WAV. Switching to FLAC also converts recordings already saved as WAV.">
+        <div style={selectWrapperStyle}>
+          <SelectField
+            value={config.recording_audio_format}
+            options={[
+              { value: 'Flac', label: 'FLAC (compressed)' },
+              { value: 'Wav', label: 'WAV' },
+            ]}
+            onChange={(format) => updateConfig('recording_audio_format', format)}
+            ariaLabel="Recording format"
+          />
+        </div>
+      </ConfigField>
+
       <ConfigField label="History Limit" description="Maximum number of history entries to keep. Oldest entries are automatically pruned when the limit is exceeded. 0 = unlimited.">
         <NumberField value={config.history_limit} onChange={(value) => updateConfig('history_limit', value)} min={0} max={10000} />
       </ConfigField>
@@ -1,6 +1,7 @@
 import { useSignal } from '@preact/signals';
 import { IconX } from '@tabler/icons-preact';
 import { ConfigField } from '../../components/ConfigField.tsx';
+import { Switch } from '../../components/Switch.tsx';
 import { Button } from '../../components/Button.tsx';
 import type { Config } from '../../types.ts';
 import { inputBaseStyle } from '../../theme/ui-primitives.ts';
@@ -23,72 +24,82 @@ export function DictionarySection({ config, updateConfig }: DictionarySectionPro
   };
 
   return (
-    <ConfigField label="Custom Words" description="Add names, jargon, or terms Whisper often gets wrong. Helps improve accuracy.">
-      <div style={{ display: 'flex', flexDirection: 'column', gap: tokens.spacing.xs, width: '100%' }}>
-        <div style={{ display: 'flex', gap: tokens.spacing.xs, width: '100%' }}>
-          <input
-            type="text"
-            value={dictionaryInput.value}
-            onInput={(e) => { dictionaryInput.value = (e.target as HTMLInputElement).value; }}
-            onKeyDown={(e) => {
-              if (e.key === 'Enter') {
-                addWord();
-              }
-            }}
-            placeholder="e.g. Anthropic, Rust, Voquill"
-            style={{ ...inputBaseStyle, flex: 1 }}
-          />
-          <Button
-            variant="configAction"
-            onClick={addWord}
-            disabled={!dictionaryInput.value.trim()}
-          >
-            Add
-          </Button>
-        </div>
-        {(config.dictionary || []).length > 0 && (
-          <div style={{ display: 'flex', flexWrap: 'wrap', gap: '4px', marginTop: tokens.spacing.xs }}>
-            {(config.dictionary || []).map((word, i) => (
-              <div
-                key={i}
-                style={{
-                  display: 'flex',
-                  alignItems: 'center',
-                  gap: '4px',
-                  padding: '3px 8px',
-                  borderRadius: '6px',
-                  background: 'rgba(255,255,255,0.06)',
-                  fontSize: tokens.typography.sizeXs,
-                  color: tokens.colors.textPrimary,
-                }}
-              >
-                <span>{word}</span>
-                <button
-                  onClick={() => {
-                    const updated = [...(config.dictionary || [])];
-                    updated.splice(i, 1);
-                    updateConfig('dictionary', updated);
-                  }}
+    <>
+      <ConfigField label="Custom Words" description="Add names, jargon, or terms Whisper often gets wrong. Helps improve accuracy.">
+        <div style={{ display: 'flex', flexDirection: 'column', gap: tokens.spacing.xs, width: '100%' }}>
+          <div style={{ display: 'flex', gap: tokens.spacing.xs, width: '100%' }}>
+            <input
+              type="text"
+              value={dictionaryInput.value}
+              onInput={(e) => { dictionaryInput.value = (e.target as HTMLInputElement).value; }}
+              onKeyDown={(e) => {
+                if (e.key === 'Enter') {
+                  addWord();
+                }
+              }}
+              placeholder="e.g. Anthropic, Rust, Voquill"
+              style={{ ...inputBaseStyle, flex: 1 }}
+            />
+            <Button
+              variant="configAction"
+              onClick={addWord}
+              disabled={!dictionaryInput.value.trim()}
+            >
+              Add
+            </Button>
+          </div>
+          {(config.dictionary || []).length > 0 && (
+            <div style={{ display: 'flex', flexWrap: 'wrap', gap: '4px', marginTop: tokens.spacing.xs }}>
+              {(config.dictionary || []).map((word, i) => (
+                <div
+                  key={i}
                   style={{
-                    background: 'none',
-                    border: 'none',
-                    color: tokens.colors.textMuted,
-                    cursor: 'pointer',
-                    padding: '0',
                     display: 'flex',
                     alignItems: 'center',
-                    justifyContent: 'center',
-                    lineHeight: 1,
+                    gap: '4px',
+                    padding: '3px 8px',
+                    borderRadius: '6px',
+                    background: 'rgba(255,255,255,0.06)',
+                    fontSize: tokens.typography.sizeXs,
+                    color: tokens.colors.textPrimary,
                   }}
-                  title={`Remove "${word}"`}
                 >
-                  <IconX size={12} />
-                </button>
-              </div>
-            ))}
-          </div>
-        )}
-      </div>
-    </ConfigField>
+                  <span>{word}</span>
+                  <button
+                    onClick={() => {
+                      const updated = [...(config.dictionary || [])];
+                      updated.splice(i, 1);
+                      updateConfig('dictionary', updated);
+                    }}
+                    style={{
+                      background: 'none',
+                      border: 'none',
+                      color: tokens.colors.textMuted,
+                      cursor: 'pointer',
+                      padding: '0',
+                      display: 'flex',
+                      alignItems: 'center',
+                      justifyContent: 'center',
+                      lineHeight: 1,
+                    }}
+                    title={`Remove "${word}"`}
+                  >
+                    <IconX size={12} />
+                  </button>
+                </div>
+              ))}
+            </div>
+          )}
+        </div>
+      </ConfigField>
+
+      <ConfigField label="Auto-Correct Custom Words" description="Replace close misspellings and sound-alikes of your custom words with their exact spelling after transcription.">
+        <Switch
+          name="Auto-Correct Custom Words"
+          checked={config.dictionary_correction_enabled}
+          onChange={(checked) => updateConfig('dictionary_correction_enabled', checked)}
+        />
+      </ConfigField>
+    </>
   );
 }
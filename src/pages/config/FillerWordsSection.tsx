@@ -3,8 +3,9 @@ import { IconX } from '@tabler/icons-preact';
 import { ConfigField } from '../../components/ConfigField.tsx';
 import { Switch } from '../../components/Switch.tsx';
 import { Button } from '../../components/Button.tsx';
-import type { Config } from '../../types.ts';
-import { inputBaseStyle } from '../../theme/ui-primitives.ts';
+import { SelectField } from '../../components/SelectField.tsx';
+import type { Config, ProfanityMaskStyle } from '../../types.ts';
+import { inputBaseStyle, selectWrapperStyle } from '../../theme/ui-primitives.ts';
 import { tokens } from '../../design-tokens.ts';
 
 interface FillerWordsSectionProps {
@@ -12,8 +13,51 @@ interface FillerWordsSectionProps {
   updateConfig: (key: string, value: string | number | boolean | null | string[] | Record<string, unknown> | unknown[]) => void;
 }
 
+function WordChips({ words, onRemove }: { words: string[]; onRemove: (index: number) => void }) {
+  if (words.length === 0) return null;
+  return (
+    <div style={{ display: 'flex', flexWrap: 'wrap', gap: '4px', marginTop: tokens.spacing.xs }}>
+      {words.map((word, i) => (
+        <div
+          key={i}
+          style={{
+            display: 'flex',
+            alignItems: 'center',
+            gap: '4px',
+            padding: '3px 8px',
+            borderRadius: '6px',
+            background: 'rgba(255,255,255,0.06)',
+            fontSize: tokens.typography.sizeXs,
+            color: tokens.colors.textPrimary,
+          }}
+        >
+          <span>{word}</span>
+          <button
+            onClick={() => onRemove(i)}
+            style={{
+              background: 'none',
+              border: 'none',
+              color: tokens.colors.textMuted,
+              cursor: 'pointer',
+              padding: '0',
+              display: 'flex',
+              alignItems: 'center',
+              justifyContent: 'center',
+              lineHeight: 1,
+            }}
+            title={`Remove "${word}"`}
+          >
+            <IconX size={12} />
+          </button>
+        </div>
+      ))}
+    </div>
+  );
+}
+
 export function FillerWordsSection({ config, updateConfig }: FillerWordsSectionProps) {
   const fillerWordInput = useSignal('');
+  const maskedWordInput = useSignal('');
 
   const addFillerWord = () => {
     const trimmed = fillerWordInput.value.trim().toLowerCase();
@@ -23,6 +67,14 @@ export function FillerWordsSection({ config, updateConfig }: FillerWordsSectionP
     fillerWordInput.value = '';
   };
 
+  const addMaskedWord = () => {
+    const trimmed = maskedWordInput.value.trim().toLowerCase();
+    if (trimmed && !(config.profanity_custom_words || []).includes(trimmed)) {
+      updateConfig('profanity_custom_words', [...(config.profanity_custom_words || []), trimmed]);
+    }
+    maskedWordInput.value = '';
+  };
+
   return (
     <>
       <ConfigField label="Remove Filler Words" description="Automatically remove filler words (uh, umm, hmm, etc.) from transcriptions using a fast regex pass. Works without post-processing.">
@@ -56,50 +108,77 @@ export function FillerWordsSection({ config, updateConfig }: FillerWordsSectionP
               Add
             </Button>
           </div>
-          {(config.custom_filler_words || []).length > 0 && (
-            <div style={{ display: 'flex', flexWrap: 'wrap', gap: '4px', marginTop: tokens.spacing.xs }}>
-              {(config.custom_filler_words || []).map((word, i) => (
-                <div
-                  key={i}
-                  style={{
-                    display: 'flex',
-                    alignItems: 'center',
-                    gap: '4px',
-                    padding: '3px 8px',
-                    borderRadius: '6px',
-                    background: 'rgba(255,255,255,0.06)',
-                    fontSize: tokens.typography.sizeXs,
-                    color: tokens.colors.textPrimary,
+          <WordChips
+            words={config.custom_filler_words || []}
+            onRemove={(i) => {
+              const updated = [...(config.custom_filler_words || [])];
+              updated.splice(i, 1);
+              updateConfig('custom_filler_words', updated);
+            }}
+          />
+        </div>
+      </ConfigField>
+
+      <ConfigField label="Mask Profanity" description="Mask profanity and your own sensitive terms before text is typed. Uses a built-in list for the selected language (all lists when set to auto). History keeps the unmasked original.">
+        <Switch
+          name="Mask Profanity"
+          checked={config.profanity_filter_enabled}
+          onChange={(checked) => updateConfig('profanity_filter_enabled', checked)}
+        />
+      </ConfigField>
+
+      {config.profanity_filter_enabled && (
+        <>
+          <ConfigField label="Masking Style" description="How masked words appear in the output.">
+            <div style={selectWrapperStyle}>
+              <SelectField
+                value={config.profanity_mask_style}
+                options={[
+                  { value: 'Asterisks', label: 'Asterisks (****)' },
+                  { value: 'FirstLetter', label: 'First letter (f***)' },
+                  { value: 'Remove', label: 'Remove the word' },
+                ]}
+                onChange={(nextStyle) => updateConfig('profanity_mask_style', nextStyle as ProfanityMaskStyle)}
+                ariaLabel="Masking Style"
+              />
+            </div>
+          </ConfigField>
+
+          <ConfigField label="Custom Masked Terms" description="Extra words or phrases to mask, such as project code names or client names.">
+            <div style={{ display: 'flex', flexDirection: 'column', gap: tokens.spacing.xs, width: '100%' }}>
+              <div style={{ display: 'flex', gap: tokens.spacing.xs, width: '100%' }}>
+                <input
+                  type="text"
+                  value={maskedWordInput.value}
+                  onInput={(e) => { maskedWordInput.value = (e.target as HTMLInputElement).value; }}
+                  onKeyDown={(e) => {
+                    if (e.key === 'Enter') {
+                      addMaskedWord();
+                    }
                   }}
+                  placeholder="e.g. project falcon"
+                  style={{ ...inputBaseStyle, flex: 1 }}
+                />
+                <Button
+                  variant="configAction"
+                  onClick={addMaskedWord}
+                  disabled={!maskedWordInput.value.trim()}
                 >
-                  <span>{word}</span>
-                  <button
-                    onClick={() => {
-                      const updated = [...(config.custom_filler_words || [])];
-                      updated.splice(i, 1);
-                      updateConfig('custom_filler_words', updated);
-                    }}
-                    style={{
-                      background: 'none',
-                      border: 'none',
-                      color: tokens.colors.textMuted,
-                      cursor: 'pointer',
-                      padding: '0',
-                      display: 'flex',
-                      alignItems: 'center',
-                      justifyContent: 'center',
-                      lineHeight: 1,
-                    }}
-                    title={`Remove "${word}"`}
-                  >
-                    <IconX size={12} />
-                  </button>
-                </div>
-              ))}
+                  Add
+                </Button>
+              </div>
+              <WordChips
+                words={config.profanity_custom_words || []}
+                onRemove={(i) => {
+                  const updated = [...(config.profanity_custom_words || [])];
+                  updated.splice(i, 1);
+                  updateConfig('profanity_custom_words', updated);
+                }}
+              />
             </div>
-          )}
-        </div>
-      </ConfigField>
+          </ConfigField>
+        </>
+      )}
     </>
   );
 }
@@ -47,7 +47,7 @@ export function GeneralSection({
     <>
       <ConfigField
         label="Recording Mode"
-        description="Hold the hotkey while speaking, or press it to start and again to stop. Pressing the hotkey while transcribing cancels."
+        description="Hold the hotkey while speaking, press it to start and again to stop, or go hands-free and let your voice start and stop recording. Pressing the hotkey while transcribing cancels."
       >
         <ModeSwitcher
           value={config.hotkey_mode}
@@ -55,14 +55,35 @@ export function GeneralSection({
           options={[
             { value: 'HoldToTalk', label: 'Hold to Talk', title: 'Record while the hotkey is held down' },
             { value: 'Toggle', label: 'Press to Toggle', title: 'Press once to start recording, press again to stop' },
+            { value: 'VoiceActivated', label: 'Hands-Free', title: 'Start recording when you speak and stop after a pause' },
           ]}
         />
       </ConfigField>
 
+      {config.hotkey_mode === 'VoiceActivated' && (
+        <>
+          <ConfigField label="Speech Threshold (dB)" description="How far above the background noise your voice must be to start recording. Raise it if noise triggers sessions.">
+            <NumberField value={config.vad_threshold_db} onChange={(value) => updateConfig('vad_threshold_db', value)} min={3} max={40} step={1} />
+          </ConfigField>
+          <ConfigField label="Minimum Speech (ms)" description="Speech needed before a session starts. Filters out clicks, taps and short noises.">
+            <NumberField value={config.vad_min_speech_ms} onChange={(value) => updateConfig('vad_min_speech_ms', value)} min={60} max={2000} step={10} />
+          </ConfigField>
+          <ConfigField label="End After Silence (ms)" description="How long a pause ends the recording.">
+            <NumberField value={config.vad_silence_ms} onChange={(value) => updateConfig('vad_silence_ms', value)} min={200} max={10000} step={100} />
+          </ConfigField>
+          <ConfigField label="Maximum Utterance (s)" description="Recording stops after this long even if you keep talking.">
+            <NumberField value={config.vad_max_utterance_secs} onChange={(value) => updateConfig('vad_max_utterance_secs', value)} min={5} max={600} step={5} />
+          </ConfigField>
+          <ConfigField label="Pre-Roll (ms)" description="Audio kept from just before speech was detected, so the first word is not clipped.">
+            <NumberField value={config.vad_pre_roll_ms} onChange={(value) => updateConfig('vad_pre_roll_ms', value)} min={0} max={1000} step={50} />
+          </ConfigField>
+        </>
+      )}
+
       <ConfigField
         label="Global Hotkey"
         description={
-          config.hotkey_mode === 'Toggle'
+          config.hotkey_mode !== 'HoldToTalk'
             ? (isSystemManagedShortcut ? 'Press your system shortcut to start recording, and again to stop.' : 'Press once to start recording, press again to stop and transcribe.')
             : (isSystemManagedShortcut ? 'Use your system shortcut to record and release to transcribe.' : 'Hold these keys to record, release to transcribe.')
         }
@@ -4,8 +4,8 @@ import { Switch } from '../../components/Switch.tsx';
 import { ModeSwitcher } from '../../components/ModeSwitcher.tsx';
 import { NumberField } from '../../components/NumberField.tsx';
 import { SelectField } from '../../components/SelectField.tsx';
-import type { Config, PasteShortcut } from '../../types.ts';
-import { helperTextStyle, selectWrapperStyle } from '../../theme/ui-primitives.ts';
+import type { Config, DictationMode, PasteShortcut, SelectionSource } from '../../types.ts';
+import { helperTextStyle, inputBaseStyle, selectWrapperStyle } from '../../theme/ui-primitives.ts';
 
 interface TypingSectionProps {
   config: Config;
@@ -115,10 +115,81 @@ export function TypingSection({ config, updateConfig }: TypingSectionProps) {
         </>
       )}
 
-      <ConfigField label="Append Trailing Space" description="Automatically append a space after typed text so you're ready to type the next word.">
+      <ConfigField label="Dictation Mode" description="Prose types text as spoken. Code turns commands like 'camel case user id' or 'double colon' into identifiers and symbols. Markdown Notes turns 'heading', 'bullet point', 'numbered item' and 'new paragraph' into Markdown.">
+        <div style={selectWrapperStyle}>
+          <SelectField
+            value={config.dictation_mode}
+            options={[
+              { value: 'Prose', label: 'Prose' },
+              { value: 'Code', label: 'Code' },
+              { value: 'Markdown', label: 'Markdown Notes' },
+            ]}
+            onChange={(nextMode) => updateConfig('dictation_mode', nextMode as DictationMode)}
+            ariaLabel="Dictation Mode"
+          />
+        </div>
+      </ConfigField>
+
+      <ConfigField label="Code Dictation Hotkey" description="Optional second hotkey that always dictates in Code mode, e.g. ctrl+alt+c. Leave empty to disable.">
+        <input
+          type="text"
+          value={config.code_dictation_hotkey}
+          onChange={(e) => updateConfig('code_dictation_hotkey', (e.target as HTMLInputElement).value.trim())}
+          placeholder="e.g. ctrl+alt+c"
+          style={inputBaseStyle}
+        />
+      </ConfigField>
+
+      <ConfigField label="Command Mode Hotkey" description="Optional hotkey that treats the dictation as an instruction for the selected text, e.g. 'make this more formal'. The result from the post-processing provider replaces the selection. Leave empty to disable.">
+        <input
+          type="text"
+          value={config.command_mode_hotkey}
+          onChange={(e) => updateConfig('command_mode_hotkey', (e.target as HTMLInputElement).value.trim())}
+          placeholder="e.g. ctrl+alt+e"
+          style={inputBaseStyle}
+        />
+      </ConfigField>
+
+      {config.command_mode_hotkey && (
+        <>
+          <ConfigField label="Read Selection From" description="Copy Shortcut presses Ctrl+C in the focused application and restores the clipboard afterwards. Primary Selection reads the highlighted text directly (Linux only).">
+            <div style={selectWrapperStyle}>
+              <SelectField
+                value={config.command_selection_source}
+                options={[
+                  { value: 'CopyShortcut', label: 'Copy Shortcut' },
+                  { value: 'PrimarySelection', label: 'Primary Selection' },
+                ]}
+                onChange={(nextSource) => updateConfig('command_selection_source', nextSource as SelectionSource)}
+                ariaLabel="Read Selection From"
+              />
+            </div>
+          </ConfigField>
+
+          <ConfigField label="Command System Prompt" description="Instructions for the model in command mode. The selection and your spoken instruction are sent with it.">
+            <textarea
+              style={{ ...inputBaseStyle, resize: 'vertical', minHeight: '100px' }}
+              value={config.command_system_prompt}
+              onChange={(e: Event) => updateConfig('command_system_prompt', (e.target as HTMLTextAreaElement).value)}
+            />
+          </ConfigField>
+        </>
+      )}
+
+      <ConfigField label="Append Trailing Space" description="Automatically append a space after typed text so you're ready to type the next word. With continuation on, Prose dictations get the space in front of the next dictation instead.">
         <Switch name="Append Trailing Space" checked={config.append_trailing_space} onChange={(checked) => updateConfig('append_trailing_space', checked)} />
       </ConfigField>
 
+      <ConfigField label="Continue Previous Dictation" description="Treat a dictation started shortly after the last one as its continuation: carries context into transcription and fixes capitalization and spacing at the join. Prose mode only.">
+        <Switch name="Continue Previous Dictation" checked={config.continuation_enabled} onChange={(checked) => updateConfig('continuation_enabled', checked)} />
+      </ConfigField>
+
+      {config.continuation_enabled && (
+        <ConfigField label="Continuation Window (seconds)" description="How long after a dictation the next one still counts as a continuation.">
+          <NumberField value={config.continuation_window_secs} onChange={(value) => updateConfig('continuation_window_secs', value)} min={1} max={600} />
+        </ConfigField>
+      )}
+
       <ConfigField label="Auto-Submit with Enter" description="Automatically press Enter after dictation finishes to submit the message or command.">
         <Switch name="Auto-Submit" checked={config.auto_submit} onChange={(checked) => updateConfig('auto_submit', checked)} />
       </ConfigField>
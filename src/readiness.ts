@@ -61,8 +61,11 @@ export function computeReadiness(inputs: ReadinessInputs): ReadinessStatus {
 
   const isHotkeyReady = hotkeyError === null;
 
+  // Custom GGUF files and a user-run llama-server need no download.
   const postProcessModelMissing = config.post_process_enabled
     && config.post_process_provider === 'Local'
+    && !config.post_process_model.toLowerCase().endsWith('.gguf')
+    && config.engine_config?.['llama.server'] !== 'url'
     && !modelStatus[config.post_process_model];
 
   return {
@@ -4,10 +4,60 @@ export interface PostProcessPrompt {
   prompt: string;
   user_prompt_template?: string | null;
   max_output_tokens?: number | null;
+  dictation_mode?: DictationMode | null;
+  triggers?: string[];
+  guardrail?: boolean | null;
+  reduce_prompt?: string | null;
+  json_schema?: string | null;
+  grammar?: string | null;
+  output_template?: string | null;
+  provider?: PostProcessProvider | null;
+  engine?: string | null;
+  model?: string | null;
+  api_url?: string | null;
+  api_key?: string | null;
+  api_model?: string | null;
+  metadata?: PresetMetadata | null;
+}
+
+export interface PresetMetadata {
+  description?: string;
+  author?: string;
+  revision?: number;
+  tags?: string[];
+  source?: string;
 }
 
+export type PresetConflictPolicy = 'Skip' | 'Replace' | 'KeepBoth' | 'Newer';
+
+export interface ImportSummary {
+  added: string[];
+  replaced: string[];
+  renamed: string[];
+  skipped: string[];
+}
+
+export type PostProcessProvider = 'Local' | 'API' | 'Anthropic' | 'Ollama';
+
+export type GuardrailAction = 'FallBack' | 'Retry';
+
+export type PostProcessFallback = 'Disabled' | 'Local' | 'API';
+
+export type PostProcessFailureOutput = 'Raw' | 'Discard';
+
+export type NoiseReductionProvider = 'SpectralGate' | 'RNNoise' | 'Python';
+export type RecordingAudioFormat = 'Wav' | 'Flac';
+
+export type DictationMode = 'Prose' | 'Code' | 'Markdown';
+
+export type HotkeyMode = 'HoldToTalk' | 'Toggle' | 'VoiceActivated';
+
 export type PasteShortcut = 'ShiftInsert' | 'CtrlV' | 'CtrlShiftV';
 
+export type SelectionSource = 'CopyShortcut' | 'PrimarySelection';
+
+export type ProfanityMaskStyle = 'Asterisks' | 'FirstLetter' | 'Remove';
+
 export interface Config {
   openai_api_key: string;
   api_url: string;
@@ -21,18 +71,38 @@ export interface Config {
   pixels_from_bottom: number;
   audio_device: string | null;
   playback_device?: string | null;
+  listen_device?: string | null;
+  listen_include_mic: boolean;
   enable_recording_logs: boolean;
+  recording_audio_format: RecordingAudioFormat;
   input_sensitivity: number;
+  agc_enabled: boolean;
+  agc_target_dbfs: number;
+  agc_max_gain_db: number;
   output_method: 'Typewriter' | 'Clipboard';
   copy_on_typewriter: boolean;
   language: string;
   post_roll_ms: number;
-  hotkey_mode: 'HoldToTalk' | 'Toggle';
+  hotkey_mode: HotkeyMode;
+  vad_threshold_db: number;
+  vad_min_speech_ms: number;
+  vad_silence_ms: number;
+  vad_max_utterance_secs: number;
+  vad_pre_roll_ms: number;
+  code_dictation_hotkey: string;
+  command_mode_hotkey: string;
+  command_selection_source: SelectionSource;
+  command_system_prompt: string;
+  dictation_mode: DictationMode;
   max_recording_duration_minutes: number;
   engine_config: Record<string, unknown> | null;
   dictionary: string[];
+  dictionary_aliases: Record<string, string[]>;
+  dictionary_correction_enabled: boolean;
+  dictionary_edit_threshold: number;
+  dictionary_phonetic_threshold: number;
   post_process_enabled: boolean;
-  post_process_provider: 'Local' | 'API';
+  post_process_provider: PostProcessProvider;
   post_process_engine: string;
   post_process_model: string;
   post_process_api_url: string;
@@ -42,16 +112,34 @@ export interface Config {
   post_process_threads: string;
   post_process_user_prompt_template: string;
   post_process_max_output_tokens: number;
+  post_process_streaming: boolean;
+  post_process_stream_typing: boolean;
+  post_process_guardrail_enabled: boolean;
+  post_process_guardrail_action: GuardrailAction;
+  post_process_chunking_enabled: boolean;
+  post_process_chunk_chars: number;
+  post_process_chunk_overlap_chars: number;
+  post_process_parallel_chunks: number;
+  post_process_timeout_secs: number;
+  post_process_retries: number;
+  post_process_fallback: PostProcessFallback;
+  post_process_failure_output: PostProcessFailureOutput;
   post_process_prompts: PostProcessPrompt[];
   post_process_selected_prompt_id: string | null;
   filler_word_removal_enabled: boolean;
   custom_filler_words: string[];
+  profanity_filter_enabled: boolean;
+  profanity_mask_style: ProfanityMaskStyle;
+  profanity_custom_words: string[];
   append_trailing_space: boolean;
+  continuation_enabled: boolean;
+  continuation_window_secs: number;
   auto_submit: boolean;
   paste_after_copy: boolean;
   paste_shortcut: PasteShortcut;
   noise_reduction_enabled: boolean;
   noise_reduction_strength: number;
+  noise_reduction_provider: NoiseReductionProvider;
   history_limit: number;
   log_level: string;
   diarization_enabled_files: boolean;
@@ -79,9 +167,52 @@ export interface HistoryItem {
   audio_file?: string | null;
   duration_secs?: number | null;
   engine?: string | null;
-  source?: 'mic' | 'file' | string | null;
+  source?: 'mic' | 'file' | 'system' | 'call' | 'command' | string | null;
   language?: string | null;
   prompt_name?: string | null;
+  metadata?: HistoryMetadata | null;
+}
+
+export interface HistoryMetadata {
+  guardrail?: string | null;
+  instruction?: string | null;
+  chunks?: number | null;
+  structured?: unknown;
+  post_process?: PostProcessReport | null;
+  agc?: AgcReport | null;
+}
+
+export interface AgcReport {
+  target_dbfs: number;
+  mean_gain_db: number;
+  min_gain_db: number;
+  max_gain_db: number;
+  noise_floor_dbfs: number;
+}
+
+export interface PostProcessAttempt {
+  service: string;
+  error?: string | null;
+  elapsed_ms: number;
+}
+
+export interface PostProcessReport {
+  outcome: 'cleaned' | 'fallback' | 'raw' | 'discarded';
+  attempts: PostProcessAttempt[];
+}
+
+/** A recording left on disk by a session that was interrupted before it was transcribed. */
+export interface RecoverableRecording {
+  id: string;
+  recorded_at: string;
+  duration_secs: number;
+  source: 'mic' | 'system' | 'call';
+}
+
+export interface ChunkProgress {
+  stage: 'map' | 'reduce';
+  completed: number;
+  total: number;
 }
 
 export interface Segment {
@@ -175,6 +306,9 @@ export interface EngineSetting {
   settingType: string;
   default: unknown;
   options: SettingOption[] | null;
+  min?: number;
+  max?: number;
+  step?: number;
 }
 
 export interface EngineCapabilities {
@@ -187,6 +321,21 @@ export interface StatusUpdatePayload {
   status: string;
 }
 
+export interface PostProcessTokenPayload {
+  text: string;
+}
+
+export interface AudioDeviceStatusPayload {
+  state: 'lost' | 'fallback' | 'recovered' | 'reconnected';
+  message: string;
+}
+
+export interface PromptPreview {
+  system_prompt: string;
+  user_prompt: string;
+  unknown_variables: string[];
+}
+
 export type DictationStatus = 'Ready' | 'Recording' | 'Transcribing' | 'Processing' | 'Typing' | 'Error';
 
 export type AppRoute = 'setup' | 'home' | 'status' | 'history' | 'settings' | 'ui-lab' | 'help';